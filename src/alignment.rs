@@ -3,29 +3,35 @@ use crate::semantic::{SemanticData, Type};
 impl Type {
     pub fn alignment(&self, semantics: &SemanticData) -> u8 {
         match self {
-            Type::Char | Type::SChar | Type::UChar => 1,
-            Type::Int | Type::UInt => 4,
+            Type::Bool | Type::Char | Type::SChar | Type::UChar => 1,
+            Type::Short | Type::UShort => 2,
+            Type::Int | Type::UInt | Type::Float => 4,
             Type::Long | Type::ULong | Type::Double | Type::Pointer(_) => 8,
-            Type::Array(inner, _) => inner.alignment(semantics),
+            Type::Array(inner, _) | Type::VariableArray(inner, _) => inner.alignment(semantics),
             Type::Struct(name) | Type::Union(name) => semantics.get_aggregate(name).alignment,
             Type::Function(_) => panic!("Function type does not have alignment"),
             Type::Void => panic!("Void does not have alignment"),
+            Type::Qualified(_, inner) => inner.alignment(semantics),
         }
     }
 
     pub fn size(&self, semantics: &SemanticData) -> usize {
         match self {
-            Type::Char | Type::UChar | Type::SChar => 1,
+            Type::Bool | Type::Char | Type::UChar | Type::SChar => 1,
+            Type::Short | Type::UShort => 2,
             Type::Int => 4,
             Type::UInt => 4,
+            Type::Float => 4,
             Type::Long => 8,
             Type::ULong => 8,
             Type::Double => 8,
             Type::Function(_) => panic!("Size of a function type"),
             Type::Pointer(_) => 8,
             Type::Array(ty, size) => ty.size(semantics) * size,
+            Type::VariableArray(..) => panic!("Size of a variable length array"),
             Type::Void => 1,
             Type::Struct(name) | Type::Union(name) => semantics.get_aggregate(name).size,
+            Type::Qualified(_, inner) => inner.size(semantics),
         }
     }
 }
@@ -6,11 +6,13 @@ pub mod register_allocation;
 use crate::alignment::align_offset;
 use crate::asm::ir::{
     AsmType, BinaryOp, CondCode, Function, Instruction, Operand, Program, Reg, StaticConstant,
-    StaticVariable, TopLevel, UnaryOp,
+    StaticVariable, TlsModel, TopLevel, UnaryOp,
 };
 use crate::asm::register_allocation::allocate_registers;
 use crate::ast::Constant;
-use crate::semantic::{AggregateType, Attributes, SemanticData, StaticInit, Type, TypeEntry};
+use crate::semantic::{
+    AggregateType, Attributes, FunctionType, SemanticData, StaticInit, Type, TypeEntry,
+};
 use crate::symbol::Symbol;
 use crate::tacky;
 use std::collections::{HashMap, HashSet};
@@ -52,6 +54,10 @@ impl Reg {
 }
 
 const INT_RETURN_REGISTERS: [Reg; 2] = [Reg::Ax, Reg::Dx];
+
+/// Size of the area where variadic functions spill their argument registers.
+const REGISTER_SAVE_AREA_SIZE: i64 =
+    8 * INT_ARG_REGISTERS.len() as i64 + 16 * SSE_ARG_REGISTERS.len() as i64;
 const SSE_RETURN_REGISTERS: [Reg; 2] = [Reg::XMM0, Reg::XMM1];
 
 enum BackendSymbolData {
@@ -59,6 +65,7 @@ enum BackendSymbolData {
         ty: AsmType,
         is_static: bool,
         is_const: bool,
+        tls: Option<TlsModel>,
     },
     Fn {
         arg_registers: Vec<Reg>,
@@ -80,9 +87,11 @@ impl SemanticData {
     fn val_asm_ty(&self, val: &tacky::Val) -> AsmType {
         match val {
             tacky::Val::Constant(Constant::Char(_) | Constant::UChar(_)) => AsmType::Byte,
+            tacky::Val::Constant(Constant::Short(_) | Constant::UShort(_)) => AsmType::Word,
             tacky::Val::Constant(Constant::Int(_) | Constant::UInt(_)) => AsmType::Longword,
             tacky::Val::Constant(Constant::Long(_) | Constant::ULong(_)) => AsmType::Quadword,
             tacky::Val::Constant(Constant::Double(_)) => AsmType::Double,
+            tacky::Val::Constant(Constant::Float(_)) => AsmType::Float,
             tacky::Val::Var(name) => self.symbol_ty(name).to_asm(self),
         }
     }
@@ -91,13 +100,20 @@ impl SemanticData {
 impl Type {
     fn to_asm(&self, semantics: &SemanticData) -> AsmType {
         match self {
-            Type::UChar | Type::SChar | Type::Char => AsmType::Byte,
+            Type::Bool | Type::UChar | Type::SChar | Type::Char => AsmType::Byte,
+            Type::Short | Type::UShort => AsmType::Word,
             Type::Int | Type::UInt => AsmType::Longword,
             Type::Long | Type::ULong => AsmType::Quadword,
             Type::Double => AsmType::Double,
+            Type::Float => AsmType::Float,
             Type::Void => unreachable!(),
             Type::Function(_) => unreachable!(),
             Type::Pointer(_) => AsmType::Quadword,
+            Type::Qualified(_, inner) => inner.to_asm(semantics),
+            // Variable length arrays are referred to by the address of their
+            // storage, allocated at runtime.
+            ty if ty.is_variable_length_array() => AsmType::Quadword,
+            Type::VariableArray(..) => unreachable!(),
             Type::Array(inner, length) => {
                 let size = inner.size(semantics) * length;
                 let inner_asm_ty = inner.to_asm(semantics);
@@ -139,6 +155,12 @@ struct FnReturn {
     in_memory: bool,
 }
 
+#[derive(Clone, Copy)]
+enum CallTarget<'a> {
+    Function(&'a Symbol),
+    Pointer(&'a tacky::Val),
+}
+
 #[derive(Clone, Default)]
 struct CallRegisters {
     args: Vec<Reg>,
@@ -147,6 +169,7 @@ struct CallRegisters {
 
 struct Compiler {
     doubles: HashMap<u64, Symbol>,
+    floats: HashMap<u32, Symbol>,
     call_registers: HashMap<Symbol, CallRegisters>,
     aliased_vars: HashMap<Symbol, HashSet<Symbol>>,
     label_counter: usize,
@@ -187,6 +210,25 @@ impl Compiler {
                     ty: AsmType::Double,
                     is_static: true,
                     is_const: true,
+                    tls: None,
+                },
+            );
+        }
+
+        for (key, name) in &self.floats {
+            let alignment = if *key == (-0.0_f32).to_bits() { 16 } else { 4 };
+            top_level.push(TopLevel::Constant(StaticConstant {
+                name: name.clone(),
+                alignment,
+                init: StaticInit::Float(f32::from_bits(*key)),
+            }));
+            backend_symbols.insert(
+                name.clone(),
+                BackendSymbolData::Obj {
+                    ty: AsmType::Float,
+                    is_static: true,
+                    is_const: true,
+                    tls: None,
                 },
             );
         }
@@ -212,8 +254,27 @@ impl Compiler {
             .collect();
         let return_in_memory = self.does_return_in_memory(&function.name);
 
-        self.assign_parameters(&mut instructions, params, return_in_memory);
+        if self.is_variadic(&function.name) {
+            let save_area = self.register_save_area(&function.name);
+            for (i, &reg) in INT_ARG_REGISTERS.iter().enumerate() {
+                instructions.push(Instruction::Mov(
+                    AsmType::Quadword,
+                    reg.into(),
+                    save_area.add_offset(8 * i as i64),
+                ));
+            }
+            for (i, &reg) in SSE_ARG_REGISTERS.iter().enumerate() {
+                instructions.push(Instruction::Mov(
+                    AsmType::Double,
+                    reg.into(),
+                    save_area.add_offset(8 * INT_ARG_REGISTERS.len() as i64 + 16 * i as i64),
+                ));
+            }
+        }
+
+        self.assign_parameters(&mut instructions, params.clone(), return_in_memory);
 
+        let mut allocates_stack = false;
         for tacky_instruction in &function.body {
             match tacky_instruction {
                 tacky::Instruction::Return(val) => {
@@ -222,11 +283,11 @@ impl Compiler {
 
                 tacky::Instruction::Unary { op, src, dst } => {
                     let ty = self.semantics.val_asm_ty(src);
-                    let is_double = matches!(ty, AsmType::Double);
+                    let is_floating = ty.is_floating();
                     match op {
                         tacky::UnaryOp::Increment => {
-                            let one = if is_double {
-                                self.make_double_constant(1.0)
+                            let one = if is_floating {
+                                self.make_floating_constant(ty, 1.0)
                             } else {
                                 Operand::Imm(1)
                             };
@@ -243,8 +304,8 @@ impl Compiler {
                             ));
                         }
                         tacky::UnaryOp::Decrement => {
-                            let one = if is_double {
-                                self.make_double_constant(1.0)
+                            let one = if is_floating {
+                                self.make_floating_constant(ty, 1.0)
                             } else {
                                 Operand::Imm(1)
                             };
@@ -260,9 +321,9 @@ impl Compiler {
                                 self.generate_val(dst),
                             ));
                         }
-                        tacky::UnaryOp::Not if is_double => {
+                        tacky::UnaryOp::Not if is_floating => {
                             instructions.push(Instruction::Binary(
-                                AsmType::Double,
+                                ty,
                                 BinaryOp::Xor,
                                 Reg::XMM0.into(),
                                 Reg::XMM0.into(),
@@ -315,13 +376,13 @@ impl Compiler {
                             instructions
                                 .push(Instruction::SetCC(CondCode::E, self.generate_val(dst)));
                         }
-                        tacky::UnaryOp::Negate if is_double => {
+                        tacky::UnaryOp::Negate if is_floating => {
                             instructions.push(Instruction::Mov(
                                 ty,
                                 self.generate_val(src),
                                 self.generate_val(dst),
                             ));
-                            let negative_zero = self.make_double_constant(-0.0);
+                            let negative_zero = self.make_floating_constant(ty, -0.0);
                             instructions.push(Instruction::Binary(
                                 ty,
                                 BinaryOp::Xor,
@@ -352,9 +413,9 @@ impl Compiler {
                     dst,
                 } => {
                     let ty = self.semantics.val_asm_ty(src1);
-                    let is_double = matches!(ty, AsmType::Double);
+                    let is_floating = ty.is_floating();
                     match op {
-                        tacky::BinaryOp::Divide if !is_double => {
+                        tacky::BinaryOp::Divide if !is_floating => {
                             instructions.push(Instruction::Mov(
                                 ty,
                                 self.generate_val(src1),
@@ -438,9 +499,8 @@ impl Compiler {
                                 Operand::Imm(0),
                                 self.generate_val(dst),
                             ));
-                            let is_double = matches!(ty, AsmType::Double);
                             let is_unsigned = !self.semantics.is_signed(src1);
-                            let cond = match (op, is_unsigned || is_double) {
+                            let cond = match (op, is_unsigned || is_floating) {
                                 (tacky::BinaryOp::Equal, _) => CondCode::E,
                                 (tacky::BinaryOp::NotEqual, _) => CondCode::NE,
                                 (tacky::BinaryOp::GreaterThan, false) => CondCode::G,
@@ -455,7 +515,7 @@ impl Compiler {
                             };
                             // Handling NaN:
                             match op {
-                                tacky::BinaryOp::NotEqual if is_double => {
+                                tacky::BinaryOp::NotEqual if is_floating => {
                                     instructions.push(Instruction::Mov(AsmType::Longword, Operand::Imm(0), Reg::Ax.into()));
                                     instructions.push(Instruction::Mov(AsmType::Longword, Operand::Imm(0), Reg::Cx.into()));
                                     instructions.push(Instruction::SetCC(cond, Reg::Ax.into()));
@@ -463,7 +523,7 @@ impl Compiler {
                                     instructions.push(Instruction::Binary(AsmType::Longword, BinaryOp::Or, Reg::Ax.into(), Reg::Cx.into()));
                                     instructions.push(Instruction::Mov(AsmType::Longword, Reg::Cx.into(), self.generate_val(dst)));
                                 }
-                                _ if is_double => {
+                                _ if is_floating => {
                                     instructions.push(Instruction::Mov(AsmType::Longword, Operand::Imm(0), Reg::Ax.into()));
                                     instructions.push(Instruction::Mov(AsmType::Longword, Operand::Imm(0), Reg::Cx.into()));
                                     instructions.push(Instruction::SetCC(cond, Reg::Ax.into()));
@@ -480,7 +540,7 @@ impl Compiler {
                         tacky::BinaryOp::Add
                         | tacky::BinaryOp::Subtract
                         | tacky::BinaryOp::Multiply
-                        | tacky::BinaryOp::Divide // Covers only if double
+                        | tacky::BinaryOp::Divide // Covers only if floating
                         | tacky::BinaryOp::BinAnd
                         | tacky::BinaryOp::BinOr
                         | tacky::BinaryOp::BinXor => {
@@ -504,9 +564,10 @@ impl Compiler {
                     instructions.push(Instruction::Jmp(target.clone()));
                 }
                 tacky::Instruction::JumpIfZero { cond, target } => {
-                    if let AsmType::Double = self.semantics.val_asm_ty(cond) {
+                    let cond_ty = self.semantics.val_asm_ty(cond);
+                    if cond_ty.is_floating() {
                         instructions.push(Instruction::Binary(
-                            AsmType::Double,
+                            cond_ty,
                             BinaryOp::Xor,
                             Reg::XMM0.into(),
                             Reg::XMM0.into(),
@@ -526,9 +587,10 @@ impl Compiler {
                     instructions.push(Instruction::JmpCC(CondCode::E, target.clone()));
                 }
                 tacky::Instruction::JumpIfNotZero { cond, target } => {
-                    if let AsmType::Double = self.semantics.val_asm_ty(cond) {
+                    let cond_ty = self.semantics.val_asm_ty(cond);
+                    if cond_ty.is_floating() {
                         instructions.push(Instruction::Binary(
-                            AsmType::Double,
+                            cond_ty,
                             BinaryOp::Xor,
                             Reg::XMM0.into(),
                             Reg::XMM0.into(),
@@ -569,7 +631,10 @@ impl Compiler {
                     instructions.push(Instruction::Label(l.clone()));
                 }
                 tacky::Instruction::FnCall { name, args, dst } => {
-                    self.generate_call(&mut instructions, name, args, dst);
+                    self.generate_call(&mut instructions, CallTarget::Function(name), args, dst);
+                }
+                tacky::Instruction::IndirectCall { ptr, args, dst } => {
+                    self.generate_call(&mut instructions, CallTarget::Pointer(ptr), args, dst);
                 }
                 tacky::Instruction::SignExtend { src, dst } => {
                     let asm_type1 = self.semantics.val_asm_ty(src);
@@ -598,62 +663,73 @@ impl Compiler {
                 }
 
                 tacky::Instruction::DoubleToInt { src, dst } => {
-                    if let AsmType::Byte = self.semantics.val_asm_ty(dst) {
-                        instructions.push(Instruction::Cvttsd2si(
+                    let src_ty = self.semantics.val_asm_ty(src);
+                    let dst_ty = self.semantics.val_asm_ty(dst);
+                    if let AsmType::Byte | AsmType::Word = dst_ty {
+                        instructions.push(Self::floating_to_int(
+                            src_ty,
                             AsmType::Longword,
                             self.generate_val(src),
                             Reg::Ax.into(),
                         ));
                         instructions.push(Instruction::Mov(
-                            AsmType::Byte,
+                            dst_ty,
                             Reg::Ax.into(),
                             self.generate_val(dst),
                         ))
                     } else {
-                        instructions.push(Instruction::Cvttsd2si(
-                            self.semantics.val_asm_ty(dst),
+                        instructions.push(Self::floating_to_int(
+                            src_ty,
+                            dst_ty,
                             self.generate_val(src),
                             self.generate_val(dst),
                         ));
                     }
                 }
                 tacky::Instruction::IntToDouble { src, dst } => {
-                    if let AsmType::Byte = self.semantics.val_asm_ty(src) {
+                    let src_ty = self.semantics.val_asm_ty(src);
+                    let dst_ty = self.semantics.val_asm_ty(dst);
+                    if let AsmType::Byte | AsmType::Word = src_ty {
                         instructions.push(Instruction::Movsx(
-                            AsmType::Byte,
+                            src_ty,
                             self.generate_val(src),
                             AsmType::Longword,
                             Reg::Ax.into(),
                         ));
-                        instructions.push(Instruction::Cvtsi2sd(
+                        instructions.push(Self::int_to_floating(
+                            dst_ty,
                             AsmType::Longword,
                             Reg::Ax.into(),
                             self.generate_val(dst),
                         ));
                     } else {
-                        instructions.push(Instruction::Cvtsi2sd(
-                            self.semantics.val_asm_ty(src),
+                        instructions.push(Self::int_to_floating(
+                            dst_ty,
+                            src_ty,
                             self.generate_val(src),
                             self.generate_val(dst),
                         ));
                     }
                 }
                 tacky::Instruction::DoubleToUInt { src, dst } => {
+                    let src_ty = self.semantics.val_asm_ty(src);
                     match self.semantics.val_asm_ty(dst) {
-                        AsmType::Byte => {
-                            instructions.push(Instruction::Cvttsd2si(
+                        dst_ty @ (AsmType::Byte | AsmType::Word) => {
+                            instructions.push(Self::floating_to_int(
+                                src_ty,
                                 AsmType::Longword,
                                 self.generate_val(src),
                                 Reg::Ax.into(),
                             ));
                             instructions.push(Instruction::Mov(
-                                AsmType::Byte,
+                                dst_ty,
                                 Reg::Ax.into(),
                                 self.generate_val(dst),
                             ));
                         }
                         AsmType::Longword => {
-                            instructions.push(Instruction::Cvttsd2si(
+                            instructions.push(Self::floating_to_int(
+                                src_ty,
                                 AsmType::Quadword,
                                 self.generate_val(src),
                                 Reg::Ax.into(),
@@ -668,15 +744,16 @@ impl Compiler {
                             let upper_bound_u64 = (i64::MAX as u64) + 1;
                             let upper_bound_f64 = upper_bound_u64 as f64;
                             instructions.push(Instruction::Cmp(
-                                AsmType::Double,
-                                self.make_double_constant(upper_bound_f64),
+                                src_ty,
+                                self.make_floating_constant(src_ty, upper_bound_f64),
                                 self.generate_val(src),
                             ));
                             let out_of_range_label = self.make_label("d2u_out_of_range");
                             let end_label = self.make_label("d2u_end");
                             instructions
                                 .push(Instruction::JmpCC(CondCode::AE, out_of_range_label.clone()));
-                            instructions.push(Instruction::Cvttsd2si(
+                            instructions.push(Self::floating_to_int(
+                                src_ty,
                                 AsmType::Quadword,
                                 self.generate_val(src),
                                 self.generate_val(dst),
@@ -684,17 +761,18 @@ impl Compiler {
                             instructions.push(Instruction::Jmp(end_label.clone()));
                             instructions.push(Instruction::Label(out_of_range_label));
                             instructions.push(Instruction::Mov(
-                                AsmType::Double,
+                                src_ty,
                                 self.generate_val(src),
                                 Reg::XMM14.into(),
                             ));
                             instructions.push(Instruction::Binary(
-                                AsmType::Double,
+                                src_ty,
                                 BinaryOp::Sub,
-                                self.make_double_constant(upper_bound_f64),
+                                self.make_floating_constant(src_ty, upper_bound_f64),
                                 Reg::XMM14.into(),
                             ));
-                            instructions.push(Instruction::Cvttsd2si(
+                            instructions.push(Self::floating_to_int(
+                                src_ty,
                                 AsmType::Quadword,
                                 Reg::XMM14.into(),
                                 self.generate_val(dst),
@@ -712,19 +790,23 @@ impl Compiler {
                             ));
                             instructions.push(Instruction::Label(end_label));
                         }
-                        AsmType::Double | AsmType::ByteArray { .. } => unreachable!(),
+                        AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => {
+                            unreachable!()
+                        }
                     }
                 }
                 tacky::Instruction::UIntToDouble { src, dst } => {
+                    let dst_ty = self.semantics.val_asm_ty(dst);
                     match self.semantics.val_asm_ty(src) {
-                        AsmType::Byte => {
+                        src_ty @ (AsmType::Byte | AsmType::Word) => {
                             instructions.push(Instruction::MovZeroExtend(
-                                AsmType::Byte,
+                                src_ty,
                                 self.generate_val(src),
                                 AsmType::Longword,
                                 Reg::Ax.into(),
                             ));
-                            instructions.push(Instruction::Cvtsi2sd(
+                            instructions.push(Self::int_to_floating(
+                                dst_ty,
                                 AsmType::Longword,
                                 Reg::Ax.into(),
                                 self.generate_val(dst),
@@ -737,7 +819,8 @@ impl Compiler {
                                 AsmType::Quadword,
                                 Reg::Ax.into(),
                             ));
-                            instructions.push(Instruction::Cvtsi2sd(
+                            instructions.push(Self::int_to_floating(
+                                dst_ty,
                                 AsmType::Quadword,
                                 Reg::Ax.into(),
                                 self.generate_val(dst),
@@ -753,7 +836,8 @@ impl Compiler {
                             let end_label = self.make_label("u2d_end");
                             instructions
                                 .push(Instruction::JmpCC(CondCode::L, out_of_range_label.clone()));
-                            instructions.push(Instruction::Cvtsi2sd(
+                            instructions.push(Self::int_to_floating(
+                                dst_ty,
                                 AsmType::Quadword,
                                 self.generate_val(src),
                                 self.generate_val(dst),
@@ -793,22 +877,47 @@ impl Compiler {
                                 Reg::Ax.into(),
                                 Reg::Dx.into(),
                             ));
-                            instructions.push(Instruction::Cvtsi2sd(
+                            instructions.push(Self::int_to_floating(
+                                dst_ty,
                                 AsmType::Quadword,
                                 Reg::Dx.into(),
                                 self.generate_val(dst),
                             ));
                             instructions.push(Instruction::Binary(
-                                AsmType::Double,
+                                dst_ty,
                                 BinaryOp::Add,
                                 self.generate_val(dst),
                                 self.generate_val(dst),
                             ));
                             instructions.push(Instruction::Label(end_label));
                         }
-                        AsmType::Double | AsmType::ByteArray { .. } => unreachable!(),
+                        AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => {
+                            unreachable!()
+                        }
                     }
                 }
+                tacky::Instruction::FloatToDouble { src, dst } => {
+                    instructions.push(Instruction::Cvtss2sd(
+                        self.generate_val(src),
+                        self.generate_val(dst),
+                    ));
+                }
+                tacky::Instruction::DoubleToFloat { src, dst } => {
+                    instructions.push(Instruction::Cvtsd2ss(
+                        self.generate_val(src),
+                        self.generate_val(dst),
+                    ));
+                }
+                tacky::Instruction::GetAddress {
+                    src: tacky::Val::Var(name),
+                    dst,
+                } if self.semantics.symbol_ty(name).is_function() => {
+                    instructions.push(Instruction::Mov(
+                        AsmType::Quadword,
+                        Operand::GotEntry(name.clone()),
+                        self.generate_val(dst),
+                    ));
+                }
                 tacky::Instruction::GetAddress { src, dst } => {
                     instructions.push(Instruction::Lea(
                         self.generate_val(src),
@@ -942,13 +1051,79 @@ impl Compiler {
                         ));
                     }
                 }
+                tacky::Instruction::VaStart { ap } => {
+                    self.generate_va_start(
+                        &mut instructions,
+                        &function.name,
+                        &params,
+                        return_in_memory,
+                        ap,
+                    );
+                }
+                tacky::Instruction::VaArg { ap, dst } => {
+                    self.generate_va_arg(&mut instructions, ap, dst);
+                }
+                tacky::Instruction::AllocateStack { size, dst } => {
+                    // The size is rounded up to keep the stack aligned
+                    instructions.push(Instruction::Mov(
+                        AsmType::Quadword,
+                        self.generate_val(size),
+                        Reg::Ax.into(),
+                    ));
+                    instructions.push(Instruction::Binary(
+                        AsmType::Quadword,
+                        BinaryOp::Add,
+                        Operand::Imm(15),
+                        Reg::Ax.into(),
+                    ));
+                    instructions.push(Instruction::Binary(
+                        AsmType::Quadword,
+                        BinaryOp::And,
+                        Operand::Imm(-16),
+                        Reg::Ax.into(),
+                    ));
+                    instructions.push(Instruction::Binary(
+                        AsmType::Quadword,
+                        BinaryOp::Sub,
+                        Reg::Ax.into(),
+                        Reg::SP.into(),
+                    ));
+                    instructions.push(Instruction::Mov(
+                        AsmType::Quadword,
+                        Reg::SP.into(),
+                        self.generate_val(dst),
+                    ));
+                    allocates_stack = true;
+                }
+                tacky::Instruction::SaveStack { dst } => {
+                    instructions.push(Instruction::Mov(
+                        AsmType::Quadword,
+                        Reg::SP.into(),
+                        self.generate_val(dst),
+                    ));
+                }
+                tacky::Instruction::RestoreStack { src } => {
+                    instructions.push(Instruction::Mov(
+                        AsmType::Quadword,
+                        self.generate_val(src),
+                        Reg::SP.into(),
+                    ));
+                }
+                tacky::Instruction::Phi { .. } => {
+                    unreachable!("Functions are translated out of SSA form before codegen")
+                }
             }
         }
 
+        let decl_attrs = &self.semantics.symbols[&function.name].decl_attrs;
         Function {
             name: function.name.clone(),
             global: function.global,
+            weak: decl_attrs.weak,
+            alignment: decl_attrs.aligned,
+            section: decl_attrs.section.clone(),
             instructions,
+            allocates_stack,
         }
     }
 
@@ -962,6 +1137,178 @@ impl Compiler {
         )
     }
 
+    fn is_variadic(&self, function_name: &Symbol) -> bool {
+        matches!(
+            self.semantics.symbol_ty(function_name),
+            Type::Function(FunctionType { variadic: true, .. })
+        )
+    }
+
+    fn register_save_area(&self, function_name: &Symbol) -> Operand {
+        let base = if self.does_return_in_memory(function_name) {
+            8
+        } else {
+            0
+        };
+        Operand::Memory(Reg::BP, -(base + REGISTER_SAVE_AREA_SIZE))
+    }
+
+    fn generate_va_start(
+        &mut self,
+        instructions: &mut Vec<Instruction>,
+        function_name: &Symbol,
+        params: &[tacky::Val],
+        return_in_memory: bool,
+        ap: &tacky::Val,
+    ) {
+        let FnArgs {
+            int_reg_args,
+            sse_reg_args,
+            stack_args,
+        } = self.classify_parameters(params, return_in_memory);
+        let gp_offset = 8 * (int_reg_args.len() + usize::from(return_in_memory)) as i64;
+        let fp_offset = 8 * INT_ARG_REGISTERS.len() as i64 + 16 * sse_reg_args.len() as i64;
+        let overflow_offset = 16 + 8 * stack_args.len() as i64;
+
+        let ap = self.generate_val(ap);
+        instructions.push(Instruction::Mov(AsmType::Quadword, ap, Reg::Dx.into()));
+        instructions.push(Instruction::Mov(
+            AsmType::Longword,
+            Operand::Imm(gp_offset),
+            Operand::Memory(Reg::Dx, 0),
+        ));
+        instructions.push(Instruction::Mov(
+            AsmType::Longword,
+            Operand::Imm(fp_offset),
+            Operand::Memory(Reg::Dx, 4),
+        ));
+        instructions.push(Instruction::Lea(
+            Operand::Memory(Reg::BP, overflow_offset),
+            Reg::Ax.into(),
+        ));
+        instructions.push(Instruction::Mov(
+            AsmType::Quadword,
+            Reg::Ax.into(),
+            Operand::Memory(Reg::Dx, 8),
+        ));
+        instructions.push(Instruction::Lea(
+            self.register_save_area(function_name),
+            Reg::Ax.into(),
+        ));
+        instructions.push(Instruction::Mov(
+            AsmType::Quadword,
+            Reg::Ax.into(),
+            Operand::Memory(Reg::Dx, 16),
+        ));
+    }
+
+    fn generate_va_arg(
+        &mut self,
+        instructions: &mut Vec<Instruction>,
+        ap: &tacky::Val,
+        dst: &tacky::Val,
+    ) {
+        let ty = self.semantics.val_asm_ty(dst);
+        let classes = if ty.is_floating() {
+            vec![ParamClass::Sse]
+        } else if ty.is_scalar() {
+            vec![ParamClass::Integer]
+        } else {
+            self.classify_type(&self.semantics.val_ty(dst))
+        };
+        let size = ty.size();
+        let dst = self.generate_val(dst);
+        let overflow_label = self.make_label("va_arg_overflow");
+        let end_label = self.make_label("va_arg_end");
+
+        let ap = self.generate_val(ap);
+        instructions.push(Instruction::Mov(AsmType::Quadword, ap, Reg::Dx.into()));
+
+        if !matches!(classes[0], ParamClass::Memory) {
+            let int_count = classes
+                .iter()
+                .filter(|c| matches!(c, ParamClass::Integer))
+                .count() as i64;
+            let sse_count = classes.len() as i64 - int_count;
+            // Both parts of a value must fit in the remaining registers
+            if int_count > 0 {
+                instructions.push(Instruction::Cmp(
+                    AsmType::Longword,
+                    Operand::Imm(8 * INT_ARG_REGISTERS.len() as i64 - 8 * int_count),
+                    Operand::Memory(Reg::Dx, 0),
+                ));
+                instructions.push(Instruction::JmpCC(CondCode::A, overflow_label.clone()));
+            }
+            if sse_count > 0 {
+                instructions.push(Instruction::Cmp(
+                    AsmType::Longword,
+                    Operand::Imm(REGISTER_SAVE_AREA_SIZE - 16 * sse_count),
+                    Operand::Memory(Reg::Dx, 4),
+                ));
+                instructions.push(Instruction::JmpCC(CondCode::A, overflow_label.clone()));
+            }
+            for (i, class) in classes.iter().enumerate() {
+                let (offset_field, increment) = match class {
+                    ParamClass::Sse => (Operand::Memory(Reg::Dx, 4), 16),
+                    _ => (Operand::Memory(Reg::Dx, 0), 8),
+                };
+                instructions.push(Instruction::MovZeroExtend(
+                    AsmType::Longword,
+                    offset_field.clone(),
+                    AsmType::Quadword,
+                    Reg::Ax.into(),
+                ));
+                instructions.push(Instruction::Binary(
+                    AsmType::Quadword,
+                    BinaryOp::Add,
+                    Operand::Memory(Reg::Dx, 16),
+                    Reg::Ax.into(),
+                ));
+                if ty.is_scalar() {
+                    instructions.push(Instruction::Mov(
+                        ty,
+                        Operand::Memory(Reg::Ax, 0),
+                        dst.clone(),
+                    ));
+                } else {
+                    let offset = 8 * i;
+                    Self::copy_bytes(
+                        instructions,
+                        Operand::Memory(Reg::Ax, 0),
+                        dst.add_offset(offset as i64),
+                        (size - offset).min(8),
+                    );
+                }
+                instructions.push(Instruction::Binary(
+                    AsmType::Longword,
+                    BinaryOp::Add,
+                    Operand::Imm(increment),
+                    offset_field,
+                ));
+            }
+            instructions.push(Instruction::Jmp(end_label.clone()));
+        }
+
+        instructions.push(Instruction::Label(overflow_label));
+        instructions.push(Instruction::Mov(
+            AsmType::Quadword,
+            Operand::Memory(Reg::Dx, 8),
+            Reg::Ax.into(),
+        ));
+        if ty.is_scalar() {
+            instructions.push(Instruction::Mov(ty, Operand::Memory(Reg::Ax, 0), dst));
+        } else {
+            Self::copy_bytes(instructions, Operand::Memory(Reg::Ax, 0), dst, size);
+        }
+        instructions.push(Instruction::Binary(
+            AsmType::Quadword,
+            BinaryOp::Add,
+            Operand::Imm(align_offset(size, 8) as i64),
+            Operand::Memory(Reg::Dx, 8),
+        ));
+        instructions.push(Instruction::Label(end_label));
+    }
+
     fn assign_parameters(
         &mut self,
         instructions: &mut Vec<Instruction>,
@@ -1102,13 +1449,21 @@ impl Compiler {
                     };
                     backend_symbols.insert(symbol.clone(), backend_symbol_data);
                 }
-                Attributes::Static { .. } => {
+                Attributes::Static { thread_local, .. } => {
+                    let tls = if !thread_local {
+                        None
+                    } else if semantic.is_external_thread_local(symbol) {
+                        Some(TlsModel::InitialExec)
+                    } else {
+                        Some(TlsModel::LocalExec)
+                    };
                     backend_symbols.insert(
                         symbol.clone(),
                         BackendSymbolData::Obj {
                             ty: semantic.symbol_ty(symbol).to_asm(semantic),
                             is_static: true,
                             is_const: false,
+                            tls,
                         },
                     );
                 }
@@ -1119,6 +1474,7 @@ impl Compiler {
                             ty: semantic.symbol_ty(symbol).to_asm(semantic),
                             is_static: true,
                             is_const: true,
+                            tls: None,
                         },
                     );
                 }
@@ -1129,6 +1485,7 @@ impl Compiler {
                             ty: semantic.symbol_ty(symbol).to_asm(semantic),
                             is_static: false,
                             is_const: false,
+                            tls: None,
                         },
                     );
                 }
@@ -1146,20 +1503,29 @@ impl Compiler {
     }
 
     fn generate_static_variable(&mut self, var: &tacky::StaticVariable) -> StaticVariable {
-        let self1 = &self.semantics;
-        let symbol = &var.name;
+        let data = &self.semantics.symbols[&var.name];
+        let decl_attrs = &data.decl_attrs;
+        // Packing only lowers the natural alignment, explicit ones always apply
+        let natural = if decl_attrs.packed {
+            1
+        } else {
+            data.ty.to_asm(&self.semantics).alignment()
+        };
         StaticVariable {
             name: var.name.clone(),
             global: var.global,
+            weak: decl_attrs.weak,
+            thread_local: var.thread_local,
             init: var.init.clone(),
-            alignment: self1.symbol_ty(symbol).to_asm(&self.semantics).alignment(),
+            alignment: natural.max(decl_attrs.aligned.unwrap_or(1)),
+            section: decl_attrs.section.clone(),
         }
     }
 
     fn generate_call(
         &mut self,
         instructions: &mut Vec<Instruction>,
-        name: &Symbol,
+        target: CallTarget,
         args: &[tacky::Val],
         dst: &Option<tacky::Val>,
     ) {
@@ -1181,8 +1547,35 @@ impl Compiler {
 
         let fn_args = self.classify_parameters(args, return_spec.in_memory);
 
-        let used_registers = self.find_used_registers(&fn_args, &return_spec);
-        self.call_registers.insert(name.clone(), used_registers);
+        let mut used_registers = self.find_used_registers(&fn_args, &return_spec);
+        let is_variadic = match target {
+            CallTarget::Function(name) => self.is_variadic(name),
+            CallTarget::Pointer(ptr) => matches!(
+                self.semantics.val_ty(ptr).unqualified(),
+                Type::Pointer(inner) if matches!(
+                    inner.as_ref(),
+                    Type::Function(FunctionType { variadic: true, .. })
+                )
+            ),
+        };
+        let sse_arg_count = fn_args.sse_reg_args.len() as i64;
+        if is_variadic {
+            used_registers.args.push(Reg::Ax);
+        }
+        if let CallTarget::Function(name) = target {
+            // Variadic functions can be called with different registers at each call site
+            let call_registers = self.call_registers.entry(name.clone()).or_default();
+            for &reg in &used_registers.args {
+                if !call_registers.args.contains(&reg) {
+                    call_registers.args.push(reg);
+                }
+            }
+            for reg in used_registers.ret {
+                if !call_registers.ret.contains(&reg) {
+                    call_registers.ret.push(reg);
+                }
+            }
+        }
 
         let FnArgs {
             int_reg_args,
@@ -1228,6 +1621,14 @@ impl Compiler {
                     Reg::SP.into(),
                 ));
                 Self::copy_bytes(instructions, operand, Operand::Memory(Reg::SP, 0), size);
+            } else if let AsmType::Float = ty {
+                instructions.push(Instruction::Binary(
+                    AsmType::Quadword,
+                    BinaryOp::Sub,
+                    Operand::Imm(8),
+                    Reg::SP.into(),
+                ));
+                instructions.push(Instruction::Mov(ty, operand, Operand::Memory(Reg::SP, 0)));
             } else if matches!(operand, Operand::Imm(_) | Operand::Reg(_))
                 || matches!(ty, AsmType::Quadword | AsmType::Double)
             {
@@ -1238,7 +1639,21 @@ impl Compiler {
             }
         }
 
-        instructions.push(Instruction::Call(name.clone()));
+        if is_variadic {
+            // %al holds an upper bound on the number of vector registers used
+            instructions.push(Instruction::Mov(
+                AsmType::Longword,
+                Operand::Imm(sse_arg_count),
+                Reg::Ax.into(),
+            ));
+        }
+        match target {
+            CallTarget::Function(name) => instructions.push(Instruction::Call(name.clone())),
+            CallTarget::Pointer(ptr) => instructions.push(Instruction::CallIndirect(
+                self.generate_val(ptr),
+                used_registers.args,
+            )),
+        }
 
         if bytes_to_remove != 0 {
             instructions.push(Instruction::Binary(
@@ -1341,7 +1756,7 @@ impl Compiler {
                 operand,
                 ty: asm_ty,
             };
-            if let AsmType::Double = asm_ty {
+            if asm_ty.is_floating() {
                 if sse_reg_args.len() < SSE_ARG_REGISTERS.len() {
                     sse_reg_args.push(operand);
                 } else {
@@ -1367,7 +1782,7 @@ impl Compiler {
                         if let ParamClass::Sse = class {
                             tentative_doubles.push(TypedOperand {
                                 operand,
-                                ty: AsmType::Double,
+                                ty: Self::get_sse_eightbyte_type(offset, type_size),
                             });
                         } else {
                             let eightbyte_type = self.get_eightbyte_type(offset, type_size);
@@ -1423,10 +1838,19 @@ impl Compiler {
         }
     }
 
+    fn get_sse_eightbyte_type(offset: i64, struct_size: usize) -> AsmType {
+        let bytes_from_end = struct_size - offset as usize;
+        if bytes_from_end > 4 {
+            AsmType::Double
+        } else {
+            AsmType::Float
+        }
+    }
+
     fn classify_return_value(&mut self, return_value: &tacky::Val) -> FnReturn {
         let return_ty = self.semantics.val_ty(return_value);
         let asm_ty = self.semantics.val_asm_ty(return_value);
-        if let AsmType::Double = asm_ty {
+        if asm_ty.is_floating() {
             let operand = self.generate_val(return_value);
             FnReturn {
                 int_values: vec![],
@@ -1466,7 +1890,7 @@ impl Compiler {
                         ParamClass::Sse => {
                             sse_values.push(TypedOperand {
                                 operand,
-                                ty: AsmType::Double,
+                                ty: Self::get_sse_eightbyte_type(offset, type_size),
                             });
                         }
                         ParamClass::Integer => {
@@ -1497,89 +1921,48 @@ impl Compiler {
             return (0..size).step_by(8).map(|_| ParamClass::Memory).collect();
         }
 
-        let mut classes: Vec<ParamClass> = Vec::new();
+        // An eightbyte is passed in an SSE register only when all the scalars
+        // that overlap it are floating point.
+        let mut classes: Vec<ParamClass> = (0..size).step_by(8).map(|_| ParamClass::Sse).collect();
 
-        fn classify_inner(classes: &mut Vec<ParamClass>, ty: &Type, semantics: &SemanticData) {
+        fn classify_inner(
+            classes: &mut [ParamClass],
+            ty: &Type,
+            offset: usize,
+            semantics: &SemanticData,
+        ) {
             match ty {
-                Type::Double => classes.push(ParamClass::Sse),
+                Type::Double | Type::Float => {}
 
-                Type::Char
+                Type::Bool
+                | Type::Char
                 | Type::SChar
                 | Type::UChar
+                | Type::Short
+                | Type::UShort
                 | Type::Int
                 | Type::UInt
                 | Type::Long
                 | Type::ULong
-                | Type::Pointer(_) => classes.push(ParamClass::Integer),
+                | Type::Pointer(_) => classes[offset / 8] = ParamClass::Integer,
 
-                Type::Struct(name) => {
-                    let struct_def = semantics.get_aggregate(name);
-                    let mut field_classes: Vec<ParamClass> = Vec::new();
-                    for field in &struct_def.fields {
-                        classify_inner(&mut field_classes, &field.ty, semantics);
-                    }
-
-                    if let Some(ParamClass::Sse) = field_classes.first() {
-                        classes.push(ParamClass::Sse);
-                    } else {
-                        classes.push(ParamClass::Integer);
-                    }
-
-                    if struct_def.size > 8 {
-                        if let Some(ParamClass::Sse) = field_classes.last() {
-                            classes.push(ParamClass::Sse);
-                        } else {
-                            classes.push(ParamClass::Integer);
-                        }
-                    }
-                }
-                Type::Array(inner_ty, ..) => {
-                    let size = ty.size(semantics);
-                    if let Type::Double = &**inner_ty {
-                        classes.push(ParamClass::Sse);
-                        if size > 8 {
-                            classes.push(ParamClass::Sse);
-                        }
-                    } else {
-                        classes.push(ParamClass::Integer);
-                        if size > 8 {
-                            classes.push(ParamClass::Integer);
-                        }
+                Type::Struct(name) | Type::Union(name) => {
+                    for field in &semantics.get_aggregate(name).fields {
+                        classify_inner(classes, &field.ty, offset + field.offset, semantics);
                     }
                 }
-                Type::Union(name) => {
-                    let union_def = semantics.get_aggregate(name);
-                    let mut field_classes = Vec::with_capacity(2);
-                    let mut first_class: Option<ParamClass> = None;
-                    let mut second_class: Option<ParamClass> = None;
-                    for field in &union_def.fields {
-                        classify_inner(&mut field_classes, &field.ty, semantics);
-                        use ParamClass::*;
-                        first_class = match (first_class, field_classes.first()) {
-                            (None | Some(Sse), Some(Sse)) => Some(Sse),
-                            (_, Some(Integer)) => Some(Integer),
-                            (Some(Integer), _) => Some(Integer),
-                            _ => unreachable!("Invalid class"),
-                        };
-                        second_class = match (second_class, field_classes.get(1)) {
-                            (old, None) => old,
-                            (None | Some(Sse), Some(Sse)) => Some(Sse),
-                            (_, Some(Integer)) => Some(Integer),
-                            (Some(Integer), _) => Some(Integer),
-                            _ => unreachable!("Invalid class"),
-                        };
-                        field_classes.clear();
-                    }
-                    classes.push(first_class.unwrap());
-                    if let Some(second_class) = second_class {
-                        classes.push(second_class);
+                Type::Array(inner_ty, length) => {
+                    let inner_size = inner_ty.size(semantics);
+                    for i in 0..*length {
+                        classify_inner(classes, inner_ty, offset + i * inner_size, semantics);
                     }
                 }
 
-                Type::Function(_) | Type::Void => {}
+                Type::Qualified(_, inner) => classify_inner(classes, inner, offset, semantics),
+                Type::VariableArray(..) | Type::Function(_) | Type::Void => {}
             }
         }
-        classify_inner(&mut classes, ty, &self.semantics);
+        classify_inner(&mut classes, ty, 0, &self.semantics);
         classes
     }
 
@@ -1593,6 +1976,9 @@ impl Compiler {
         } else {
             0
         };
+        if self.is_variadic(&function.name) {
+            stack_size += REGISTER_SAVE_AREA_SIZE as usize;
+        }
         let mut stack_vars = HashMap::new();
 
         let mut update_operand = |operand: &mut Operand| {
@@ -1601,6 +1987,7 @@ impl Compiler {
                     ty,
                     is_static,
                     is_const,
+                    tls,
                 }) = symbols.get(name)
                 else {
                     panic!("Operand '{name}' without symbol data")
@@ -1618,12 +2005,16 @@ impl Compiler {
                         is_static: is_const,
                         name,
                         offset,
+                        tls,
                     };
                     return;
                 } else {
                     match ty {
                         AsmType::Byte => stack_size += 1,
-                        AsmType::Longword => {
+                        AsmType::Word => {
+                            stack_size = align_offset(stack_size + 2, 2);
+                        }
+                        AsmType::Longword | AsmType::Float => {
                             stack_size = align_offset(stack_size + 4, 8);
                         }
                         AsmType::Quadword | AsmType::Double => {
@@ -1633,6 +2024,16 @@ impl Compiler {
                             stack_size = align_offset(stack_size + size, alignment);
                         }
                     }
+                    // The frame pointer is aligned to 16 bytes, which is the
+                    // most the type checker allows for local variables
+                    if let Some(aligned) = self
+                        .semantics
+                        .symbols
+                        .get(name)
+                        .and_then(|data| data.decl_attrs.aligned)
+                    {
+                        stack_size = align_offset(stack_size, aligned);
+                    }
                     stack_vars.insert(name.clone(), stack_size);
                     stack_size
                 };
@@ -1653,6 +2054,10 @@ impl Compiler {
                 | Instruction::Binary(_, _, src, dst)
                 | Instruction::Cvttsd2si(_, src, dst)
                 | Instruction::Cvtsi2sd(_, src, dst)
+                | Instruction::Cvttss2si(_, src, dst)
+                | Instruction::Cvtsi2ss(_, src, dst)
+                | Instruction::Cvtss2sd(src, dst)
+                | Instruction::Cvtsd2ss(src, dst)
                 | Instruction::Lea(src, dst)
                 | Instruction::Cmp(_, src, dst) => {
                     update_operand(src);
@@ -1662,7 +2067,8 @@ impl Compiler {
                 | Instruction::Push(src)
                 | Instruction::Idiv(_, src)
                 | Instruction::Div(_, src)
-                | Instruction::SetCC(_, src) => update_operand(src),
+                | Instruction::SetCC(_, src)
+                | Instruction::CallIndirect(src, _) => update_operand(src),
 
                 Instruction::Cdq(_)
                 | Instruction::Jmp(_)
@@ -1726,7 +2132,7 @@ impl Compiler {
         }
 
         fn src_register(ty: AsmType) -> Reg {
-            if let AsmType::Double = ty {
+            if ty.is_floating() {
                 Reg::XMM14
             } else {
                 Reg::R10
@@ -1734,7 +2140,7 @@ impl Compiler {
         }
 
         fn dst_register(ty: AsmType) -> Reg {
-            if let AsmType::Double = ty {
+            if ty.is_floating() {
                 Reg::XMM15
             } else {
                 Reg::R11
@@ -1749,9 +2155,11 @@ impl Compiler {
                         && !dst.is_reg()
                     {
                         let value = match ty {
-                            AsmType::Byte | AsmType::Longword => (v as i32) as i64,
+                            AsmType::Byte | AsmType::Word | AsmType::Longword => (v as i32) as i64,
                             AsmType::Quadword => v,
-                            AsmType::Double => panic!("Immediate values, can't be double"),
+                            AsmType::Double | AsmType::Float => {
+                                panic!("Immediate values, can't be floating")
+                            }
                             AsmType::ByteArray { .. } => {
                                 panic!("Immediate values can't be byte array")
                             }
@@ -1809,9 +2217,7 @@ impl Compiler {
                     } else {
                         left
                     };
-                    if (matches!(op, BinaryOp::Mul) || matches!(ty, AsmType::Double))
-                        && right.is_mem()
-                    {
+                    if (matches!(op, BinaryOp::Mul) || ty.is_floating()) && right.is_mem() {
                         let dst_reg = dst_register(ty);
                         fixed.push(Instruction::Mov(ty, right.clone(), dst_reg.into()));
                         fixed.push(Instruction::Binary(ty, op, left, dst_reg.into()));
@@ -1820,15 +2226,15 @@ impl Compiler {
                         fixed.push(Instruction::Binary(ty, op, left, right));
                     }
                 }
-                Instruction::Cmp(AsmType::Double, left, right) => {
+                Instruction::Cmp(ty, left, right) if ty.is_floating() => {
                     let right = if let Operand::Reg(_) = right {
                         right
                     } else {
-                        let dst_reg = dst_register(AsmType::Double);
-                        fixed.push(Instruction::Mov(AsmType::Double, right, dst_reg.into()));
+                        let dst_reg = dst_register(ty);
+                        fixed.push(Instruction::Mov(ty, right, dst_reg.into()));
                         dst_reg.into()
                     };
-                    fixed.push(Instruction::Cmp(AsmType::Double, left, right));
+                    fixed.push(Instruction::Cmp(ty, left, right));
                 }
                 Instruction::Cmp(ty, left, right) => {
                     let left = if let Operand::Imm(v) = left
@@ -1860,6 +2266,14 @@ impl Compiler {
                     fixed.push(Instruction::Mov(ty, Operand::Imm(value), Reg::R10.into()));
                     fixed.push(Instruction::Div(ty, Reg::R10.into()));
                 }
+                Instruction::CallIndirect(Operand::Imm(value), arg_registers) => {
+                    fixed.push(Instruction::Mov(
+                        AsmType::Quadword,
+                        Operand::Imm(value),
+                        Reg::R10.into(),
+                    ));
+                    fixed.push(Instruction::CallIndirect(Reg::R10.into(), arg_registers));
+                }
                 Instruction::Push(Operand::Reg(reg)) if reg.is_xmm() => {
                     fixed.push(Instruction::Binary(
                         AsmType::Quadword,
@@ -1890,11 +2304,10 @@ impl Compiler {
                 }
 
                 Instruction::MovZeroExtend(src_ty, src, dst_ty, dst)
-                    if matches!(src_ty, AsmType::Byte) =>
+                    if matches!(src_ty, AsmType::Byte | AsmType::Word) =>
                 {
-                    let src = if let Operand::Imm(v) = src
-                        && i32::try_from(v).is_err()
-                    {
+                    // movz doesn't take immediate operands
+                    let src = if let Operand::Imm(_) = src {
                         fixed.push(Instruction::Mov(src_ty, src, Reg::R10.into()));
                         Reg::R10.into()
                     } else {
@@ -1929,6 +2342,14 @@ impl Compiler {
                         Operand::Memory(mem_reg, offset),
                     ));
                 }
+                Instruction::Cvttss2si(ty, src, Operand::Memory(mem_reg, offset)) => {
+                    fixed.push(Instruction::Cvttss2si(ty, src, Reg::R11.into()));
+                    fixed.push(Instruction::Mov(
+                        ty,
+                        Reg::R11.into(),
+                        Operand::Memory(mem_reg, offset),
+                    ));
+                }
                 Instruction::Lea(src, Operand::Memory(mem_reg, offset)) => {
                     fixed.push(Instruction::Lea(src, Reg::R11.into()));
                     fixed.push(Instruction::Mov(
@@ -1952,7 +2373,39 @@ impl Compiler {
                         fixed.push(Instruction::Cvtsi2sd(ty, src, dst));
                     }
                 }
+                Instruction::Cvtsi2ss(ty, src, dst) => {
+                    let src = if let Operand::Imm(_) = src {
+                        fixed.push(Instruction::Mov(ty, src, src_register(ty).into()));
+                        src_register(ty).into()
+                    } else {
+                        src
+                    };
+                    if dst.is_mem() {
+                        let dst_reg = dst_register(AsmType::Float);
+                        fixed.push(Instruction::Cvtsi2ss(ty, src, dst_reg.into()));
+                        fixed.push(Instruction::Mov(AsmType::Float, dst_reg.into(), dst));
+                    } else {
+                        fixed.push(Instruction::Cvtsi2ss(ty, src, dst));
+                    }
+                }
+                Instruction::Cvtss2sd(src, dst) if dst.is_mem() => {
+                    let dst_reg = dst_register(AsmType::Double);
+                    fixed.push(Instruction::Cvtss2sd(src, dst_reg.into()));
+                    fixed.push(Instruction::Mov(AsmType::Double, dst_reg.into(), dst));
+                }
+                Instruction::Cvtsd2ss(src, dst) if dst.is_mem() => {
+                    let dst_reg = dst_register(AsmType::Float);
+                    fixed.push(Instruction::Cvtsd2ss(src, dst_reg.into()));
+                    fixed.push(Instruction::Mov(AsmType::Float, dst_reg.into(), dst));
+                }
                 Instruction::Ret => {
+                    if function.allocates_stack && !callee_saved_registers.is_empty() {
+                        let saved_bytes = callee_saved_registers.len() * 8;
+                        fixed.push(Instruction::Lea(
+                            Operand::Memory(Reg::BP, -((adjusted_stack_size + saved_bytes) as i64)),
+                            Reg::SP.into(),
+                        ));
+                    }
                     for &reg in callee_saved_registers.iter().rev() {
                         fixed.push(Instruction::Pop(reg));
                     }
@@ -1969,6 +2422,8 @@ impl Compiler {
             tacky::Val::Constant(value) => {
                 if let Constant::Double(double) = value {
                     self.make_double_constant(*double)
+                } else if let Constant::Float(float) = value {
+                    self.make_float_constant(*float)
                 } else {
                     Operand::Imm(value.as_u64() as i64)
                 }
@@ -1995,6 +2450,50 @@ impl Compiler {
             is_static: true,
             name,
             offset: 0,
+            tls: None,
+        }
+    }
+
+    fn make_float_constant(&mut self, value: f32) -> Operand {
+        let key = value.to_bits();
+        let existing_constant = self.floats.get(&key);
+        let name = match existing_constant {
+            Some(name) => name.clone(),
+            None => {
+                let name = Symbol::from(format!("_float_{}", self.floats.len()));
+                self.floats.insert(key, name.clone());
+                name
+            }
+        };
+        Operand::Data {
+            is_static: true,
+            name,
+            offset: 0,
+            tls: None,
+        }
+    }
+
+    fn make_floating_constant(&mut self, ty: AsmType, value: f64) -> Operand {
+        if let AsmType::Float = ty {
+            self.make_float_constant(value as f32)
+        } else {
+            self.make_double_constant(value)
+        }
+    }
+
+    fn floating_to_int(src_ty: AsmType, ty: AsmType, src: Operand, dst: Operand) -> Instruction {
+        if let AsmType::Float = src_ty {
+            Instruction::Cvttss2si(ty, src, dst)
+        } else {
+            Instruction::Cvttsd2si(ty, src, dst)
+        }
+    }
+
+    fn int_to_floating(dst_ty: AsmType, ty: AsmType, src: Operand, dst: Operand) -> Instruction {
+        if let AsmType::Float = dst_ty {
+            Instruction::Cvtsi2ss(ty, src, dst)
+        } else {
+            Instruction::Cvtsi2sd(ty, src, dst)
         }
     }
 
@@ -2045,7 +2544,8 @@ impl AsmType {
     fn size(&self) -> usize {
         match self {
             AsmType::Byte => 1,
-            AsmType::Longword => 4,
+            AsmType::Word => 2,
+            AsmType::Longword | AsmType::Float => 4,
             AsmType::Quadword | AsmType::Double => 8,
             AsmType::ByteArray { size, .. } => *size,
         }
@@ -2054,15 +2554,25 @@ impl AsmType {
     fn alignment(&self) -> u8 {
         match self {
             AsmType::Byte => 1,
-            AsmType::Longword => 4,
+            AsmType::Word => 2,
+            AsmType::Longword | AsmType::Float => 4,
             AsmType::Quadword | AsmType::Double => 8,
             AsmType::ByteArray { alignment, .. } => *alignment,
         }
     }
 
+    fn is_floating(&self) -> bool {
+        matches!(self, AsmType::Double | AsmType::Float)
+    }
+
     fn is_scalar(&self) -> bool {
         match self {
-            AsmType::Byte | AsmType::Longword | AsmType::Quadword | AsmType::Double => true,
+            AsmType::Byte
+            | AsmType::Word
+            | AsmType::Longword
+            | AsmType::Quadword
+            | AsmType::Double
+            | AsmType::Float => true,
             AsmType::ByteArray { .. } => false,
         }
     }
@@ -2080,7 +2590,8 @@ impl tacky::Val {
         let tacky::Val::Var(value_name) = self else {
             panic!("Non-scalar value that is not a struct");
         };
-        let (Type::Struct(type_name) | Type::Union(type_name)) = semantics.symbol_ty(value_name)
+        let (Type::Struct(type_name) | Type::Union(type_name)) =
+            semantics.symbol_ty(value_name).unqualified()
         else {
             panic!("Non-scalar value that is not a struct");
         };
@@ -2095,7 +2606,10 @@ impl Operand {
     fn is_mem(&self) -> bool {
         matches!(
             self,
-            Operand::Memory(..) | Operand::Data { .. } | Operand::Indexed(..)
+            Operand::Memory(..)
+                | Operand::Data { .. }
+                | Operand::Indexed(..)
+                | Operand::GotEntry(_)
         )
     }
 
@@ -2111,6 +2625,7 @@ impl Operand {
 pub fn generate(program: &tacky::Program) -> Program {
     let mut compiler = Compiler {
         doubles: HashMap::new(),
+        floats: HashMap::new(),
         call_registers: Default::default(),
         aliased_vars: Default::default(),
         label_counter: 0,
@@ -1,5 +1,6 @@
 use crate::asm::ir::Instruction;
 use crate::optimization::cfg::{GenericCfg, GenericInstruction, GenericNode, InstructionKind};
+use crate::symbol::Symbol;
 use std::fmt::Formatter;
 
 pub type Cfg = GenericCfg<Instruction>;
@@ -23,4 +24,15 @@ impl GenericInstruction for Instruction {
     fn pp(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{self:?}")
     }
+
+    fn label(label: Symbol) -> Self {
+        Instruction::Label(label)
+    }
+
+    fn retarget(&mut self, label: Symbol) {
+        match self {
+            Instruction::Jmp(target) | Instruction::JmpCC(_, target) => *target = label,
+            _ => panic!("Not a jump"),
+        }
+    }
 }
@@ -17,14 +17,23 @@ pub enum TopLevel {
 pub struct Function {
     pub name: Symbol,
     pub global: bool,
+    pub weak: bool,
+    pub alignment: Option<u8>,
+    pub section: Option<Symbol>,
     pub instructions: Vec<Instruction>,
+    /// Whether the stack grows while running the function, after which the
+    /// stack pointer no longer points to the saved registers.
+    pub allocates_stack: bool,
 }
 
 #[derive(Debug)]
 pub struct StaticVariable {
     pub name: Symbol,
     pub global: bool,
+    pub weak: bool,
+    pub thread_local: bool,
     pub alignment: u8,
+    pub section: Option<Symbol>,
     pub init: Vec<StaticInit>,
 }
 
@@ -43,6 +52,10 @@ pub enum Instruction {
     Lea(Operand, Operand),
     Cvttsd2si(AsmType, Operand, Operand),
     Cvtsi2sd(AsmType, Operand, Operand),
+    Cvttss2si(AsmType, Operand, Operand),
+    Cvtsi2ss(AsmType, Operand, Operand),
+    Cvtss2sd(Operand, Operand),
+    Cvtsd2ss(Operand, Operand),
     Unary(AsmType, UnaryOp, Operand),
     Binary(AsmType, BinaryOp, Operand, Operand),
     Cmp(AsmType, Operand, Operand),
@@ -56,6 +69,7 @@ pub enum Instruction {
     Push(Operand),
     Pop(Reg),
     Call(Symbol),
+    CallIndirect(Operand, Vec<Reg>),
     Ret,
 }
 
@@ -90,9 +104,22 @@ pub enum Operand {
         is_static: bool,
         name: Symbol,
         offset: i64,
+        tls: Option<TlsModel>,
     },
     Memory(Reg, i64),
     Indexed(Reg, Reg, u8),
+    GotEntry(Symbol),
+}
+
+/// How the offset of a thread local variable from the thread pointer, held
+/// in `%fs`, is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsModel {
+    /// Known at link time, for variables defined in the program.
+    LocalExec,
+    /// Loaded from the GOT, for variables that might be defined in a shared
+    /// library. These are only accessed through their address.
+    InitialExec,
 }
 
 impl From<Reg> for Operand {
@@ -140,9 +167,11 @@ pub enum Reg {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AsmType {
     Byte,
+    Word,
     Longword,
     Quadword,
     Double,
+    Float,
     ByteArray { size: usize, alignment: u8 },
 }
 
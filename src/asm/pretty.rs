@@ -29,7 +29,16 @@ pub(super) fn pp_function(stream: &mut impl Write, f: &Function) -> Result<()> {
 
 fn pp_static_variable(stream: &mut impl Write, var: &StaticVariable) -> Result<()> {
     let global = if var.global { "global " } else { "" };
-    writeln!(stream, "{global}[{}] static {} = ", var.alignment, var.name)?;
+    let thread_local = if var.thread_local {
+        "thread_local "
+    } else {
+        ""
+    };
+    writeln!(
+        stream,
+        "{global}[{}] {thread_local}static {} = ",
+        var.alignment, var.name
+    )?;
     for init in &var.init {
         write!(stream, "    ")?;
         pp_static_init(stream, init)?;
@@ -42,11 +51,14 @@ fn pp_static_init(stream: &mut impl Write, init: &StaticInit) -> Result<()> {
     match init {
         StaticInit::Char(v) => writeln!(stream, "{:?}", (*v as u8) as char)?,
         StaticInit::UChar(v) => writeln!(stream, "{}UC", *v)?,
+        StaticInit::Short(v) => writeln!(stream, "{v}S")?,
+        StaticInit::UShort(v) => writeln!(stream, "{v}US")?,
         StaticInit::Int(v) => writeln!(stream, "{v}")?,
         StaticInit::UInt(v) => writeln!(stream, "{v}U")?,
         StaticInit::Long(v) => writeln!(stream, "{v}L")?,
         StaticInit::ULong(v) => writeln!(stream, "{v}UL")?,
         StaticInit::Double(v) => writeln!(stream, "{v}D")?,
+        StaticInit::Float(v) => writeln!(stream, "{v}F")?,
         StaticInit::ZeroInit(v) => writeln!(stream, "zero[{v}]")?,
         StaticInit::String {
             symbol,
@@ -59,7 +71,13 @@ fn pp_static_init(stream: &mut impl Write, init: &StaticInit) -> Result<()> {
             };
             writeln!(stream, "{:?}", s)?
         }
-        StaticInit::Pointer(name) => writeln!(stream, "&{name}")?,
+        StaticInit::Pointer(name, offset) | StaticInit::Address(name, offset) => {
+            if *offset != 0 {
+                writeln!(stream, "&{name}{offset:+}")?
+            } else {
+                writeln!(stream, "&{name}")?
+            }
+        }
     }
     Ok(())
 }
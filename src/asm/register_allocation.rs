@@ -89,8 +89,13 @@ fn allocate_general_purpose_regs(function: &mut Function, symbols: &mut BackendS
 fn allocate_sse_regs(function: &mut Function, symbols: &mut BackendSymbolTable) {
     let mut interference_graph;
     loop {
-        interference_graph =
-            build_interference_graph(function, symbols, &SSE_REGS, &[AsmType::Double], &SSE_REGS);
+        interference_graph = build_interference_graph(
+            function,
+            symbols,
+            &SSE_REGS,
+            &[AsmType::Double, AsmType::Float],
+            &SSE_REGS,
+        );
         let coalesced_regs = coalesce(
             &mut interference_graph,
             &function.instructions,
@@ -396,7 +401,11 @@ fn walk_operands(instructions: &[Instruction], mut lambda: impl FnMut(&Operand))
             | Instruction::Cmp(_, op1, op2)
             | Instruction::Lea(op1, op2)
             | Instruction::Cvttsd2si(_, op1, op2)
-            | Instruction::Cvtsi2sd(_, op1, op2) => {
+            | Instruction::Cvtsi2sd(_, op1, op2)
+            | Instruction::Cvttss2si(_, op1, op2)
+            | Instruction::Cvtsi2ss(_, op1, op2)
+            | Instruction::Cvtss2sd(op1, op2)
+            | Instruction::Cvtsd2ss(op1, op2) => {
                 lambda(op1);
                 lambda(op2);
             }
@@ -405,7 +414,8 @@ fn walk_operands(instructions: &[Instruction], mut lambda: impl FnMut(&Operand))
             | Instruction::Idiv(_, op)
             | Instruction::SetCC(_, op)
             | Instruction::Push(op)
-            | Instruction::Div(_, op) => {
+            | Instruction::Div(_, op)
+            | Instruction::CallIndirect(op, _) => {
                 lambda(op);
             }
 
@@ -469,6 +479,10 @@ fn rewrite_instructions(instructions: &mut [Instruction], rewrite_fn: impl Fn(&m
             | Instruction::Lea(op1, op2)
             | Instruction::Cvttsd2si(_, op1, op2)
             | Instruction::Cvtsi2sd(_, op1, op2)
+            | Instruction::Cvttss2si(_, op1, op2)
+            | Instruction::Cvtsi2ss(_, op1, op2)
+            | Instruction::Cvtss2sd(op1, op2)
+            | Instruction::Cvtsd2ss(op1, op2)
             | Instruction::Binary(_, _, op1, op2)
             | Instruction::Cmp(_, op1, op2) => {
                 rewrite_fn(op1);
@@ -478,7 +492,8 @@ fn rewrite_instructions(instructions: &mut [Instruction], rewrite_fn: impl Fn(&m
             | Instruction::Idiv(_, op)
             | Instruction::Div(_, op)
             | Instruction::Push(op)
-            | Instruction::SetCC(_, op) => {
+            | Instruction::SetCC(_, op)
+            | Instruction::CallIndirect(op, _) => {
                 rewrite_fn(op);
             }
             Instruction::Cdq(_)
@@ -564,6 +579,10 @@ fn find_used_and_updated(
         | Instruction::MovZeroExtend(_, src, _, dst)
         | Instruction::Cvttsd2si(_, src, dst)
         | Instruction::Cvtsi2sd(_, src, dst)
+        | Instruction::Cvttss2si(_, src, dst)
+        | Instruction::Cvtsi2ss(_, src, dst)
+        | Instruction::Cvtss2sd(src, dst)
+        | Instruction::Cvtsd2ss(src, dst)
         | Instruction::Lea(src, dst) => UsedAndUpdated {
             used: vec![src.clone()],
             updated: vec![dst.clone()],
@@ -617,6 +636,17 @@ fn find_used_and_updated(
                     .collect(),
             }
         }
+        Instruction::CallIndirect(target, arg_registers) => {
+            let mut used = vec![target.clone()];
+            used.extend(arg_registers.iter().map(|&reg| Operand::from(reg)));
+            UsedAndUpdated {
+                used,
+                updated: caller_saved_registers
+                    .iter()
+                    .map(|&reg| reg.into())
+                    .collect(),
+            }
+        }
         Instruction::Jmp(_)
         | Instruction::Label(_)
         | Instruction::Ret
@@ -2,6 +2,8 @@ pub mod pretty;
 
 use crate::lexer::Span;
 use crate::symbol::Symbol;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 
@@ -117,18 +119,24 @@ pub enum Expression {
         else_expr: Node<Expression>,
     },
     FunctionCall {
-        name: Node<Identifier>,
+        function: Node<Expression>,
         args: Vec<Node<Expression>>,
     },
     Cast {
         target: Node<TypeSpec>,
         expr: Node<Expression>,
     },
+    CompoundLiteral {
+        type_spec: Node<TypeSpec>,
+        init: Node<Initializer>,
+    },
     Dereference(Node<Expression>),
     AddressOf(Node<Expression>),
     Subscript(Node<Expression>, Node<Expression>),
     SizeOfExpr(Node<Expression>),
     SizeOfType(Node<TypeSpec>),
+    AlignOfType(Node<TypeSpec>),
+    Comma(Node<Expression>, Node<Expression>),
     Dot {
         aggregate: Node<Expression>,
         field: Node<Identifier>,
@@ -137,6 +145,10 @@ pub enum Expression {
         pointer: Node<Expression>,
         field: Node<Identifier>,
     },
+    VaStart(Node<Expression>, Node<Expression>),
+    VaArg(Node<Expression>, Node<TypeSpec>),
+    VaEnd(Node<Expression>),
+    VaCopy(Node<Expression>, Node<Expression>),
 }
 
 impl Expression {
@@ -157,8 +169,11 @@ pub enum Constant {
     Long(i64),
     ULong(u64),
     Double(f64),
+    Float(f32),
     Char(i8),
     UChar(u8),
+    Short(i16),
+    UShort(u16),
 }
 
 impl Hash for Constant {
@@ -186,30 +201,82 @@ pub enum Declaration {
     Function(FunctionDeclaration),
     Struct(NameAndFields),
     Union(NameAndFields),
+    Enum(EnumDeclaration),
+    TypeDef(TypeDefDeclaration),
+    StaticAssert(StaticAssert),
 }
 
 #[derive(Debug)]
 pub enum TypeSpec {
+    Bool,
     Char,
     SChar,
     UChar,
+    Short,
+    UShort,
     Int,
     UInt,
     Long,
     ULong,
     Double,
+    Float,
     Function(FunctionTypeSpec),
     Pointer(Node<TypeSpec>),
     Array(Node<TypeSpec>, usize),
+    /// Array sized by an expression, variable length unless it turns out to
+    /// be an integer constant expression during type checking.
+    VariableArray(Node<TypeSpec>, Node<Expression>),
+    /// Array whose length is given by its initializer, as in `(int[]){1, 2}`.
+    UnsizedArray(Node<TypeSpec>),
     Struct(Node<Identifier>),
     Union(Node<Identifier>),
+    Enum(Option<Node<Identifier>>),
+    Named(Node<Identifier>),
+    Qualified(Qualifiers, Node<TypeSpec>),
+    VaList,
     Void,
 }
 
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+}
+
+impl Qualifiers {
+    pub fn is_empty(&self) -> bool {
+        !self.is_const && !self.is_volatile
+    }
+
+    pub fn union(self, other: Qualifiers) -> Qualifiers {
+        Qualifiers {
+            is_const: self.is_const || other.is_const,
+            is_volatile: self.is_volatile || other.is_volatile,
+        }
+    }
+
+    /// Whether every qualifier in `other` is also present in `self`.
+    pub fn contains(&self, other: Qualifiers) -> bool {
+        (self.is_const || !other.is_const) && (self.is_volatile || !other.is_volatile)
+    }
+}
+
+impl Display for Qualifiers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.is_const, self.is_volatile) {
+            (true, true) => write!(f, "const volatile"),
+            (true, false) => write!(f, "const"),
+            (false, true) => write!(f, "volatile"),
+            (false, false) => Ok(()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FunctionTypeSpec {
     pub params: Vec<Node<TypeSpec>>,
     pub ret: Node<TypeSpec>,
+    pub variadic: bool,
 }
 
 #[derive(Debug)]
@@ -218,6 +285,8 @@ pub struct VarDeclaration {
     pub init: Option<Node<Initializer>>,
     pub type_spec: Node<TypeSpec>,
     pub storage_class: Option<Node<StorageClass>>,
+    pub is_thread_local: bool,
+    pub attributes: Vec<Node<Attribute>>,
 }
 
 #[derive(Debug)]
@@ -227,24 +296,91 @@ pub struct FunctionDeclaration {
     pub body: Option<Node<Block>>,
     pub type_spec: FunctionTypeSpec,
     pub storage_class: Option<Node<StorageClass>>,
+    pub is_thread_local: bool,
+    pub attributes: Vec<Node<Attribute>>,
+}
+
+#[derive(Debug)]
+pub struct EnumDeclaration {
+    pub name: Option<Node<Identifier>>,
+    pub enumerators: Vec<Node<Enumerator>>,
+}
+
+#[derive(Debug)]
+pub struct Enumerator {
+    pub name: Node<Identifier>,
+    pub value: Option<Node<Expression>>,
+}
+
+#[derive(Debug)]
+pub struct TypeDefDeclaration {
+    pub name: Node<Identifier>,
+    pub type_spec: Node<TypeSpec>,
+}
+
+#[derive(Debug)]
+pub struct StaticAssert {
+    pub condition: Node<Expression>,
+    pub message: Symbol,
 }
 
 #[derive(Debug)]
 pub struct NameAndFields {
     pub name: Node<Identifier>,
     pub fields: Vec<Node<Field>>,
+    /// Static assertions declared among the fields.
+    pub static_asserts: Vec<StaticAssert>,
+    pub attributes: Vec<Node<Attribute>>,
 }
 
 #[derive(Debug)]
 pub struct Field {
-    pub name: Node<Identifier>,
+    /// Only bit-fields can be unnamed.
+    pub name: Option<Node<Identifier>>,
     pub type_spec: Node<TypeSpec>,
+    pub width: Option<Node<Expression>>,
+    pub attributes: Vec<Node<Attribute>>,
 }
 
 #[derive(Debug)]
 pub enum Initializer {
     Single(Node<Expression>),
-    Compound(Vec<Node<Initializer>>),
+    Compound(Vec<InitializerItem>),
+}
+
+impl Initializer {
+    /// All expressions initializing scalars or whole aggregates, by node id.
+    pub fn expressions(&self) -> HashMap<NodeId, &Node<Expression>> {
+        let mut expressions = HashMap::new();
+        self.collect_expressions(&mut expressions);
+        expressions
+    }
+
+    fn collect_expressions<'a>(&'a self, expressions: &mut HashMap<NodeId, &'a Node<Expression>>) {
+        match self {
+            Initializer::Single(expr) => {
+                expressions.insert(expr.id, expr);
+            }
+            Initializer::Compound(items) => {
+                for item in items {
+                    item.init.collect_expressions(expressions);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InitializerItem {
+    pub designators: Vec<Node<Designator>>,
+    pub init: Node<Initializer>,
+}
+
+#[derive(Debug)]
+pub enum Designator {
+    Field(Node<Identifier>),
+    /// Evaluated as an integer constant expression during type checking.
+    Index(Node<Expression>),
 }
 
 #[derive(Debug)]
@@ -253,10 +389,24 @@ pub enum StorageClass {
     Extern,
 }
 
+/// `_Noreturn` and the GCC attributes given with `__attribute__((...))`
+/// that have an effect, other attributes are dropped by the parser.
+#[derive(Debug)]
+pub enum Attribute {
+    Noreturn,
+    /// Without an argument, the largest alignment of the target.
+    Aligned(Option<Node<Expression>>),
+    Packed,
+    Unused,
+    Weak,
+    Section(Symbol),
+}
+
 #[derive(Debug)]
 pub enum UnaryOp {
     Complement,
     Negate,
+    Plus,
     Not,
     Increment,
     Decrement,
@@ -343,18 +493,21 @@ impl<T> InnerRef<T> for Option<Node<T>> {
 
 impl Constant {
     pub fn is_int(&self) -> bool {
-        !matches!(self, Constant::Double(_))
+        !matches!(self, Constant::Double(_) | Constant::Float(_))
     }
 
     pub fn as_u64(&self) -> u64 {
         match self {
             Constant::Char(v) => *v as u64,
             Constant::UChar(v) => *v as u64,
+            Constant::Short(v) => *v as u64,
+            Constant::UShort(v) => *v as u64,
             Constant::Int(v) => *v as u64,
             Constant::UInt(v) => *v as u64,
             Constant::Long(v) => *v as u64,
             Constant::ULong(v) => *v,
             Constant::Double(v) => (*v).to_bits(),
+            Constant::Float(v) => (*v).to_bits() as u64,
         }
     }
 
@@ -362,11 +515,14 @@ impl Constant {
         match self {
             Constant::Char(v) => *v == 0,
             Constant::UChar(v) => *v == 0,
+            Constant::Short(v) => *v == 0,
+            Constant::UShort(v) => *v == 0,
             Constant::Int(v) => *v == 0,
             Constant::UInt(v) => *v == 0,
             Constant::Long(v) => *v == 0,
             Constant::ULong(v) => *v == 0,
             Constant::Double(v) => *v == 0.0,
+            Constant::Float(v) => *v == 0.0,
         }
     }
 }
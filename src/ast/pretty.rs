@@ -1,7 +1,8 @@
 use crate::ast::{
-    AssignOp, BinaryOp, BlockItem, Constant, Declaration, Expression, Field, ForInit,
-    FunctionDeclaration, Identifier, Initializer, NameAndFields, Node, NodeId, PostfixOp, Program,
-    Statement, StorageClass, TypeSpec, UnaryOp, VarDeclaration,
+    AssignOp, Attribute, BinaryOp, BlockItem, Constant, Declaration, Designator, EnumDeclaration,
+    Expression, Field, ForInit, FunctionDeclaration, Identifier, Initializer, NameAndFields, Node,
+    NodeId, PostfixOp, Program, Statement, StaticAssert, StorageClass, TypeDefDeclaration,
+    TypeSpec, UnaryOp, VarDeclaration,
 };
 use std::fmt::Write;
 
@@ -32,50 +33,89 @@ impl PrettyAst {
             Declaration::Function(d) => Self::from_function_declaration(d),
             Declaration::Struct(d) => Self::from_struct_declaration(d),
             Declaration::Union(d) => Self::from_union_declaration(d),
+            Declaration::Enum(d) => Self::from_enum_declaration(d),
+            Declaration::TypeDef(d) => Self::from_typedef_declaration(d),
+            Declaration::StaticAssert(d) => Self::from_static_assert(d),
         }
     }
-    fn from_struct_declaration(s: &NameAndFields) -> PrettyAst {
+    fn from_static_assert(static_assert: &StaticAssert) -> PrettyAst {
         Self::new(
-            format!("Struct [{}]", &s.name.symbol),
-            s.fields.iter().map(Self::from_field),
+            format!("StaticAssert \"{}\"", static_assert.message),
+            vec![Self::from_expression(&static_assert.condition)],
         )
     }
-    fn from_union_declaration(s: &NameAndFields) -> PrettyAst {
+    fn from_enum_declaration(declaration: &EnumDeclaration) -> PrettyAst {
+        let label = match &declaration.name {
+            Some(name) => format!("Enum [{}]", name.symbol),
+            None => "Enum".to_string(),
+        };
         Self::new(
-            format!("Union [{}]", &s.name.symbol),
-            s.fields.iter().map(Self::from_field),
+            label,
+            declaration.enumerators.iter().map(|enumerator| {
+                Self::new(
+                    format!("Enumerator [{}]", enumerator.name.symbol),
+                    enumerator.value.iter().map(Self::from_expression),
+                )
+            }),
         )
     }
-    fn from_field(field: &Node<Field>) -> PrettyAst {
+    fn from_typedef_declaration(declaration: &TypeDefDeclaration) -> PrettyAst {
         Self::new(
-            "Field",
+            "TypeDef",
             vec![
-                Self::new("Name", vec![Self::from_identifier(&field.name)]),
-                Self::new("Type", vec![Self::from_type(&field.type_spec)]),
+                Self::new("Name", vec![Self::from_identifier(&declaration.name)]),
+                Self::new("Type", vec![Self::from_type(&declaration.type_spec)]),
             ],
         )
     }
+    fn from_struct_declaration(s: &NameAndFields) -> PrettyAst {
+        let mut children: Vec<_> = s.fields.iter().map(Self::from_field).collect();
+        children.extend(s.static_asserts.iter().map(Self::from_static_assert));
+        children.extend(Self::from_attributes(&s.attributes));
+        Self::new(format!("Struct [{}]", &s.name.symbol), children)
+    }
+    fn from_union_declaration(s: &NameAndFields) -> PrettyAst {
+        let mut children: Vec<_> = s.fields.iter().map(Self::from_field).collect();
+        children.extend(s.static_asserts.iter().map(Self::from_static_assert));
+        children.extend(Self::from_attributes(&s.attributes));
+        Self::new(format!("Union [{}]", &s.name.symbol), children)
+    }
+    fn from_field(field: &Node<Field>) -> PrettyAst {
+        let mut children = Vec::new();
+        if let Some(name) = &field.name {
+            children.push(Self::new("Name", vec![Self::from_identifier(name)]));
+        }
+        children.push(Self::new("Type", vec![Self::from_type(&field.type_spec)]));
+        if let Some(width) = &field.width {
+            children.push(Self::new("Width", vec![Self::from_expression(width)]));
+        }
+        children.extend(Self::from_attributes(&field.attributes));
+        Self::new("Field", children)
+    }
     fn from_function_declaration(function: &FunctionDeclaration) -> PrettyAst {
         let mut children = Vec::new();
 
         if !function.params.is_empty() {
-            children.push(Self::new(
-                "Parameters",
-                function
-                    .params
-                    .iter()
-                    .zip(function.type_spec.params.iter())
-                    .map(|(name, ty)| {
-                        Self::new(
-                            "Param",
-                            vec![
-                                Self::new("Name", vec![Self::from_identifier(name)]),
-                                Self::new("Type", vec![Self::from_type(ty)]),
-                            ],
-                        )
-                    }),
-            ))
+            let mut params: Vec<_> = function
+                .params
+                .iter()
+                .zip(function.type_spec.params.iter())
+                .map(|(name, ty)| {
+                    let mut param = Vec::new();
+                    // Parameters of prototypes may be unnamed
+                    if !name.symbol.as_ref().is_empty() {
+                        param.push(Self::new("Name", vec![Self::from_identifier(name)]));
+                    }
+                    param.push(Self::new("Type", vec![Self::from_type(ty)]));
+                    Self::new("Param", param)
+                })
+                .collect();
+            if function.type_spec.variadic {
+                params.push(Self::new("...", vec![]));
+            }
+            children.push(Self::new("Parameters", params))
         }
+        children.extend(Self::from_attributes(&function.attributes));
         if let Some(body) = &function.body {
             children.push(Self::new(
                 "Body",
@@ -84,8 +124,13 @@ impl PrettyAst {
         }
         Self::new(
             format!(
-                "Function [{}{}]",
+                "Function [{}{}{}]",
                 Self::storage_class(&function.storage_class),
+                if function.is_thread_local {
+                    "_Thread_local "
+                } else {
+                    ""
+                },
                 &function.name.symbol
             ),
             children,
@@ -109,14 +154,51 @@ impl PrettyAst {
                 }
             }
         }
+        if declaration.is_thread_local {
+            children.push(Self::new("ThreadLocal", vec![]));
+        }
+        children.extend(Self::from_attributes(&declaration.attributes));
         Self::new("VarDeclaration", children)
     }
+    fn from_attributes(attributes: &[Node<Attribute>]) -> Option<PrettyAst> {
+        if attributes.is_empty() {
+            return None;
+        }
+        Some(Self::new(
+            "Attributes",
+            attributes.iter().map(|attribute| match attribute.as_ref() {
+                Attribute::Noreturn => Self::new("Noreturn", vec![]),
+                Attribute::Aligned(alignment) => {
+                    Self::new("Aligned", alignment.iter().map(Self::from_expression))
+                }
+                Attribute::Packed => Self::new("Packed", vec![]),
+                Attribute::Unused => Self::new("Unused", vec![]),
+                Attribute::Weak => Self::new("Weak", vec![]),
+                Attribute::Section(name) => Self::new(format!("Section \"{name}\""), vec![]),
+            }),
+        ))
+    }
     fn from_initializer(initializer: &Node<Initializer>) -> PrettyAst {
         match initializer.as_ref() {
             Initializer::Single(expr) => Self::from_expression(expr),
-            Initializer::Compound(elements) => {
-                Self::new("Compound", elements.iter().map(Self::from_initializer))
-            }
+            Initializer::Compound(items) => Self::new(
+                "Compound",
+                items.iter().map(|item| {
+                    if item.designators.is_empty() {
+                        return Self::from_initializer(&item.init);
+                    }
+                    let mut children: Vec<_> =
+                        item.designators.iter().map(Self::from_designator).collect();
+                    children.push(Self::from_initializer(&item.init));
+                    Self::new("Designated", children)
+                }),
+            ),
+        }
+    }
+    fn from_designator(designator: &Node<Designator>) -> PrettyAst {
+        match designator.as_ref() {
+            Designator::Field(name) => Self::new("Field", vec![Self::from_identifier(name)]),
+            Designator::Index(index) => Self::new("Index", vec![Self::from_expression(index)]),
         }
     }
     fn storage_class(storage: &Option<Node<StorageClass>>) -> &str {
@@ -173,11 +255,14 @@ impl PrettyAst {
                     let value = match value {
                         Constant::UChar(v) => *v as i64,
                         Constant::Char(v) => *v as i64,
+                        Constant::Short(v) => *v as i64,
+                        Constant::UShort(v) => *v as i64,
                         Constant::Int(v) => *v as i64,
                         Constant::Long(v) => *v,
                         Constant::UInt(v) => *v as i64,
                         Constant::ULong(v) => *v as i64,
                         Constant::Double(v) => *v as i64,
+                        Constant::Float(v) => *v as i64,
                     };
                     Self::new(format!("Case [{value}]"), vec![Self::from_statement(body)])
                 } else {
@@ -273,10 +358,19 @@ impl PrettyAst {
                 ];
                 Self::new(format!("<{node_id}> Conditional [?]"), children)
             }
-            Expression::FunctionCall { name, args } => Self::new(
-                format!("<{node_id}> FunctionCall [{}]", name.symbol),
-                args.iter().map(Self::from_expression),
-            ),
+            Expression::FunctionCall { function, args } => match function.as_ref() {
+                Expression::Var(name) => Self::new(
+                    format!("<{node_id}> FunctionCall [{name}]"),
+                    args.iter().map(Self::from_expression),
+                ),
+                _ => Self::new(
+                    format!("<{node_id}> FunctionCall"),
+                    vec![
+                        Self::new("Function", vec![Self::from_expression(function)]),
+                        Self::new("Arguments", args.iter().map(Self::from_expression)),
+                    ],
+                ),
+            },
             Expression::Cast { target, expr } => Self::new(
                 format!("<{node_id}> Cast"),
                 vec![
@@ -284,6 +378,13 @@ impl PrettyAst {
                     Self::new("Expression", vec![Self::from_expression(expr)]),
                 ],
             ),
+            Expression::CompoundLiteral { type_spec, init } => Self::new(
+                format!("<{node_id}> CompoundLiteral"),
+                vec![
+                    Self::new("Type", vec![Self::from_type(type_spec)]),
+                    Self::new("Initializer", vec![Self::from_initializer(init)]),
+                ],
+            ),
             Expression::AddressOf(expr) => Self::new(
                 format!("<{node_id}> AddressOf"),
                 vec![Self::from_expression(expr)],
@@ -299,10 +400,34 @@ impl PrettyAst {
             Expression::SizeOfType(ty) => {
                 Self::new(format!("<{node_id}> SizeOfType"), vec![Self::from_type(ty)])
             }
+            Expression::AlignOfType(ty) => Self::new(
+                format!("<{node_id}> AlignOfType"),
+                vec![Self::from_type(ty)],
+            ),
+            Expression::Comma(left, right) => Self::new(
+                format!("<{node_id}> Comma"),
+                vec![Self::from_expression(left), Self::from_expression(right)],
+            ),
             Expression::SizeOfExpr(ty) => Self::new(
                 format!("<{node_id}> SizeOfExpr"),
                 vec![Self::from_expression(ty)],
             ),
+            Expression::VaStart(ap, param) => Self::new(
+                format!("<{node_id}> VaStart"),
+                vec![Self::from_expression(ap), Self::from_expression(param)],
+            ),
+            Expression::VaArg(ap, ty) => Self::new(
+                format!("<{node_id}> VaArg"),
+                vec![Self::from_expression(ap), Self::from_type(ty)],
+            ),
+            Expression::VaEnd(ap) => Self::new(
+                format!("<{node_id}> VaEnd"),
+                vec![Self::from_expression(ap)],
+            ),
+            Expression::VaCopy(dst, src) => Self::new(
+                format!("<{node_id}> VaCopy"),
+                vec![Self::from_expression(dst), Self::from_expression(src)],
+            ),
             Expression::Dot {
                 aggregate: structure,
                 field: member,
@@ -344,8 +469,15 @@ impl PrettyAst {
             Constant::Double(v) => {
                 Self::new(format!("<{node_id}> Constant Double [{:+e}]", *v), vec![])
             }
+            Constant::Float(v) => {
+                Self::new(format!("<{node_id}> Constant Float [{:+e}]", *v), vec![])
+            }
             Constant::Char(v) => Self::new(format!("<{node_id}> Constant Char [{}]", *v), vec![]),
             Constant::UChar(v) => Self::new(format!("<{node_id}> Constant UChar [{}]", *v), vec![]),
+            Constant::Short(v) => Self::new(format!("<{node_id}> Constant Short [{}]", *v), vec![]),
+            Constant::UShort(v) => {
+                Self::new(format!("<{node_id}> Constant UShort [{}]", *v), vec![])
+            }
         }
     }
 
@@ -353,27 +485,50 @@ impl PrettyAst {
         match ty.as_ref() {
             TypeSpec::Int => Self::new("Int", vec![]),
             TypeSpec::Long => Self::new("Long", vec![]),
+            TypeSpec::Bool => Self::new("Bool", vec![]),
             TypeSpec::Char => Self::new("Char", vec![]),
             TypeSpec::SChar => Self::new("Signed Char", vec![]),
             TypeSpec::UChar => Self::new("Unsigned Char", vec![]),
+            TypeSpec::Short => Self::new("Short", vec![]),
+            TypeSpec::UShort => Self::new("Unsigned Short", vec![]),
             TypeSpec::ULong => Self::new("Unsigned Long", vec![]),
             TypeSpec::UInt => Self::new("Unsigned Int", vec![]),
             TypeSpec::Double => Self::new("Double", vec![]),
+            TypeSpec::Float => Self::new("Float", vec![]),
             TypeSpec::Void => Self::new("Void", vec![]),
-            TypeSpec::Function(f) => Self::new(
-                "FunctionType",
-                vec![
-                    Self::new("Return", vec![Self::from_type(&f.ret)]),
-                    Self::new("Params", f.params.iter().map(Self::from_type)),
-                ],
-            ),
+            TypeSpec::Function(f) => {
+                let mut params: Vec<_> = f.params.iter().map(Self::from_type).collect();
+                if f.variadic {
+                    params.push(Self::new("...", vec![]));
+                }
+                Self::new(
+                    "FunctionType",
+                    vec![
+                        Self::new("Return", vec![Self::from_type(&f.ret)]),
+                        Self::new("Params", params),
+                    ],
+                )
+            }
             TypeSpec::Pointer(t) => Self::new("Pointer", vec![Self::from_type(t)]),
             TypeSpec::Array(t, size) => Self::new(
                 "Array",
                 vec![Self::new(format!("{size}"), vec![]), Self::from_type(t)],
             ),
+            TypeSpec::VariableArray(t, size) => Self::new(
+                "VariableArray",
+                vec![Self::from_expression(size), Self::from_type(t)],
+            ),
+            TypeSpec::UnsizedArray(t) => Self::new("UnsizedArray", vec![Self::from_type(t)]),
             TypeSpec::Struct(name) => Self::new(format!("Struct [{}]", name.symbol), vec![]),
             TypeSpec::Union(name) => Self::new(format!("Union [{}]", name.symbol), vec![]),
+            TypeSpec::Enum(Some(name)) => Self::new(format!("Enum [{}]", name.symbol), vec![]),
+            TypeSpec::Enum(None) => Self::new("Enum", vec![]),
+            TypeSpec::Named(name) => Self::new(format!("Named [{}]", name.symbol), vec![]),
+            TypeSpec::Qualified(qualifiers, t) => Self::new(
+                format!("Qualified [{qualifiers}]"),
+                vec![Self::from_type(t)],
+            ),
+            TypeSpec::VaList => Self::new("VaList", vec![]),
         }
     }
 
@@ -454,6 +609,7 @@ impl PrettyAst {
         match op {
             Complement => "~",
             Negate => "-",
+            Plus => "+",
             Not => "!",
             Increment => "++",
             Decrement => "--",
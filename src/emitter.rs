@@ -1,6 +1,6 @@
 use crate::asm::ir::{
     AsmType, BinaryOp, CondCode, Function, Instruction, Operand, Program, Reg, StaticConstant,
-    StaticVariable, TopLevel, UnaryOp,
+    StaticVariable, TlsModel, TopLevel, UnaryOp,
 };
 use crate::semantic::StaticInit;
 use crate::symbol::Symbol;
@@ -30,14 +30,21 @@ pub fn emit_program(output: &mut impl Write, program: &Program, target_os: Targe
 }
 
 fn emit_function(output: &mut impl Write, function: &Function, target_os: TargetOs) -> Result<()> {
-    if function.global {
-        writeln!(
-            output,
-            "\t.globl {}",
-            emit_symbol(&function.name, target_os)
-        )?;
+    emit_linkage(
+        output,
+        &function.name,
+        function.global,
+        function.weak,
+        target_os,
+    )?;
+    match &function.section {
+        Some(section) => emit_section(output, section, "ax", target_os)?,
+        None => writeln!(output, "\t.text")?,
+    }
+    if let Some(alignment) = function.alignment {
+        emit_ins(output, ".balign")?;
+        writeln!(output, "{alignment}")?;
     }
-    writeln!(output, "\t.text")?;
     writeln!(output, "{}:", emit_symbol(&function.name, target_os))?;
 
     // Prologue
@@ -51,9 +58,11 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Mov(ty, src, dst) => {
                 let op = match ty {
                     AsmType::Byte => "movb",
+                    AsmType::Word => "movw",
                     AsmType::Longword => "movl",
                     AsmType::Quadword | AsmType::ByteArray { .. } => "movq",
                     AsmType::Double => "movsd",
+                    AsmType::Float => "movss",
                 };
                 emit_ins(output, op)?;
                 emit_operand(output, src, RegSize::from_ty(ty), target_os)?;
@@ -63,14 +72,17 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Unary(ty, op, src) => {
                 let op = match (op, ty) {
                     (UnaryOp::Neg, AsmType::Byte) => "negb",
+                    (UnaryOp::Neg, AsmType::Word) => "negw",
                     (UnaryOp::Neg, AsmType::Longword) => "negl",
                     (UnaryOp::Neg, AsmType::Quadword) => "negq",
                     (UnaryOp::Neg, AsmType::Double) => "negsd",
+                    (UnaryOp::Neg, AsmType::Float) => unreachable!(),
 
                     (UnaryOp::Not, AsmType::Byte) => "notb",
+                    (UnaryOp::Not, AsmType::Word) => "notw",
                     (UnaryOp::Not, AsmType::Longword) => "notl",
                     (UnaryOp::Not, AsmType::Quadword) => "notq",
-                    (UnaryOp::Not, AsmType::Double) => unreachable!(),
+                    (UnaryOp::Not, AsmType::Double | AsmType::Float) => unreachable!(),
                     (_, AsmType::ByteArray { .. }) => unreachable!(),
                 };
                 emit_ins(output, op)?;
@@ -79,56 +91,71 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Binary(ty, op, left, right) => {
                 let typed_instruction = match (op, ty) {
                     (BinaryOp::Add, AsmType::Byte) => "addb",
+                    (BinaryOp::Add, AsmType::Word) => "addw",
                     (BinaryOp::Add, AsmType::Longword) => "addl",
                     (BinaryOp::Add, AsmType::Quadword) => "addq",
                     (BinaryOp::Add, AsmType::Double) => "addsd",
+                    (BinaryOp::Add, AsmType::Float) => "addss",
 
                     (BinaryOp::Sub, AsmType::Byte) => "subb",
+                    (BinaryOp::Sub, AsmType::Word) => "subw",
                     (BinaryOp::Sub, AsmType::Longword) => "subl",
                     (BinaryOp::Sub, AsmType::Quadword) => "subq",
                     (BinaryOp::Sub, AsmType::Double) => "subsd",
+                    (BinaryOp::Sub, AsmType::Float) => "subss",
 
                     (BinaryOp::Mul, AsmType::Byte) => "imulb",
+                    (BinaryOp::Mul, AsmType::Word) => "imulw",
                     (BinaryOp::Mul, AsmType::Longword) => "imull",
                     (BinaryOp::Mul, AsmType::Quadword) => "imulq",
                     (BinaryOp::Mul, AsmType::Double) => "mulsd",
+                    (BinaryOp::Mul, AsmType::Float) => "mulss",
 
                     (BinaryOp::And, AsmType::Byte) => "andb",
+                    (BinaryOp::And, AsmType::Word) => "andw",
                     (BinaryOp::And, AsmType::Longword) => "andl",
                     (BinaryOp::And, AsmType::Quadword) => "andq",
-                    (BinaryOp::And, AsmType::Double) => unreachable!(),
+                    (BinaryOp::And, AsmType::Double | AsmType::Float) => unreachable!(),
 
                     (BinaryOp::Or, AsmType::Byte) => "orb",
+                    (BinaryOp::Or, AsmType::Word) => "orw",
                     (BinaryOp::Or, AsmType::Longword) => "orl",
                     (BinaryOp::Or, AsmType::Quadword) => "orq",
-                    (BinaryOp::Or, AsmType::Double) => unreachable!(),
+                    (BinaryOp::Or, AsmType::Double | AsmType::Float) => unreachable!(),
 
                     (BinaryOp::Xor, AsmType::Byte) => "xorb",
+                    (BinaryOp::Xor, AsmType::Word) => "xorw",
                     (BinaryOp::Xor, AsmType::Longword) => "xorl",
                     (BinaryOp::Xor, AsmType::Quadword) => "xorq",
                     (BinaryOp::Xor, AsmType::Double) => "xorpd",
+                    (BinaryOp::Xor, AsmType::Float) => "xorps",
 
                     (BinaryOp::Sal, AsmType::Byte) => "salb",
+                    (BinaryOp::Sal, AsmType::Word) => "salw",
                     (BinaryOp::Sal, AsmType::Longword) => "sall",
                     (BinaryOp::Sal, AsmType::Quadword) => "salq",
-                    (BinaryOp::Sal, AsmType::Double) => unreachable!(),
+                    (BinaryOp::Sal, AsmType::Double | AsmType::Float) => unreachable!(),
 
                     (BinaryOp::Shl, AsmType::Byte) => "shlb",
+                    (BinaryOp::Shl, AsmType::Word) => "shlw",
                     (BinaryOp::Shl, AsmType::Longword) => "shll",
                     (BinaryOp::Shl, AsmType::Quadword) => "shlq",
-                    (BinaryOp::Shl, AsmType::Double) => unreachable!(),
+                    (BinaryOp::Shl, AsmType::Double | AsmType::Float) => unreachable!(),
 
                     (BinaryOp::Sar, AsmType::Byte) => "sarb",
+                    (BinaryOp::Sar, AsmType::Word) => "sarw",
                     (BinaryOp::Sar, AsmType::Longword) => "sarl",
                     (BinaryOp::Sar, AsmType::Quadword) => "sarq",
-                    (BinaryOp::Sar, AsmType::Double) => unreachable!(),
+                    (BinaryOp::Sar, AsmType::Double | AsmType::Float) => unreachable!(),
 
                     (BinaryOp::Shr, AsmType::Byte) => "shrb",
+                    (BinaryOp::Shr, AsmType::Word) => "shrw",
                     (BinaryOp::Shr, AsmType::Longword) => "shrl",
                     (BinaryOp::Shr, AsmType::Quadword) => "shrq",
-                    (BinaryOp::Shr, AsmType::Double) => unreachable!(),
+                    (BinaryOp::Shr, AsmType::Double | AsmType::Float) => unreachable!(),
 
                     (BinaryOp::DivDouble, AsmType::Double) => "divsd",
+                    (BinaryOp::DivDouble, AsmType::Float) => "divss",
                     (BinaryOp::DivDouble, _) => unreachable!(),
                     (_, AsmType::ByteArray { .. }) => unreachable!(),
                 };
@@ -145,9 +172,10 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Idiv(ty, src) => {
                 let op = match ty {
                     AsmType::Byte => "idivb",
+                    AsmType::Word => "idivw",
                     AsmType::Longword => "idivl",
                     AsmType::Quadword => "idivq",
-                    AsmType::Double | AsmType::ByteArray { .. } => unreachable!(),
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => unreachable!(),
                 };
                 emit_ins(output, op)?;
                 emit_operand(output, src, RegSize::from_ty(ty), target_os)?;
@@ -156,9 +184,10 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Div(ty, src) => {
                 let op = match ty {
                     AsmType::Byte => "divb",
+                    AsmType::Word => "divw",
                     AsmType::Longword => "divl",
                     AsmType::Quadword => "divq",
-                    AsmType::Double | AsmType::ByteArray { .. } => unreachable!(),
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => unreachable!(),
                 };
                 emit_ins(output, op)?;
                 emit_operand(output, src, RegSize::from_ty(ty), target_os)?;
@@ -167,9 +196,10 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Cdq(ty) => {
                 let op = match ty {
                     AsmType::Byte => "cdqb",
+                    AsmType::Word => "cwd",
                     AsmType::Longword => "cdq",
                     AsmType::Quadword => "cqo",
-                    AsmType::Double | AsmType::ByteArray { .. } => unreachable!(),
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => unreachable!(),
                 };
                 emit_ins(output, op)?;
             }
@@ -186,9 +216,11 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Cmp(ty, left, right) => {
                 let op = match ty {
                     AsmType::Byte => "cmpb",
+                    AsmType::Word => "cmpw",
                     AsmType::Longword => "cmpl",
                     AsmType::Quadword | AsmType::ByteArray { .. } => "cmpq",
                     AsmType::Double => "comisd",
+                    AsmType::Float => "comiss",
                 };
                 emit_ins(output, op)?;
                 emit_operand(output, left, RegSize::from_ty(ty), target_os)?;
@@ -249,6 +281,11 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
                 emit_ins(output, "call")?;
                 write!(output, "{}", emit_symbol(name, target_os))?;
             }
+            Instruction::CallIndirect(target, _) => {
+                emit_ins(output, "call")?;
+                write!(output, "*")?;
+                emit_operand(output, target, RegSize::Quad, target_os)?;
+            }
             Instruction::Movsx(src_ty, src, dst_ty, dst) => {
                 let s1 = RegSize::from_ty(src_ty);
                 let s2 = RegSize::from_ty(dst_ty);
@@ -265,6 +302,46 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
                 write!(output, ", ")?;
                 emit_operand(output, dst, s2, target_os)?;
             }
+            Instruction::Lea(
+                Operand::Data {
+                    name,
+                    offset,
+                    tls: Some(tls),
+                    ..
+                },
+                dst,
+            ) => {
+                // The address of a thread local variable is relative to the
+                // thread pointer, which lives at %fs:0
+                let symbol = emit_symbol(name, target_os);
+                match tls {
+                    TlsModel::LocalExec => {
+                        emit_ins(output, "movq")?;
+                        write!(output, "%fs:0, ")?;
+                        emit_operand(output, dst, RegSize::Quad, target_os)?;
+                        writeln!(output)?;
+                        emit_ins(output, "leaq")?;
+                        write!(output, "{symbol}@tpoff+{offset}(")?;
+                        emit_operand(output, dst, RegSize::Quad, target_os)?;
+                        write!(output, "), ")?;
+                    }
+                    TlsModel::InitialExec => {
+                        emit_ins(output, "movq")?;
+                        write!(output, "{symbol}@gottpoff(%rip), ")?;
+                        emit_operand(output, dst, RegSize::Quad, target_os)?;
+                        writeln!(output)?;
+                        if *offset != 0 {
+                            emit_ins(output, "addq")?;
+                            write!(output, "${offset}, ")?;
+                            emit_operand(output, dst, RegSize::Quad, target_os)?;
+                            writeln!(output)?;
+                        }
+                        emit_ins(output, "addq")?;
+                        write!(output, "%fs:0, ")?;
+                    }
+                }
+                emit_operand(output, dst, RegSize::Quad, target_os)?;
+            }
             Instruction::Lea(src, dst) => {
                 emit_ins(output, "leaq")?;
                 emit_operand(output, src, RegSize::Quad, target_os)?;
@@ -274,10 +351,11 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Cvttsd2si(ty, src, dst) => {
                 let op = match ty {
                     AsmType::Byte => "cvttsd2sib",
+                    AsmType::Word => "cvttsd2siw",
                     AsmType::Longword => "cvttsd2sil",
                     AsmType::Quadword => "cvttsd2siq",
-                    AsmType::Double | AsmType::ByteArray { .. } => {
-                        panic!("Should never be called with double or bytearray")
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => {
+                        panic!("Should never be called with floating or bytearray")
                     }
                 };
                 emit_ins(output, op)?;
@@ -288,10 +366,11 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
             Instruction::Cvtsi2sd(ty, src, dst) => {
                 let op = match ty {
                     AsmType::Byte => unreachable!("Can't convert char to double"),
+                    AsmType::Word => unreachable!("Can't convert short to double"),
                     AsmType::Longword => "cvtsi2sdl",
                     AsmType::Quadword => "cvtsi2sdq",
-                    AsmType::Double | AsmType::ByteArray { .. } => {
-                        unreachable!("Should never be called with double or bytearray")
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => {
+                        unreachable!("Should never be called with floating or bytearray")
                     }
                 };
                 emit_ins(output, op)?;
@@ -299,6 +378,48 @@ fn emit_function(output: &mut impl Write, function: &Function, target_os: Target
                 write!(output, ", ")?;
                 emit_operand(output, dst, RegSize::Quad, target_os)?;
             }
+            Instruction::Cvttss2si(ty, src, dst) => {
+                let op = match ty {
+                    AsmType::Byte => "cvttss2sib",
+                    AsmType::Word => "cvttss2siw",
+                    AsmType::Longword => "cvttss2sil",
+                    AsmType::Quadword => "cvttss2siq",
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => {
+                        panic!("Should never be called with floating or bytearray")
+                    }
+                };
+                emit_ins(output, op)?;
+                emit_operand(output, src, RegSize::Quad, target_os)?;
+                write!(output, ", ")?;
+                emit_operand(output, dst, RegSize::from_ty(ty), target_os)?;
+            }
+            Instruction::Cvtsi2ss(ty, src, dst) => {
+                let op = match ty {
+                    AsmType::Byte => unreachable!("Can't convert char to float"),
+                    AsmType::Word => unreachable!("Can't convert short to float"),
+                    AsmType::Longword => "cvtsi2ssl",
+                    AsmType::Quadword => "cvtsi2ssq",
+                    AsmType::Double | AsmType::Float | AsmType::ByteArray { .. } => {
+                        unreachable!("Should never be called with floating or bytearray")
+                    }
+                };
+                emit_ins(output, op)?;
+                emit_operand(output, src, RegSize::from_ty(ty), target_os)?;
+                write!(output, ", ")?;
+                emit_operand(output, dst, RegSize::Quad, target_os)?;
+            }
+            Instruction::Cvtss2sd(src, dst) => {
+                emit_ins(output, "cvtss2sd")?;
+                emit_operand(output, src, RegSize::Quad, target_os)?;
+                write!(output, ", ")?;
+                emit_operand(output, dst, RegSize::Quad, target_os)?;
+            }
+            Instruction::Cvtsd2ss(src, dst) => {
+                emit_ins(output, "cvtsd2ss")?;
+                emit_operand(output, src, RegSize::Quad, target_os)?;
+                write!(output, ", ")?;
+                emit_operand(output, dst, RegSize::Quad, target_os)?;
+            }
         }
         writeln!(output)?;
     }
@@ -310,30 +431,42 @@ fn emit_variable(
     variable: &StaticVariable,
     target_os: TargetOs,
 ) -> Result<()> {
-    if variable.global {
-        writeln!(
-            output,
-            "\t.globl {}",
-            emit_symbol(&variable.name, target_os)
-        )?;
-    }
-
-    if matches!(
-        variable.init[..],
-        [StaticInit::Int(0)]
-            | [StaticInit::Long(0)]
-            | [StaticInit::UInt(0)]
-            | [StaticInit::ULong(0)]
-            | [StaticInit::ZeroInit(0)]
-    ) {
-        writeln!(output, "\t.bss")?;
+    emit_linkage(
+        output,
+        &variable.name,
+        variable.global,
+        variable.weak,
+        target_os,
+    )?;
+
+    let zero_size = match variable.init[..] {
+        [StaticInit::Int(0) | StaticInit::UInt(0)] => Some(4),
+        [StaticInit::Long(0) | StaticInit::ULong(0)] => Some(8),
+        [StaticInit::ZeroInit(size)] => Some(size),
+        _ => None,
+    };
+    // Thread local variables are only supported in ELF, where the loader
+    // copies .tdata and .tbss into each thread's block
+    let flags = if variable.thread_local { "awT" } else { "aw" };
+    if let Some(size) = zero_size {
+        match &variable.section {
+            Some(section) => emit_section(output, section, flags, target_os)?,
+            None if variable.thread_local => writeln!(output, "\t.section .tbss,\"awT\",@nobits")?,
+            None => writeln!(output, "\t.bss")?,
+        }
         emit_ins(output, ".balign")?;
         writeln!(output, "{}", variable.alignment)?;
         writeln!(output, "{}:", emit_symbol(&variable.name, target_os))?;
         emit_ins(output, ".zero")?;
-        writeln!(output, "{}", variable.alignment)?;
+        writeln!(output, "{size}")?;
     } else {
-        writeln!(output, "\t.data")?;
+        match &variable.section {
+            Some(section) => emit_section(output, section, flags, target_os)?,
+            None if variable.thread_local => {
+                writeln!(output, "\t.section .tdata,\"awT\",@progbits")?
+            }
+            None => writeln!(output, "\t.data")?,
+        }
         emit_ins(output, ".balign")?;
         writeln!(output, "{}", variable.alignment)?;
         writeln!(output, "{}:", emit_symbol(&variable.name, target_os))?;
@@ -344,6 +477,39 @@ fn emit_variable(
     Ok(())
 }
 
+fn emit_linkage(
+    output: &mut impl Write,
+    name: &Symbol,
+    global: bool,
+    weak: bool,
+    target_os: TargetOs,
+) -> Result<()> {
+    let symbol = emit_symbol(name, target_os);
+    match target_os {
+        TargetOs::Linux if weak => writeln!(output, "\t.weak {symbol}"),
+        TargetOs::MacOs if weak => {
+            writeln!(output, "\t.globl {symbol}")?;
+            writeln!(output, "\t.weak_definition {symbol}")
+        }
+        _ if global => writeln!(output, "\t.globl {symbol}"),
+        _ => Ok(()),
+    }
+}
+
+/// Switches to a section given with the `section` attribute, `flags` are
+/// the ELF flags used when the section is new.
+fn emit_section(
+    output: &mut impl Write,
+    section: &Symbol,
+    flags: &str,
+    target_os: TargetOs,
+) -> Result<()> {
+    match target_os {
+        TargetOs::Linux => writeln!(output, "\t.section {section},\"{flags}\",@progbits"),
+        TargetOs::MacOs => writeln!(output, "\t.section {section}"),
+    }
+}
+
 fn emit_static_init(output: &mut impl Write, init: &StaticInit, target_os: TargetOs) -> Result<()> {
     match init {
         StaticInit::String {
@@ -366,9 +532,16 @@ fn emit_static_init(output: &mut impl Write, init: &StaticInit, target_os: Targe
             }
             writeln!(output, "\"")?;
         }
-        StaticInit::Pointer(label) => {
+        StaticInit::Pointer(label, offset) => {
             emit_ins(output, ".quad")?;
             emit_label(output, label, target_os)?;
+            emit_address_offset(output, *offset)?;
+            writeln!(output)?;
+        }
+        StaticInit::Address(name, offset) => {
+            emit_ins(output, ".quad")?;
+            write!(output, "{}", emit_symbol(name, target_os))?;
+            emit_address_offset(output, *offset)?;
             writeln!(output)?;
         }
         StaticInit::Char(v) => {
@@ -379,6 +552,14 @@ fn emit_static_init(output: &mut impl Write, init: &StaticInit, target_os: Targe
             emit_ins(output, ".byte")?;
             writeln!(output, "{v}")?;
         }
+        StaticInit::Short(v) => {
+            emit_ins(output, ".short")?;
+            writeln!(output, "{v}")?;
+        }
+        StaticInit::UShort(v) => {
+            emit_ins(output, ".short")?;
+            writeln!(output, "{v}")?;
+        }
         StaticInit::Int(v) => {
             emit_ins(output, ".long")?;
             writeln!(output, "{v}")?;
@@ -399,6 +580,10 @@ fn emit_static_init(output: &mut impl Write, init: &StaticInit, target_os: Targe
             emit_ins(output, ".quad")?;
             writeln!(output, "{:#x} # {v}_f64", v.to_bits())?;
         }
+        StaticInit::Float(v) => {
+            emit_ins(output, ".long")?;
+            writeln!(output, "{:#x} # {v}_f32", v.to_bits())?;
+        }
         StaticInit::ZeroInit(size) => {
             emit_ins(output, ".zero")?;
             writeln!(output, "{size}")?;
@@ -423,6 +608,21 @@ fn emit_constant(
         return Ok(());
     }
     match constant.alignment {
+        4 => {
+            match target_os {
+                TargetOs::MacOs => {
+                    writeln!(output, "\t.literal4")?;
+                    writeln!(output, "\t.balign 4")?;
+                }
+                TargetOs::Linux => {
+                    writeln!(output, "\t.section .rodata")?;
+                    writeln!(output, "\t.balign 4")?;
+                }
+            }
+            emit_label(output, &constant.name, target_os)?;
+            writeln!(output, ":")?;
+            emit_static_init(output, &constant.init, target_os)?;
+        }
         8 => {
             match target_os {
                 TargetOs::MacOs => {
@@ -452,6 +652,10 @@ fn emit_constant(
             emit_label(output, &constant.name, target_os)?;
             writeln!(output, ":")?;
             emit_static_init(output, &constant.init, target_os)?;
+            if let StaticInit::Float(_) = constant.init {
+                emit_ins(output, ".long")?;
+                writeln!(output, "0")?;
+            }
             emit_ins(output, ".quad")?;
             writeln!(output, "0")?;
         }
@@ -472,6 +676,7 @@ fn emit_2sized(output: &mut impl Write, ins: &str, s1: RegSize, s2: RegSize) ->
 #[derive(Copy, Clone)]
 enum RegSize {
     Byte,
+    Word,
     Long,
     Quad,
 }
@@ -480,9 +685,11 @@ impl RegSize {
     fn from_ty(ty: &AsmType) -> RegSize {
         match ty {
             AsmType::Byte => RegSize::Byte,
+            AsmType::Word => RegSize::Word,
             AsmType::Longword => RegSize::Long,
             AsmType::Quadword => RegSize::Quad,
             AsmType::Double => RegSize::Quad,
+            AsmType::Float => RegSize::Long,
             AsmType::ByteArray { .. } => RegSize::Quad,
         }
     }
@@ -490,6 +697,7 @@ impl RegSize {
     fn suffix(self) -> char {
         match self {
             RegSize::Byte => 'b',
+            RegSize::Word => 'w',
             RegSize::Long => 'l',
             RegSize::Quad => 'q',
         }
@@ -504,58 +712,72 @@ fn emit_operand(
 ) -> Result<()> {
     match (operand, size) {
         (Operand::Reg(Reg::Ax), RegSize::Byte) => write!(output, "%al"),
+        (Operand::Reg(Reg::Ax), RegSize::Word) => write!(output, "%ax"),
         (Operand::Reg(Reg::Ax), RegSize::Long) => write!(output, "%eax"),
         (Operand::Reg(Reg::Ax), RegSize::Quad) => write!(output, "%rax"),
 
         (Operand::Reg(Reg::Bx), RegSize::Byte) => write!(output, "%bl"),
+        (Operand::Reg(Reg::Bx), RegSize::Word) => write!(output, "%bx"),
         (Operand::Reg(Reg::Bx), RegSize::Long) => write!(output, "%ebx"),
         (Operand::Reg(Reg::Bx), RegSize::Quad) => write!(output, "%rbx"),
 
         (Operand::Reg(Reg::Cx), RegSize::Byte) => write!(output, "%cl"),
+        (Operand::Reg(Reg::Cx), RegSize::Word) => write!(output, "%cx"),
         (Operand::Reg(Reg::Cx), RegSize::Long) => write!(output, "%ecx"),
         (Operand::Reg(Reg::Cx), RegSize::Quad) => write!(output, "%rcx"),
 
         (Operand::Reg(Reg::Dx), RegSize::Byte) => write!(output, "%dl"),
+        (Operand::Reg(Reg::Dx), RegSize::Word) => write!(output, "%dx"),
         (Operand::Reg(Reg::Dx), RegSize::Long) => write!(output, "%edx"),
         (Operand::Reg(Reg::Dx), RegSize::Quad) => write!(output, "%rdx"),
 
         (Operand::Reg(Reg::Di), RegSize::Byte) => write!(output, "%dil"),
+        (Operand::Reg(Reg::Di), RegSize::Word) => write!(output, "%di"),
         (Operand::Reg(Reg::Di), RegSize::Long) => write!(output, "%edi"),
         (Operand::Reg(Reg::Di), RegSize::Quad) => write!(output, "%rdi"),
 
         (Operand::Reg(Reg::Si), RegSize::Byte) => write!(output, "%sil"),
+        (Operand::Reg(Reg::Si), RegSize::Word) => write!(output, "%si"),
         (Operand::Reg(Reg::Si), RegSize::Long) => write!(output, "%esi"),
         (Operand::Reg(Reg::Si), RegSize::Quad) => write!(output, "%rsi"),
 
         (Operand::Reg(Reg::R8), RegSize::Byte) => write!(output, "%r8b"),
+        (Operand::Reg(Reg::R8), RegSize::Word) => write!(output, "%r8w"),
         (Operand::Reg(Reg::R8), RegSize::Long) => write!(output, "%r8d"),
         (Operand::Reg(Reg::R8), RegSize::Quad) => write!(output, "%r8"),
 
         (Operand::Reg(Reg::R9), RegSize::Byte) => write!(output, "%r9b"),
+        (Operand::Reg(Reg::R9), RegSize::Word) => write!(output, "%r9w"),
         (Operand::Reg(Reg::R9), RegSize::Long) => write!(output, "%r9d"),
         (Operand::Reg(Reg::R9), RegSize::Quad) => write!(output, "%r9"),
 
         (Operand::Reg(Reg::R10), RegSize::Byte) => write!(output, "%r10b"),
+        (Operand::Reg(Reg::R10), RegSize::Word) => write!(output, "%r10w"),
         (Operand::Reg(Reg::R10), RegSize::Long) => write!(output, "%r10d"),
         (Operand::Reg(Reg::R10), RegSize::Quad) => write!(output, "%r10"),
 
         (Operand::Reg(Reg::R11), RegSize::Byte) => write!(output, "%r11b"),
+        (Operand::Reg(Reg::R11), RegSize::Word) => write!(output, "%r11w"),
         (Operand::Reg(Reg::R11), RegSize::Long) => write!(output, "%r11d"),
         (Operand::Reg(Reg::R11), RegSize::Quad) => write!(output, "%r11"),
 
         (Operand::Reg(Reg::R12), RegSize::Byte) => write!(output, "%r12b"),
+        (Operand::Reg(Reg::R12), RegSize::Word) => write!(output, "%r12w"),
         (Operand::Reg(Reg::R12), RegSize::Long) => write!(output, "%r12d"),
         (Operand::Reg(Reg::R12), RegSize::Quad) => write!(output, "%r12"),
 
         (Operand::Reg(Reg::R13), RegSize::Byte) => write!(output, "%r13b"),
+        (Operand::Reg(Reg::R13), RegSize::Word) => write!(output, "%r13w"),
         (Operand::Reg(Reg::R13), RegSize::Long) => write!(output, "%r13d"),
         (Operand::Reg(Reg::R13), RegSize::Quad) => write!(output, "%r13"),
 
         (Operand::Reg(Reg::R14), RegSize::Byte) => write!(output, "%r14b"),
+        (Operand::Reg(Reg::R14), RegSize::Word) => write!(output, "%r14w"),
         (Operand::Reg(Reg::R14), RegSize::Long) => write!(output, "%r14d"),
         (Operand::Reg(Reg::R14), RegSize::Quad) => write!(output, "%r14"),
 
         (Operand::Reg(Reg::R15), RegSize::Byte) => write!(output, "%r15b"),
+        (Operand::Reg(Reg::R15), RegSize::Word) => write!(output, "%r15w"),
         (Operand::Reg(Reg::R15), RegSize::Long) => write!(output, "%r15d"),
         (Operand::Reg(Reg::R15), RegSize::Quad) => write!(output, "%r15"),
 
@@ -595,11 +817,32 @@ fn emit_operand(
             write!(output, "{scale}")?;
             write!(output, ")")
         }
+        (
+            Operand::Data {
+                name,
+                offset,
+                tls: Some(TlsModel::LocalExec),
+                ..
+            },
+            _,
+        ) => write!(
+            output,
+            "%fs:{}@tpoff+{offset}",
+            emit_symbol(name, target_os)
+        ),
+        (
+            Operand::Data {
+                tls: Some(TlsModel::InitialExec),
+                ..
+            },
+            _,
+        ) => unreachable!("Thread locals from other modules are only accessed through Lea"),
         (
             Operand::Data {
                 is_static: true,
                 name,
                 offset,
+                tls: None,
             },
             _,
         ) => {
@@ -611,9 +854,13 @@ fn emit_operand(
                 is_static: false,
                 name,
                 offset,
+                tls: None,
             },
             _,
         ) => write!(output, "{}+{offset}(%rip)", emit_symbol(name, target_os)),
+        (Operand::GotEntry(name), _) => {
+            write!(output, "{}@GOTPCREL(%rip)", emit_symbol(name, target_os))
+        }
         (Operand::Pseudo(..) | Operand::PseudoMem(..), _) => {
             unreachable!("Pseudo-registers should not appear here")
         }
@@ -627,6 +874,13 @@ fn emit_label(output: &mut impl Write, label: &Symbol, target_os: TargetOs) -> R
     }
 }
 
+fn emit_address_offset(output: &mut impl Write, offset: i64) -> Result<()> {
+    if offset != 0 {
+        write!(output, "{offset:+}")?;
+    }
+    Ok(())
+}
+
 fn emit_symbol(name: &Symbol, target_os: TargetOs) -> String {
     match target_os {
         TargetOs::MacOs => format!("_{name}"),
@@ -1,11 +1,97 @@
+#[cfg(test)]
+mod test;
+
 use crate::lexer::Span;
-use std::fmt::{Display, Formatter};
+use crate::source_map::{SourceFile, SourceMap};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Write};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ErrorKind {
     Parse,
     Resolve,
     Type,
+    Warning(Warning),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Warning {
+    Conversion,
+    UnusedVariable,
+    UnusedLabel,
+    UnreachableCode,
+    ReturnType,
+    SignCompare,
+    DiscardedQualifiers,
+    Overflow,
+}
+
+impl Warning {
+    pub const ALL: [Warning; 8] = [
+        Warning::Conversion,
+        Warning::UnusedVariable,
+        Warning::UnusedLabel,
+        Warning::UnreachableCode,
+        Warning::ReturnType,
+        Warning::SignCompare,
+        Warning::DiscardedQualifiers,
+        Warning::Overflow,
+    ];
+
+    /// Name used in `-W<name>` and `-Wno-<name>` flags.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Warning::Conversion => "conversion",
+            Warning::UnusedVariable => "unused-variable",
+            Warning::UnusedLabel => "unused-label",
+            Warning::UnreachableCode => "unreachable-code",
+            Warning::ReturnType => "return-type",
+            Warning::SignCompare => "sign-compare",
+            Warning::DiscardedQualifiers => "discarded-qualifiers",
+            Warning::Overflow => "overflow",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Warning> {
+        Warning::ALL.into_iter().find(|w| w.name() == name)
+    }
+}
+
+/// Which warnings are reported and whether they are turned into errors.
+pub struct WarningOptions {
+    enabled: HashSet<Warning>,
+    pub as_errors: bool,
+}
+
+impl Default for WarningOptions {
+    fn default() -> Self {
+        WarningOptions {
+            enabled: HashSet::from([
+                Warning::ReturnType,
+                Warning::DiscardedQualifiers,
+                Warning::Overflow,
+            ]),
+            as_errors: false,
+        }
+    }
+}
+
+impl WarningOptions {
+    pub fn enable_all(&mut self) {
+        self.enabled.extend(Warning::ALL);
+    }
+
+    pub fn set(&mut self, warning: Warning, enabled: bool) {
+        if enabled {
+            self.enabled.insert(warning);
+        } else {
+            self.enabled.remove(&warning);
+        }
+    }
+
+    pub fn is_enabled(&self, warning: Warning) -> bool {
+        self.enabled.contains(&warning)
+    }
 }
 
 impl Display for ErrorKind {
@@ -14,15 +100,168 @@ impl Display for ErrorKind {
             ErrorKind::Parse => write!(f, "Parse error"),
             ErrorKind::Resolve => write!(f, "Resolution error"),
             ErrorKind::Type => write!(f, "Type error"),
+            ErrorKind::Warning(_) => write!(f, "Warning"),
         }
     }
 }
 
-#[derive(Debug)]
+/// A secondary location related to an error, like a previous declaration.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub msg: String,
+}
+
+#[derive(Debug, Clone)]
 pub struct CompilerError {
     pub kind: ErrorKind,
     pub msg: String,
     pub span: Span,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl CompilerError {
+    pub fn new(kind: ErrorKind, msg: impl Into<String>, span: Span) -> Self {
+        CompilerError {
+            kind,
+            msg: msg.into(),
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(warning: Warning, msg: impl Into<String>, span: Span) -> Self {
+        CompilerError::new(ErrorKind::Warning(warning), msg, span)
+    }
+
+    pub fn warning_kind(&self) -> Option<Warning> {
+        match self.kind {
+            ErrorKind::Warning(warning) => Some(warning),
+            _ => None,
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, msg: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            msg: msg.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, msg: impl Into<String>) -> Self {
+        self.notes.push(msg.into());
+        self
+    }
+
+    /// Renders the error as `file:line:col: error: msg` followed by the
+    /// offending source lines, then every label and note.
+    pub fn render(&self, source_map: &SourceMap) -> String {
+        self.render_with(source_map, false)
+    }
+
+    /// Renders a warning as an error, as done with `-Werror`.
+    pub fn render_as_error(&self, source_map: &SourceMap) -> String {
+        self.render_with(source_map, true)
+    }
+
+    fn render_with(&self, source_map: &SourceMap, as_error: bool) -> String {
+        let (severity, msg) = match self.warning_kind() {
+            Some(warning) if as_error => (
+                "error",
+                format!("{} [-Werror={}]", self.msg, warning.name()),
+            ),
+            Some(warning) => ("warning", format!("{} [-W{}]", self.msg, warning.name())),
+            None => ("error", self.msg.clone()),
+        };
+        let mut out = String::new();
+        render_span(&mut out, source_map, severity, &msg, self.span, '^');
+        for label in &self.labels {
+            render_span(&mut out, source_map, "note", &label.msg, label.span, '-');
+        }
+        for note in &self.notes {
+            writeln!(out, "note: {note}").unwrap();
+        }
+        out
+    }
+}
+
+/// Spans covering more lines than this only show the first and last ones.
+const MAX_SNIPPET_LINES: usize = 4;
+
+fn render_span(
+    out: &mut String,
+    source_map: &SourceMap,
+    severity: &str,
+    msg: &str,
+    span: Span,
+    marker: char,
+) {
+    let location = source_map.location(span.0);
+    writeln!(out, "{location}: {severity}: {msg}").unwrap();
+    let Some((file, start)) = source_map.resolve(span.0) else {
+        return;
+    };
+    // The end of the span can land in a different file when it comes from a
+    // macro expansion, in that case only the first token is underlined.
+    let end = match source_map.resolve(span.1.saturating_sub(1).max(span.0)) {
+        Some((end_file, end)) if std::ptr::eq(end_file, file) && end >= start => end + 1,
+        _ => start + 1,
+    };
+    let line_delta = location.line as isize - (file.line_index(start) + 1) as isize;
+    render_snippet(out, file, start, end, line_delta, marker);
+}
+
+/// Writes the lines of `file` covering `start..end` with `marker` underlining
+/// the range. `line_delta` adjusts the printed line numbers for `#line`.
+pub fn render_snippet(
+    out: &mut String,
+    file: &SourceFile,
+    start: usize,
+    end: usize,
+    line_delta: isize,
+    marker: char,
+) {
+    let first = file.line_index(start);
+    let last = file.line_index(end - 1);
+    let line_number = |index: usize| (index as isize + 1 + line_delta).to_string();
+    let width = line_number(last).len();
+
+    for index in first..=last {
+        let elided = last - first >= MAX_SNIPPET_LINES && index > first && index < last;
+        if elided {
+            if index == first + 1 {
+                writeln!(out, " {:width$} | ...", "").unwrap();
+            }
+            continue;
+        }
+        let line = file.line(index);
+        let line_start = file.line_start(index);
+        let from = if index == first {
+            start - line_start
+        } else {
+            line.len() - line.trim_start().len()
+        };
+        let to = if index == last {
+            end - line_start
+        } else {
+            line.len()
+        };
+        let from = from.min(line.len());
+        let to = to.min(line.len());
+        let padding: String = line
+            .get(..from)
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let count = line.get(from..to).unwrap_or_default().chars().count();
+        let underline = marker.to_string().repeat(count.max(1));
+        writeln!(out, " {:>width$} | {line}", line_number(index)).unwrap();
+        writeln!(out, " {:width$} | {padding}{underline}", "").unwrap();
+    }
 }
 
 impl Display for CompilerError {
@@ -0,0 +1,187 @@
+use crate::error::{CompilerError, ErrorKind};
+use crate::lexer::Span;
+use crate::parser::{DEFAULT_ERROR_LIMIT, parse};
+use crate::preprocessor::{Options, Preprocessed, preprocess_source};
+use crate::pretty::dedent;
+use crate::semantic::validate;
+
+fn preprocess(src: &str) -> Preprocessed {
+    preprocess_source("test.c", &dedent(src), &Options::default()).unwrap()
+}
+
+fn assert_rendered(src: &str, expected: &str) {
+    let preprocessed = preprocess(src);
+    let error = match parse(&preprocessed.source, DEFAULT_ERROR_LIMIT) {
+        Ok(ast) => validate(ast).expect_err("Compilation should have failed"),
+        Err(mut errors) => errors.remove(0),
+    };
+    assert_eq!(
+        error.render(&preprocessed.source_map).trim_end(),
+        dedent(expected)
+    );
+}
+
+fn span_of(preprocessed: &Preprocessed, start: &str, end: &str) -> Span {
+    let begin = preprocessed.source.find(start).unwrap();
+    let end = preprocessed.source.find(end).unwrap() + end.len();
+    Span(begin, end)
+}
+
+#[test]
+fn test_parse_error() {
+    assert_rendered(
+        r#"
+        int main(void) {
+            return 1 +;
+        }
+    "#,
+        r#"
+        test.c:2:15: error: Expected expression, but found ';'
+         2 |     return 1 +;
+           |               ^
+    "#,
+    );
+}
+
+#[test]
+fn test_previous_declaration_label() {
+    assert_rendered(
+        r#"
+        int main(void) {
+            int value = 1;
+            int value = 2;
+            return value;
+        }
+    "#,
+        r#"
+        test.c:3:9: error: Variable 'value' was already declared
+         3 |     int value = 2;
+           |         ^^^^^
+        test.c:2:9: note: previous declaration is here
+         2 |     int value = 1;
+           |         -----
+    "#,
+    );
+}
+
+#[test]
+fn test_duplicated_label() {
+    assert_rendered(
+        r#"
+        int main(void) {
+        done:
+            return 0;
+        done:
+            return 1;
+        }
+    "#,
+        r#"
+        test.c:4:1: error: Label 'done' was already defined
+         4 | done:
+           | ^^^^
+        test.c:2:1: note: previous definition is here
+         2 | done:
+           | ----
+    "#,
+    );
+}
+
+#[test]
+fn test_note() {
+    assert_rendered(
+        r#"
+        int f(void);
+        static int f(void);
+    "#,
+        r#"
+        test.c:2:12: error: Function 'f' was previously declared as non-static
+         2 | static int f(void);
+           |            ^
+        test.c:1:5: note: previous declaration is here
+         1 | int f(void);
+           |     -
+        note: a static declaration must come before any other declaration of the function
+    "#,
+    );
+}
+
+#[test]
+fn test_multi_line_span() {
+    let preprocessed = preprocess(
+        r#"
+        int x = (1 +
+                 2 +
+                 3);
+    "#,
+    );
+    let span = span_of(&preprocessed, "(1", "3)");
+    let error = CompilerError::new(ErrorKind::Type, "Bad expression", span);
+    assert_eq!(
+        error.render(&preprocessed.source_map).trim_end(),
+        dedent(
+            r#"
+        test.c:1:9: error: Bad expression
+         1 | int x = (1 +
+           |         ^^^^
+         2 |          2 +
+           |          ^^^
+         3 |          3);
+           |          ^^
+    "#
+        )
+    );
+}
+
+#[test]
+fn test_long_span_is_elided() {
+    let preprocessed = preprocess(
+        r#"
+        int f(void) {
+            int a;
+            int b;
+            int c;
+            int d;
+        }
+    "#,
+    );
+    let span = span_of(&preprocessed, "{", "}");
+    let error = CompilerError::new(ErrorKind::Type, "Bad body", span);
+    assert_eq!(
+        error.render(&preprocessed.source_map).trim_end(),
+        dedent(
+            r#"
+        test.c:1:13: error: Bad body
+         1 | int f(void) {
+           |             ^
+           | ...
+         6 | }
+           | ^
+    "#
+        )
+    );
+}
+
+#[test]
+fn test_line_directive() {
+    let preprocessed = preprocess(
+        r#"
+        #line 41 "other.c"
+        int x = y;
+    "#,
+    );
+    let error = CompilerError::new(
+        ErrorKind::Resolve,
+        "Undeclared variable",
+        span_of(&preprocessed, "y", "y"),
+    );
+    assert_eq!(
+        error.render(&preprocessed.source_map).trim_end(),
+        dedent(
+            r#"
+        other.c:41:9: error: Undeclared variable
+         41 | int x = y;
+            |         ^
+    "#
+        )
+    );
+}
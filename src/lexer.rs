@@ -27,16 +27,23 @@ pub enum TokenKind {
     Identifier,
     IntConstant(IntKind),
     DoubleConstant,
+    FloatConstant,
     CharLiteral,
     StringLiteral,
 
     Char,
+    Short,
     Int,
     Long,
     Void,
     Signed,
     Unsigned,
     Double,
+    Float,
+    Bool,
+    VaList,
+    Const,
+    Volatile,
 
     If,
     Else,
@@ -52,9 +59,21 @@ pub enum TokenKind {
     Return,
     Static,
     Extern,
+    ThreadLocal,
     Sizeof,
+    Alignof,
+    StaticAssert,
+    Inline,
+    Noreturn,
+    Attribute,
     Struct,
     Union,
+    Enum,
+    Typedef,
+    VaStart,
+    VaArg,
+    VaEnd,
+    VaCopy,
 
     Plus,
     PlusPlus,
@@ -104,6 +123,7 @@ pub enum TokenKind {
     Semicolon,
     Comma,
     Dot,
+    Ellipsis,
 
     Eof,
     Error,
@@ -126,15 +146,22 @@ impl Display for TokenKind {
             TokenKind::IntConstant(IntKind::Long) => "long constant",
             TokenKind::IntConstant(IntKind::ULong) => "unsigned long constant",
             TokenKind::DoubleConstant => "double constant",
+            TokenKind::FloatConstant => "float constant",
             TokenKind::CharLiteral => "character literal",
             TokenKind::StringLiteral => "string literal",
             TokenKind::Char => "'char'",
+            TokenKind::Short => "'short'",
             TokenKind::Int => "'int'",
             TokenKind::Long => "'long'",
             TokenKind::Void => "'void'",
             TokenKind::Signed => "'signed'",
             TokenKind::Unsigned => "'unsigned'",
             TokenKind::Double => "'double'",
+            TokenKind::Float => "'float'",
+            TokenKind::Bool => "'_Bool'",
+            TokenKind::VaList => "'__builtin_va_list'",
+            TokenKind::Const => "'const'",
+            TokenKind::Volatile => "'volatile'",
             TokenKind::If => "'if'",
             TokenKind::Else => "'else'",
             TokenKind::Switch => "'switch'",
@@ -149,9 +176,21 @@ impl Display for TokenKind {
             TokenKind::Return => "'return'",
             TokenKind::Static => "'static'",
             TokenKind::Extern => "'extern'",
+            TokenKind::ThreadLocal => "'_Thread_local'",
             TokenKind::Sizeof => "'sizeof'",
+            TokenKind::Alignof => "'_Alignof'",
+            TokenKind::StaticAssert => "'_Static_assert'",
+            TokenKind::Inline => "'inline'",
+            TokenKind::Noreturn => "'_Noreturn'",
+            TokenKind::Attribute => "'__attribute__'",
             TokenKind::Struct => "'struct'",
             TokenKind::Union => "'union'",
+            TokenKind::Enum => "'enum'",
+            TokenKind::Typedef => "'typedef'",
+            TokenKind::VaStart => "'__builtin_va_start'",
+            TokenKind::VaArg => "'__builtin_va_arg'",
+            TokenKind::VaEnd => "'__builtin_va_end'",
+            TokenKind::VaCopy => "'__builtin_va_copy'",
             TokenKind::Plus => "'+'",
             TokenKind::PlusPlus => "'++'",
             TokenKind::PlusEqual => "'+='",
@@ -197,6 +236,7 @@ impl Display for TokenKind {
             TokenKind::Semicolon => "';'",
             TokenKind::Comma => "','",
             TokenKind::Dot => "'.'",
+            TokenKind::Ellipsis => "'...'",
             TokenKind::Eof => "end of file",
             TokenKind::Error => "error token",
         };
@@ -318,6 +358,8 @@ impl<'src> Lexer<'src> {
             '.' => {
                 if let Some('0'..='9') = self.peek() {
                     self.constant(c)
+                } else if let (Some('.'), Some('.')) = (self.peek(), self.peek_next()) {
+                    self.eat_eat_and(TokenKind::Ellipsis)
                 } else {
                     TokenKind::Dot
                 }
@@ -357,6 +399,10 @@ impl<'src> Lexer<'src> {
             }
             self.eat_numbers()
         }
+        if let Some('f' | 'F') = self.peek() {
+            self.advance();
+            return TokenKind::FloatConstant;
+        }
         TokenKind::DoubleConstant
     }
 
@@ -371,25 +417,7 @@ impl<'src> Lexer<'src> {
                     self.fraction()
                 }
                 (Some('e' | 'E'), _) => self.fraction(),
-                (Some('u') | Some('U'), Some('l') | Some('L')) => {
-                    self.advance();
-                    self.advance();
-                    TokenKind::IntConstant(IntKind::ULong)
-                }
-                (Some('l') | Some('L'), Some('u') | Some('U')) => {
-                    self.advance();
-                    self.advance();
-                    TokenKind::IntConstant(IntKind::ULong)
-                }
-                (Some('l') | Some('L'), _) => {
-                    self.advance();
-                    TokenKind::IntConstant(IntKind::Long)
-                }
-                (Some('u') | Some('U'), _) => {
-                    self.advance();
-                    TokenKind::IntConstant(IntKind::Uint)
-                }
-                _ => TokenKind::IntConstant(IntKind::Int),
+                _ => self.int_suffix(),
             }
         };
 
@@ -400,6 +428,43 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    /// Integer suffixes are `u`, `l` and `ll` in any order, `long long` has the
+    /// same size as `long` so both give a long constant.
+    fn int_suffix(&mut self) -> TokenKind {
+        let unsigned = self.eat_unsigned_suffix();
+        let long = self.eat_long_suffix();
+        let unsigned = unsigned || (long && self.eat_unsigned_suffix());
+        let kind = match (unsigned, long) {
+            (false, false) => IntKind::Int,
+            (true, false) => IntKind::Uint,
+            (false, true) => IntKind::Long,
+            (true, true) => IntKind::ULong,
+        };
+        TokenKind::IntConstant(kind)
+    }
+
+    fn eat_unsigned_suffix(&mut self) -> bool {
+        if let Some('u' | 'U') = self.peek() {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_long_suffix(&mut self) -> bool {
+        match self.peek() {
+            Some(c @ ('l' | 'L')) => {
+                self.advance();
+                if self.peek() == Some(c) {
+                    self.advance();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
     fn identifier(&mut self) -> TokenKind {
         while let Some(c) = self.peek() {
             if c.is_alphanumeric() || c == '_' {
@@ -410,12 +475,17 @@ impl<'src> Lexer<'src> {
         }
         match &self.source[self.start..self.offset] {
             "char" => TokenKind::Char,
+            "short" => TokenKind::Short,
             "int" => TokenKind::Int,
             "long" => TokenKind::Long,
             "void" => TokenKind::Void,
             "signed" => TokenKind::Signed,
             "unsigned" => TokenKind::Unsigned,
             "double" => TokenKind::Double,
+            "float" => TokenKind::Float,
+            "_Bool" => TokenKind::Bool,
+            "const" => TokenKind::Const,
+            "volatile" => TokenKind::Volatile,
             "return" => TokenKind::Return,
             "if" => TokenKind::If,
             "else" => TokenKind::Else,
@@ -430,9 +500,22 @@ impl<'src> Lexer<'src> {
             "continue" => TokenKind::Continue,
             "static" => TokenKind::Static,
             "extern" => TokenKind::Extern,
+            "_Thread_local" | "__thread" => TokenKind::ThreadLocal,
             "sizeof" => TokenKind::Sizeof,
+            "_Alignof" => TokenKind::Alignof,
+            "_Static_assert" => TokenKind::StaticAssert,
+            "inline" | "__inline" | "__inline__" => TokenKind::Inline,
+            "_Noreturn" => TokenKind::Noreturn,
+            "__attribute__" | "__attribute" => TokenKind::Attribute,
             "struct" => TokenKind::Struct,
             "union" => TokenKind::Union,
+            "enum" => TokenKind::Enum,
+            "typedef" => TokenKind::Typedef,
+            "__builtin_va_list" => TokenKind::VaList,
+            "__builtin_va_start" => TokenKind::VaStart,
+            "__builtin_va_arg" => TokenKind::VaArg,
+            "__builtin_va_end" => TokenKind::VaEnd,
+            "__builtin_va_copy" => TokenKind::VaCopy,
             _ => TokenKind::Identifier,
         }
     }
@@ -16,3 +16,11 @@ mod test_chapter_15;
 mod test_chapter_16;
 mod test_chapter_17;
 mod test_chapter_18;
+mod test_integer_types;
+mod test_float;
+mod test_variadic;
+mod test_qualifiers;
+mod test_expressions;
+mod test_attributes;
+mod test_thread_local;
+mod test_bool;
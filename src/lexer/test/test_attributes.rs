@@ -0,0 +1,36 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::{IntKind, tokenize};
+
+#[test]
+fn test_function_specifiers() {
+    let src = r#"
+        inline __inline __inline__ _Noreturn
+    "#;
+    let expected = vec![Inline, Inline, Inline, Noreturn];
+    assert_eq!(tokenize(src), expected);
+}
+
+#[test]
+fn test_attribute_tokens() {
+    let src = r#"
+        __attribute__((aligned(8))) __attribute((packed))
+    "#;
+    let expected = vec![
+        Attribute,
+        OpenParen,
+        OpenParen,
+        Identifier,
+        OpenParen,
+        IntConstant(IntKind::Int),
+        CloseParen,
+        CloseParen,
+        CloseParen,
+        Attribute,
+        OpenParen,
+        OpenParen,
+        Identifier,
+        CloseParen,
+        CloseParen,
+    ];
+    assert_eq!(tokenize(src), expected);
+}
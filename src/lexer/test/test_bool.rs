@@ -0,0 +1,11 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::tokenize;
+
+#[test]
+fn test_bool_keyword() {
+    let src = r#"
+        _Bool b;
+    "#;
+    let expected = vec![Bool, Identifier, Semicolon];
+    assert_eq!(tokenize(src), expected);
+}
@@ -0,0 +1,24 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::{IntKind, tokenize};
+
+#[test]
+fn test_alignof_and_static_assert_tokens() {
+    let src = r#"
+        _Static_assert(_Alignof(int) == 4, "int");
+    "#;
+    let expected = vec![
+        StaticAssert,
+        OpenParen,
+        Alignof,
+        OpenParen,
+        Int,
+        CloseParen,
+        EqualEqual,
+        IntConstant(IntKind::Int),
+        Comma,
+        StringLiteral,
+        CloseParen,
+        Semicolon,
+    ];
+    assert_eq!(tokenize(src), expected);
+}
@@ -0,0 +1,49 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::tokenize;
+
+#[test]
+#[should_panic]
+fn test_invalid_lex_float_suffix_on_integer() {
+    tokenize(
+        r#"
+        float f = 1f;
+    "#,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_lex_double_float_suffix() {
+    tokenize(
+        r#"
+        float f = 1.0ff;
+    "#,
+    );
+}
+
+#[test]
+fn test_valid_float_constants() {
+    let src = r#"
+        float f = 1.5f + 2e3F - .5f * 3.f;
+        double d = 1.5;
+    "#;
+    let expected = vec![
+        Float,
+        Identifier,
+        Equal,
+        FloatConstant,
+        Plus,
+        FloatConstant,
+        Minus,
+        FloatConstant,
+        Star,
+        FloatConstant,
+        Semicolon,
+        Double,
+        Identifier,
+        Equal,
+        DoubleConstant,
+        Semicolon,
+    ];
+    assert_eq!(tokenize(src), expected);
+}
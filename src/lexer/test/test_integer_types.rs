@@ -0,0 +1,66 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::{IntKind, tokenize};
+
+#[test]
+#[should_panic]
+fn test_invalid_lex_mixed_case_long_long_suffix() {
+    tokenize(
+        r#"
+        long long l = 1lL;
+    "#,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_lex_unsigned_mixed_case_long_long_suffix() {
+    tokenize(
+        r#"
+        unsigned long long l = 1uLl;
+    "#,
+    );
+}
+
+#[test]
+fn test_valid_short_and_long_long() {
+    let src = r#"
+        short s = 1;
+        unsigned short int us = 2u;
+        long long ll = 3ll + 4LL;
+        unsigned long long ull = 5ull + 6LLU + 7uLL;
+    "#;
+    let expected = vec![
+        Short,
+        Identifier,
+        Equal,
+        IntConstant(IntKind::Int),
+        Semicolon,
+        Unsigned,
+        Short,
+        Int,
+        Identifier,
+        Equal,
+        IntConstant(IntKind::Uint),
+        Semicolon,
+        Long,
+        Long,
+        Identifier,
+        Equal,
+        IntConstant(IntKind::Long),
+        Plus,
+        IntConstant(IntKind::Long),
+        Semicolon,
+        Unsigned,
+        Long,
+        Long,
+        Identifier,
+        Equal,
+        IntConstant(IntKind::ULong),
+        Plus,
+        IntConstant(IntKind::ULong),
+        Plus,
+        IntConstant(IntKind::ULong),
+        Semicolon,
+    ];
+    assert_eq!(tokenize(src), expected);
+}
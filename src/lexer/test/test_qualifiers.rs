@@ -0,0 +1,14 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::tokenize;
+
+#[test]
+fn test_qualifier_tokens() {
+    let src = r#"
+        const char *const s;
+        volatile int constant;
+    "#;
+    let expected = vec![
+        Const, Char, Star, Const, Identifier, Semicolon, Volatile, Int, Identifier, Semicolon,
+    ];
+    assert_eq!(tokenize(src), expected);
+}
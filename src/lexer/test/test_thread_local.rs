@@ -0,0 +1,11 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::tokenize;
+
+#[test]
+fn test_thread_local_keywords() {
+    let src = r#"
+        _Thread_local __thread
+    "#;
+    let expected = vec![ThreadLocal, ThreadLocal];
+    assert_eq!(tokenize(src), expected);
+}
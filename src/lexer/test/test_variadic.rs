@@ -0,0 +1,23 @@
+use crate::lexer::TokenKind::*;
+use crate::lexer::tokenize;
+
+#[test]
+fn test_variadic_tokens() {
+    let src = r#"
+        int f(int n, ...) {
+            __builtin_va_list ap;
+            __builtin_va_start(ap, n);
+            __builtin_va_arg(ap, int);
+            __builtin_va_copy(ap, ap);
+            __builtin_va_end(ap);
+        }
+    "#;
+    let expected = vec![
+        Int, Identifier, OpenParen, Int, Identifier, Comma, Ellipsis, CloseParen, OpenBrace,
+        VaList, Identifier, Semicolon, VaStart, OpenParen, Identifier, Comma, Identifier,
+        CloseParen, Semicolon, VaArg, OpenParen, Identifier, Comma, Int, CloseParen, Semicolon,
+        VaCopy, OpenParen, Identifier, Comma, Identifier, CloseParen, Semicolon, VaEnd, OpenParen,
+        Identifier, CloseParen, Semicolon, CloseBrace,
+    ];
+    assert_eq!(tokenize(src), expected);
+}
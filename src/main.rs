@@ -4,8 +4,10 @@ mod emitter;
 mod error;
 mod lexer;
 mod parser;
+mod preprocessor;
 mod pretty;
 mod semantic;
+mod source_map;
 mod symbol;
 mod tacky;
 mod tempfile;
@@ -15,9 +17,10 @@ mod optimization;
 
 use crate::asm::ir::Program;
 use crate::emitter::TargetOs;
+use crate::error::{CompilerError, Warning, WarningOptions};
 use crate::optimization::OptimizationFlags;
+use crate::source_map::SourceMap;
 use crate::tempfile::TempPath;
-use std::fs;
 use std::fs::OpenOptions;
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
@@ -27,8 +30,15 @@ type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
     let options = parse_args();
-    let preprocessed = run_preprocessor(&options.filename)?;
-    let source = fs::read_to_string(preprocessed.as_path())?;
+    let preprocessed = match preprocessor::preprocess(&options.filename, &options.preprocessor) {
+        Ok(preprocessed) => preprocessed,
+        Err(error) => {
+            eprint!("{}", error.render());
+            std::process::exit(1);
+        }
+    };
+    let source = preprocessed.source;
+    let source_map = preprocessed.source_map;
 
     if let Flag::Lex = options.flag {
         let tokens = lexer::tokenize(&source);
@@ -36,12 +46,12 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    let ast = match parser::parse(&source) {
+    let ast = match parser::parse(&source, options.error_limit) {
         Ok(ast) => ast,
-        Err(error) => {
-            let annotated = pretty::annotate(&source, &error);
-            eprintln!("Parsing error:\n");
-            eprintln!("{annotated}");
+        Err(errors) => {
+            for error in &errors {
+                eprint!("{}", error.render(&source_map));
+            }
             std::process::exit(1);
         }
     };
@@ -54,20 +64,20 @@ fn main() -> Result<()> {
     let (validated_ast, semantic_data) = match semantic::validate(ast) {
         Ok(ast) => ast,
         Err(error) => {
-            let annotated = pretty::annotate(&source, &error);
-            eprintln!("Semantic error:\n");
-            eprintln!("{annotated}");
+            eprint!("{}", error.render(&source_map));
             std::process::exit(1);
         }
     };
 
     if let Flag::Validate = options.flag {
+        report_warnings(&semantic_data.warnings, &options.warnings, &source_map);
         println!("{}", ast::pretty::dump(&validated_ast)?);
         println!("{semantic_data:#?}");
         return Ok(());
     }
 
     let tacky = tacky::emit(&validated_ast, semantic_data);
+    report_warnings(&tacky.semantics.warnings, &options.warnings, &source_map);
     if let Flag::Tacky = options.flag {
         println!("{}", tacky::pretty::pp(&tacky)?);
         println!("{:#?}", tacky.semantics);
@@ -95,10 +105,36 @@ fn main() -> Result<()> {
     }
 }
 
+/// Prints the enabled warnings sorted by location. Exits when they are
+/// treated as errors.
+fn report_warnings(warnings: &[CompilerError], options: &WarningOptions, source_map: &SourceMap) {
+    let mut enabled: Vec<_> = warnings
+        .iter()
+        .filter(|w| {
+            w.warning_kind()
+                .is_some_and(|kind| options.is_enabled(kind))
+        })
+        .collect();
+    enabled.sort_by_key(|w| w.span.0);
+    for warning in &enabled {
+        if options.as_errors {
+            eprint!("{}", warning.render_as_error(source_map));
+        } else {
+            eprint!("{}", warning.render(source_map));
+        }
+    }
+    if options.as_errors && !enabled.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 struct Options {
     filename: PathBuf,
     flag: Flag,
     optimization: OptimizationFlags,
+    preprocessor: preprocessor::Options,
+    error_limit: usize,
+    warnings: WarningOptions,
     linker_arg: Option<String>,
 }
 
@@ -139,10 +175,29 @@ fn parse_args() -> Options {
         eprintln!("  --propagate-copies");
         eprintln!("  --eliminate-unreachable-code");
         eprintln!("  --eliminate-dead-stores");
+        eprintln!("  --eliminate-common-subexpressions");
+        eprintln!("  --hoist-loop-invariants");
+        eprintln!("  --inline");
+        eprintln!("  --propagate-constants");
         eprintln!("  --trace              Enable debug optimizer passes\n");
+        eprintln!("Preprocessor:");
+        eprintln!("  -I<DIR>              Add a directory to the include search path");
+        eprintln!("  -D<NAME>[=VALUE]     Define a macro\n");
+        eprintln!("Warnings:");
+        eprintln!("  -Wall                Enable all warnings");
+        eprintln!("  -W<NAME>             Enable a warning, one of:");
+        for warning in Warning::ALL {
+            eprintln!("                         {}", warning.name());
+        }
+        eprintln!("  -Wno-<NAME>          Disable a warning");
+        eprintln!("  -Werror              Treat warnings as errors\n");
         eprintln!("Linking:");
         eprintln!("  -l<NAME>             Pass a single -l flag to linker");
         eprintln!("General:");
+        eprintln!(
+            "  --error-limit=<N>    Stop after N errors, 0 for no limit (default {})",
+            parser::DEFAULT_ERROR_LIMIT
+        );
         eprintln!("  -h, --help           Show this help and exit");
     }
 
@@ -167,6 +222,60 @@ fn parse_args() -> Options {
         .position(|arg| arg.starts_with("-l"))
         .map(|arg| args.remove(arg).clone());
 
+    let mut preprocessor = preprocessor::Options::default();
+    while let Some(i) = args.iter().position(|arg| arg.starts_with("-I")) {
+        let arg = args.remove(i);
+        let dir = match &arg[2..] {
+            "" if i < args.len() => args.remove(i),
+            dir => dir.to_owned(),
+        };
+        preprocessor.include_paths.push(PathBuf::from(dir));
+    }
+    while let Some(i) = args.iter().position(|arg| arg.starts_with("-D")) {
+        let arg = args.remove(i);
+        let definition = match &arg[2..] {
+            "" if i < args.len() => args.remove(i),
+            definition => definition.to_owned(),
+        };
+        let (name, value) = definition.split_once('=').unwrap_or((&definition, "1"));
+        preprocessor
+            .defines
+            .push((name.to_owned(), value.to_owned()));
+    }
+
+    let mut error_limit = parser::DEFAULT_ERROR_LIMIT;
+    if let Some(i) = args
+        .iter()
+        .position(|arg| arg.starts_with("--error-limit="))
+    {
+        let arg = args.remove(i);
+        error_limit = arg["--error-limit=".len()..].parse().unwrap_or_else(|_| {
+            eprintln!("Invalid error limit: {arg}");
+            std::process::exit(1);
+        });
+    }
+
+    // Like gcc, specific warning flags take precedence over -Wall
+    let mut warnings = WarningOptions::default();
+    if consume_flag(&mut args, "-Wall") {
+        warnings.enable_all();
+    }
+    if consume_flag(&mut args, "-Werror") {
+        warnings.as_errors = true;
+    }
+    while let Some(i) = args.iter().position(|arg| arg.starts_with("-W")) {
+        let arg = args.remove(i);
+        let (name, enabled) = match arg[2..].strip_prefix("no-") {
+            Some(name) => (name, false),
+            None => (&arg[2..], true),
+        };
+        let Some(warning) = Warning::from_name(name) else {
+            eprintln!("Unknown warning option: {arg}");
+            std::process::exit(1);
+        };
+        warnings.set(warning, enabled);
+    }
+
     if consume_flag(&mut args, "--fold-constants") {
         optimization.fold_constants = true;
     }
@@ -179,6 +288,18 @@ fn parse_args() -> Options {
     if consume_flag(&mut args, "--eliminate-dead-stores") {
         optimization.eliminate_dead_stores = true;
     }
+    if consume_flag(&mut args, "--eliminate-common-subexpressions") {
+        optimization.eliminate_common_subexpressions = true;
+    }
+    if consume_flag(&mut args, "--hoist-loop-invariants") {
+        optimization.hoist_loop_invariants = true;
+    }
+    if consume_flag(&mut args, "--inline") {
+        optimization.inline = true;
+    }
+    if consume_flag(&mut args, "--propagate-constants") {
+        optimization.propagate_constants = true;
+    }
     if consume_flag(&mut args, "--optimize") {
         optimization.optimize = true;
     }
@@ -209,27 +330,13 @@ fn parse_args() -> Options {
         filename: PathBuf::from(path),
         flag,
         optimization,
+        preprocessor,
+        error_limit,
+        warnings,
         linker_arg,
     }
 }
 
-fn run_preprocessor(filename: &Path) -> Result<TempPath> {
-    let output_path = TempPath::new(filename.with_extension("i"));
-    let output = Command::new("gcc")
-        .arg("-E")
-        .arg("-P")
-        .arg(filename)
-        .arg("-o")
-        .arg(output_path.as_path())
-        .output()?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8(output.stderr)?.into());
-    }
-
-    Ok(output_path)
-}
-
 fn write_assembly_to_stdout(program: &Program) -> Result<()> {
     let output = &mut std::io::stdout();
     emitter::emit_program(output, program, current_target())?;
@@ -1,12 +1,26 @@
 pub mod cfg;
+mod common_subexpressions;
 mod constant_folding;
+mod constant_propagation;
 mod copy_propagation;
 mod dead_store_elimination;
+mod dominators;
+mod inlining;
+mod loop_invariants;
+mod loops;
+mod ssa;
+#[cfg(test)]
+mod test;
 mod unreachable_code;
 
+use crate::optimization::common_subexpressions::eliminate_common_subexpressions;
 use crate::optimization::constant_folding::constant_fold;
+use crate::optimization::constant_propagation::propagate_constants;
 use crate::optimization::copy_propagation::copy_propagation;
 use crate::optimization::dead_store_elimination::dead_store_elimination;
+use crate::optimization::inlining::inline_functions;
+use crate::optimization::loop_invariants::hoist_loop_invariants;
+use crate::optimization::ssa::{from_ssa, to_ssa};
 use crate::optimization::unreachable_code::remove_unreachable_code;
 use crate::semantic::{Attributes, SemanticData, Type};
 use crate::tacky;
@@ -20,13 +34,40 @@ pub struct OptimizationFlags {
     pub propagate_copies: bool,
     pub eliminate_unreachable_code: bool,
     pub eliminate_dead_stores: bool,
+    pub eliminate_common_subexpressions: bool,
+    pub hoist_loop_invariants: bool,
+    pub inline: bool,
+    pub propagate_constants: bool,
     pub optimize: bool,
     pub trace: bool,
 }
 
 pub fn optimize(mut program: tacky::Program, flags: &OptimizationFlags) -> tacky::Program {
+    if flags.inline || flags.optimize {
+        inline_functions(&mut program, flags.trace);
+    }
     for top_level in &mut program.top_level {
         if let tacky::TopLevel::Function(f) = top_level {
+            if flags.propagate_constants
+                || flags.eliminate_common_subexpressions
+                || flags.hoist_loop_invariants
+                || flags.optimize
+            {
+                let mut cfg = Cfg::new(&f.body);
+                to_ssa(&mut cfg, &mut program.semantics, flags.trace);
+                let var_data = VariableData::new(&f.body, &program.semantics);
+                if flags.propagate_constants || flags.optimize {
+                    propagate_constants(&mut cfg, &var_data, flags.trace);
+                }
+                if flags.eliminate_common_subexpressions || flags.optimize {
+                    eliminate_common_subexpressions(&mut cfg, &var_data, flags.trace);
+                }
+                if flags.hoist_loop_invariants || flags.optimize {
+                    hoist_loop_invariants(&mut cfg, &var_data, flags.trace);
+                }
+                from_ssa(&mut cfg, &mut program.semantics, flags.trace);
+                f.body = cfg.dump();
+            }
             loop {
                 if flags.trace {
                     println!();
@@ -110,6 +151,11 @@ impl<'a> VariableData<'a> {
         self.is_aliased(val) || self.is_static(val)
     }
 
+    /// Accesses to volatile objects must be kept as they are written.
+    pub fn is_volatile(&self, val: &Val) -> bool {
+        self.semantics.val_ty(val).is_volatile()
+    }
+
     pub fn ty(&self, val: &Val) -> Type {
         self.semantics.val_ty(val)
     }
@@ -1,11 +1,14 @@
 use crate::symbol::Symbol;
 use std::cmp::PartialEq;
 use std::collections::HashMap;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 
 pub trait GenericInstruction: Clone {
     fn kind(&self) -> InstructionKind;
     fn pp(&self, f: &mut Formatter<'_>) -> std::fmt::Result;
+    fn label(label: Symbol) -> Self;
+    /// Changes the target of a jump or conditional jump.
+    fn retarget(&mut self, label: Symbol);
 }
 
 #[derive(Clone)]
@@ -26,6 +29,12 @@ impl NodeId {
     }
 }
 
+impl Display for NodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 pub struct GenericNode<T: GenericInstruction> {
     pub id: NodeId,
     pub instructions: Vec<T>,
@@ -46,6 +55,9 @@ impl<T: GenericInstruction> GenericNode<T> {
 
 pub struct GenericCfg<T: GenericInstruction> {
     nodes: Vec<GenericNode<T>>,
+    /// Layout of the blocks, a block without a jump at the end falls through
+    /// to the next one.
+    order: Vec<NodeId>,
     removed: Vec<NodeId>,
     entry_id: NodeId,
     exit_id: NodeId,
@@ -56,6 +68,7 @@ impl<T: GenericInstruction> GenericCfg<T> {
     pub fn new(instructions: &[T]) -> GenericCfg<T> {
         let mut cfg = GenericCfg {
             nodes: Vec::new(),
+            order: Vec::new(),
             removed: vec![],
             entry_id: NodeId(0),
             exit_id: NodeId(0),
@@ -101,6 +114,7 @@ impl<T: GenericInstruction> GenericCfg<T> {
     fn add_node(&mut self, instructions: Vec<T>) -> NodeId {
         let id = NodeId(self.nodes.len());
         self.nodes.push(GenericNode::new(id, instructions));
+        self.order.push(id);
         id
     }
 
@@ -134,7 +148,7 @@ impl<T: GenericInstruction> GenericCfg<T> {
         self.nodes[to.0].predecessors.push(from);
     }
 
-    fn get_by_label(&self, label: &Symbol) -> NodeId {
+    pub fn get_by_label(&self, label: &Symbol) -> NodeId {
         *self.by_label.get(label).unwrap()
     }
 
@@ -143,9 +157,9 @@ impl<T: GenericInstruction> GenericCfg<T> {
     }
 
     pub fn all_ids(&self) -> impl Iterator<Item = NodeId> {
-        self.nodes
+        self.order
             .iter()
-            .map(|node| node.id)
+            .copied()
             .filter(|id| !self.removed.contains(id))
     }
 
@@ -173,6 +187,11 @@ impl<T: GenericInstruction> GenericCfg<T> {
         &mut self.nodes[id.0]
     }
 
+    pub fn remove_edge(&mut self, from: NodeId, to: NodeId) {
+        self.nodes[from.0].successors.retain(|&id| id != to);
+        self.nodes[to.0].predecessors.retain(|&id| id != from);
+    }
+
     pub fn remove_node(&mut self, id: NodeId) {
         self.removed.push(id);
         let predecessors = self.nodes[id.0].predecessors.clone();
@@ -196,6 +215,70 @@ impl<T: GenericInstruction> GenericCfg<T> {
         self.nodes[id.0].successors.clear()
     }
 
+    /// Adds a block right before `header`, which takes over the edges coming
+    /// into it from `predecessors`. Fails when the header doesn't start with
+    /// a label, or when a block that isn't one of the predecessors falls
+    /// through into it.
+    pub fn insert_preheader(
+        &mut self,
+        header_id: NodeId,
+        predecessors: &[NodeId],
+    ) -> Option<NodeId> {
+        let Some(InstructionKind::Label(header_label)) = self.nodes[header_id.0]
+            .instructions
+            .first()
+            .map(|i| i.kind())
+        else {
+            return None;
+        };
+        let previous_id = self
+            .all_ids()
+            .take_while(|&id| id != header_id)
+            .last()
+            .expect("The entry comes before every block");
+        let falls_through = !matches!(
+            self.nodes[previous_id.0]
+                .instructions
+                .last()
+                .map(|i| i.kind()),
+            Some(InstructionKind::Jump { .. } | InstructionKind::Return)
+        );
+        if falls_through && !predecessors.contains(&previous_id) {
+            return None;
+        }
+
+        let label = Symbol::from(format!("{header_label}.preheader"));
+        let id = NodeId(self.nodes.len());
+        self.nodes
+            .push(GenericNode::new(id, vec![T::label(label.clone())]));
+        self.by_label.insert(label.clone(), id);
+        let position = self.order.iter().position(|&i| i == header_id).unwrap();
+        self.order.insert(position, id);
+
+        for &pred_id in predecessors {
+            let pred = &mut self.nodes[pred_id.0];
+            if let Some(last) = pred.instructions.last_mut()
+                && let InstructionKind::Jump { label: target }
+                | InstructionKind::ConditionalJump { label: target } = last.kind()
+                && target == header_label
+            {
+                last.retarget(label.clone());
+            }
+            for succ_id in pred.successors.iter_mut() {
+                if *succ_id == header_id {
+                    *succ_id = id;
+                }
+            }
+            pred.successors.dedup();
+            self.nodes[id.0].predecessors.push(pred_id);
+        }
+        self.nodes[header_id.0]
+            .predecessors
+            .retain(|pred_id| !predecessors.contains(pred_id));
+        self.add_edge(id, header_id);
+        Some(id)
+    }
+
     pub fn dump(&self) -> Vec<T> {
         let mut result = Vec::new();
         for node_id in self.all_ids() {
@@ -241,7 +324,7 @@ impl<T> Annotation<T> {
 
 impl<T: GenericInstruction> Debug for GenericCfg<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for node in self.nodes.iter() {
+        for node in self.order.iter().map(|id| &self.nodes[id.0]) {
             match node.id {
                 id if id == self.entry_id() => writeln!(f, "  ##ENTRY: ")?,
                 id if id == self.exit_id() => writeln!(f, "  ##EXIT: ")?,
@@ -0,0 +1,247 @@
+use crate::optimization::VariableData;
+use crate::optimization::cfg::NodeId;
+use crate::optimization::dominators::Dominators;
+use crate::optimization::ssa::{Definitions, defined_var};
+use crate::tacky::cfg::Cfg;
+use crate::tacky::{BinaryOp, Instruction, UnaryOp, Val};
+use std::collections::HashMap;
+use std::mem::Discriminant;
+
+/// Replaces a `Binary`, `AddPtr`, `Load`, unary operation or conversion with
+/// a copy of an earlier instruction computing the same thing from the same
+/// operands, when that instruction dominates it. Expects the function to be
+/// in SSA form, so operands holding a single value for the whole function
+/// can be told apart from variables that keep being reassigned.
+///
+/// Loads are only reused along straight line code, and any write that could
+/// reach memory through a pointer forgets them.
+pub fn eliminate_common_subexpressions(cfg: &mut Cfg, var_data: &VariableData, trace: bool) {
+    if trace {
+        println!("=======================");
+        println!("Common subexpression elimination");
+        println!("=======================");
+        println!("INITIAL\n {cfg:#?}");
+    }
+
+    let dominators = Dominators::new(cfg);
+    let definitions = Definitions::new(cfg);
+
+    let mut eliminator = Eliminator {
+        var_data,
+        definitions,
+        numbering: HashMap::new(),
+        available: HashMap::new(),
+    };
+    eliminator.visit(cfg, &dominators, cfg.entry_id(), HashMap::new());
+
+    if trace {
+        println!("Instructions Rewritten:\n {cfg:#?}");
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Expression {
+    Unary(UnaryOp, Val),
+    /// Conversions are told apart by the kind of instruction, the type of
+    /// the result is compared when reusing them.
+    Conversion(Discriminant<Instruction>, Val),
+    Binary(BinaryOp, Val, Val),
+    AddPtr(Val, Val, usize),
+    Load(Val),
+}
+
+impl Expression {
+    fn from_instruction(instruction: &Instruction) -> Option<(Self, Val)> {
+        match instruction {
+            Instruction::Unary { op, src, dst } => {
+                Some((Self::Unary(op.clone(), src.clone()), dst.clone()))
+            }
+            Instruction::SignExtend { src, dst }
+            | Instruction::Truncate { src, dst }
+            | Instruction::ZeroExtend { src, dst }
+            | Instruction::DoubleToInt { src, dst }
+            | Instruction::DoubleToUInt { src, dst }
+            | Instruction::IntToDouble { src, dst }
+            | Instruction::UIntToDouble { src, dst }
+            | Instruction::FloatToDouble { src, dst }
+            | Instruction::DoubleToFloat { src, dst } => Some((
+                Self::Conversion(std::mem::discriminant(instruction), src.clone()),
+                dst.clone(),
+            )),
+            Instruction::Binary {
+                op,
+                src1,
+                src2,
+                dst,
+            } => Some((
+                Self::Binary(op.clone(), src1.clone(), src2.clone()),
+                dst.clone(),
+            )),
+            Instruction::AddPtr {
+                ptr,
+                index,
+                scale,
+                dst,
+            } => Some((
+                Self::AddPtr(ptr.clone(), index.clone(), *scale),
+                dst.clone(),
+            )),
+            Instruction::Load { ptr, dst } => Some((Self::Load(ptr.clone()), dst.clone())),
+            _ => None,
+        }
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut Val> {
+        match self {
+            Self::Unary(_, src) | Self::Conversion(_, src) => vec![src],
+            Self::Binary(_, src1, src2) => vec![src1, src2],
+            Self::AddPtr(ptr, index, _) => vec![ptr, index],
+            Self::Load(ptr) => vec![ptr],
+        }
+    }
+
+    /// The same expression with the operands swapped, if that doesn't
+    /// change its value.
+    fn commuted(&self) -> Option<Self> {
+        let Self::Binary(op, src1, src2) = self else {
+            return None;
+        };
+        match op {
+            BinaryOp::Add
+            | BinaryOp::Multiply
+            | BinaryOp::BinAnd
+            | BinaryOp::BinOr
+            | BinaryOp::BinXor
+            | BinaryOp::Equal
+            | BinaryOp::NotEqual => Some(Self::Binary(op.clone(), src2.clone(), src1.clone())),
+            _ => None,
+        }
+    }
+}
+
+struct Eliminator<'a, 'b> {
+    var_data: &'a VariableData<'b>,
+    definitions: Definitions,
+    /// Variables known to be copies of another value, which is used in
+    /// their place when looking expressions up.
+    numbering: HashMap<Val, Val>,
+    /// Pure expressions computed by the blocks dominating the current one.
+    available: HashMap<Expression, Val>,
+}
+
+impl Eliminator<'_, '_> {
+    /// `loads` holds the loads still valid on entry, which are only passed
+    /// down to a block that can only be reached from its dominator.
+    fn visit(
+        &mut self,
+        cfg: &mut Cfg,
+        dominators: &Dominators,
+        node_id: NodeId,
+        mut loads: HashMap<Expression, Val>,
+    ) {
+        let mut added = Vec::new();
+        for instruction in &mut cfg.get_node_mut(node_id).instructions {
+            if self.clobbers_memory(instruction) {
+                loads.clear();
+            }
+            if let Instruction::Copy { src, dst } = instruction {
+                self.number_copy(src, dst);
+                continue;
+            }
+            let Some((mut expression, dst)) = Expression::from_instruction(instruction) else {
+                continue;
+            };
+            if self.is_volatile_load(&expression) {
+                continue;
+            }
+            let mut operands = expression.operands_mut();
+            if !operands.iter().all(|val| self.is_value(val)) {
+                continue;
+            }
+            for val in &mut operands {
+                if let Some(number) = self.numbering.get(val) {
+                    **val = number.clone();
+                }
+            }
+
+            let is_value = self.is_value(&dst);
+            let table = match expression {
+                Expression::Load(_) => &mut loads,
+                _ => &mut self.available,
+            };
+            let previous = table.get(&expression).or_else(|| {
+                expression
+                    .commuted()
+                    .and_then(|commuted| table.get(&commuted))
+            });
+            if let Some(previous) = previous {
+                if self.var_data.ty(previous) == self.var_data.ty(&dst) {
+                    if is_value {
+                        self.numbering.insert(dst.clone(), previous.clone());
+                    }
+                    *instruction = Instruction::Copy {
+                        src: previous.clone(),
+                        dst,
+                    };
+                }
+            } else if is_value {
+                if !matches!(expression, Expression::Load(_)) {
+                    added.push(expression.clone());
+                }
+                table.insert(expression, dst);
+            }
+        }
+
+        for &child_id in dominators.children(node_id) {
+            let child_loads = if cfg.get_node(child_id).predecessors == [node_id] {
+                loads.clone()
+            } else {
+                HashMap::new()
+            };
+            self.visit(cfg, dominators, child_id, child_loads);
+        }
+        for expression in added {
+            self.available.remove(&expression);
+        }
+    }
+
+    fn number_copy(&mut self, src: &Val, dst: &Val) {
+        if self.is_value(src)
+            && self.is_value(dst)
+            && self.var_data.ty(src) == self.var_data.ty(dst)
+        {
+            let number = self.numbering.get(src).unwrap_or(src).clone();
+            self.numbering.insert(dst.clone(), number);
+        }
+    }
+
+    fn is_value(&self, val: &Val) -> bool {
+        self.definitions.is_value(val, self.var_data)
+    }
+
+    fn is_volatile_load(&self, expression: &Expression) -> bool {
+        matches!(
+            expression,
+            Expression::Load(ptr) if self.var_data.ty(ptr).is_pointer_to_volatile()
+        )
+    }
+
+    /// Whether the instruction may change an object some pointer could load.
+    fn clobbers_memory(&self, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::Store { .. }
+            | Instruction::FnCall { .. }
+            | Instruction::IndirectCall { .. }
+            | Instruction::VaStart { .. }
+            | Instruction::VaArg { .. }
+            | Instruction::AllocateStack { .. }
+            | Instruction::RestoreStack { .. } => true,
+            Instruction::CopyToOffset { dst, .. } => {
+                self.var_data.is_aliased_or_static(&Val::Var(dst.clone()))
+            }
+            _ => {
+                defined_var(instruction).is_some_and(|dst| self.var_data.is_aliased_or_static(dst))
+            }
+        }
+    }
+}
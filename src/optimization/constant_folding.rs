@@ -1,11 +1,11 @@
-use crate::ast::Constant::{Char, Double, Int, Long, UChar, UInt, ULong};
+use crate::ast::Constant::{Char, Double, Float, Int, Long, Short, UChar, UInt, ULong, UShort};
 use crate::optimization::VariableData;
 use crate::semantic::Type;
 use crate::tacky::pretty::pp_instruction;
 use crate::tacky::{BinaryOp, Instruction, UnaryOp, Val};
 use Instruction::{
-    Binary, DoubleToInt, DoubleToUInt, IntToDouble, Jump, JumpIfNotZero, JumpIfZero, SignExtend,
-    Truncate, UIntToDouble, Unary, ZeroExtend,
+    Binary, DoubleToFloat, DoubleToInt, DoubleToUInt, FloatToDouble, IntToDouble, Jump,
+    JumpIfNotZero, JumpIfZero, SignExtend, Truncate, UIntToDouble, Unary, ZeroExtend,
 };
 use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
 
@@ -37,8 +37,11 @@ pub fn constant_fold(
                     ULong(value) => ULong(value.not()),
                     Char(value) => Char(value.not()),
                     UChar(value) => UChar(value.not()),
+                    Short(value) => Short(value.not()),
+                    UShort(value) => UShort(value.not()),
 
                     Double(_) => unreachable!("Type checker should prevent complement of doubles"),
+                    Float(_) => unreachable!("Type checker should prevent complement of floats"),
                 };
                 new.push(Instruction::Copy {
                     src: Val::Constant(complement),
@@ -57,7 +60,10 @@ pub fn constant_fold(
                     ULong(value) => ULong(value.wrapping_neg()),
                     Char(value) => Char(value.neg()),
                     UChar(value) => UChar(value.wrapping_neg()),
+                    Short(value) => Short(value.neg()),
+                    UShort(value) => UShort(value.wrapping_neg()),
                     Double(value) => Double(value.neg()),
+                    Float(value) => Float(value.neg()),
                 };
                 new.push(Instruction::Copy {
                     src: Val::Constant(negated),
@@ -93,7 +99,10 @@ pub fn constant_fold(
                     ULong(value) => ULong(value.wrapping_add(1)),
                     Char(value) => Char(value.wrapping_add(1)),
                     UChar(value) => UChar(value.wrapping_add(1)),
+                    Short(value) => Short(value.wrapping_add(1)),
+                    UShort(value) => UShort(value.wrapping_add(1)),
                     Double(value) => Double(value.add(1.0)),
+                    Float(value) => Float(value.add(1.0)),
                 };
                 new.push(Instruction::Copy {
                     src: Val::Constant(incremented),
@@ -112,7 +121,10 @@ pub fn constant_fold(
                     ULong(value) => ULong(value.wrapping_sub(1)),
                     Char(value) => Char(value.wrapping_sub(1)),
                     UChar(value) => UChar(value.wrapping_sub(1)),
+                    Short(value) => Short(value.wrapping_sub(1)),
+                    UShort(value) => UShort(value.wrapping_sub(1)),
                     Double(value) => Double(value.sub(1.0)),
+                    Float(value) => Float(value.sub(1.0)),
                 };
                 new.push(Instruction::Copy {
                     src: Val::Constant(decremented),
@@ -132,7 +144,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => ULong(left.wrapping_add(*right)),
                     (Char(left), Char(right)) => Char(left.wrapping_add(*right)),
                     (UChar(left), UChar(right)) => UChar(left.wrapping_add(*right)),
+                    (Short(left), Short(right)) => Short(left.wrapping_add(*right)),
+                    (UShort(left), UShort(right)) => UShort(left.wrapping_add(*right)),
                     (Double(left), Double(right)) => Double(left.add(*right)),
+                    (Float(left), Float(right)) => Float(left.add(*right)),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -155,7 +170,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => ULong(left.wrapping_sub(*right)),
                     (Char(left), Char(right)) => Char(left.wrapping_sub(*right)),
                     (UChar(left), UChar(right)) => UChar(left.wrapping_sub(*right)),
+                    (Short(left), Short(right)) => Short(left.wrapping_sub(*right)),
+                    (UShort(left), UShort(right)) => UShort(left.wrapping_sub(*right)),
                     (Double(left), Double(right)) => Double(left.sub(*right)),
+                    (Float(left), Float(right)) => Float(left.sub(*right)),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -178,7 +196,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => ULong(left.wrapping_mul(*right)),
                     (Char(left), Char(right)) => Char(left.wrapping_mul(*right)),
                     (UChar(left), UChar(right)) => UChar(left.wrapping_mul(*right)),
+                    (Short(left), Short(right)) => Short(left.wrapping_mul(*right)),
+                    (UShort(left), UShort(right)) => UShort(left.wrapping_mul(*right)),
                     (Double(left), Double(right)) => Double(left.mul(*right)),
+                    (Float(left), Float(right)) => Float(left.mul(*right)),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -204,7 +225,10 @@ pub fn constant_fold(
                         (ULong(left), ULong(right)) => ULong(left.wrapping_div(*right)),
                         (Char(left), Char(right)) => Char(left.div(*right)),
                         (UChar(left), UChar(right)) => UChar(left.wrapping_div(*right)),
+                        (Short(left), Short(right)) => Short(left.div(*right)),
+                        (UShort(left), UShort(right)) => UShort(left.wrapping_div(*right)),
                         (Double(left), Double(right)) => Double(left.div(*right)),
+                        (Float(left), Float(right)) => Float(left.div(*right)),
                         _ => unreachable!(
                             "Type checker should prevent binary ops with different types"
                         ),
@@ -232,7 +256,10 @@ pub fn constant_fold(
                         (ULong(left), ULong(right)) => ULong(left.wrapping_rem(*right)),
                         (Char(left), Char(right)) => Char(left.rem(*right)),
                         (UChar(left), UChar(right)) => UChar(left.wrapping_rem(*right)),
+                        (Short(left), Short(right)) => Short(left.rem(*right)),
+                        (UShort(left), UShort(right)) => UShort(left.wrapping_rem(*right)),
                         (Double(left), Double(right)) => Double(left.rem(*right)),
+                        (Float(left), Float(right)) => Float(left.rem(*right)),
                         _ => unreachable!(
                             "Type checker should prevent binary ops with different types"
                         ),
@@ -257,7 +284,9 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => ULong(left.bitand(*right)),
                     (Char(left), Char(right)) => Char(left.bitand(*right)),
                     (UChar(left), UChar(right)) => UChar(left.bitand(*right)),
-                    (Double(_), Double(_)) => {
+                    (Short(left), Short(right)) => Short(left.bitand(*right)),
+                    (UShort(left), UShort(right)) => UShort(left.bitand(*right)),
+                    (Double(_), Double(_)) | (Float(_), Float(_)) => {
                         unreachable!("Type checker should prevent binary ops with doubles")
                     }
                     _ => {
@@ -283,7 +312,9 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => ULong(left.bitor(*right)),
                     (Char(left), Char(right)) => Char(left.bitor(*right)),
                     (UChar(left), UChar(right)) => UChar(left.bitor(*right)),
-                    (Double(_), Double(_)) => {
+                    (Short(left), Short(right)) => Short(left.bitor(*right)),
+                    (UShort(left), UShort(right)) => UShort(left.bitor(*right)),
+                    (Double(_), Double(_)) | (Float(_), Float(_)) => {
                         unreachable!("Type checker should prevent binary ops with doubles")
                     }
                     _ => {
@@ -309,7 +340,9 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => ULong(left.bitxor(*right)),
                     (Char(left), Char(right)) => Char(left.bitxor(*right)),
                     (UChar(left), UChar(right)) => UChar(left.bitxor(*right)),
-                    (Double(_), Double(_)) => {
+                    (Short(left), Short(right)) => Short(left.bitxor(*right)),
+                    (UShort(left), UShort(right)) => UShort(left.bitxor(*right)),
+                    (Double(_), Double(_)) | (Float(_), Float(_)) => {
                         unreachable!("Type checker should prevent binary ops with doubles")
                     }
                     _ => {
@@ -335,7 +368,9 @@ pub fn constant_fold(
                     (ULong(left), right) if right.is_int() => ULong(left.shl(right.as_u64())),
                     (Char(left), right) if right.is_int() => Char(left.shl(right.as_u64())),
                     (UChar(left), right) if right.is_int() => UChar(left.shl(right.as_u64())),
-                    (Double(_), Double(_)) => {
+                    (Short(left), right) if right.is_int() => Short(left.shl(right.as_u64())),
+                    (UShort(left), right) if right.is_int() => UShort(left.shl(right.as_u64())),
+                    (Double(_), Double(_)) | (Float(_), Float(_)) => {
                         unreachable!("Type checker should prevent binary ops with doubles")
                     }
                     _ => {
@@ -361,7 +396,9 @@ pub fn constant_fold(
                     (ULong(left), right) if right.is_int() => ULong(left.shr(right.as_u64())),
                     (Char(left), right) if right.is_int() => Char(left.shr(right.as_u64())),
                     (UChar(left), right) if right.is_int() => UChar(left.shr(right.as_u64())),
-                    (Double(_), Double(_)) => {
+                    (Short(left), right) if right.is_int() => Short(left.shr(right.as_u64())),
+                    (UShort(left), right) if right.is_int() => UShort(left.shr(right.as_u64())),
+                    (Double(_), Double(_)) | (Float(_), Float(_)) => {
                         unreachable!("Type checker should prevent binary ops with doubles")
                     }
                     _ => {
@@ -387,7 +424,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => Int(left.eq(right) as i32),
                     (Char(left), Char(right)) => Int(left.eq(right) as i32),
                     (UChar(left), UChar(right)) => Int(left.eq(right) as i32),
+                    (Short(left), Short(right)) => Int(left.eq(right) as i32),
+                    (UShort(left), UShort(right)) => Int(left.eq(right) as i32),
                     (Double(left), Double(right)) => Int(left.eq(right) as i32),
+                    (Float(left), Float(right)) => Int(left.eq(right) as i32),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -411,7 +451,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => Int(left.ne(right) as i32),
                     (Char(left), Char(right)) => Int(left.ne(right) as i32),
                     (UChar(left), UChar(right)) => Int(left.ne(right) as i32),
+                    (Short(left), Short(right)) => Int(left.ne(right) as i32),
+                    (UShort(left), UShort(right)) => Int(left.ne(right) as i32),
                     (Double(left), Double(right)) => Int(left.ne(right) as i32),
+                    (Float(left), Float(right)) => Int(left.ne(right) as i32),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -435,7 +478,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => Int(left.lt(right) as i32),
                     (Char(left), Char(right)) => Int(left.lt(right) as i32),
                     (UChar(left), UChar(right)) => Int(left.lt(right) as i32),
+                    (Short(left), Short(right)) => Int(left.lt(right) as i32),
+                    (UShort(left), UShort(right)) => Int(left.lt(right) as i32),
                     (Double(left), Double(right)) => Int(left.lt(right) as i32),
+                    (Float(left), Float(right)) => Int(left.lt(right) as i32),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -459,7 +505,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => Int(left.le(right) as i32),
                     (Char(left), Char(right)) => Int(left.le(right) as i32),
                     (UChar(left), UChar(right)) => Int(left.le(right) as i32),
+                    (Short(left), Short(right)) => Int(left.le(right) as i32),
+                    (UShort(left), UShort(right)) => Int(left.le(right) as i32),
                     (Double(left), Double(right)) => Int(left.le(right) as i32),
+                    (Float(left), Float(right)) => Int(left.le(right) as i32),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -483,7 +532,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => Int(left.gt(right) as i32),
                     (Char(left), Char(right)) => Int(left.gt(right) as i32),
                     (UChar(left), UChar(right)) => Int(left.gt(right) as i32),
+                    (Short(left), Short(right)) => Int(left.gt(right) as i32),
+                    (UShort(left), UShort(right)) => Int(left.gt(right) as i32),
                     (Double(left), Double(right)) => Int(left.gt(right) as i32),
+                    (Float(left), Float(right)) => Int(left.gt(right) as i32),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -507,7 +559,10 @@ pub fn constant_fold(
                     (ULong(left), ULong(right)) => Int(left.ge(right) as i32),
                     (Char(left), Char(right)) => Int(left.ge(right) as i32),
                     (UChar(left), UChar(right)) => Int(left.ge(right) as i32),
+                    (Short(left), Short(right)) => Int(left.ge(right) as i32),
+                    (UShort(left), UShort(right)) => Int(left.ge(right) as i32),
                     (Double(left), Double(right)) => Int(left.ge(right) as i32),
+                    (Float(left), Float(right)) => Int(left.ge(right) as i32),
                     _ => {
                         unreachable!("Type checker should prevent binary ops with different types")
                     }
@@ -566,6 +621,14 @@ pub fn constant_fold(
                 src: Val::Constant(c),
                 dst,
             }
+            | FloatToDouble {
+                src: Val::Constant(c),
+                dst,
+            }
+            | DoubleToFloat {
+                src: Val::Constant(c),
+                dst,
+            }
             | Instruction::Copy {
                 src: Val::Constant(c),
                 dst,
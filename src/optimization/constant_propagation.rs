@@ -0,0 +1,331 @@
+use crate::ast::Constant;
+use crate::optimization::VariableData;
+use crate::optimization::cfg::NodeId;
+use crate::optimization::constant_folding::constant_fold;
+use crate::optimization::ssa::{Definitions, defined_var, used_vals_mut, used_vars};
+use crate::symbol::Symbol;
+use crate::tacky::cfg::Cfg;
+use crate::tacky::{Instruction, Val};
+use std::collections::{HashMap, HashSet};
+
+/// Sparse conditional constant propagation. Finds the values that are
+/// constant on every path that can actually be executed, starting from the
+/// entry and only following the branches whose condition allows it.
+/// Expects the function to be in SSA form.
+///
+/// Uses of constant values are replaced by the constant, and branches that
+/// always go the same way become jumps, leaving the blocks that can't be
+/// executed for `remove_unreachable_code`.
+pub fn propagate_constants(cfg: &mut Cfg, var_data: &VariableData, trace: bool) {
+    if trace {
+        println!("=======================");
+        println!("Sparse conditional constant propagation");
+        println!("=======================");
+        println!("INITIAL\n {cfg:#?}");
+    }
+
+    let mut propagation = Propagation::new(cfg, var_data);
+    propagation.run(cfg);
+    if trace {
+        println!("Constants:\n {:?}", propagation.values);
+        println!("Executable blocks:\n {:?}", propagation.executable_blocks);
+    }
+    propagation.rewrite(cfg);
+
+    if trace {
+        println!("Instructions Rewritten:\n {cfg:#?}");
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Lattice {
+    /// No executed definition was found yet.
+    Undefined,
+    Constant(Constant),
+    /// Not a constant, or not known to be one.
+    Varying,
+}
+
+impl Lattice {
+    fn meet(&self, other: &Lattice) -> Lattice {
+        match (self, other) {
+            (Lattice::Undefined, value) | (value, Lattice::Undefined) => value.clone(),
+            (Lattice::Constant(a), Lattice::Constant(b)) if a == b => self.clone(),
+            _ => Lattice::Varying,
+        }
+    }
+}
+
+struct Propagation<'a, 'b> {
+    var_data: &'a VariableData<'b>,
+    definitions: Definitions,
+    /// Instructions reading each variable.
+    uses: HashMap<Val, Vec<(NodeId, usize)>>,
+    values: HashMap<Val, Lattice>,
+    executable_edges: HashSet<(NodeId, NodeId)>,
+    executable_blocks: HashSet<NodeId>,
+    edge_worklist: Vec<(NodeId, NodeId)>,
+    instruction_worklist: Vec<(NodeId, usize)>,
+}
+
+impl<'a, 'b> Propagation<'a, 'b> {
+    fn new(cfg: &Cfg, var_data: &'a VariableData<'b>) -> Self {
+        let mut uses: HashMap<Val, Vec<(NodeId, usize)>> = HashMap::new();
+        for node_id in cfg.all_ids() {
+            for (i, instruction) in cfg.get_node(node_id).instructions.iter().enumerate() {
+                for name in used_vars(instruction) {
+                    uses.entry(Val::Var(name.clone()))
+                        .or_default()
+                        .push((node_id, i));
+                }
+            }
+        }
+        Self {
+            var_data,
+            definitions: Definitions::new(cfg),
+            uses,
+            values: HashMap::new(),
+            executable_edges: HashSet::new(),
+            executable_blocks: HashSet::new(),
+            edge_worklist: vec![],
+            instruction_worklist: vec![],
+        }
+    }
+
+    fn run(&mut self, cfg: &Cfg) {
+        self.executable_blocks.insert(cfg.entry_id());
+        self.visit_terminator(cfg, cfg.entry_id());
+        loop {
+            if let Some((from, to)) = self.edge_worklist.pop() {
+                if !self.executable_edges.insert((from, to)) {
+                    continue;
+                }
+                let first_visit = self.executable_blocks.insert(to);
+                let node = cfg.get_node(to);
+                for (i, instruction) in node.instructions.iter().enumerate() {
+                    if first_visit || matches!(instruction, Instruction::Phi { .. }) {
+                        self.visit_instruction(cfg, to, i);
+                    }
+                }
+                if first_visit && !ends_with_branch(&node.instructions) {
+                    self.visit_terminator(cfg, to);
+                }
+            } else if let Some((node_id, i)) = self.instruction_worklist.pop() {
+                if self.executable_blocks.contains(&node_id) {
+                    self.visit_instruction(cfg, node_id, i);
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn visit_instruction(&mut self, cfg: &Cfg, node_id: NodeId, i: usize) {
+        let instruction = &cfg.get_node(node_id).instructions[i];
+        match instruction {
+            Instruction::Jump { .. }
+            | Instruction::JumpIfZero { .. }
+            | Instruction::JumpIfNotZero { .. }
+            | Instruction::Return(_) => self.visit_terminator(cfg, node_id),
+            Instruction::Phi { dst, args } => {
+                let value = args
+                    .iter()
+                    .filter(|(pred_id, _)| self.executable_edges.contains(&(*pred_id, node_id)))
+                    .fold(Lattice::Undefined, |value, (_, arg)| {
+                        value.meet(&self.value(arg))
+                    });
+                self.update(dst, value);
+            }
+            instruction => {
+                if let Some(dst) = defined_var(instruction) {
+                    let value = self.evaluate(instruction);
+                    self.update(dst, value);
+                }
+            }
+        }
+    }
+
+    /// Marks the edges leaving the block that can be taken.
+    fn visit_terminator(&mut self, cfg: &Cfg, node_id: NodeId) {
+        let node = cfg.get_node(node_id);
+        let taken = match node.instructions.last() {
+            Some(Instruction::JumpIfZero { cond, target }) => {
+                self.branch(cfg, node_id, cond, target, true)
+            }
+            Some(Instruction::JumpIfNotZero { cond, target }) => {
+                self.branch(cfg, node_id, cond, target, false)
+            }
+            _ => node.successors.clone(),
+        };
+        self.edge_worklist
+            .extend(taken.into_iter().map(|succ_id| (node_id, succ_id)));
+    }
+
+    fn branch(
+        &self,
+        cfg: &Cfg,
+        node_id: NodeId,
+        cond: &Val,
+        target: &Symbol,
+        jump_if_zero: bool,
+    ) -> Vec<NodeId> {
+        let successors = &cfg.get_node(node_id).successors;
+        match self.value(cond) {
+            Lattice::Undefined => vec![],
+            Lattice::Varying => successors.clone(),
+            Lattice::Constant(c) => {
+                let target_id = cfg.get_by_label(target);
+                let fallthrough: Vec<_> = successors
+                    .iter()
+                    .copied()
+                    .filter(|&succ_id| succ_id != target_id)
+                    .collect();
+                if c.is_zero() == jump_if_zero || fallthrough.is_empty() {
+                    vec![target_id]
+                } else {
+                    fallthrough
+                }
+            }
+        }
+    }
+
+    fn evaluate(&self, instruction: &Instruction) -> Lattice {
+        if !matches!(
+            instruction,
+            Instruction::Copy { .. }
+                | Instruction::Unary { .. }
+                | Instruction::Binary { .. }
+                | Instruction::SignExtend { .. }
+                | Instruction::Truncate { .. }
+                | Instruction::ZeroExtend { .. }
+                | Instruction::DoubleToInt { .. }
+                | Instruction::DoubleToUInt { .. }
+                | Instruction::IntToDouble { .. }
+                | Instruction::UIntToDouble { .. }
+                | Instruction::FloatToDouble { .. }
+                | Instruction::DoubleToFloat { .. }
+        ) {
+            return Lattice::Varying;
+        }
+
+        let mut folded = instruction.clone();
+        for val in used_vals_mut(&mut folded) {
+            match self.value(val) {
+                Lattice::Undefined => return Lattice::Undefined,
+                Lattice::Varying => return Lattice::Varying,
+                Lattice::Constant(c) => *val = Val::Constant(c),
+            }
+        }
+        match &constant_fold(&[folded], self.var_data, false)[..] {
+            [
+                Instruction::Copy {
+                    src: Val::Constant(c),
+                    ..
+                },
+            ] => Lattice::Constant(c.clone()),
+            _ => Lattice::Varying,
+        }
+    }
+
+    fn value(&self, val: &Val) -> Lattice {
+        match val {
+            Val::Constant(c) => Lattice::Constant(c.clone()),
+            // Parameters and variables read before being written
+            Val::Var(_) if self.definitions.block(val).is_none() => Lattice::Varying,
+            Val::Var(_) if !self.definitions.is_value(val, self.var_data) => Lattice::Varying,
+            Val::Var(_) => self.values.get(val).cloned().unwrap_or(Lattice::Undefined),
+        }
+    }
+
+    fn update(&mut self, dst: &Val, value: Lattice) {
+        if !self.definitions.is_value(dst, self.var_data) {
+            return;
+        }
+        let old = self.values.get(dst).cloned().unwrap_or(Lattice::Undefined);
+        // Values only go down the lattice, which bounds the iterations
+        let value = old.meet(&value);
+        if value != old {
+            self.values.insert(dst.clone(), value);
+            if let Some(uses) = self.uses.get(dst) {
+                self.instruction_worklist.extend(uses.iter().copied());
+            }
+        }
+    }
+
+    fn rewrite(&self, cfg: &mut Cfg) {
+        let ids: Vec<_> = cfg.all_ids().collect();
+        for node_id in ids {
+            if !self.executable_blocks.contains(&node_id) {
+                continue;
+            }
+            let node = cfg.get_node_mut(node_id);
+            for instruction in &mut node.instructions {
+                if let Some(dst) = defined_var(instruction)
+                    && let Some(Lattice::Constant(c)) = self.values.get(dst)
+                {
+                    *instruction = Instruction::Copy {
+                        src: Val::Constant(c.clone()),
+                        dst: dst.clone(),
+                    };
+                    continue;
+                }
+                let vals = match instruction {
+                    Instruction::Phi { args, .. } => args.iter_mut().map(|(_, arg)| arg).collect(),
+                    instruction => used_vals_mut(instruction),
+                };
+                for val in vals {
+                    if let Lattice::Constant(c) = self.value(val) {
+                        *val = Val::Constant(c);
+                    }
+                }
+            }
+
+            // Branches that can only go one way
+            let successors = cfg.get_node(node_id).successors.clone();
+            let dead: Vec<_> = successors
+                .iter()
+                .copied()
+                .filter(|&succ_id| !self.executable_edges.contains(&(node_id, succ_id)))
+                .collect();
+            if dead.is_empty() || dead.len() == successors.len() {
+                continue;
+            }
+            if let Some(
+                Instruction::JumpIfZero { target, .. } | Instruction::JumpIfNotZero { target, .. },
+            ) = cfg.get_node(node_id).instructions.last()
+            {
+                let target = target.clone();
+                if dead.contains(&cfg.get_by_label(&target)) {
+                    cfg.get_node_mut(node_id).instructions.pop();
+                } else {
+                    *cfg.get_node_mut(node_id).instructions.last_mut().unwrap() =
+                        Instruction::Jump { target };
+                }
+                for succ_id in dead {
+                    cfg.remove_edge(node_id, succ_id);
+                    remove_phi_args(cfg, succ_id, node_id);
+                }
+            }
+        }
+    }
+}
+
+fn remove_phi_args(cfg: &mut Cfg, node_id: NodeId, pred_id: NodeId) {
+    for instruction in &mut cfg.get_node_mut(node_id).instructions {
+        if let Instruction::Phi { args, .. } = instruction {
+            args.retain(|(id, _)| *id != pred_id);
+        }
+    }
+}
+
+fn ends_with_branch(instructions: &[Instruction]) -> bool {
+    matches!(
+        instructions.last(),
+        Some(
+            Instruction::Jump { .. }
+                | Instruction::JumpIfZero { .. }
+                | Instruction::JumpIfNotZero { .. }
+                | Instruction::Return(_)
+        )
+    )
+}
@@ -137,9 +137,15 @@ fn rewrite_instructions(cfg: &mut Cfg, annotations: &ReachingCopies) {
                 | Instruction::DoubleToUInt { src, .. }
                 | Instruction::IntToDouble { src, .. }
                 | Instruction::UIntToDouble { src, .. }
+                | Instruction::FloatToDouble { src, .. }
+                | Instruction::DoubleToFloat { src, .. }
                 | Instruction::Load { ptr: src, .. }
                 | Instruction::Store { src, .. }
-                | Instruction::CopyToOffset { src, .. } => {
+                | Instruction::CopyToOffset { src, .. }
+                | Instruction::VaStart { ap: src }
+                | Instruction::VaArg { ap: src, .. }
+                | Instruction::AllocateStack { size: src, .. }
+                | Instruction::RestoreStack { src } => {
                     *src = replace_operand(src.clone(), reaching_copies);
                 }
                 Instruction::Binary { src1, src2, .. } => {
@@ -154,6 +160,12 @@ fn rewrite_instructions(cfg: &mut Cfg, annotations: &ReachingCopies) {
                         *arg = replace_operand(arg.clone(), reaching_copies);
                     }
                 }
+                Instruction::IndirectCall { ptr, args, .. } => {
+                    *ptr = replace_operand(ptr.clone(), reaching_copies);
+                    for arg in args {
+                        *arg = replace_operand(arg.clone(), reaching_copies);
+                    }
+                }
                 Instruction::JumpIfZero { cond, .. } | Instruction::JumpIfNotZero { cond, .. } => {
                     *cond = replace_operand(cond.clone(), reaching_copies);
                 }
@@ -229,11 +241,14 @@ fn transfer_function(
                 let src_ty = var_data.ty(src);
                 let dst_ty = var_data.ty(dst);
 
+                if var_data.is_volatile(src) || var_data.is_volatile(dst) {
+                    continue;
+                }
                 if src_ty == dst_ty || (src_ty.is_signed() == dst_ty.is_signed()) {
                     current_reaching_copies.add(instruction.clone())
                 }
             }
-            Instruction::FnCall { dst, .. } => {
+            Instruction::FnCall { dst, .. } | Instruction::IndirectCall { dst, .. } => {
                 current_reaching_copies.remove_if(|current_src, current_dst| {
                     var_data.is_aliased_or_static(current_src)
                         || var_data.is_aliased_or_static(current_dst)
@@ -241,10 +256,18 @@ fn transfer_function(
                         || Some(current_dst) == dst.as_ref()
                 });
             }
-            Instruction::Store { .. } => {
+            Instruction::Store { .. } | Instruction::VaStart { .. } => {
+                current_reaching_copies.remove_if(|current_src, current_dst| {
+                    var_data.is_aliased_or_static(current_src)
+                        || var_data.is_aliased_or_static(current_dst)
+                });
+            }
+            Instruction::VaArg { dst, .. } => {
                 current_reaching_copies.remove_if(|current_src, current_dst| {
                     var_data.is_aliased_or_static(current_src)
                         || var_data.is_aliased_or_static(current_dst)
+                        || current_src == dst
+                        || current_dst == dst
                 });
             }
             Instruction::Binary { dst, .. }
@@ -256,10 +279,14 @@ fn transfer_function(
             | Instruction::DoubleToUInt { dst, .. }
             | Instruction::IntToDouble { dst, .. }
             | Instruction::UIntToDouble { dst, .. }
+            | Instruction::FloatToDouble { dst, .. }
+            | Instruction::DoubleToFloat { dst, .. }
             | Instruction::GetAddress { dst, .. }
             | Instruction::Load { dst, .. }
             | Instruction::AddPtr { dst, .. }
-            | Instruction::CopyFromOffset { dst, .. } => {
+            | Instruction::CopyFromOffset { dst, .. }
+            | Instruction::AllocateStack { dst, .. }
+            | Instruction::SaveStack { dst } => {
                 current_reaching_copies
                     .remove_if(|current_src, current_dst| current_src == dst || current_dst == dst);
             }
@@ -17,7 +17,7 @@ pub fn dead_store_elimination(cfg: &mut Cfg, var_data: &VariableData, trace: boo
     if trace {
         println!("Live variables:\n {annotations:?}");
     }
-    rewrite_instructions(cfg, &annotations);
+    rewrite_instructions(cfg, &annotations, var_data);
     if trace {
         println!("Instructions Rewritten:\n {cfg:#?}");
     }
@@ -99,6 +99,8 @@ fn transfer_function(
             | Instruction::DoubleToUInt { src, dst }
             | Instruction::IntToDouble { src, dst }
             | Instruction::UIntToDouble { src, dst }
+            | Instruction::FloatToDouble { src, dst }
+            | Instruction::DoubleToFloat { src, dst }
             | Instruction::Unary { src, dst, .. } => {
                 current_live_vars.remove(dst);
                 current_live_vars.add(src);
@@ -107,10 +109,13 @@ fn transfer_function(
                 current_live_vars.add(cond);
             }
 
-            Instruction::FnCall { args, dst, .. } => {
+            Instruction::FnCall { args, dst, .. } | Instruction::IndirectCall { args, dst, .. } => {
                 if let Some(dst) = dst {
                     current_live_vars.remove(dst);
                 }
+                if let Instruction::IndirectCall { ptr, .. } = instruction {
+                    current_live_vars.add(ptr);
+                }
                 for arg in args {
                     current_live_vars.add(arg);
                 }
@@ -151,6 +156,32 @@ fn transfer_function(
                 current_live_vars.0.insert(src.clone());
                 current_live_vars.remove(dst);
             }
+            Instruction::VaStart { ap } => {
+                current_live_vars.add(ap);
+            }
+            Instruction::VaArg { ap, dst } => {
+                current_live_vars.remove(dst);
+                current_live_vars.add(ap);
+                for var in var_data.aliased_vars.iter() {
+                    current_live_vars.add(var)
+                }
+            }
+            Instruction::AllocateStack { size, dst } => {
+                current_live_vars.remove(dst);
+                current_live_vars.add(size);
+            }
+            Instruction::SaveStack { dst } => {
+                current_live_vars.remove(dst);
+            }
+            Instruction::RestoreStack { src } => {
+                current_live_vars.add(src);
+            }
+            Instruction::Phi { dst, args } => {
+                current_live_vars.remove(dst);
+                for (_, arg) in args {
+                    current_live_vars.add(arg);
+                }
+            }
 
             Instruction::Return(val) => {
                 if let Some(val) = val {
@@ -221,11 +252,14 @@ fn find_live_vars(cfg: &Cfg, all_static_vars: &VarSet, var_data: &VariableData)
     annotations
 }
 
-fn rewrite_instructions(cfg: &mut Cfg, annotations: &LiveVars) {
+fn rewrite_instructions(cfg: &mut Cfg, annotations: &LiveVars, var_data: &VariableData) {
     for node in cfg.nodes_mut() {
         let mut new_instructions = Vec::new();
         for (i, instruction) in node.instructions.iter().enumerate() {
             match instruction {
+                // Reads of volatile objects are kept even if the result is unused
+                Instruction::Load { ptr, .. } if var_data.ty(ptr).is_pointer_to_volatile() => {}
+                Instruction::Copy { src, .. } if var_data.is_volatile(src) => {}
                 Instruction::Unary { dst, .. }
                 | Instruction::Binary { dst, .. }
                 | Instruction::Copy { dst, .. }
@@ -236,18 +270,21 @@ fn rewrite_instructions(cfg: &mut Cfg, annotations: &LiveVars) {
                 | Instruction::DoubleToUInt { dst, .. }
                 | Instruction::IntToDouble { dst, .. }
                 | Instruction::UIntToDouble { dst, .. }
+                | Instruction::FloatToDouble { dst, .. }
+                | Instruction::DoubleToFloat { dst, .. }
                 | Instruction::GetAddress { dst, .. }
                 | Instruction::Load { dst, .. }
                 | Instruction::AddPtr { dst, .. }
-                | Instruction::CopyFromOffset { dst, .. } => {
+                | Instruction::CopyFromOffset { dst, .. }
+                | Instruction::SaveStack { dst } => {
                     let live_vars = annotations.get_instruction_annotation(node.id, i);
-                    if !live_vars.contains(dst) {
+                    if !live_vars.contains(dst) && !var_data.is_volatile(dst) {
                         continue;
                     }
                 }
                 Instruction::CopyToOffset { dst, .. } => {
                     let live_vars = annotations.get_instruction_annotation(node.id, i);
-                    if !live_vars.0.contains(dst) {
+                    if !live_vars.0.contains(dst) && !var_data.is_volatile(&Val::Var(dst.clone())) {
                         continue;
                     }
                 }
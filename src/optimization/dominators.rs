@@ -0,0 +1,134 @@
+use crate::optimization::cfg::{GenericCfg, GenericInstruction, NodeId};
+use std::collections::{BTreeSet, HashMap};
+
+/// Dominator tree and dominance frontiers of the blocks reachable from the
+/// entry, computed with the algorithm of Cooper, Harvey and Kennedy.
+pub struct Dominators {
+    idom: HashMap<NodeId, NodeId>,
+    children: HashMap<NodeId, Vec<NodeId>>,
+    frontiers: HashMap<NodeId, BTreeSet<NodeId>>,
+}
+
+impl Dominators {
+    pub fn new<T: GenericInstruction>(cfg: &GenericCfg<T>) -> Self {
+        let order = reverse_postorder(cfg);
+        let position: HashMap<NodeId, usize> =
+            order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let entry_id = cfg.entry_id();
+        let mut idom = HashMap::from([(entry_id, entry_id)]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node_id in order.iter().skip(1) {
+                let mut new_idom = None;
+                for pred_id in &cfg.get_node(node_id).predecessors {
+                    if !idom.contains_key(pred_id) {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => *pred_id,
+                        Some(other) => intersect(&idom, &position, *pred_id, other),
+                    });
+                }
+                let new_idom = new_idom.expect("Reachable blocks have a processed predecessor");
+                if idom.insert(node_id, new_idom) != Some(new_idom) {
+                    changed = true;
+                }
+            }
+        }
+
+        let mut children: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for &node_id in order.iter().skip(1) {
+            children.entry(idom[&node_id]).or_default().push(node_id);
+        }
+
+        let mut frontiers: HashMap<NodeId, BTreeSet<NodeId>> = HashMap::new();
+        for &node_id in &order {
+            let predecessors = &cfg.get_node(node_id).predecessors;
+            if predecessors.len() < 2 {
+                continue;
+            }
+            for pred_id in predecessors {
+                let mut runner = *pred_id;
+                while idom.contains_key(&runner) && runner != idom[&node_id] {
+                    frontiers.entry(runner).or_default().insert(node_id);
+                    runner = idom[&runner];
+                }
+            }
+        }
+
+        Self {
+            idom,
+            children,
+            frontiers,
+        }
+    }
+
+    pub fn is_reachable(&self, node_id: NodeId) -> bool {
+        self.idom.contains_key(&node_id)
+    }
+
+    /// Whether every path from the entry to `node_id` goes through `dominator`.
+    pub fn dominates(&self, dominator: NodeId, node_id: NodeId) -> bool {
+        let mut current = node_id;
+        loop {
+            if current == dominator {
+                return true;
+            }
+            match self.idom.get(&current) {
+                Some(&idom) if idom != current => current = idom,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Blocks immediately dominated by `node_id`.
+    pub fn children(&self, node_id: NodeId) -> &[NodeId] {
+        self.children.get(&node_id).map_or(&[], |c| c.as_slice())
+    }
+
+    /// Blocks where the dominance of `node_id` ends, which are the first
+    /// ones reachable from it without being dominated by it.
+    pub fn frontier(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.frontiers.get(&node_id).into_iter().flatten().copied()
+    }
+}
+
+fn intersect(
+    idom: &HashMap<NodeId, NodeId>,
+    position: &HashMap<NodeId, usize>,
+    mut a: NodeId,
+    mut b: NodeId,
+) -> NodeId {
+    while a != b {
+        while position[&a] > position[&b] {
+            a = idom[&a];
+        }
+        while position[&b] > position[&a] {
+            b = idom[&b];
+        }
+    }
+    a
+}
+
+fn reverse_postorder<T: GenericInstruction>(cfg: &GenericCfg<T>) -> Vec<NodeId> {
+    let mut order = Vec::with_capacity(cfg.size());
+    let mut visited = BTreeSet::from([cfg.entry_id()]);
+    // Each entry holds a block and the index of its next successor to visit
+    let mut stack = vec![(cfg.entry_id(), 0)];
+    while let Some((node_id, next)) = stack.last_mut() {
+        let successors = &cfg.get_node(*node_id).successors;
+        if let Some(&succ_id) = successors.get(*next) {
+            *next += 1;
+            if visited.insert(succ_id) {
+                stack.push((succ_id, 0));
+            }
+        } else {
+            order.push(*node_id);
+            stack.pop();
+        }
+    }
+    order.reverse();
+    order
+}
@@ -0,0 +1,351 @@
+use crate::semantic::{Attributes, SemanticData, StaticInit};
+use crate::symbol::Symbol;
+use crate::tacky::{Function, Instruction, Program, TopLevel, Val};
+use std::collections::{HashMap, HashSet};
+
+/// Functions with at most this many instructions, not counting labels, are
+/// inlined at every call site.
+const MAX_INLINED_SIZE: usize = 24;
+
+/// Replaces calls to functions defined in the program with a copy of their
+/// body, when the function is small or it is `static` and called from a
+/// single place. Callees are handled before their callers, so the bodies
+/// being copied already had their own calls inlined. Calls to a function
+/// that isn't done yet, as happens with recursion, are left as they are.
+/// Static functions that are no longer referenced are removed afterwards.
+pub fn inline_functions(program: &mut Program, trace: bool) {
+    let references = References::new(program);
+    let mut functions: HashMap<Symbol, &mut Function> = program
+        .top_level
+        .iter_mut()
+        .filter_map(|top_level| match top_level {
+            TopLevel::Function(f) => Some((f.name.clone(), f)),
+            _ => None,
+        })
+        .collect();
+
+    let mut done: HashMap<Symbol, Function> = HashMap::new();
+    let mut inlined_calls = 0;
+    for name in callees_first(&functions) {
+        let f = functions.get_mut(&name).unwrap();
+        let mut body = Vec::with_capacity(f.body.len());
+        for instruction in std::mem::take(&mut f.body) {
+            if let Instruction::FnCall { name, args, dst } = &instruction
+                && let Some(callee) = done.get(name)
+                && can_inline(callee, &references, &program.semantics)
+                && args_match(callee, args, &program.semantics)
+            {
+                if trace {
+                    println!("Inlining {name} into {caller}", caller = f.name);
+                }
+                inlined_calls += 1;
+                let mut inliner = Inliner {
+                    suffix: format!("inline{inlined_calls}"),
+                    semantics: &mut program.semantics,
+                };
+                body.extend(inliner.inline_call(callee, args, dst.as_ref()));
+            } else {
+                body.push(instruction);
+            }
+        }
+        f.body = body;
+        done.insert(name, f.clone());
+    }
+
+    remove_unused_functions(program, trace);
+}
+
+/// Orders the functions so that each one comes after the functions it
+/// calls, except for calls going back to a function still being visited.
+fn callees_first(functions: &HashMap<Symbol, &mut Function>) -> Vec<Symbol> {
+    fn visit(
+        name: &Symbol,
+        functions: &HashMap<Symbol, &mut Function>,
+        visited: &mut HashSet<Symbol>,
+        order: &mut Vec<Symbol>,
+    ) {
+        if !visited.insert(name.clone()) {
+            return;
+        }
+        let Some(f) = functions.get(name) else {
+            return;
+        };
+        for instruction in &f.body {
+            if let Instruction::FnCall { name, .. } = instruction {
+                visit(name, functions, visited, order);
+            }
+        }
+        order.push(name.clone());
+    }
+
+    // Sorted so the inlined names don't depend on the order of the map
+    let mut names: Vec<_> = functions.keys().cloned().collect();
+    names.sort();
+    let mut visited = HashSet::new();
+    let mut order = Vec::new();
+    for name in &names {
+        visit(name, functions, &mut visited, &mut order);
+    }
+    order
+}
+
+fn can_inline(f: &Function, references: &References, semantics: &SemanticData) -> bool {
+    if semantics.symbols[&f.name].decl_attrs.weak {
+        return false;
+    }
+    // Variadic functions need their own frame for `va_start`, and the
+    // stack used by variable length arrays is only released on return
+    let needs_frame = f.body.iter().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::VaStart { .. } | Instruction::AllocateStack { .. }
+        )
+    });
+    if needs_frame {
+        return false;
+    }
+    let size = f
+        .body
+        .iter()
+        .filter(|instruction| !matches!(instruction, Instruction::Label(_)))
+        .count();
+    size <= MAX_INLINED_SIZE || (!f.global && references.is_called_once(&f.name))
+}
+
+/// Calls through prototypes that don't match the definition are left alone.
+fn args_match(callee: &Function, args: &[Val], semantics: &SemanticData) -> bool {
+    callee.params.len() == args.len()
+        && callee.params.iter().zip(args).all(|(param, arg)| {
+            semantics.symbols[param].ty.unqualified() == semantics.val_ty(arg).unqualified()
+        })
+}
+
+struct Inliner<'a> {
+    /// Appended to the names of the callee's local variables and labels.
+    suffix: String,
+    semantics: &'a mut SemanticData,
+}
+
+impl Inliner<'_> {
+    fn inline_call(
+        &mut self,
+        callee: &Function,
+        args: &[Val],
+        dst: Option<&Val>,
+    ) -> Vec<Instruction> {
+        let mut result = Vec::with_capacity(callee.body.len() + args.len() + 1);
+        for (param, arg) in callee.params.iter().zip(args) {
+            result.push(Instruction::Copy {
+                src: arg.clone(),
+                dst: Val::Var(self.rename_var(param)),
+            });
+        }
+
+        let return_label = self.rename_label(&Symbol::from(format!("return_{}", callee.name)));
+        for instruction in &callee.body {
+            let mut instruction = instruction.clone();
+            self.rename_instruction(&mut instruction);
+            match instruction {
+                Instruction::Return(value) => {
+                    if let (Some(src), Some(dst)) = (value, dst)
+                        && let Some(src) = self.convert_return(src, dst)
+                    {
+                        result.push(Instruction::Copy {
+                            src,
+                            dst: dst.clone(),
+                        });
+                    }
+                    result.push(Instruction::Jump {
+                        target: return_label.clone(),
+                    });
+                }
+                instruction => result.push(instruction),
+            }
+        }
+        result.push(Instruction::Label(return_label));
+        result
+    }
+
+    /// The `return 0` added at the end of every function is an `int`
+    /// whatever the return type is, and has no value at all for structures.
+    fn convert_return(&self, src: Val, dst: &Val) -> Option<Val> {
+        match src {
+            Val::Constant(c) => c.cast(&self.semantics.val_ty(dst)).map(Val::Constant),
+            src => Some(src),
+        }
+    }
+
+    fn rename_instruction(&mut self, instruction: &mut Instruction) {
+        match instruction {
+            Instruction::Return(value) => {
+                if let Some(val) = value {
+                    self.rename_val(val);
+                }
+            }
+            Instruction::Unary { src, dst, .. }
+            | Instruction::Copy { src, dst }
+            | Instruction::SignExtend { src, dst }
+            | Instruction::Truncate { src, dst }
+            | Instruction::ZeroExtend { src, dst }
+            | Instruction::DoubleToInt { src, dst }
+            | Instruction::DoubleToUInt { src, dst }
+            | Instruction::IntToDouble { src, dst }
+            | Instruction::UIntToDouble { src, dst }
+            | Instruction::FloatToDouble { src, dst }
+            | Instruction::DoubleToFloat { src, dst }
+            | Instruction::GetAddress { src, dst }
+            | Instruction::Load { ptr: src, dst }
+            | Instruction::Store { src, ptr: dst }
+            | Instruction::VaArg { ap: src, dst }
+            | Instruction::AllocateStack { size: src, dst } => {
+                self.rename_val(src);
+                self.rename_val(dst);
+            }
+            Instruction::Binary {
+                src1, src2, dst, ..
+            } => {
+                self.rename_val(src1);
+                self.rename_val(src2);
+                self.rename_val(dst);
+            }
+            Instruction::AddPtr {
+                ptr, index, dst, ..
+            } => {
+                self.rename_val(ptr);
+                self.rename_val(index);
+                self.rename_val(dst);
+            }
+            Instruction::Jump { target } => *target = self.rename_label(target),
+            Instruction::JumpIfZero { cond, target }
+            | Instruction::JumpIfNotZero { cond, target } => {
+                self.rename_val(cond);
+                *target = self.rename_label(target);
+            }
+            Instruction::Label(label) => *label = self.rename_label(label),
+            Instruction::FnCall { args, dst, .. } => {
+                args.iter_mut().for_each(|arg| self.rename_val(arg));
+                if let Some(dst) = dst {
+                    self.rename_val(dst);
+                }
+            }
+            Instruction::IndirectCall { ptr, args, dst } => {
+                self.rename_val(ptr);
+                args.iter_mut().for_each(|arg| self.rename_val(arg));
+                if let Some(dst) = dst {
+                    self.rename_val(dst);
+                }
+            }
+            Instruction::CopyToOffset { src, dst, .. } => {
+                self.rename_val(src);
+                *dst = self.rename_var(dst);
+            }
+            Instruction::CopyFromOffset { src, dst, .. } => {
+                *src = self.rename_var(src);
+                self.rename_val(dst);
+            }
+            Instruction::VaStart { ap: val }
+            | Instruction::SaveStack { dst: val }
+            | Instruction::RestoreStack { src: val } => self.rename_val(val),
+            Instruction::Phi { .. } => unreachable!("Inlining runs before SSA construction"),
+        }
+    }
+
+    fn rename_val(&mut self, val: &mut Val) {
+        if let Val::Var(name) = val {
+            *name = self.rename_var(name);
+        }
+    }
+
+    /// Static variables keep their name, every inlined copy of a function
+    /// shares them.
+    fn rename_var(&mut self, name: &Symbol) -> Symbol {
+        let Some(data) = self.semantics.symbols.get(name) else {
+            return name.clone();
+        };
+        if !matches!(data.attrs, Attributes::Local) {
+            return name.clone();
+        }
+        let renamed = Symbol::from(format!("{name}.{}", self.suffix));
+        if !self.semantics.symbols.contains_key(&renamed) {
+            let data = data.clone();
+            self.semantics.symbols.insert(renamed.clone(), data);
+        }
+        renamed
+    }
+
+    fn rename_label(&self, label: &Symbol) -> Symbol {
+        Symbol::from(format!("{label}.{}", self.suffix))
+    }
+}
+
+/// Where functions are used, by direct calls or by taking their address.
+/// Recursive calls don't count, they don't keep a function alive.
+struct References {
+    calls: HashMap<Symbol, usize>,
+    addresses: HashSet<Symbol>,
+}
+
+impl References {
+    fn new(program: &Program) -> Self {
+        let mut calls: HashMap<Symbol, usize> = HashMap::new();
+        let mut addresses = HashSet::new();
+        for top_level in &program.top_level {
+            match top_level {
+                TopLevel::Function(f) => {
+                    for instruction in &f.body {
+                        match instruction {
+                            Instruction::FnCall { name, .. } if *name != f.name => {
+                                *calls.entry(name.clone()).or_default() += 1
+                            }
+                            Instruction::GetAddress {
+                                src: Val::Var(name),
+                                ..
+                            } => {
+                                addresses.insert(name.clone());
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                TopLevel::Variable(v) => {
+                    for init in &v.init {
+                        if let StaticInit::Address(name, _) = init {
+                            addresses.insert(name.clone());
+                        }
+                    }
+                }
+                TopLevel::Constant(_) => {}
+            }
+        }
+        Self { calls, addresses }
+    }
+
+    fn is_called_once(&self, name: &Symbol) -> bool {
+        self.calls.get(name) == Some(&1) && !self.addresses.contains(name)
+    }
+
+    fn is_referenced(&self, name: &Symbol) -> bool {
+        self.calls.contains_key(name) || self.addresses.contains(name)
+    }
+}
+
+/// Removing a function can leave the static functions it called unused, so
+/// this repeats until nothing else can be removed.
+fn remove_unused_functions(program: &mut Program, trace: bool) {
+    loop {
+        let references = References::new(program);
+        let before = program.top_level.len();
+        program.top_level.retain(|top_level| match top_level {
+            TopLevel::Function(f) if !f.global && !references.is_referenced(&f.name) => {
+                if trace {
+                    println!("Removing unused function {}", f.name);
+                }
+                false
+            }
+            _ => true,
+        });
+        if program.top_level.len() == before {
+            break;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::ast::Constant;
+use crate::optimization::VariableData;
+use crate::optimization::cfg::NodeId;
+use crate::optimization::dominators::Dominators;
+use crate::optimization::loops::{Loop, find_loops};
+use crate::optimization::ssa::Definitions;
+use crate::semantic::Type;
+use crate::tacky::cfg::Cfg;
+use crate::tacky::{BinaryOp, Instruction, Val};
+
+/// Moves the computations whose operands don't change while a loop runs to
+/// a preheader block executed once before entering it. Expects the function
+/// to be in SSA form, where an operand doesn't change if it is defined
+/// outside the loop.
+///
+/// Only instructions that can't trap are hoisted, since the loop might not
+/// have executed them at all.
+pub fn hoist_loop_invariants(cfg: &mut Cfg, var_data: &VariableData, trace: bool) {
+    if trace {
+        println!("=======================");
+        println!("Loop invariant code motion");
+        println!("=======================");
+        println!("INITIAL\n {cfg:#?}");
+    }
+
+    let dominators = Dominators::new(cfg);
+    let mut loops = find_loops(cfg, &dominators);
+    let mut definitions = Definitions::new(cfg);
+    if trace {
+        println!("Loops:\n {loops:?}");
+    }
+
+    for i in 0..loops.len() {
+        let Some(preheader_id) = insert_preheader(cfg, &loops[i]) else {
+            continue;
+        };
+        // The preheader of an inner loop is part of the loops around it
+        let header = loops[i].header;
+        for outer in &mut loops[i + 1..] {
+            if outer.body.contains(&header) {
+                outer.body.insert(preheader_id);
+            }
+        }
+        hoist(cfg, &loops[i], preheader_id, &mut definitions, var_data);
+    }
+
+    if trace {
+        println!("Instructions Hoisted:\n {cfg:#?}");
+    }
+}
+
+/// Only loops entered from a single block get a preheader, otherwise the
+/// phis of the header would need their own phis in the preheader.
+fn insert_preheader(cfg: &mut Cfg, l: &Loop) -> Option<NodeId> {
+    let mut entries: Vec<_> = cfg
+        .get_node(l.header)
+        .predecessors
+        .iter()
+        .copied()
+        .filter(|pred_id| !l.body.contains(pred_id))
+        .collect();
+    entries.sort();
+    entries.dedup();
+    let [entry_id] = entries[..] else {
+        return None;
+    };
+
+    let preheader_id = cfg.insert_preheader(l.header, &[entry_id])?;
+    for instruction in &mut cfg.get_node_mut(l.header).instructions {
+        if let Instruction::Phi { args, .. } = instruction {
+            for (pred_id, _) in args {
+                if *pred_id == entry_id {
+                    *pred_id = preheader_id;
+                }
+            }
+        }
+    }
+    Some(preheader_id)
+}
+
+fn hoist(
+    cfg: &mut Cfg,
+    l: &Loop,
+    preheader_id: NodeId,
+    definitions: &mut Definitions,
+    var_data: &VariableData,
+) {
+    // Hoisting an instruction can make the ones using its result invariant
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &node_id in &l.body {
+            let mut i = 0;
+            while i < cfg.get_node(node_id).instructions.len() {
+                let instruction = &cfg.get_node(node_id).instructions[i];
+                let Some(dst) = invariant_dst(instruction, l, definitions, var_data) else {
+                    i += 1;
+                    continue;
+                };
+                definitions.move_to(&dst, preheader_id);
+                let instruction = cfg.get_node_mut(node_id).instructions.remove(i);
+                cfg.get_node_mut(preheader_id)
+                    .instructions
+                    .push(instruction);
+                changed = true;
+            }
+        }
+    }
+}
+
+/// The variable defined by an instruction that can be moved out of the loop.
+fn invariant_dst(
+    instruction: &Instruction,
+    l: &Loop,
+    definitions: &Definitions,
+    var_data: &VariableData,
+) -> Option<Val> {
+    let is_invariant = |val: &Val| {
+        definitions.is_value(val, var_data)
+            && definitions
+                .block(val)
+                .is_none_or(|node_id| !l.body.contains(&node_id))
+    };
+    let (operands, dst) = match instruction {
+        Instruction::Binary {
+            op,
+            src1,
+            src2,
+            dst,
+        } => {
+            if may_trap(op, src2, &var_data.ty(src1)) {
+                return None;
+            }
+            (vec![src1, src2], dst)
+        }
+        Instruction::AddPtr {
+            ptr, index, dst, ..
+        } => (vec![ptr, index], dst),
+        Instruction::SignExtend { src, dst }
+        | Instruction::Truncate { src, dst }
+        | Instruction::ZeroExtend { src, dst }
+        | Instruction::DoubleToInt { src, dst }
+        | Instruction::DoubleToUInt { src, dst }
+        | Instruction::IntToDouble { src, dst }
+        | Instruction::UIntToDouble { src, dst }
+        | Instruction::FloatToDouble { src, dst }
+        | Instruction::DoubleToFloat { src, dst } => (vec![src], dst),
+        // The address of a variable is the same for the whole function
+        Instruction::GetAddress { dst, .. } => (vec![], dst),
+        _ => return None,
+    };
+    (definitions.is_value(dst, var_data) && operands.into_iter().all(is_invariant))
+        .then(|| dst.clone())
+}
+
+/// Integer division traps when dividing by zero, or dividing the smallest
+/// signed value by -1.
+fn may_trap(op: &BinaryOp, divisor: &Val, ty: &Type) -> bool {
+    if !matches!(op, BinaryOp::Divide | BinaryOp::Reminder) || ty.is_floating() {
+        return false;
+    }
+    match divisor {
+        Val::Constant(c) => {
+            c.is_zero() || (ty.is_signed() && c.cast(&Type::Long) == Some(Constant::Long(-1)))
+        }
+        Val::Var(_) => true,
+    }
+}
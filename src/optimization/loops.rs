@@ -0,0 +1,51 @@
+use crate::optimization::cfg::{GenericCfg, GenericInstruction, NodeId};
+use crate::optimization::dominators::Dominators;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A natural loop, made of the blocks that can reach one of the back edges
+/// into `header` without going through it.
+#[derive(Debug)]
+pub struct Loop {
+    pub header: NodeId,
+    pub body: BTreeSet<NodeId>,
+}
+
+/// Finds the natural loops of the function, with the loops sharing a header
+/// merged together. Inner loops come before the loops containing them.
+pub fn find_loops<T: GenericInstruction>(
+    cfg: &GenericCfg<T>,
+    dominators: &Dominators,
+) -> Vec<Loop> {
+    let mut latches: BTreeMap<NodeId, Vec<NodeId>> = BTreeMap::new();
+    for node_id in cfg.all_ids() {
+        if !dominators.is_reachable(node_id) {
+            continue;
+        }
+        for &succ_id in &cfg.get_node(node_id).successors {
+            if dominators.dominates(succ_id, node_id) {
+                latches.entry(succ_id).or_default().push(node_id);
+            }
+        }
+    }
+
+    let mut loops: Vec<_> = latches
+        .into_iter()
+        .map(|(header, latches)| {
+            let mut body = BTreeSet::from([header]);
+            let mut worklist = latches;
+            while let Some(node_id) = worklist.pop() {
+                if body.insert(node_id) {
+                    worklist.extend(
+                        cfg.get_node(node_id)
+                            .predecessors
+                            .iter()
+                            .filter(|&&pred_id| dominators.is_reachable(pred_id)),
+                    );
+                }
+            }
+            Loop { header, body }
+        })
+        .collect();
+    loops.sort_by_key(|l| l.body.len());
+    loops
+}
@@ -0,0 +1,435 @@
+use crate::optimization::VariableData;
+use crate::optimization::cfg::{GenericInstruction, InstructionKind, NodeId};
+use crate::optimization::dominators::Dominators;
+use crate::semantic::{Attributes, SemanticData};
+use crate::symbol::Symbol;
+use crate::tacky::cfg::Cfg;
+use crate::tacky::{Instruction, Val};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Gives every definition of a local scalar variable its own name, joining
+/// them with phi instructions where control flow merges. Variables whose
+/// address is taken, static and volatile ones keep their name.
+///
+/// Uses that no definition reaches, like parameters, keep the original name.
+pub fn to_ssa(cfg: &mut Cfg, semantics: &mut SemanticData, trace: bool) {
+    if trace {
+        println!("=======================");
+        println!("SSA construction");
+        println!("=======================");
+        println!("INITIAL\n {cfg:#?}");
+    }
+
+    let dominators = Dominators::new(cfg);
+    let variables = renamed_variables(cfg, semantics);
+    insert_phis(cfg, &dominators, &variables);
+    if trace {
+        println!("PHIS INSERTED\n {cfg:#?}");
+    }
+
+    let mut renamer = Renamer {
+        variables: &variables,
+        stacks: HashMap::new(),
+        versions: HashMap::new(),
+        semantics,
+    };
+    renamer.rename_block(cfg, &dominators, cfg.entry_id());
+    if trace {
+        println!("RENAMED\n {cfg:#?}");
+    }
+}
+
+/// Replaces each phi with copies into a new variable at the end of the
+/// predecessors, read back where the phi was. Phis of the same block may
+/// depend on each other, going through a separate variable keeps their
+/// copies from overwriting values still needed by the others.
+pub fn from_ssa(cfg: &mut Cfg, semantics: &mut SemanticData, trace: bool) {
+    let ids: Vec<_> = cfg.all_ids().collect();
+    let mut pred_copies: HashMap<NodeId, Vec<Instruction>> = HashMap::new();
+    for &node_id in &ids {
+        for instruction in &mut cfg.get_node_mut(node_id).instructions {
+            let Instruction::Phi { dst, args } = instruction else {
+                continue;
+            };
+            let Val::Var(name) = dst else {
+                panic!("Phi defines a variable")
+            };
+            let incoming = Symbol::from(format!("{name}.phi"));
+            let data = semantics.symbols[name].clone();
+            semantics.symbols.insert(incoming.clone(), data);
+
+            let mut seen = HashSet::new();
+            for (pred_id, arg) in args.iter() {
+                if seen.insert(*pred_id) {
+                    pred_copies
+                        .entry(*pred_id)
+                        .or_default()
+                        .push(Instruction::Copy {
+                            src: arg.clone(),
+                            dst: Val::Var(incoming.clone()),
+                        });
+                }
+            }
+            *instruction = Instruction::Copy {
+                src: Val::Var(incoming),
+                dst: dst.clone(),
+            };
+        }
+    }
+
+    for (pred_id, copies) in pred_copies {
+        let instructions = &mut cfg.get_node_mut(pred_id).instructions;
+        let position = match instructions.last().map(|i| i.kind()) {
+            Some(InstructionKind::Jump { .. } | InstructionKind::ConditionalJump { .. }) => {
+                instructions.len() - 1
+            }
+            _ => instructions.len(),
+        };
+        instructions.splice(position..position, copies);
+    }
+
+    if trace {
+        println!("=======================");
+        println!("SSA destruction");
+        println!("=======================");
+        println!("AFTER\n {cfg:#?}");
+    }
+}
+
+/// Blocks defining each variable, for the passes working on SSA form.
+pub(super) struct Definitions(HashMap<Val, Vec<NodeId>>);
+
+impl Definitions {
+    pub fn new(cfg: &Cfg) -> Self {
+        let mut blocks: HashMap<Val, Vec<NodeId>> = HashMap::new();
+        for node_id in cfg.all_ids() {
+            for instruction in &cfg.get_node(node_id).instructions {
+                if let Some(dst) = defined_var(instruction) {
+                    blocks.entry(dst.clone()).or_default().push(node_id);
+                }
+            }
+        }
+        Self(blocks)
+    }
+
+    /// Whether `val` holds the same value everywhere it is used, which is
+    /// the case for constants and the variables renamed by [`to_ssa`].
+    pub fn is_value(&self, val: &Val, var_data: &VariableData) -> bool {
+        match val {
+            Val::Constant(_) => true,
+            Val::Var(_) => {
+                self.0.get(val).map_or(0, Vec::len) <= 1
+                    && var_data.ty(val).is_scalar()
+                    && !var_data.is_aliased_or_static(val)
+                    && !var_data.is_volatile(val)
+            }
+        }
+    }
+
+    /// The block defining a value, `None` for constants and parameters.
+    pub fn block(&self, val: &Val) -> Option<NodeId> {
+        self.0.get(val).and_then(|blocks| blocks.first().copied())
+    }
+
+    pub fn move_to(&mut self, val: &Val, node_id: NodeId) {
+        self.0.insert(val.clone(), vec![node_id]);
+    }
+}
+
+/// Variables defined in the function that can be renamed, along with
+/// whether they are used in a block before being defined there. Only those
+/// need phis, the others are never live across blocks.
+fn renamed_variables(cfg: &Cfg, semantics: &SemanticData) -> HashMap<Symbol, bool> {
+    let mut aliased = HashSet::new();
+    let mut variables = HashMap::new();
+    for node_id in cfg.all_ids() {
+        let mut defined = HashSet::new();
+        for instruction in &cfg.get_node(node_id).instructions {
+            if let Instruction::GetAddress {
+                src: Val::Var(name),
+                ..
+            } = instruction
+            {
+                aliased.insert(name.clone());
+            }
+            for name in used_vars(instruction) {
+                if !defined.contains(name) {
+                    variables.insert(name.clone(), true);
+                }
+            }
+            if let Some(Val::Var(name)) = defined_var(instruction) {
+                defined.insert(name.clone());
+                variables.entry(name.clone()).or_insert(false);
+            }
+        }
+    }
+    variables.retain(|name, _| {
+        let data = &semantics.symbols[name];
+        matches!(data.attrs, Attributes::Local)
+            && data.ty.is_scalar()
+            && !data.ty.is_volatile()
+            && !aliased.contains(name)
+    });
+    variables
+}
+
+fn insert_phis(cfg: &mut Cfg, dominators: &Dominators, variables: &HashMap<Symbol, bool>) {
+    let mut def_blocks: HashMap<Symbol, BTreeSet<NodeId>> = HashMap::new();
+    for node_id in cfg.all_ids() {
+        if !dominators.is_reachable(node_id) {
+            continue;
+        }
+        for instruction in &cfg.get_node(node_id).instructions {
+            if let Some(Val::Var(name)) = defined_var(instruction)
+                && variables.get(name) == Some(&true)
+            {
+                def_blocks.entry(name.clone()).or_default().insert(node_id);
+            }
+        }
+    }
+
+    // Sorted so the phis of a block are always inserted in the same order
+    let mut def_blocks: Vec<_> = def_blocks.into_iter().collect();
+    def_blocks.sort();
+    for (name, blocks) in def_blocks {
+        let mut worklist: Vec<_> = blocks.iter().copied().collect();
+        let mut has_phi = HashSet::new();
+        while let Some(node_id) = worklist.pop() {
+            for frontier_id in dominators.frontier(node_id) {
+                if frontier_id == cfg.exit_id() || !has_phi.insert(frontier_id) {
+                    continue;
+                }
+                let node = cfg.get_node_mut(frontier_id);
+                let mut predecessors = node.predecessors.clone();
+                predecessors.dedup();
+                let phi = Instruction::Phi {
+                    dst: Val::Var(name.clone()),
+                    args: predecessors
+                        .into_iter()
+                        .map(|pred_id| (pred_id, Val::Var(name.clone())))
+                        .collect(),
+                };
+                let position = match node.instructions.first() {
+                    Some(Instruction::Label(_)) => 1,
+                    _ => 0,
+                };
+                node.instructions.insert(position, phi);
+                if !blocks.contains(&frontier_id) {
+                    worklist.push(frontier_id);
+                }
+            }
+        }
+    }
+}
+
+struct Renamer<'a> {
+    variables: &'a HashMap<Symbol, bool>,
+    /// Names currently reaching the block being renamed.
+    stacks: HashMap<Symbol, Vec<Symbol>>,
+    versions: HashMap<Symbol, usize>,
+    semantics: &'a mut SemanticData,
+}
+
+impl Renamer<'_> {
+    fn rename_block(&mut self, cfg: &mut Cfg, dominators: &Dominators, node_id: NodeId) {
+        let mut pushed = Vec::new();
+        let node = cfg.get_node_mut(node_id);
+        for instruction in &mut node.instructions {
+            if !matches!(instruction, Instruction::Phi { .. }) {
+                for val in used_vals_mut(instruction) {
+                    self.rename_use(val);
+                }
+            }
+            if let Some(val) = defined_val_mut(instruction)
+                && let Val::Var(name) = val
+                && self.variables.contains_key(name)
+            {
+                let original = name.clone();
+                *val = Val::Var(self.new_version(&original));
+                pushed.push(original);
+            }
+        }
+
+        let successors = cfg.get_node(node_id).successors.clone();
+        for succ_id in successors {
+            for instruction in &mut cfg.get_node_mut(succ_id).instructions {
+                let Instruction::Phi { args, .. } = instruction else {
+                    continue;
+                };
+                for (pred_id, arg) in args {
+                    if *pred_id == node_id {
+                        self.rename_use(arg);
+                    }
+                }
+            }
+        }
+
+        for &child_id in dominators.children(node_id) {
+            self.rename_block(cfg, dominators, child_id);
+        }
+        for name in pushed {
+            self.stacks.get_mut(&name).map(Vec::pop);
+        }
+    }
+
+    fn rename_use(&self, val: &mut Val) {
+        if let Val::Var(name) = val
+            && let Some(current) = self.stacks.get(name).and_then(|stack| stack.last())
+        {
+            *name = current.clone();
+        }
+    }
+
+    fn new_version(&mut self, name: &Symbol) -> Symbol {
+        let version = self.versions.entry(name.clone()).or_default();
+        *version += 1;
+        let new_name = Symbol::from(format!("{name}.{version}"));
+        let data = self.semantics.symbols[name].clone();
+        self.semantics.symbols.insert(new_name.clone(), data);
+        self.stacks
+            .entry(name.clone())
+            .or_default()
+            .push(new_name.clone());
+        new_name
+    }
+}
+
+pub(super) fn defined_var(instruction: &Instruction) -> Option<&Val> {
+    match instruction {
+        Instruction::Unary { dst, .. }
+        | Instruction::Binary { dst, .. }
+        | Instruction::Copy { dst, .. }
+        | Instruction::SignExtend { dst, .. }
+        | Instruction::Truncate { dst, .. }
+        | Instruction::ZeroExtend { dst, .. }
+        | Instruction::DoubleToInt { dst, .. }
+        | Instruction::DoubleToUInt { dst, .. }
+        | Instruction::IntToDouble { dst, .. }
+        | Instruction::UIntToDouble { dst, .. }
+        | Instruction::FloatToDouble { dst, .. }
+        | Instruction::DoubleToFloat { dst, .. }
+        | Instruction::GetAddress { dst, .. }
+        | Instruction::Load { dst, .. }
+        | Instruction::AddPtr { dst, .. }
+        | Instruction::CopyFromOffset { dst, .. }
+        | Instruction::VaArg { dst, .. }
+        | Instruction::AllocateStack { dst, .. }
+        | Instruction::SaveStack { dst }
+        | Instruction::Phi { dst, .. } => Some(dst),
+        Instruction::FnCall { dst, .. } | Instruction::IndirectCall { dst, .. } => dst.as_ref(),
+        Instruction::Return(_)
+        | Instruction::Jump { .. }
+        | Instruction::JumpIfZero { .. }
+        | Instruction::JumpIfNotZero { .. }
+        | Instruction::Label(_)
+        | Instruction::Store { .. }
+        | Instruction::CopyToOffset { .. }
+        | Instruction::VaStart { .. }
+        | Instruction::RestoreStack { .. } => None,
+    }
+}
+
+fn defined_val_mut(instruction: &mut Instruction) -> Option<&mut Val> {
+    match instruction {
+        Instruction::Unary { dst, .. }
+        | Instruction::Binary { dst, .. }
+        | Instruction::Copy { dst, .. }
+        | Instruction::SignExtend { dst, .. }
+        | Instruction::Truncate { dst, .. }
+        | Instruction::ZeroExtend { dst, .. }
+        | Instruction::DoubleToInt { dst, .. }
+        | Instruction::DoubleToUInt { dst, .. }
+        | Instruction::IntToDouble { dst, .. }
+        | Instruction::UIntToDouble { dst, .. }
+        | Instruction::FloatToDouble { dst, .. }
+        | Instruction::DoubleToFloat { dst, .. }
+        | Instruction::GetAddress { dst, .. }
+        | Instruction::Load { dst, .. }
+        | Instruction::AddPtr { dst, .. }
+        | Instruction::CopyFromOffset { dst, .. }
+        | Instruction::VaArg { dst, .. }
+        | Instruction::AllocateStack { dst, .. }
+        | Instruction::SaveStack { dst }
+        | Instruction::Phi { dst, .. } => Some(dst),
+        Instruction::FnCall { dst, .. } | Instruction::IndirectCall { dst, .. } => dst.as_mut(),
+        _ => None,
+    }
+}
+
+pub(super) fn used_vars(instruction: &Instruction) -> impl Iterator<Item = &Symbol> {
+    let vals: Vec<&Val> = match instruction {
+        Instruction::Unary { src, .. }
+        | Instruction::Copy { src, .. }
+        | Instruction::SignExtend { src, .. }
+        | Instruction::Truncate { src, .. }
+        | Instruction::ZeroExtend { src, .. }
+        | Instruction::DoubleToInt { src, .. }
+        | Instruction::DoubleToUInt { src, .. }
+        | Instruction::IntToDouble { src, .. }
+        | Instruction::UIntToDouble { src, .. }
+        | Instruction::FloatToDouble { src, .. }
+        | Instruction::DoubleToFloat { src, .. }
+        | Instruction::GetAddress { src, .. }
+        | Instruction::Load { ptr: src, .. }
+        | Instruction::CopyToOffset { src, .. }
+        | Instruction::VaStart { ap: src }
+        | Instruction::VaArg { ap: src, .. }
+        | Instruction::AllocateStack { size: src, .. }
+        | Instruction::RestoreStack { src }
+        | Instruction::JumpIfZero { cond: src, .. }
+        | Instruction::JumpIfNotZero { cond: src, .. }
+        | Instruction::Return(Some(src)) => vec![src],
+        Instruction::Binary { src1, src2, .. } => vec![src1, src2],
+        Instruction::Store { src, ptr } => vec![src, ptr],
+        Instruction::AddPtr { ptr, index, .. } => vec![ptr, index],
+        Instruction::FnCall { args, .. } => args.iter().collect(),
+        Instruction::IndirectCall { ptr, args, .. } => {
+            std::iter::once(ptr).chain(args.iter()).collect()
+        }
+        Instruction::Phi { args, .. } => args.iter().map(|(_, arg)| arg).collect(),
+        Instruction::Return(None)
+        | Instruction::Jump { .. }
+        | Instruction::Label(_)
+        | Instruction::CopyFromOffset { .. }
+        | Instruction::SaveStack { .. } => vec![],
+    };
+    vals.into_iter().filter_map(|val| match val {
+        Val::Var(name) => Some(name),
+        Val::Constant(_) => None,
+    })
+}
+
+/// Values read by an instruction other than a phi. The address taken by
+/// `GetAddress` isn't a read, but such variables are never renamed.
+pub(super) fn used_vals_mut(instruction: &mut Instruction) -> Vec<&mut Val> {
+    match instruction {
+        Instruction::Unary { src, .. }
+        | Instruction::Copy { src, .. }
+        | Instruction::SignExtend { src, .. }
+        | Instruction::Truncate { src, .. }
+        | Instruction::ZeroExtend { src, .. }
+        | Instruction::DoubleToInt { src, .. }
+        | Instruction::DoubleToUInt { src, .. }
+        | Instruction::IntToDouble { src, .. }
+        | Instruction::UIntToDouble { src, .. }
+        | Instruction::FloatToDouble { src, .. }
+        | Instruction::DoubleToFloat { src, .. }
+        | Instruction::Load { ptr: src, .. }
+        | Instruction::CopyToOffset { src, .. }
+        | Instruction::VaStart { ap: src }
+        | Instruction::VaArg { ap: src, .. }
+        | Instruction::AllocateStack { size: src, .. }
+        | Instruction::RestoreStack { src }
+        | Instruction::JumpIfZero { cond: src, .. }
+        | Instruction::JumpIfNotZero { cond: src, .. }
+        | Instruction::Return(Some(src)) => vec![src],
+        Instruction::Binary { src1, src2, .. } => vec![src1, src2],
+        Instruction::Store { src, ptr } => vec![src, ptr],
+        Instruction::AddPtr { ptr, index, .. } => vec![ptr, index],
+        Instruction::FnCall { args, .. } => args.iter_mut().collect(),
+        Instruction::IndirectCall { ptr, args, .. } => {
+            std::iter::once(ptr).chain(args.iter_mut()).collect()
+        }
+        _ => vec![],
+    }
+}
@@ -0,0 +1,32 @@
+use crate::optimization::ssa::{from_ssa, to_ssa};
+use crate::parser::{DEFAULT_ERROR_LIMIT, parse};
+use crate::semantic::validate;
+use crate::tacky::cfg::Cfg;
+use crate::tacky::{self, TopLevel};
+
+/// Prints the program after converting every function to SSA form and
+/// running `pass` on it, then going back out of SSA form when `out_of_ssa`
+/// is set.
+fn dump_ssa(src: &str, pass: impl Fn(&mut Cfg), out_of_ssa: bool) -> String {
+    let ast = parse(src, DEFAULT_ERROR_LIMIT).unwrap();
+    let (ast, semantics) = validate(ast).unwrap();
+    let mut program = tacky::emit(&ast, semantics);
+    for top_level in &mut program.top_level {
+        if let TopLevel::Function(f) = top_level {
+            let mut cfg = Cfg::new(&f.body);
+            to_ssa(&mut cfg, &mut program.semantics, false);
+            pass(&mut cfg);
+            if out_of_ssa {
+                from_ssa(&mut cfg, &mut program.semantics, false);
+            }
+            f.body = cfg.dump();
+        }
+    }
+    tacky::pretty::pp(&program).unwrap().trim().to_owned()
+}
+
+mod test_common_subexpressions;
+mod test_constant_propagation;
+mod test_inlining;
+mod test_loop_invariants;
+mod test_ssa;
@@ -0,0 +1,110 @@
+use crate::optimization::OptimizationFlags;
+use crate::pretty::{dedent, dump_optimized_tacky};
+
+fn dump(src: &str) -> String {
+    let flags = OptimizationFlags {
+        eliminate_common_subexpressions: true,
+        ..Default::default()
+    };
+    dump_optimized_tacky(src, &flags)
+}
+
+#[test]
+fn test_reuse_in_dominated_block() {
+    let src = r#"
+        int f(int a, int b) {
+            int x = a * b;
+            if (x > 10)
+                return b * a + 1;
+            return x;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0, b.1) { 
+            tmp.0.1 = a.0 * b.1
+            x.2.1 = tmp.0.1
+            tmp.1.1 = x.2.1 > 10
+            if !tmp.1.1 jump end_if_0
+            tmp.2.1 = tmp.0.1
+            tmp.3.1 = tmp.2.1 + 1
+            return tmp.3.1
+        
+          end_if_0:
+            return x.2.1
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_reuse_of_load_across_store_or_call() {
+    let src = r#"
+        int g(void);
+        int f(int *p) {
+            int a = *p;
+            *p = 5;
+            int b = *p;
+            g();
+            int c = *p;
+            int d = *p;
+            return a + b + c + d;
+        }
+    "#;
+    let expected = r#"
+        global function f(p.0) { 
+            tmp.0.1 = *p.0
+            a.1.1 = tmp.0.1
+            *p.0 = 5
+            tmp.1.1 = *p.0
+            tmp.2.1 = tmp.1.1
+            b.2.1 = tmp.2.1
+            tmp.3.1 = g()
+            tmp.4.1 = *p.0
+            c.3.1 = tmp.4.1
+            tmp.5.1 = tmp.4.1
+            d.4.1 = tmp.5.1
+            tmp.6.1 = a.1.1 + b.2.1
+            tmp.7.1 = tmp.6.1 + c.3.1
+            tmp.8.1 = tmp.7.1 + d.4.1
+            return tmp.8.1
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_reuse_in_sibling_branch() {
+    let src = r#"
+        int f(int a, int b) {
+            int x;
+            if (a)
+                x = a + b;
+            else
+                x = a + b + 1;
+            return x;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0, b.1) { 
+            if !a.0 jump else_1
+            tmp.0.1 = a.0 + b.1
+            x.2.1 = tmp.0.1
+            x.2.3.phi = x.2.1
+            jump end_if_0
+        
+          else_1:
+            tmp.1.1 = a.0 + b.1
+            tmp.2.1 = tmp.1.1 + 1
+            x.2.2 = tmp.2.1
+            x.2.3.phi = x.2.2
+        
+          end_if_0:
+            x.2.3 = x.2.3.phi
+            return x.2.3
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
@@ -0,0 +1,91 @@
+use crate::optimization::OptimizationFlags;
+use crate::pretty::{dedent, dump_optimized_tacky};
+
+/// The blocks that can't be executed are only removed by the unreachable
+/// code elimination.
+fn dump(src: &str) -> String {
+    let flags = OptimizationFlags {
+        propagate_constants: true,
+        eliminate_unreachable_code: true,
+        ..Default::default()
+    };
+    dump_optimized_tacky(src, &flags)
+}
+
+#[test]
+fn test_propagate_through_agreeing_phi() {
+    let src = r#"
+        int f(int a) {
+            int x;
+            if (a)
+                x = 3;
+            else
+                x = 3;
+            return x + 1;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0) { 
+            if !a.0 jump else_1
+            x.1.1 = 3
+            jump end_if_0
+        
+          else_1:
+            x.1.2 = 3
+        
+          end_if_0:
+            x.1.3 = 3
+            tmp.0.1 = 4
+            return 4
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_remove_dead_arm_of_constant_branch() {
+    let src = r#"
+        int f(int a) {
+            int debug = 0;
+            if (debug)
+                a = a * 2;
+            else
+                a = a + 1;
+            return a;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0) { 
+            debug.1.1 = 0
+            tmp.1.1 = a.0 + 1
+            a.0.2 = tmp.1.1
+            a.0.3.phi = a.0.2
+            a.0.3 = a.0.3.phi
+            return a.0.3
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_fold_of_constant_from_non_executable_edge() {
+    let src = r#"
+        int f(int a) {
+            int x = a;
+            int debug = 0;
+            if (debug)
+                x = 5;
+            return x;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0) { 
+            x.1.1 = a.0
+            debug.2.1 = 0
+            x.1.3.phi = x.1.1
+            x.1.3 = x.1.3.phi
+            return x.1.3
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
@@ -0,0 +1,289 @@
+use crate::optimization::OptimizationFlags;
+use crate::pretty::{dedent, dump_optimized_tacky};
+
+fn dump(src: &str) -> String {
+    let flags = OptimizationFlags {
+        inline: true,
+        ..Default::default()
+    };
+    dump_optimized_tacky(src, &flags)
+}
+
+#[test]
+fn test_inline_small_function() {
+    let src = r#"
+        int abs(int x) {
+            int result = x;
+            if (x < 0)
+                result = -x;
+            return result;
+        }
+        int main(void) {
+            return abs(-3) + abs(4);
+        }
+    "#;
+    let expected = r#"
+        global function abs(x.0) { 
+            result.1 = x.0
+            tmp.0 = x.0 < 0
+            if !tmp.0 jump end_if_0
+            tmp.1 = - x.0
+            result.1 = tmp.1
+        
+          end_if_0:
+            return result.1
+            return 0
+        }
+        global function main() { 
+            tmp.2 = - 3
+            x.0.inline1 = tmp.2
+            result.1.inline1 = x.0.inline1
+            tmp.0.inline1 = x.0.inline1 < 0
+            if !tmp.0.inline1 jump end_if_0.inline1
+            tmp.1.inline1 = - x.0.inline1
+            result.1.inline1 = tmp.1.inline1
+        
+          end_if_0.inline1:
+            tmp.3 = result.1.inline1
+            jump return_abs.inline1
+            tmp.3 = 0
+            jump return_abs.inline1
+        
+          return_abs.inline1:
+            x.0.inline2 = 4
+            result.1.inline2 = x.0.inline2
+            tmp.0.inline2 = x.0.inline2 < 0
+            if !tmp.0.inline2 jump end_if_0.inline2
+            tmp.1.inline2 = - x.0.inline2
+            result.1.inline2 = tmp.1.inline2
+        
+          end_if_0.inline2:
+            tmp.5 = result.1.inline2
+            jump return_abs.inline2
+            tmp.5 = 0
+            jump return_abs.inline2
+        
+          return_abs.inline2:
+            tmp.4 = tmp.3 + tmp.5
+            return tmp.4
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_inline_into_itself() {
+    let src = r#"
+        int fact(int n) {
+            if (n <= 1)
+                return 1;
+            return n * fact(n - 1);
+        }
+        int main(void) {
+            return fact(5);
+        }
+    "#;
+    let expected = r#"
+        global function fact(n.0) { 
+            tmp.0 = n.0 <= 1
+            if !tmp.0 jump end_if_0
+            return 1
+        
+          end_if_0:
+            tmp.2 = n.0 - 1
+            tmp.3 = fact(tmp.2)
+            tmp.1 = n.0 * tmp.3
+            return tmp.1
+            return 0
+        }
+        global function main() { 
+            n.0.inline1 = 5
+            tmp.0.inline1 = n.0.inline1 <= 1
+            if !tmp.0.inline1 jump end_if_0.inline1
+            tmp.4 = 1
+            jump return_fact.inline1
+        
+          end_if_0.inline1:
+            tmp.2.inline1 = n.0.inline1 - 1
+            tmp.3.inline1 = fact(tmp.2.inline1)
+            tmp.1.inline1 = n.0.inline1 * tmp.3.inline1
+            tmp.4 = tmp.1.inline1
+            jump return_fact.inline1
+            tmp.4 = 0
+            jump return_fact.inline1
+        
+          return_fact.inline1:
+            return tmp.4
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_inline_and_remove_static_function_called_once() {
+    let src = r#"
+        static int sum(int *a, int n) {
+            int s = 0;
+            for (int i = 0; i < n; i = i + 1) {
+                if (a[i] < 0)
+                    continue;
+                s = s + a[i] * 2 - a[i] / 3;
+            }
+            return s;
+        }
+        int main(void) {
+            int a[3] = {1, -2, 3};
+            return sum(a, 3);
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            a.4[0] = 1
+            tmp.16 = - 2
+            a.4[4] = tmp.16
+            a.4[8] = 3
+            tmp.17 = &a.4
+            a.0.inline1 = tmp.17
+            n.1.inline1 = 3
+            s.2.inline1 = 0
+            i.3.inline1 = 0
+        
+          start_loop_0.inline1:
+            tmp.0.inline1 = i.3.inline1 < n.1.inline1
+            if !tmp.0.inline1 jump break_loop_0.inline1
+            tmp.1.inline1 = sign_extend i.3.inline1
+            tmp.2.inline1 = add_ptr(a.0.inline1, index=tmp.1.inline1, scale=4)
+            tmp.3.inline1 = *tmp.2.inline1
+            tmp.4.inline1 = tmp.3.inline1 < 0
+            if !tmp.4.inline1 jump end_if_0.inline1
+            jump continue_loop_0.inline1
+        
+          end_if_0.inline1:
+            tmp.6.inline1 = sign_extend i.3.inline1
+            tmp.7.inline1 = add_ptr(a.0.inline1, index=tmp.6.inline1, scale=4)
+            tmp.8.inline1 = *tmp.7.inline1
+            tmp.9.inline1 = tmp.8.inline1 * 2
+            tmp.5.inline1 = s.2.inline1 + tmp.9.inline1
+            tmp.11.inline1 = sign_extend i.3.inline1
+            tmp.12.inline1 = add_ptr(a.0.inline1, index=tmp.11.inline1, scale=4)
+            tmp.13.inline1 = *tmp.12.inline1
+            tmp.14.inline1 = tmp.13.inline1 / 3
+            tmp.10.inline1 = tmp.5.inline1 - tmp.14.inline1
+            s.2.inline1 = tmp.10.inline1
+        
+          continue_loop_0.inline1:
+            tmp.15.inline1 = i.3.inline1 + 1
+            i.3.inline1 = tmp.15.inline1
+            jump start_loop_0.inline1
+        
+          break_loop_0.inline1:
+            tmp.18 = s.2.inline1
+            jump return_sum.inline1
+            tmp.18 = 0
+            jump return_sum.inline1
+        
+          return_sum.inline1:
+            return tmp.18
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_keep_function_with_address_taken() {
+    let src = r#"
+        static int twice(int x) {
+            return x * 2;
+        }
+        int (*fp)(int x) = twice;
+        int main(void) {
+            return twice(1) + fp(2);
+        }
+    "#;
+    let expected = r#"
+        function twice(x.0) { 
+            tmp.0 = x.0 * 2
+            return tmp.0
+            return 0
+        }
+        global function main() { 
+            x.0.inline1 = 1
+            tmp.0.inline1 = x.0.inline1 * 2
+            tmp.1 = tmp.0.inline1
+            jump return_twice.inline1
+            tmp.1 = 0
+            jump return_twice.inline1
+        
+          return_twice.inline1:
+            tmp.3 = (*fp)(2)
+            tmp.2 = tmp.1 + tmp.3
+            return tmp.2
+            return 0
+        }
+        static global fp: Pointer(Function(...)) = &twice
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_inline_of_variadic_or_variable_length_array_function() {
+    let src = r#"
+        static int first(int count, ...) {
+            __builtin_va_list args;
+            __builtin_va_start(args, count);
+            int result = __builtin_va_arg(args, int);
+            __builtin_va_end(args);
+            return result;
+        }
+        static int last(int n) {
+            int a[n];
+            a[n - 1] = n;
+            return a[n - 1];
+        }
+        int main(void) {
+            return first(1, 2) + last(3);
+        }
+    "#;
+    let expected = r#"
+        function first(count.0) { 
+            tmp.0 = &args.1
+            va_start(tmp.0)
+            tmp.1 = &args.1
+            tmp.2 = va_arg(tmp.1)
+            result.2 = tmp.2
+            tmp.3 = &args.1
+            return result.2
+            return 0
+        }
+        function last(n.3) { 
+            tmp.4 = save_stack()
+            tmp.5 = sign_extend n.3
+            vla_length.52 = tmp.5
+            tmp.6 = vla_length.52 * 4UL
+            a.4 = allocate_stack(tmp.6)
+            tmp.7 = n.3 - 1
+            tmp.8 = sign_extend tmp.7
+            tmp.9 = add_ptr(a.4, index=tmp.8, scale=4)
+            *tmp.9 = n.3
+            tmp.10 = *tmp.9
+            tmp.11 = n.3 - 1
+            tmp.12 = sign_extend tmp.11
+            tmp.13 = add_ptr(a.4, index=tmp.12, scale=4)
+            tmp.14 = *tmp.13
+            return tmp.14
+            restore_stack(tmp.4)
+            return 0
+        }
+        global function main() { 
+            tmp.15 = first(1, 2)
+            tmp.17 = last(3)
+            tmp.16 = tmp.15 + tmp.17
+            return tmp.16
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
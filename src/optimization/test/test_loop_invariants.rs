@@ -0,0 +1,145 @@
+use crate::optimization::OptimizationFlags;
+use crate::pretty::{dedent, dump_optimized_tacky};
+
+fn dump(src: &str) -> String {
+    let flags = OptimizationFlags {
+        hoist_loop_invariants: true,
+        ..Default::default()
+    };
+    dump_optimized_tacky(src, &flags)
+}
+
+#[test]
+fn test_hoist_into_preheader() {
+    let src = r#"
+        int f(int a, int b, int n) {
+            int s = 0;
+            for (int i = 0; i < n; i = i + 1)
+                s = s + a * b;
+            return s;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0, b.1, n.2) { 
+            s.3.1 = 0
+            i.4.1 = 0
+        
+          start_loop_0.preheader:
+            tmp.2.1 = a.0 * b.1
+            s.3.2.phi = s.3.1
+            i.4.2.phi = i.4.1
+        
+          start_loop_0:
+            s.3.2 = s.3.2.phi
+            i.4.2 = i.4.2.phi
+            tmp.0.1 = i.4.2 < n.2
+            if !tmp.0.1 jump break_loop_0
+            tmp.1.1 = s.3.2 + tmp.2.1
+            s.3.3 = tmp.1.1
+        
+          continue_loop_0:
+            tmp.3.1 = i.4.2 + 1
+            i.4.3 = tmp.3.1
+            s.3.2.phi = s.3.3
+            i.4.2.phi = i.4.3
+            jump start_loop_0
+        
+          break_loop_0:
+            return s.3.2
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_hoist_of_redefined_value_or_trapping_division() {
+    let src = r#"
+        int f(int a, int b, int n) {
+            int s = 0;
+            for (int i = 0; i < n; i = i + 1) {
+                a = a + 1;
+                s = s + a * 2 + n / b;
+            }
+            return s;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0, b.1, n.2) { 
+            s.3.1 = 0
+            i.4.1 = 0
+        
+          start_loop_0.preheader:
+            s.3.2.phi = s.3.1
+            i.4.2.phi = i.4.1
+            a.0.1.phi = a.0
+        
+          start_loop_0:
+            s.3.2 = s.3.2.phi
+            i.4.2 = i.4.2.phi
+            a.0.1 = a.0.1.phi
+            tmp.0.1 = i.4.2 < n.2
+            if !tmp.0.1 jump break_loop_0
+            tmp.1.1 = a.0.1 + 1
+            a.0.2 = tmp.1.1
+            tmp.3.1 = a.0.2 * 2
+            tmp.2.1 = s.3.2 + tmp.3.1
+            tmp.5.1 = n.2 / b.1
+            tmp.4.1 = tmp.2.1 + tmp.5.1
+            s.3.3 = tmp.4.1
+        
+          continue_loop_0:
+            tmp.6.1 = i.4.2 + 1
+            i.4.3 = tmp.6.1
+            s.3.2.phi = s.3.3
+            i.4.2.phi = i.4.3
+            a.0.1.phi = a.0.2
+            jump start_loop_0
+        
+          break_loop_0:
+            return s.3.2
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
+
+#[test]
+fn test_no_preheader_when_header_is_fallen_into() {
+    let src = r#"
+        int f(int a, int b, int n) {
+            int i = 0;
+            goto head;
+        back:
+            i = i + a * b;
+        head:
+            if (i < n)
+                goto back;
+            return i;
+        }
+    "#;
+    let expected = r#"
+        global function f(a.0, b.1, n.2) { 
+            i.3.1 = 0
+            i.3.2.phi = i.3.1
+            jump head_1
+        
+          back_0:
+            tmp.1.1 = a.0 * b.1
+            tmp.0.1 = i.3.2 + tmp.1.1
+            i.3.3 = tmp.0.1
+            i.3.2.phi = i.3.3
+        
+          head_1:
+            i.3.2 = i.3.2.phi
+            tmp.2.1 = i.3.2 < n.2
+            if !tmp.2.1 jump end_if_0
+            jump back_0
+        
+          end_if_0:
+            return i.3.2
+            return 0
+        }
+    "#;
+    assert_eq!(dump(src), dedent(expected));
+}
@@ -0,0 +1,171 @@
+use super::dump_ssa;
+use crate::optimization::ssa::used_vals_mut;
+use crate::pretty::dedent;
+use crate::tacky::cfg::Cfg;
+use crate::tacky::{Instruction, Val};
+use std::collections::HashMap;
+
+/// Replaces the uses of copied variables, phi arguments included, with the
+/// copied value, so phis end up reading each other.
+fn propagate_copies(cfg: &mut Cfg) {
+    let ids: Vec<_> = cfg.all_ids().collect();
+    let mut copies = HashMap::new();
+    for &node_id in &ids {
+        for instruction in &cfg.get_node(node_id).instructions {
+            if let Instruction::Copy {
+                src: src @ Val::Var(_),
+                dst,
+            } = instruction
+            {
+                copies.insert(dst.clone(), src.clone());
+            }
+        }
+    }
+    for &node_id in &ids {
+        for instruction in &mut cfg.get_node_mut(node_id).instructions {
+            let uses = match instruction {
+                Instruction::Phi { args, .. } => args.iter_mut().map(|(_, arg)| arg).collect(),
+                instruction => used_vals_mut(instruction),
+            };
+            for val in uses {
+                while let Some(src) = copies.get(val) {
+                    *val = src.clone();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_phi_at_loop_header_and_join() {
+    let src = r#"
+        int main(void) {
+            int x = 0;
+            for (int i = 0; i < 10; i = i + 1)
+                x = x + i;
+            int y;
+            if (x > 5)
+                y = 1;
+            else
+                y = 2;
+            return y;
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            x.0.1 = 0
+            i.1.1 = 0
+        
+          start_loop_0:
+            x.0.2 = phi(1: x.0.1, 4: x.0.3)
+            i.1.2 = phi(1: i.1.1, 4: i.1.3)
+            tmp.0.1 = i.1.2 < 10
+            if !tmp.0.1 jump break_loop_0
+            tmp.1.1 = x.0.2 + i.1.2
+            x.0.3 = tmp.1.1
+        
+          continue_loop_0:
+            tmp.2.1 = i.1.2 + 1
+            i.1.3 = tmp.2.1
+            jump start_loop_0
+        
+          break_loop_0:
+            tmp.3.1 = x.0.2 > 5
+            if !tmp.3.1 jump else_1
+            y.2.1 = 1
+            jump end_if_0
+        
+          else_1:
+            y.2.2 = 2
+        
+          end_if_0:
+            y.2.3 = phi(6: y.2.1, 7: y.2.2)
+            return y.2.3
+            return 0
+        }
+    "#;
+    assert_eq!(dump_ssa(src, |_| {}, false), dedent(expected));
+}
+
+#[test]
+fn test_out_of_ssa_swap() {
+    let src = r#"
+        int main(void) {
+            int a = 1;
+            int b = 2;
+            int i = 0;
+            do {
+                int t = a;
+                a = b;
+                b = t;
+                i = i + 1;
+            } while (i < 3);
+            return a * 10 + b;
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            a.0.1 = 1
+            b.1.1 = 2
+            i.2.1 = 0
+            i.2.2.phi = i.2.1
+            b.1.2.phi = b.1.1
+            a.0.2.phi = a.0.1
+        
+          start_loop_0:
+            i.2.2 = i.2.2.phi
+            b.1.2 = b.1.2.phi
+            a.0.2 = a.0.2.phi
+            t.3.1 = a.0.2
+            a.0.3 = b.1.2
+            b.1.3 = a.0.2
+            tmp.0.1 = i.2.2 + 1
+            i.2.3 = tmp.0.1
+        
+          continue_loop_0:
+            tmp.1.1 = tmp.0.1 < 3
+            i.2.2.phi = tmp.0.1
+            b.1.2.phi = a.0.2
+            a.0.2.phi = b.1.2
+            if tmp.1.1 jump start_loop_0
+        
+          break_loop_0:
+            tmp.2.1 = b.1.2 * 10
+            tmp.3.1 = tmp.2.1 + a.0.2
+            return tmp.3.1
+            return 0
+        }
+    "#;
+    assert_eq!(dump_ssa(src, propagate_copies, true), dedent(expected));
+}
+
+#[test]
+fn test_unreachable_blocks() {
+    let src = r#"
+        int main(void) {
+            int x = 1;
+            goto end;
+            x = 2;
+            if (x)
+                x = 3;
+        end:
+            return x;
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            x.0.1 = 1
+            jump end_0
+            x.0 = 2
+            if !x.0 jump end_if_0
+            x.0 = 3
+        
+          end_if_0:
+        
+          end_0:
+            return x.0.1
+            return 0
+        }
+    "#;
+    assert_eq!(dump_ssa(src, |_| {}, false), dedent(expected));
+}
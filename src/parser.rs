@@ -2,13 +2,15 @@
 mod test;
 
 use crate::ast::{
-    AssignOp, BinaryOp, Block, BlockItem, Constant, Declaration, Expression, Field, ForInit,
-    FunctionDeclaration, FunctionTypeSpec, Identifier, Initializer, NameAndFields, Node, PostfixOp,
-    Program, Statement, StorageClass, TypeSpec, UnaryOp, VarDeclaration,
+    AssignOp, Attribute, BinaryOp, Block, BlockItem, Constant, Declaration, Designator,
+    EnumDeclaration, Enumerator, Expression, Field, ForInit, FunctionDeclaration, FunctionTypeSpec,
+    Identifier, Initializer, InitializerItem, NameAndFields, Node, PostfixOp, Program, Qualifiers,
+    Statement, StaticAssert, StorageClass, TypeDefDeclaration, TypeSpec, UnaryOp, VarDeclaration,
 };
 use crate::error::{CompilerError, ErrorKind, Result};
 use crate::lexer::{IntKind, Lexer, Span, Token, TokenKind};
 use crate::symbol::Symbol;
+use std::collections::HashMap;
 
 struct Parser<'src> {
     source: &'src str,
@@ -16,18 +18,44 @@ struct Parser<'src> {
     next: Token,
     lexer: Lexer<'src>,
     node_id: u32,
+    errors: Vec<CompilerError>,
+    error_limit: usize,
+    aborted: bool,
+    // For each scope, the names declared in it and whether they are typedefs
+    // or ordinary identifiers shadowing them.
+    type_names: Vec<HashMap<Symbol, bool>>,
+    // Declarations found inside another one, like the struct in
+    // `typedef struct s { ... } t;`, waiting to be added before it.
+    hoisted: Vec<Node<Declaration>>,
 }
 
+/// Number of errors reported before giving up when no limit is given.
+pub const DEFAULT_ERROR_LIMIT: usize = 20;
+
 impl TokenKind {
     fn is_decl_specifier(&self) -> bool {
-        self.is_type_specifier() || matches!(self, TokenKind::Extern | TokenKind::Static)
+        self.is_type_specifier()
+            || self.is_qualifier()
+            || matches!(
+                self,
+                TokenKind::Extern
+                    | TokenKind::Static
+                    | TokenKind::ThreadLocal
+                    | TokenKind::Typedef
+                    | TokenKind::Inline
+                    | TokenKind::Noreturn
+                    | TokenKind::Attribute
+            )
     }
 
     fn is_type_specifier(&self) -> bool {
         matches!(
             self,
             TokenKind::Double
+                | TokenKind::Float
+                | TokenKind::Bool
                 | TokenKind::Char
+                | TokenKind::Short
                 | TokenKind::Int
                 | TokenKind::Long
                 | TokenKind::Unsigned
@@ -35,19 +63,26 @@ impl TokenKind {
                 | TokenKind::Void
                 | TokenKind::Struct
                 | TokenKind::Union
+                | TokenKind::Enum
+                | TokenKind::VaList
         )
     }
+
+    fn is_qualifier(&self) -> bool {
+        matches!(self, TokenKind::Const | TokenKind::Volatile)
+    }
 }
 
 enum Declarator {
     Identifier(Node<Identifier>),
-    Pointer(Node<Declarator>),
+    Pointer(Qualifiers, Node<Declarator>),
     Array {
-        size: usize,
+        size: ArraySize,
         declarator: Node<Declarator>,
     },
     Function {
         params: Vec<Param>,
+        variadic: bool,
         declarator: Node<Declarator>,
     },
 }
@@ -64,13 +99,34 @@ struct Param {
 }
 
 enum AbstractDeclarator {
-    Pointer(Node<AbstractDeclarator>),
-    Array(Node<AbstractDeclarator>, usize),
+    Pointer(Qualifiers, Node<AbstractDeclarator>),
+    Array(Node<AbstractDeclarator>, ArraySize),
+    Function {
+        params: Vec<Param>,
+        variadic: bool,
+        declarator: Node<AbstractDeclarator>,
+    },
     Base,
 }
 
+enum ArraySize {
+    Constant(usize),
+    Variable(Node<Expression>),
+    Unsized,
+}
+
+impl ArraySize {
+    fn array_of(self, element: Node<TypeSpec>) -> TypeSpec {
+        match self {
+            ArraySize::Constant(size) => TypeSpec::Array(element, size),
+            ArraySize::Variable(size) => TypeSpec::VariableArray(element, size),
+            ArraySize::Unsized => TypeSpec::UnsizedArray(element),
+        }
+    }
+}
+
 impl<'src> Parser<'src> {
-    fn new(source: &'src str) -> Self {
+    fn new(source: &'src str, error_limit: usize) -> Self {
         let mut lexer = Lexer::new(source);
         Parser {
             source,
@@ -78,33 +134,156 @@ impl<'src> Parser<'src> {
             next: lexer.next(),
             lexer,
             node_id: 0,
+            errors: Vec::new(),
+            error_limit,
+            aborted: false,
+            type_names: vec![HashMap::new()],
+            hoisted: Vec::new(),
+        }
+    }
+
+    fn is_type_name(&self, token: Token) -> bool {
+        if token.kind != TokenKind::Identifier {
+            return false;
         }
+        let symbol = Symbol::from(token.slice(self.source));
+        self.type_names
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&symbol))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Like `TokenKind::is_type_specifier`, but also accepts identifiers that
+    /// name a typedef visible in the current scope.
+    fn is_type_specifier(&self, token: Token) -> bool {
+        token.kind.is_type_specifier() || token.kind.is_qualifier() || self.is_type_name(token)
+    }
+
+    fn is_decl_specifier(&self, token: Token) -> bool {
+        token.kind.is_decl_specifier() || self.is_type_name(token)
+    }
+
+    fn declare_name(&mut self, name: &Node<Identifier>, is_type: bool) {
+        let scope = self.type_names.last_mut().expect("No scope");
+        scope.insert(name.symbol.clone(), is_type);
     }
 
-    fn program(&mut self) -> Result<Node<Program>> {
+    fn scoped<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.type_names.push(HashMap::new());
+        let result = parse(self);
+        self.type_names.pop();
+        result
+    }
+
+    fn program(&mut self) -> Node<Program> {
         let begin = self.current.span;
         let mut declarations = Vec::new();
         while self.current.kind != TokenKind::Eof {
-            declarations.push(self.declaration()?);
+            let start = self.current.span;
+            match self.declaration() {
+                Ok(declaration) => {
+                    declarations.append(&mut self.hoisted);
+                    declarations.push(declaration);
+                }
+                Err(error) => {
+                    if let Err(fatal) = self.recover(error, start) {
+                        self.errors.push(fatal);
+                        break;
+                    }
+                }
+            }
         }
-        let end = self.expect(TokenKind::Eof)?.span;
-        Ok(self.node(begin + end, Program { declarations }))
+        let end = self.current.span;
+        self.node(begin + end, Program { declarations })
     }
 
     fn block(&mut self) -> Result<Node<Block>> {
+        self.scoped(Self::block_items)
+    }
+
+    fn block_items(&mut self) -> Result<Node<Block>> {
         let begin = self.current.span;
         self.expect(TokenKind::OpenBrace)?;
         let mut items = Vec::new();
         while self.current.kind != TokenKind::CloseBrace {
-            items.push(self.block_item()?)
+            let start = self.current.span;
+            match self.block_item() {
+                Ok(item) => {
+                    items.extend(self.hoisted.drain(..).map(BlockItem::Decl));
+                    items.push(item);
+                }
+                Err(error) if self.current.kind == TokenKind::Eof => return Err(error),
+                Err(error) => self.recover(error, start)?,
+            }
         }
         let end = self.current.span;
         self.advance();
         Ok(self.node(begin + end, Block { items }))
     }
 
+    /// Records `error` and skips tokens until parsing can resume: after a
+    /// `;`, before a `}` closing the current block or before the start of a
+    /// declaration outside of any parentheses or brackets, so that casts and
+    /// `sizeof` type names aren't mistaken for one. Fails once the error
+    /// limit is reached, which stops parsing altogether.
+    fn recover(&mut self, error: CompilerError, start: Span) -> Result<()> {
+        if self.aborted {
+            return Err(error);
+        }
+        self.errors.push(error);
+        if self.error_limit > 0 && self.errors.len() >= self.error_limit {
+            self.aborted = true;
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                "Too many errors, stopping now",
+                self.current.span,
+            ));
+        }
+        // Always make progress, otherwise the same error would be found again
+        if self.current.span.0 == start.0 && self.current.kind != TokenKind::Eof {
+            self.advance();
+        }
+        let mut depth = 0;
+        // Closing tokens without a matching opening one after the error are
+        // ignored, the opening one was consumed before the error.
+        let mut nesting: usize = 0;
+        loop {
+            match self.current.kind {
+                TokenKind::Eof => break,
+                TokenKind::Semicolon if depth == 0 => {
+                    self.advance();
+                    break;
+                }
+                TokenKind::CloseBrace if depth == 0 => break,
+                _ if depth == 0 && nesting == 0 && self.is_decl_specifier(self.current) => break,
+                TokenKind::OpenBrace => {
+                    depth += 1;
+                    self.advance();
+                }
+                TokenKind::CloseBrace => {
+                    depth -= 1;
+                    self.advance();
+                }
+                TokenKind::OpenParen | TokenKind::OpenBracket => {
+                    nesting += 1;
+                    self.advance();
+                }
+                TokenKind::CloseParen | TokenKind::CloseBracket => {
+                    nesting = nesting.saturating_sub(1);
+                    self.advance();
+                }
+                _ => self.advance(),
+            }
+        }
+        Ok(())
+    }
+
     fn block_item(&mut self) -> Result<BlockItem> {
-        let block = if self.current.kind.is_decl_specifier() {
+        let block = if self.current.kind == TokenKind::StaticAssert
+            || self.is_decl_specifier(self.current)
+        {
             BlockItem::Decl(self.declaration()?)
         } else {
             BlockItem::Stmt(self.statement()?)
@@ -113,52 +292,79 @@ impl<'src> Parser<'src> {
     }
 
     fn declaration(&mut self) -> Result<Node<Declaration>> {
+        if self.current.kind == TokenKind::Typedef {
+            return self.typedef_declaration();
+        }
+        if self.current.kind == TokenKind::StaticAssert {
+            return self.static_assert();
+        }
         let begin = self.current.span;
-        let (ty, storage_class) = if let TokenKind::Struct | TokenKind::Union = self.current.kind {
-            let keyword = self.current.kind;
-            self.advance(); // consume keyword
-            let name = self.identifier()?;
-            if let TokenKind::Semicolon | TokenKind::OpenBrace = self.current.kind {
-                let mut fields = Vec::new();
-                if self.matches(TokenKind::OpenBrace) {
-                    if self.matches(TokenKind::CloseBrace) {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Parse,
-                            msg: "Expected struct field but found '}}'".into(),
-                            span: self.current.span,
-                        });
-                    }
-                    while !self.matches(TokenKind::CloseBrace) {
-                        let field = self.field()?;
-                        fields.push(field);
-                    }
+        let mut attributes = Vec::new();
+        let (ty, storage_class, is_thread_local) =
+            if let TokenKind::Struct | TokenKind::Union = self.current.kind {
+                let keyword = self.current.kind;
+                self.advance(); // consume keyword
+                let mut tag_attributes = Vec::new();
+                self.attributes(&mut tag_attributes)?;
+                let name = self.identifier()?;
+                if let TokenKind::Semicolon | TokenKind::OpenBrace = self.current.kind {
+                    let mut static_asserts = Vec::new();
+                    let fields = if self.current.kind == TokenKind::OpenBrace {
+                        self.fields(&mut static_asserts)?
+                    } else {
+                        Vec::new()
+                    };
+                    self.attributes(&mut tag_attributes)?;
+                    let end = self.expect(TokenKind::Semicolon)?.span;
+                    let declaration = NameAndFields {
+                        name,
+                        fields,
+                        static_asserts,
+                        attributes: tag_attributes,
+                    };
+                    let declaration = match keyword {
+                        TokenKind::Struct => Declaration::Struct(declaration),
+                        TokenKind::Union => Declaration::Union(declaration),
+                        _ => unreachable!(),
+                    };
+                    return Ok(self.node(begin + end, declaration));
+                } else {
+                    let span = name.span;
+                    let ty_spec = match keyword {
+                        TokenKind::Struct => TypeSpec::Struct(name),
+                        TokenKind::Union => TypeSpec::Union(name),
+                        _ => unreachable!(),
+                    };
+                    let ty = self.node(begin + span, ty_spec);
+                    let mut qualifiers = Qualifiers::default();
+                    while self.type_qualifier(&mut qualifiers) {}
+                    (self.qualified(ty, qualifiers), None, false)
                 }
-                let end = self.expect(TokenKind::Semicolon)?.span;
-                let declaration = NameAndFields { name, fields };
-                let declaration = match keyword {
-                    TokenKind::Struct => Declaration::Struct(declaration),
-                    TokenKind::Union => Declaration::Union(declaration),
-                    _ => unreachable!(),
-                };
-                return Ok(self.node(begin + end, declaration));
             } else {
-                let span = name.span;
-                let ty_spec = match keyword {
-                    TokenKind::Struct => TypeSpec::Struct(name),
-                    TokenKind::Union => TypeSpec::Union(name),
-                    _ => unreachable!(),
-                };
-                (self.node(begin + span, ty_spec), None)
-            }
-        } else {
-            self.type_and_storage()?
-        };
-        let declarator = self.parse_declarator()?;
+                self.type_and_storage(&mut attributes)?
+            };
+        // A lone `enum e { ... };` only declares the enumeration
+        if let TypeSpec::Enum(_) = ty.as_ref()
+            && self.current.kind == TokenKind::Semicolon
+            && let Some(enumeration) = self.hoisted.pop()
+        {
+            self.advance();
+            return Ok(enumeration);
+        }
+        let declarator = self.parse_declarator(false)?;
         let processed = self.process_declarator(declarator, ty)?;
+        self.declare_name(&processed.name, false);
+        self.attributes(&mut attributes)?;
 
         if let TypeSpec::Function(function_ty) = *processed.type_spec.data {
             if let TokenKind::OpenBrace = self.current.kind {
-                let body = self.block()?;
+                let params = &processed.param_names;
+                let body = self.scoped(|parser| {
+                    for param in params {
+                        parser.declare_name(param, false);
+                    }
+                    parser.block()
+                })?;
                 Ok(self.node(
                     begin + body.span,
                     Declaration::Function(FunctionDeclaration {
@@ -167,6 +373,8 @@ impl<'src> Parser<'src> {
                         body: Some(body),
                         type_spec: function_ty,
                         storage_class,
+                        is_thread_local,
+                        attributes,
                     }),
                 ))
             } else {
@@ -179,6 +387,8 @@ impl<'src> Parser<'src> {
                         body: None,
                         type_spec: function_ty,
                         storage_class,
+                        is_thread_local,
+                        attributes,
                     }),
                 ))
             }
@@ -196,27 +406,169 @@ impl<'src> Parser<'src> {
                     init,
                     type_spec: processed.type_spec,
                     storage_class,
+                    is_thread_local,
+                    attributes,
                 }),
             ))
         }
     }
 
+    fn static_assert(&mut self) -> Result<Node<Declaration>> {
+        let begin = self.expect(TokenKind::StaticAssert)?.span;
+        self.expect(TokenKind::OpenParen)?;
+        let condition = self.expression_precedence(2, "expression")?;
+        self.expect(TokenKind::Comma)?;
+        if self.current.kind != TokenKind::StringLiteral {
+            self.expect(TokenKind::StringLiteral)?;
+        }
+        let Expression::String(message) = *self.string_literal()?.data else {
+            unreachable!("String literals parse to strings");
+        };
+        self.expect(TokenKind::CloseParen)?;
+        let end = self.expect(TokenKind::Semicolon)?.span;
+        Ok(self.node(
+            begin + end,
+            Declaration::StaticAssert(StaticAssert { condition, message }),
+        ))
+    }
+
+    fn typedef_declaration(&mut self) -> Result<Node<Declaration>> {
+        let begin = self.expect(TokenKind::Typedef)?.span;
+        let ty = if let TokenKind::Struct | TokenKind::Union = self.current.kind
+            && let TokenKind::Identifier | TokenKind::Attribute = self.next.kind
+        {
+            let keyword = self.current;
+            self.advance(); // consume keyword
+            let mut attributes = Vec::new();
+            self.attributes(&mut attributes)?;
+            let name = self.identifier()?;
+            if self.current.kind == TokenKind::OpenBrace {
+                let mut static_asserts = Vec::new();
+                let fields = self.fields(&mut static_asserts)?;
+                let end = self.current.span;
+                self.attributes(&mut attributes)?;
+                let tag = self.node(
+                    name.span,
+                    Identifier {
+                        symbol: name.symbol.clone(),
+                    },
+                );
+                let declaration = NameAndFields {
+                    name: tag,
+                    fields,
+                    static_asserts,
+                    attributes,
+                };
+                let declaration = match keyword.kind {
+                    TokenKind::Struct => Declaration::Struct(declaration),
+                    TokenKind::Union => Declaration::Union(declaration),
+                    _ => unreachable!(),
+                };
+                let declaration = self.node(keyword.span + end, declaration);
+                self.hoisted.push(declaration);
+            }
+            let span = keyword.span + name.span;
+            let ty_spec = match keyword.kind {
+                TokenKind::Struct => TypeSpec::Struct(name),
+                TokenKind::Union => TypeSpec::Union(name),
+                _ => unreachable!(),
+            };
+            self.node(span, ty_spec)
+        } else {
+            self.type_specifier()?
+        };
+        let declarator = self.parse_declarator(false)?;
+        let ProcessedDeclarator {
+            name, type_spec, ..
+        } = self.process_declarator(declarator, ty)?;
+        // Attributes of type aliases are not supported
+        self.attributes(&mut Vec::new())?;
+        let end = self.expect(TokenKind::Semicolon)?.span;
+        self.declare_name(&name, true);
+        Ok(self.node(
+            begin + end,
+            Declaration::TypeDef(TypeDefDeclaration { name, type_spec }),
+        ))
+    }
+
+    /// Parses the member list of a struct or union, adding the static
+    /// assertions found in between the fields to `static_asserts`.
+    fn fields(&mut self, static_asserts: &mut Vec<StaticAssert>) -> Result<Vec<Node<Field>>> {
+        self.expect(TokenKind::OpenBrace)?;
+        let mut fields = Vec::new();
+        while self.current.kind != TokenKind::CloseBrace {
+            if self.current.kind == TokenKind::StaticAssert {
+                let Declaration::StaticAssert(static_assert) = *self.static_assert()?.data else {
+                    unreachable!("Static assertions parse to static assertions");
+                };
+                static_asserts.push(static_assert);
+                continue;
+            }
+            let field = self.field()?;
+            fields.push(field);
+        }
+        self.advance();
+        if fields.is_empty() {
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                "Expected struct field but found '}}'",
+                self.current.span,
+            ));
+        }
+        Ok(fields)
+    }
+
     fn field(&mut self) -> Result<Node<Field>> {
         let base_ty = self.type_specifier()?;
         let begin = base_ty.span;
-        let declarator = self.parse_declarator()?;
-        if let Declarator::Function { .. } = declarator.as_ref() {
-            return Err(CompilerError {
-                kind: ErrorKind::Parse,
-                msg: "Structs can't have fields".into(),
-                span: declarator.span,
-            });
+        if self.matches(TokenKind::Colon) {
+            let width = self.expression_precedence(2, "expression")?;
+            let mut attributes = Vec::new();
+            self.attributes(&mut attributes)?;
+            let end = self.expect(TokenKind::Semicolon)?.span;
+            return Ok(self.node(
+                begin + end,
+                Field {
+                    name: None,
+                    type_spec: base_ty,
+                    width: Some(width),
+                    attributes,
+                },
+            ));
+        }
+        let declarator = self.parse_declarator(false)?;
+        if let Declarator::Function {
+            declarator: inner, ..
+        } = declarator.as_ref()
+            && let Declarator::Identifier(_) = inner.as_ref()
+        {
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                "Structs can't have fields",
+                inner.span,
+            ));
         }
         let ProcessedDeclarator {
             name, type_spec, ..
         } = self.process_declarator(declarator, base_ty)?;
+        let mut attributes = Vec::new();
+        self.attributes(&mut attributes)?;
+        let width = if self.matches(TokenKind::Colon) {
+            Some(self.expression_precedence(2, "expression")?)
+        } else {
+            None
+        };
+        self.attributes(&mut attributes)?;
         let end = self.expect(TokenKind::Semicolon)?.span;
-        Ok(self.node(begin + end, Field { name, type_spec }))
+        Ok(self.node(
+            begin + end,
+            Field {
+                name: Some(name),
+                type_spec,
+                width,
+                attributes,
+            },
+        ))
     }
 
     fn initializer(&mut self) -> Result<Node<Initializer>> {
@@ -225,9 +577,10 @@ impl<'src> Parser<'src> {
             let mut end;
             let mut initializers = Vec::new();
             loop {
-                let inner = self.initializer()?;
-                end = inner.span;
-                initializers.push(inner);
+                let designators = self.designators()?;
+                let init = self.initializer()?;
+                end = init.span;
+                initializers.push(InitializerItem { designators, init });
                 if self.matches(TokenKind::Comma) {
                     if self.matches(TokenKind::CloseBrace) {
                         break;
@@ -240,25 +593,69 @@ impl<'src> Parser<'src> {
             }
             Ok(self.node(begin + end, Initializer::Compound(initializers)))
         } else {
-            let expr = self.expression()?;
+            let expr = self.assignment_expression()?;
             Ok(self.node(expr.span, Initializer::Single(expr)))
         }
     }
 
-    fn parse_declarator(&mut self) -> Result<Node<Declarator>> {
+    fn designators(&mut self) -> Result<Vec<Node<Designator>>> {
+        let mut designators = Vec::new();
+        loop {
+            let begin = self.current.span;
+            if self.matches(TokenKind::Dot) {
+                let name = self.identifier()?;
+                designators.push(self.node(begin + name.span, Designator::Field(name)));
+            } else if self.matches(TokenKind::OpenBracket) {
+                let index = self.expression()?;
+                let end = self.expect(TokenKind::CloseBracket)?.span;
+                designators.push(self.node(begin + end, Designator::Index(index)));
+            } else {
+                break;
+            }
+        }
+        if !designators.is_empty() {
+            self.expect(TokenKind::Equal)?;
+        }
+        Ok(designators)
+    }
+
+    /// Parses a declarator. Parameters may omit the name, in which case the
+    /// identifier of the returned declarator is empty.
+    fn parse_declarator(&mut self, is_param: bool) -> Result<Node<Declarator>> {
         let begin = self.current.span;
         if self.matches(TokenKind::Star) {
-            let referenced = self.parse_declarator()?;
-            Ok(self.node(begin + referenced.span, Declarator::Pointer(referenced)))
+            let mut qualifiers = Qualifiers::default();
+            while self.type_qualifier(&mut qualifiers) {}
+            let referenced = self.parse_declarator(is_param)?;
+            Ok(self.node(
+                begin + referenced.span,
+                Declarator::Pointer(qualifiers, referenced),
+            ))
         } else {
-            self.parse_direct_declarator()
+            self.parse_direct_declarator(is_param)
         }
     }
 
-    fn parse_simple_declarator(&mut self) -> Result<Node<Declarator>> {
+    fn parse_simple_declarator(&mut self, is_param: bool) -> Result<Node<Declarator>> {
         let begin = self.current.span;
-        if self.matches(TokenKind::OpenParen) {
-            let declarator = self.parse_declarator()?;
+        let is_unnamed = is_param
+            && match self.current.kind {
+                TokenKind::Identifier => false,
+                // A parenthesis starts the parameter list of an unnamed
+                // function parameter, unless it wraps a nested declarator
+                TokenKind::OpenParen => !self.starts_nested_declarator(),
+                _ => true,
+            };
+        if is_unnamed {
+            let identifier = self.node(
+                Span(begin.0, begin.0),
+                Identifier {
+                    symbol: Symbol::default(),
+                },
+            );
+            Ok(self.node(identifier.span, Declarator::Identifier(identifier)))
+        } else if self.matches(TokenKind::OpenParen) {
+            let declarator = self.parse_declarator(is_param)?;
             let end = self.expect(TokenKind::CloseParen)?.span;
             Ok(self.node(begin + end, *declarator.data))
         } else {
@@ -267,32 +664,26 @@ impl<'src> Parser<'src> {
         }
     }
 
-    fn parse_direct_declarator(&mut self) -> Result<Node<Declarator>> {
+    /// Whether the current `(` wraps a nested declarator rather than opening
+    /// a parameter list.
+    fn starts_nested_declarator(&self) -> bool {
+        match self.next.kind {
+            TokenKind::Star | TokenKind::OpenParen | TokenKind::OpenBracket => true,
+            TokenKind::Identifier => !self.is_type_name(self.next),
+            _ => false,
+        }
+    }
+
+    fn parse_direct_declarator(&mut self, is_param: bool) -> Result<Node<Declarator>> {
         let begin = self.current.span;
-        let simple = self.parse_simple_declarator()?;
-        let mut end = simple.span;
+        let simple = self.parse_simple_declarator(is_param)?;
         if self.matches(TokenKind::OpenParen) {
-            let mut params = Vec::new();
-            if self.current.kind == TokenKind::Void && self.next.kind == TokenKind::CloseParen {
-                // empty argument list foo(void)
-                self.advance(); // consume void
-                self.advance(); // consume close paren
-            } else {
-                loop {
-                    let param = self.parse_param()?;
-                    params.push(param);
-                    end = self.current.span;
-                    if self.matches(TokenKind::Comma) {
-                        continue;
-                    }
-                    self.expect(TokenKind::CloseParen)?;
-                    break;
-                }
-            }
+            let (params, variadic, end) = self.parse_params()?;
             Ok(self.node(
                 begin + end,
                 Declarator::Function {
                     params,
+                    variadic,
                     declarator: simple,
                 },
             ))
@@ -300,8 +691,7 @@ impl<'src> Parser<'src> {
             let mut declarator = simple;
             loop {
                 let size = self.parse_array_index()?;
-                end = self.current.span;
-                self.expect(TokenKind::CloseBracket)?;
+                let end = self.expect(TokenKind::CloseBracket)?.span;
                 declarator = self.node(begin + end, Declarator::Array { size, declarator });
                 if !self.matches(TokenKind::OpenBracket) {
                     break;
@@ -313,28 +703,67 @@ impl<'src> Parser<'src> {
         }
     }
 
-    fn parse_array_index(&mut self) -> Result<usize> {
+    /// Parses the parameters of a function declarator after the opening
+    /// parenthesis, returning them with the span of the closing one.
+    fn parse_params(&mut self) -> Result<(Vec<Param>, bool, Span)> {
+        let mut params = Vec::new();
+        let mut variadic = false;
+        let end;
+        if self.current.kind == TokenKind::Void && self.next.kind == TokenKind::CloseParen {
+            // empty argument list foo(void)
+            self.advance(); // consume void
+            end = self.current.span;
+            self.advance(); // consume close paren
+            return Ok((params, variadic, end));
+        }
+        loop {
+            if self.current.kind == TokenKind::Ellipsis {
+                if params.is_empty() {
+                    return Err(CompilerError::new(
+                        ErrorKind::Parse,
+                        "A named parameter is required before '...'",
+                        self.current.span,
+                    ));
+                }
+                self.advance();
+                variadic = true;
+                end = self.current.span;
+                self.expect(TokenKind::CloseParen)?;
+                break;
+            }
+            let param = self.parse_param()?;
+            params.push(param);
+            let current = self.current.span;
+            if self.matches(TokenKind::Comma) {
+                continue;
+            }
+            self.expect(TokenKind::CloseParen)?;
+            end = current;
+            break;
+        }
+        Ok((params, variadic, end))
+    }
+
+    fn parse_array_index(&mut self) -> Result<ArraySize> {
         let size_expr = self.expression()?;
         let Expression::Constant(size_const) = size_expr.as_ref() else {
-            return Err(CompilerError {
-                kind: ErrorKind::Parse,
-                msg: "Array size should be a constant".into(),
-                span: size_expr.span,
-            });
+            return Ok(ArraySize::Variable(size_expr));
         };
         if !size_const.is_int() {
-            return Err(CompilerError {
-                kind: ErrorKind::Parse,
-                msg: "Array size should be an integer constant".into(),
-                span: size_expr.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                "Array size should be an integer constant",
+                size_expr.span,
+            ));
         }
-        Ok(size_const.as_u64() as usize)
+        Ok(ArraySize::Constant(size_const.as_u64() as usize))
     }
 
     fn parse_param(&mut self) -> Result<Param> {
         let ty = self.type_specifier()?;
-        let declarator = self.parse_declarator()?;
+        let declarator = self.parse_declarator(true)?;
+        // Attributes of parameters are not supported
+        self.attributes(&mut Vec::new())?;
         Ok(Param { ty, declarator })
     }
 
@@ -349,57 +778,59 @@ impl<'src> Parser<'src> {
                 type_spec,
                 param_names: vec![],
             }),
-            Declarator::Pointer(declarator) => {
+            Declarator::Pointer(qualifiers, declarator) => {
                 let derived_type = self.node(
                     type_spec.span + declarator.span,
                     TypeSpec::Pointer(type_spec),
                 );
+                let derived_type = self.qualified(derived_type, qualifiers);
                 self.process_declarator(declarator, derived_type)
             }
             Declarator::Array {
                 size,
                 declarator: inner,
             } => {
-                let derived_type = self.node(
-                    type_spec.span + declarator.span,
-                    TypeSpec::Array(type_spec, size),
-                );
+                let derived_type =
+                    self.node(type_spec.span + declarator.span, size.array_of(type_spec));
                 self.process_declarator(inner, derived_type)
             }
-            Declarator::Function { params, declarator } => {
+            Declarator::Function {
+                params,
+                variadic,
+                declarator,
+            } => {
+                if let Declarator::Array { .. } | Declarator::Function { .. } = declarator.as_ref()
+                {
+                    return Err(CompilerError::new(
+                        ErrorKind::Parse,
+                        "Can't apply additional derivations to a function type",
+                        declarator.span,
+                    ));
+                }
                 let mut param_names = Vec::new();
                 let mut param_types = Vec::new();
+                for Param { ty, declarator } in params {
+                    let processed = self.process_declarator(declarator, ty)?;
+                    param_names.push(processed.name);
+                    param_types.push(processed.type_spec);
+                }
+                let span = type_spec.span;
+                let function_type = FunctionTypeSpec {
+                    params: param_types,
+                    ret: type_spec,
+                    variadic,
+                };
+                let type_spec =
+                    self.node(span + declarator.span, TypeSpec::Function(function_type));
                 if let Declarator::Identifier(name) = *declarator.data {
-                    for Param { ty, declarator } in params {
-                        let processed = self.process_declarator(declarator, ty)?;
-                        if let TypeSpec::Function { .. } = processed.type_spec.as_ref() {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Parse,
-                                msg: "Function pointers in parameters are not supported".into(),
-                                span: processed.type_spec.span,
-                            });
-                        }
-                        param_names.push(processed.name);
-                        param_types.push(processed.type_spec);
-                    }
-                    let span = type_spec.span;
-                    let function_type = FunctionTypeSpec {
-                        params: param_types,
-                        ret: type_spec,
-                    };
-                    let type_spec =
-                        self.node(span + declarator.span, TypeSpec::Function(function_type));
                     Ok(ProcessedDeclarator {
                         name,
                         type_spec,
                         param_names,
                     })
                 } else {
-                    Err(CompilerError {
-                        kind: ErrorKind::Parse,
-                        msg: "Can't apply additional derivations to a function type".into(),
-                        span: declarator.span,
-                    })
+                    // A pointer to a function, the parameter names are not needed
+                    self.process_declarator(declarator, type_spec)
                 }
             }
         }
@@ -409,15 +840,65 @@ impl<'src> Parser<'src> {
         let mut types = Vec::new();
         let begin = self.current.span;
         let mut end = self.current.span;
-        while self.current.kind.is_type_specifier() {
-            types.push(self.current.kind);
-            end = self.current.span;
-            self.advance()
+        let mut named = None;
+        let mut qualifiers = Qualifiers::default();
+        loop {
+            if self.current.kind.is_type_specifier() {
+                types.push(self.current.kind);
+                end = self.current.span;
+                self.advance()
+            } else if self.current.kind.is_qualifier() {
+                end = self.current.span;
+                self.type_qualifier(&mut qualifiers);
+            } else if self.current.kind == TokenKind::Attribute {
+                self.attributes(&mut Vec::new())?;
+            } else if types.is_empty() && named.is_none() && self.is_type_name(self.current) {
+                end = self.current.span;
+                named = Some(self.identifier()?);
+            } else {
+                break;
+            }
+        }
+        let ty = self.type_from_list(begin + end, &types, named)?;
+        while self.type_qualifier(&mut qualifiers) {}
+        Ok(self.qualified(ty, qualifiers))
+    }
+
+    /// Consumes a `const` or `volatile` token, adding it to `qualifiers`.
+    fn type_qualifier(&mut self, qualifiers: &mut Qualifiers) -> bool {
+        match self.current.kind {
+            TokenKind::Const => qualifiers.is_const = true,
+            TokenKind::Volatile => qualifiers.is_volatile = true,
+            _ => return false,
+        }
+        self.advance();
+        true
+    }
+
+    fn qualified(&mut self, ty: Node<TypeSpec>, qualifiers: Qualifiers) -> Node<TypeSpec> {
+        if qualifiers.is_empty() {
+            ty
+        } else {
+            self.node(ty.span, TypeSpec::Qualified(qualifiers, ty))
         }
-        self.type_from_list(begin + end, &types)
     }
 
-    fn type_from_list(&mut self, span: Span, types: &[TokenKind]) -> Result<Node<TypeSpec>> {
+    fn type_from_list(
+        &mut self,
+        span: Span,
+        types: &[TokenKind],
+        named: Option<Node<Identifier>>,
+    ) -> Result<Node<TypeSpec>> {
+        if let Some(name) = named {
+            if !types.is_empty() {
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    "Invalid type specifier",
+                    span,
+                ));
+            }
+            return Ok(self.node(span, TypeSpec::Named(name)));
+        }
         match types {
             [TokenKind::Struct] => {
                 let name = self.identifier()?;
@@ -427,16 +908,29 @@ impl<'src> Parser<'src> {
                 let name = self.identifier()?;
                 return Ok(self.node(span + name.span, TypeSpec::Union(name)));
             }
+            [TokenKind::Enum] => {
+                return self.enum_specifier(span);
+            }
             [TokenKind::Double] => {
                 return Ok(self.node(span, TypeSpec::Double));
             }
+            [TokenKind::Float] => {
+                return Ok(self.node(span, TypeSpec::Float));
+            }
+            [TokenKind::Bool] => {
+                return Ok(self.node(span, TypeSpec::Bool));
+            }
             [TokenKind::Void] => {
                 return Ok(self.node(span, TypeSpec::Void));
             }
+            [TokenKind::VaList] => {
+                return Ok(self.node(span, TypeSpec::VaList));
+            }
             _ => {}
         }
 
         let mut char = 0;
+        let mut short = 0;
         let mut signed = 0;
         let mut unsigned = 0;
         let mut int = 0;
@@ -445,54 +939,119 @@ impl<'src> Parser<'src> {
         for ty in types {
             match ty {
                 TokenKind::Char => char += 1,
+                TokenKind::Short => short += 1,
                 TokenKind::Signed => signed += 1,
                 TokenKind::Unsigned => unsigned += 1,
                 TokenKind::Int => int += 1,
                 TokenKind::Long => long += 1,
-                TokenKind::Double | TokenKind::Void => {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Parse,
-                        msg: "Invalid type specifier".into(),
+                _ => {
+                    return Err(CompilerError::new(
+                        ErrorKind::Parse,
+                        "Invalid type specifier",
                         span,
-                    });
+                    ));
                 }
-                _ => unreachable!(),
             };
         }
 
-        let ty = match (signed, unsigned, char, int, long) {
-            (0, 0, 1, 0, 0) => TypeSpec::Char,
-            (1, 0, 1, 0, 0) => TypeSpec::SChar,
-            (0, 1, 1, 0, 0) => TypeSpec::UChar,
-            (0, 1, 0, 0 | 1, 1) => TypeSpec::ULong,
-            (0, 1, 0, 0 | 1, 0) => TypeSpec::UInt,
-            (0 | 1, 0, 0, 0 | 1, 1) => TypeSpec::Long,
-            (0 | 1, 0, 0, 1, 0) => TypeSpec::Int,
-            (1, 0, 0, 0, 0) => TypeSpec::Int,
-            (0, 0, 0, 0, 0) => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: "Expected type specifier".into(),
+        let ty = match (signed, unsigned, char, short, int, long) {
+            (0, 0, 1, 0, 0, 0) => TypeSpec::Char,
+            (1, 0, 1, 0, 0, 0) => TypeSpec::SChar,
+            (0, 1, 1, 0, 0, 0) => TypeSpec::UChar,
+            (0 | 1, 0, 0, 1, 0 | 1, 0) => TypeSpec::Short,
+            (0, 1, 0, 1, 0 | 1, 0) => TypeSpec::UShort,
+            (0, 1, 0, 0, 0 | 1, 1 | 2) => TypeSpec::ULong,
+            (0, 1, 0, 0, 0 | 1, 0) => TypeSpec::UInt,
+            (0 | 1, 0, 0, 0, 0 | 1, 1 | 2) => TypeSpec::Long,
+            (0 | 1, 0, 0, 0, 1, 0) => TypeSpec::Int,
+            (1, 0, 0, 0, 0, 0) => TypeSpec::Int,
+            (0, 0, 0, 0, 0, 0) => {
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    "Expected type specifier",
                     span,
-                });
+                ));
             }
             _ => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: "Invalid type specifier".into(),
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    "Invalid type specifier",
                     span,
-                });
+                ));
             }
         };
 
         Ok(self.node(span, ty))
     }
 
-    fn type_and_storage(&mut self) -> Result<(Node<TypeSpec>, Option<Node<StorageClass>>)> {
+    /// Parses what follows the `enum` keyword. When the enumerators are
+    /// given, the enumeration declaration is hoisted before the current one.
+    fn enum_specifier(&mut self, begin: Span) -> Result<Node<TypeSpec>> {
+        let name = if self.current.kind == TokenKind::Identifier {
+            Some(self.identifier()?)
+        } else {
+            None
+        };
+        let mut end = name.as_ref().map_or(begin, |name| name.span);
+        if self.matches(TokenKind::OpenBrace) {
+            let enumerators = self.enumerators()?;
+            end = self.expect(TokenKind::CloseBrace)?.span;
+            let tag = name.as_ref().map(|name| {
+                self.node(
+                    name.span,
+                    Identifier {
+                        symbol: name.symbol.clone(),
+                    },
+                )
+            });
+            let declaration = Declaration::Enum(EnumDeclaration {
+                name: tag,
+                enumerators,
+            });
+            let declaration = self.node(begin + end, declaration);
+            self.hoisted.push(declaration);
+        } else if name.is_none() {
+            self.identifier()?;
+        }
+        Ok(self.node(begin + end, TypeSpec::Enum(name)))
+    }
+
+    fn enumerators(&mut self) -> Result<Vec<Node<Enumerator>>> {
+        let mut enumerators = Vec::new();
+        loop {
+            let name = self.identifier()?;
+            let value = if self.matches(TokenKind::Equal) {
+                Some(self.expression_precedence(2, "expression")?)
+            } else {
+                None
+            };
+            self.declare_name(&name, false);
+            let span = value
+                .as_ref()
+                .map_or(name.span, |value| name.span + value.span);
+            enumerators.push(self.node(span, Enumerator { name, value }));
+            if !self.matches(TokenKind::Comma) || self.current.kind == TokenKind::CloseBrace {
+                break;
+            }
+        }
+        Ok(enumerators)
+    }
+
+    /// Parses the specifiers of a declaration, function specifiers and
+    /// attributes are added to `attributes`. Also tells whether the
+    /// declaration is `_Thread_local`, which combines with other storage
+    /// classes.
+    fn type_and_storage(
+        &mut self,
+        attributes: &mut Vec<Node<Attribute>>,
+    ) -> Result<(Node<TypeSpec>, Option<Node<StorageClass>>, bool)> {
         let begin = self.current.span;
         let mut end = self.current.span;
         let mut types = Vec::new();
         let mut storage: Option<Node<StorageClass>> = None;
+        let mut is_thread_local = false;
+        let mut named = None;
+        let mut qualifiers = Qualifiers::default();
         loop {
             let token = self.current;
             match token.kind {
@@ -501,13 +1060,23 @@ impl<'src> Parser<'src> {
                     end = self.current.span;
                     self.advance();
                 }
+                kind if kind.is_qualifier() => {
+                    end = self.current.span;
+                    self.type_qualifier(&mut qualifiers);
+                }
+                TokenKind::Identifier
+                    if types.is_empty() && named.is_none() && self.is_type_name(token) =>
+                {
+                    end = self.current.span;
+                    named = Some(self.identifier()?);
+                }
                 TokenKind::Static | TokenKind::Extern => {
                     if storage.is_some() {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Parse,
-                            msg: "Duplicated storage class in declaration".to_string(),
-                            span: token.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Parse,
+                            "Duplicated storage class in declaration",
+                            token.span,
+                        ));
                     }
                     let s = match token.kind {
                         TokenKind::Static => StorageClass::Static,
@@ -517,11 +1086,130 @@ impl<'src> Parser<'src> {
                     storage = Some(self.node(token.span, s));
                     self.advance();
                 }
+                TokenKind::ThreadLocal => {
+                    if is_thread_local {
+                        return Err(CompilerError::new(
+                            ErrorKind::Parse,
+                            "Duplicated storage class in declaration",
+                            token.span,
+                        ));
+                    }
+                    is_thread_local = true;
+                    self.advance();
+                }
+                // Inline functions are compiled like any other
+                TokenKind::Inline => self.advance(),
+                TokenKind::Noreturn => {
+                    attributes.push(self.node(token.span, Attribute::Noreturn));
+                    self.advance();
+                }
+                TokenKind::Attribute => self.attributes(attributes)?,
                 _ => break,
             }
         }
-        let ty = self.type_from_list(begin + end, &types)?;
-        Ok((ty, storage))
+        let ty = self.type_from_list(begin + end, &types, named)?;
+        while self.type_qualifier(&mut qualifiers) {}
+        Ok((self.qualified(ty, qualifiers), storage, is_thread_local))
+    }
+
+    /// Parses any number of `__attribute__((...))` specifiers, adding the
+    /// supported attributes to `attributes`.
+    fn attributes(&mut self, attributes: &mut Vec<Node<Attribute>>) -> Result<()> {
+        while self.matches(TokenKind::Attribute) {
+            self.expect(TokenKind::OpenParen)?;
+            self.expect(TokenKind::OpenParen)?;
+            while self.current.kind != TokenKind::CloseParen {
+                if let Some(attribute) = self.attribute()? {
+                    attributes.push(attribute);
+                }
+                if !self.matches(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::CloseParen)?;
+            self.expect(TokenKind::CloseParen)?;
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self) -> Result<Option<Node<Attribute>>> {
+        let token = self.current;
+        // Attribute names may be keywords, like in `__attribute__((const))`
+        let is_name = token
+            .slice(self.source)
+            .starts_with(|c: char| c.is_alphabetic() || c == '_');
+        if !is_name {
+            self.expect(TokenKind::Identifier)?;
+        }
+        self.advance();
+        let name = token.slice(self.source);
+        let name = name
+            .strip_prefix("__")
+            .and_then(|name| name.strip_suffix("__"))
+            .unwrap_or(name);
+        let attribute = match name {
+            "noreturn" => Attribute::Noreturn,
+            "packed" => Attribute::Packed,
+            "unused" => Attribute::Unused,
+            "weak" => Attribute::Weak,
+            "aligned" => {
+                if self.matches(TokenKind::OpenParen) {
+                    let alignment = self.expression_precedence(2, "expression")?;
+                    let end = self.expect(TokenKind::CloseParen)?.span;
+                    return Ok(Some(
+                        self.node(token.span + end, Attribute::Aligned(Some(alignment))),
+                    ));
+                }
+                Attribute::Aligned(None)
+            }
+            "section" => {
+                self.expect(TokenKind::OpenParen)?;
+                if self.current.kind != TokenKind::StringLiteral {
+                    self.expect(TokenKind::StringLiteral)?;
+                }
+                let Expression::String(section) = *self.string_literal()?.data else {
+                    unreachable!("String literals parse to strings");
+                };
+                let end = self.expect(TokenKind::CloseParen)?.span;
+                return Ok(Some(
+                    self.node(token.span + end, Attribute::Section(section)),
+                ));
+            }
+            _ => {
+                self.skip_attribute_arguments()?;
+                return Ok(None);
+            }
+        };
+        if self.current.kind == TokenKind::OpenParen {
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                format!("Wrong number of arguments specified for '{name}' attribute"),
+                self.current.span,
+            ));
+        }
+        Ok(Some(self.node(token.span, attribute)))
+    }
+
+    /// Skips the arguments of an unsupported attribute, if any.
+    fn skip_attribute_arguments(&mut self) -> Result<()> {
+        if self.current.kind != TokenKind::OpenParen {
+            return Ok(());
+        }
+        let mut depth = 0;
+        loop {
+            match self.current.kind {
+                TokenKind::OpenParen => depth += 1,
+                TokenKind::CloseParen => depth -= 1,
+                TokenKind::Eof => {
+                    self.expect(TokenKind::CloseParen)?;
+                }
+                _ => {}
+            }
+            self.advance();
+            if depth == 0 {
+                return Ok(());
+            }
+        }
     }
 
     fn statement(&mut self) -> Result<Node<Statement>> {
@@ -587,6 +1275,10 @@ impl<'src> Parser<'src> {
     }
 
     fn for_stmt(&mut self) -> Result<Node<Statement>> {
+        self.scoped(Self::for_stmt_scoped)
+    }
+
+    fn for_stmt_scoped(&mut self) -> Result<Node<Statement>> {
         let begin = self.current.span;
         self.expect(TokenKind::For)?;
         self.expect(TokenKind::OpenParen)?;
@@ -597,14 +1289,14 @@ impl<'src> Parser<'src> {
             }
             // Checkin if declaration contains storage class specifier (static, extern) is done
             // during type checking for better error messages.
-            _ if self.current.kind.is_decl_specifier() => {
+            _ if self.is_decl_specifier(self.current) => {
                 let Node { span, data, .. } = self.declaration()?;
                 let Declaration::Var(decl) = *data else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Parse,
-                        msg: "Expected variable declaration, but found function declaration".into(),
+                    return Err(CompilerError::new(
+                        ErrorKind::Parse,
+                        "Expected variable declaration, but found function declaration",
                         span,
-                    });
+                    ));
                 };
                 ForInit::Decl(self.node(span, decl))
             }
@@ -774,6 +1466,12 @@ impl<'src> Parser<'src> {
         self.expression_precedence(0, "expression")
     }
 
+    /// An expression without a top level comma operator, as used in
+    /// initializers and function arguments.
+    fn assignment_expression(&mut self) -> Result<Node<Expression>> {
+        self.expression_precedence(1, "expression")
+    }
+
     fn expression_precedence(
         &mut self,
         min_precedence: u8,
@@ -781,13 +1479,18 @@ impl<'src> Parser<'src> {
     ) -> Result<Node<Expression>> {
         let mut expr = match self.current.kind {
             TokenKind::Sizeof => self.sizeof()?,
+            TokenKind::Alignof => self.alignof()?,
+            TokenKind::VaStart | TokenKind::VaArg | TokenKind::VaEnd | TokenKind::VaCopy => {
+                self.va_builtin()?
+            }
             TokenKind::StringLiteral => self.string_literal()?,
             TokenKind::IntConstant(_) => self.int_constant()?,
             TokenKind::DoubleConstant => self.double_constant()?,
+            TokenKind::FloatConstant => self.float_constant()?,
             TokenKind::CharLiteral => self.char_literal()?,
             TokenKind::Identifier => {
                 if self.next.kind == TokenKind::OpenParen {
-                    self.function_call()?
+                    self.function_name()?
                 } else {
                     self.var()?
                 }
@@ -795,6 +1498,7 @@ impl<'src> Parser<'src> {
             TokenKind::Star => self.dereference()?,
             TokenKind::Ampersand => self.address_of()?,
             TokenKind::Minus
+            | TokenKind::Plus
             | TokenKind::Tilde
             | TokenKind::Bang
             | TokenKind::PlusPlus
@@ -802,21 +1506,21 @@ impl<'src> Parser<'src> {
             TokenKind::OpenParen => {
                 let begin = self.current.span;
                 self.advance();
-                if self.current.kind.is_type_specifier() {
+                if self.is_type_specifier(self.current) {
                     self.cast_expression(begin)?
                 } else {
                     self.paren_expression(begin)?
                 }
             }
             _ => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: format!(
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    format!(
                         "Expected {error_kind}, but found '{}'",
                         self.current.slice(self.source)
                     ),
-                    span: self.current.span,
-                });
+                    self.current.span,
+                ));
             }
         };
 
@@ -843,6 +1547,22 @@ impl<'src> Parser<'src> {
                 continue;
             }
 
+            if self.current.kind == TokenKind::OpenParen {
+                let precedence = 14;
+                if precedence < min_precedence {
+                    break;
+                }
+                let args = self.arguments()?;
+                expr = self.node(
+                    expr.span,
+                    Expression::FunctionCall {
+                        function: expr,
+                        args,
+                    },
+                );
+                continue;
+            }
+
             if self.matches(TokenKind::Dot) {
                 let precedence = 14;
                 if precedence < min_precedence {
@@ -876,6 +1596,7 @@ impl<'src> Parser<'src> {
             }
 
             let precedence = match self.current.kind {
+                TokenKind::Comma => 0,
                 TokenKind::Equal
                 | TokenKind::PlusEqual
                 | TokenKind::MinusEqual
@@ -921,6 +1642,10 @@ impl<'src> Parser<'src> {
                         else_expr,
                     },
                 )
+            } else if self.matches(TokenKind::Comma) {
+                let left = expr;
+                let right = self.expression_precedence(precedence + 1, "expression")?;
+                self.node(left.span + right.span, Expression::Comma(left, right))
             } else if let Ok(op) = self.assignment_op() {
                 let left = expr;
                 let right = self.expression_precedence(precedence, "expression")?;
@@ -947,18 +1672,35 @@ impl<'src> Parser<'src> {
         let base_ty = self.type_specifier()?;
         let declarator = self.abstract_declarator()?;
         self.expect(TokenKind::CloseParen)?;
-        let target = Self::process_abstract_declaration(base_ty, declarator);
+        let target = self.process_abstract_declaration(base_ty, declarator)?;
+        if self.current.kind == TokenKind::OpenBrace {
+            return self.compound_literal(begin, target);
+        }
         let expr = self.expression_precedence(13, "expression")?;
         Ok(self.node(begin + expr.span, Expression::Cast { target, expr }))
     }
 
+    fn compound_literal(
+        &mut self,
+        begin: Span,
+        type_spec: Node<TypeSpec>,
+    ) -> Result<Node<Expression>> {
+        let init = self.initializer()?;
+        Ok(self.node(
+            begin + init.span,
+            Expression::CompoundLiteral { type_spec, init },
+        ))
+    }
+
     fn abstract_declarator(&mut self) -> Result<Node<AbstractDeclarator>> {
         let begin = self.current.span;
         if self.matches(TokenKind::Star) {
+            let mut qualifiers = Qualifiers::default();
+            while self.type_qualifier(&mut qualifiers) {}
             let declarator = self.abstract_declarator()?;
             Ok(self.node(
                 begin + declarator.span,
-                AbstractDeclarator::Pointer(declarator),
+                AbstractDeclarator::Pointer(qualifiers, declarator),
             ))
         } else if let TokenKind::OpenParen | TokenKind::OpenBracket = self.current.kind {
             self.direct_abstract_declarator()
@@ -971,42 +1713,95 @@ impl<'src> Parser<'src> {
         let mut decl;
         let begin = self.current.span;
         let mut end = begin;
-        if self.matches(TokenKind::OpenParen) {
+        if self.current.kind == TokenKind::OpenParen
+            && let TokenKind::Star | TokenKind::OpenParen | TokenKind::OpenBracket = self.next.kind
+        {
+            self.advance();
             decl = self.abstract_declarator()?;
             end = self.expect(TokenKind::CloseParen)?.span;
         } else {
             decl = self.node(begin, AbstractDeclarator::Base);
         }
-        while self.matches(TokenKind::OpenBracket) {
-            let size = self.parse_array_index()?;
-            end = self.expect(TokenKind::CloseBracket)?.span;
-            decl = self.node(begin + end, AbstractDeclarator::Array(decl, size));
+        // Like for declarators, either a parameter list or array sizes follow
+        if self.matches(TokenKind::OpenParen) {
+            let (params, variadic, close) = self.parse_params()?;
+            end = close;
+            decl = self.node(
+                begin + end,
+                AbstractDeclarator::Function {
+                    params,
+                    variadic,
+                    declarator: decl,
+                },
+            );
+        } else {
+            while self.matches(TokenKind::OpenBracket) {
+                let size = if self.current.kind == TokenKind::CloseBracket {
+                    ArraySize::Unsized
+                } else {
+                    self.parse_array_index()?
+                };
+                end = self.expect(TokenKind::CloseBracket)?.span;
+                decl = self.node(begin + end, AbstractDeclarator::Array(decl, size));
+            }
         }
         Ok(self.node(begin + end, *decl.data))
     }
 
     fn process_abstract_declaration(
+        &mut self,
         base_ty: Node<TypeSpec>,
         declarator: Node<AbstractDeclarator>,
-    ) -> Node<TypeSpec> {
+    ) -> Result<Node<TypeSpec>> {
         match *declarator.data {
-            AbstractDeclarator::Pointer(inner) => {
+            AbstractDeclarator::Pointer(qualifiers, inner) => {
                 let derived_type = Node {
                     id: declarator.id,
                     span: declarator.span,
                     data: Box::new(TypeSpec::Pointer(base_ty)),
                 };
-                Self::process_abstract_declaration(derived_type, inner)
+                let derived_type = self.qualified(derived_type, qualifiers);
+                self.process_abstract_declaration(derived_type, inner)
             }
             AbstractDeclarator::Array(inner, size) => {
                 let derived_type = Node {
                     id: declarator.id,
                     span: declarator.span,
-                    data: Box::new(TypeSpec::Array(base_ty, size)),
+                    data: Box::new(size.array_of(base_ty)),
+                };
+                self.process_abstract_declaration(derived_type, inner)
+            }
+            AbstractDeclarator::Function {
+                params,
+                variadic,
+                declarator: inner,
+            } => {
+                if let AbstractDeclarator::Array(..) | AbstractDeclarator::Function { .. } =
+                    inner.as_ref()
+                {
+                    return Err(CompilerError::new(
+                        ErrorKind::Parse,
+                        "Can't apply additional derivations to a function type",
+                        inner.span,
+                    ));
+                }
+                let mut param_types = Vec::new();
+                for Param { ty, declarator } in params {
+                    param_types.push(self.process_declarator(declarator, ty)?.type_spec);
+                }
+                let function_type = FunctionTypeSpec {
+                    params: param_types,
+                    ret: base_ty,
+                    variadic,
+                };
+                let derived_type = Node {
+                    id: declarator.id,
+                    span: declarator.span,
+                    data: Box::new(TypeSpec::Function(function_type)),
                 };
-                Self::process_abstract_declaration(derived_type, inner)
+                self.process_abstract_declaration(derived_type, inner)
             }
-            AbstractDeclarator::Base => base_ty,
+            AbstractDeclarator::Base => Ok(base_ty),
         }
     }
 
@@ -1048,14 +1843,14 @@ impl<'src> Parser<'src> {
             TokenKind::LessLessEqual => AssignOp::ShiftLeftEqual,
             TokenKind::GreaterGreaterEqual => AssignOp::ShiftRightEqual,
             _ => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: format!(
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    format!(
                         "Expected assignment operator, but found '{}'",
                         self.current.slice(self.source)
                     ),
-                    span: self.current.span,
-                });
+                    self.current.span,
+                ));
             }
         };
         let span = self.current.span;
@@ -1085,14 +1880,14 @@ impl<'src> Parser<'src> {
             TokenKind::PipePipe => BinaryOp::Or,
 
             _ => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: format!(
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    format!(
                         "Expected binary operator, but found '{}'",
                         self.current.slice(self.source)
                     ),
-                    span: self.current.span,
-                });
+                    self.current.span,
+                ));
             }
         };
         let span = self.current.span;
@@ -1103,19 +1898,20 @@ impl<'src> Parser<'src> {
     fn unary_op(&mut self) -> Result<Node<UnaryOp>> {
         let op = match self.current.kind {
             TokenKind::Minus => UnaryOp::Negate,
+            TokenKind::Plus => UnaryOp::Plus,
             TokenKind::Tilde => UnaryOp::Complement,
             TokenKind::Bang => UnaryOp::Not,
             TokenKind::PlusPlus => UnaryOp::Increment,
             TokenKind::MinusMinus => UnaryOp::Decrement,
             _ => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: format!(
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    format!(
                         "Expected prefix unary operator, but found '{}'",
                         self.current.slice(self.source)
                     ),
-                    span: self.current.span,
-                });
+                    self.current.span,
+                ));
             }
         };
         let span = self.current.span;
@@ -1128,14 +1924,14 @@ impl<'src> Parser<'src> {
             TokenKind::PlusPlus => PostfixOp::Increment,
             TokenKind::MinusMinus => PostfixOp::Decrement,
             _ => {
-                return Err(CompilerError {
-                    kind: ErrorKind::Parse,
-                    msg: format!(
+                return Err(CompilerError::new(
+                    ErrorKind::Parse,
+                    format!(
                         "Expected postfix unary operator, but found '{}'",
                         self.current.slice(self.source)
                     ),
-                    span: self.current.span,
-                });
+                    self.current.span,
+                ));
             }
         };
         let span = self.current.span;
@@ -1149,13 +1945,22 @@ impl<'src> Parser<'src> {
         Ok(self.node(token.span, Identifier { symbol }))
     }
 
-    fn function_call(&mut self) -> Result<Node<Expression>> {
+    fn function_name(&mut self) -> Result<Node<Expression>> {
+        // The name of a called function shares its node with the variable
         let name = self.identifier()?;
+        Ok(Node {
+            id: name.id,
+            span: name.span,
+            data: Box::new(Expression::Var(name.data.symbol)),
+        })
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Node<Expression>>> {
         let mut args = Vec::new();
         self.expect(TokenKind::OpenParen)?;
         if self.current.kind != TokenKind::CloseParen {
             loop {
-                args.push(self.expression()?);
+                args.push(self.assignment_expression()?);
                 if self.matches(TokenKind::Comma) {
                     continue;
                 }
@@ -1163,7 +1968,7 @@ impl<'src> Parser<'src> {
             }
         }
         self.expect(TokenKind::CloseParen)?;
-        Ok(self.node(name.span, Expression::FunctionCall { name, args }))
+        Ok(args)
     }
 
     fn var(&mut self) -> Result<Node<Expression>> {
@@ -1174,23 +1979,43 @@ impl<'src> Parser<'src> {
     fn double_constant(&mut self) -> Result<Node<Expression>> {
         let token = self.expect(TokenKind::DoubleConstant)?;
         let lexeme = token.slice(self.source);
-        let value: f64 = lexeme.parse().map_err(|e| CompilerError {
-            kind: ErrorKind::Parse,
-            msg: format!("Integer constant out of range: {e:?}"),
-            span: token.span,
+        let value: f64 = lexeme.parse().map_err(|e| {
+            CompilerError::new(
+                ErrorKind::Parse,
+                format!("Integer constant out of range: {e:?}"),
+                token.span,
+            )
         })?;
         Ok(self.node(token.span, Expression::Constant(Constant::Double(value))))
     }
 
+    fn float_constant(&mut self) -> Result<Node<Expression>> {
+        let token = self.expect(TokenKind::FloatConstant)?;
+        let lexeme = token.slice(self.source).trim_end_matches(['f', 'F']);
+        let value: f32 = lexeme.parse().map_err(|e| {
+            CompilerError::new(
+                ErrorKind::Parse,
+                format!("Float constant out of range: {e:?}"),
+                token.span,
+            )
+        })?;
+        Ok(self.node(token.span, Expression::Constant(Constant::Float(value))))
+    }
+
     fn sizeof(&mut self) -> Result<Node<Expression>> {
         let begin = self.current.span;
         self.expect(TokenKind::Sizeof)?;
-        if self.current.kind == TokenKind::OpenParen && self.next.kind.is_type_specifier() {
+        if self.current.kind == TokenKind::OpenParen && self.is_type_specifier(self.next) {
+            let open = self.current.span;
             self.advance(); // consume open paren
             let base_ty = self.type_specifier()?;
             let declarator = self.abstract_declarator()?;
-            let target = Self::process_abstract_declaration(base_ty, declarator);
+            let target = self.process_abstract_declaration(base_ty, declarator)?;
             let end = self.expect(TokenKind::CloseParen)?.span;
+            if self.current.kind == TokenKind::OpenBrace {
+                let literal = self.compound_literal(open, target)?;
+                return Ok(self.node(begin + literal.span, Expression::SizeOfExpr(literal)));
+            }
             Ok(self.node(begin + end, Expression::SizeOfType(target)))
         } else {
             let expr = self.expression_precedence(13, "expression")?;
@@ -1199,17 +2024,53 @@ impl<'src> Parser<'src> {
         }
     }
 
+    fn alignof(&mut self) -> Result<Node<Expression>> {
+        let begin = self.expect(TokenKind::Alignof)?.span;
+        self.expect(TokenKind::OpenParen)?;
+        let base_ty = self.type_specifier()?;
+        let declarator = self.abstract_declarator()?;
+        let target = self.process_abstract_declaration(base_ty, declarator)?;
+        let end = self.expect(TokenKind::CloseParen)?.span;
+        Ok(self.node(begin + end, Expression::AlignOfType(target)))
+    }
+
+    fn va_builtin(&mut self) -> Result<Node<Expression>> {
+        let builtin = self.current;
+        self.advance();
+        self.expect(TokenKind::OpenParen)?;
+        let ap = self.assignment_expression()?;
+        let expr = match builtin.kind {
+            TokenKind::VaStart => {
+                self.expect(TokenKind::Comma)?;
+                Expression::VaStart(ap, self.assignment_expression()?)
+            }
+            TokenKind::VaArg => {
+                self.expect(TokenKind::Comma)?;
+                let base_ty = self.type_specifier()?;
+                let declarator = self.abstract_declarator()?;
+                Expression::VaArg(ap, self.process_abstract_declaration(base_ty, declarator)?)
+            }
+            TokenKind::VaCopy => {
+                self.expect(TokenKind::Comma)?;
+                Expression::VaCopy(ap, self.assignment_expression()?)
+            }
+            _ => Expression::VaEnd(ap),
+        };
+        let end = self.expect(TokenKind::CloseParen)?.span;
+        Ok(self.node(builtin.span + end, expr))
+    }
+
     fn char_literal(&mut self) -> Result<Node<Expression>> {
         let token = self.expect(TokenKind::CharLiteral)?;
         let lexeme = token.slice(self.source);
         let trimmed = &lexeme[1..lexeme.len() - 1];
         let mut chars = trimmed.chars();
         let Some(c) = self.next_character(&mut chars)? else {
-            return Err(CompilerError {
-                kind: ErrorKind::Parse,
-                msg: "Invalid escape sequence".to_string(),
-                span: self.current.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                "Invalid escape sequence",
+                self.current.span,
+            ));
         };
         Ok(self.node(token.span, Expression::Constant(Constant::Int(c as i32))))
     }
@@ -1247,18 +2108,18 @@ impl<'src> Parser<'src> {
                     Some('t') => '\t',
                     Some('v') => '\x0B',
                     Some(escape) => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Parse,
-                            msg: format!("Invalid escape sequence: '\\{escape}'"),
-                            span: self.current.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Parse,
+                            format!("Invalid escape sequence: '\\{escape}'"),
+                            self.current.span,
+                        ));
                     }
                     _ => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Parse,
-                            msg: "Invalid escape sequence".to_string(),
-                            span: self.current.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Parse,
+                            "Invalid escape sequence",
+                            self.current.span,
+                        ));
                     }
                 }
             } else {
@@ -1273,27 +2134,26 @@ impl<'src> Parser<'src> {
     fn int_constant(&mut self) -> Result<Node<Expression>> {
         let token = self.current;
         let TokenKind::IntConstant(kind) = token.kind else {
-            return Err(CompilerError {
-                kind: ErrorKind::Parse,
-                msg: format!(
+            return Err(CompilerError::new(
+                ErrorKind::Parse,
+                format!(
                     "Expected constant but found '{}'",
                     self.current.slice(self.source)
                 ),
-                span: token.span,
-            });
+                token.span,
+            ));
         };
         self.advance();
         let lexeme = token.slice(self.source);
-        let string_value = lexeme.strip_suffix(['l', 'L']).unwrap_or(lexeme);
-        let string_value = string_value
-            .strip_suffix(['u', 'U'])
-            .unwrap_or(string_value);
+        let string_value = lexeme.trim_end_matches(['l', 'L', 'u', 'U']);
 
         // Constant tokens don't have a sign, so u64 is the correct type
-        let value: u64 = string_value.parse().map_err(|e| CompilerError {
-            kind: ErrorKind::Parse,
-            msg: format!("Integer constant out of range: {e:?}"),
-            span: token.span,
+        let value: u64 = string_value.parse().map_err(|e| {
+            CompilerError::new(
+                ErrorKind::Parse,
+                format!("Integer constant out of range: {e:?}"),
+                token.span,
+            )
         })?;
 
         let constant = match kind {
@@ -1334,11 +2194,11 @@ impl<'src> Parser<'src> {
                 &format!("'{}'", token.slice(self.source))
             };
 
-            Err(CompilerError {
-                kind: ErrorKind::Parse,
-                msg: format!("Expected {expected}, but found {found}"),
-                span: token.span,
-            })
+            Err(CompilerError::new(
+                ErrorKind::Parse,
+                format!("Expected {expected}, but found {found}"),
+                token.span,
+            ))
         }
     }
 
@@ -1352,6 +2212,18 @@ impl<'src> Parser<'src> {
     }
 }
 
-pub fn parse(source: &str) -> Result<Node<Program>> {
-    Parser::new(source).program()
+/// Parses a whole translation unit, recovering from syntax errors to report as
+/// many of them as possible. At most `error_limit` errors are reported, zero
+/// means no limit.
+pub fn parse(
+    source: &str,
+    error_limit: usize,
+) -> std::result::Result<Node<Program>, Vec<CompilerError>> {
+    let mut parser = Parser::new(source, error_limit);
+    let program = parser.program();
+    if parser.errors.is_empty() {
+        Ok(program)
+    } else {
+        Err(parser.errors)
+    }
 }
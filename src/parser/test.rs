@@ -1,15 +1,23 @@
-use crate::parser::parse;
-use crate::pretty::{annotate, dedent, dump_ast, remove_annotation};
+use crate::parser::{parse, DEFAULT_ERROR_LIMIT};
+use crate::pretty::{annotate, dedent, diagnostics, dump_ast, remove_annotation};
 
 fn assert_error(expected_annotated: &str) {
     let clean_source = remove_annotation(expected_annotated);
-    let Err(error) = parse(&clean_source) else {
+    let Err(errors) = parse(&clean_source, DEFAULT_ERROR_LIMIT) else {
         panic!("No error produced!")
     };
-    let actual_annotated = annotate(&clean_source, &error);
+    let actual_annotated = annotate(&clean_source, &errors[0]);
     assert_eq!(actual_annotated, expected_annotated);
 }
 
+fn assert_errors(src: &str, error_limit: usize, expected: &[&str]) {
+    let src = dedent(src);
+    let Err(errors) = parse(&src, error_limit) else {
+        panic!("No error produced!")
+    };
+    assert_eq!(diagnostics(&src, &errors), expected);
+}
+
 fn assert_parse(src: &str, expected: &str) {
     assert_eq!(dump_ast(src), dedent(expected));
 }
@@ -32,3 +40,19 @@ mod test_chapter_15;
 mod test_chapter_16;
 mod test_chapter_17;
 mod test_chapter_18;
+mod test_recovery;
+mod test_enum;
+mod test_typedef;
+mod test_integer_types;
+mod test_float;
+mod test_qualifiers;
+mod test_bit_fields;
+mod test_designated_initializers;
+mod test_compound_literals;
+mod test_variable_length_arrays;
+mod test_expressions;
+mod test_function_pointers;
+mod test_variadic;
+mod test_attributes;
+mod test_thread_local;
+mod test_bool;
@@ -0,0 +1,202 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_function_specifiers() {
+    let src = r#"
+        static inline int twice(int x) {
+            return 2 * x;
+        }
+        _Noreturn void fail(void);
+        __inline__ int __attribute__((noreturn)) stop(void);
+    "#;
+    let expected = r#"
+        Program
+            ├── Function [static twice]
+            │   ├── Parameters
+            │   │   ╰── Param
+            │   │       ├── Name
+            │   │       │   ╰── x
+            │   │       ╰── Type
+            │   │           ╰── Int
+            │   ╰── Body
+            │       ╰── Return
+            │           ╰── <14>  [*]
+            │               ├── <10> Constant Int [2]
+            │               ╰── <13> Var [x]
+            ├── Function [fail]
+            │   ╰── Attributes
+            │       ╰── Noreturn
+            ╰── Function [stop]
+                ╰── Attributes
+                    ╰── Noreturn
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_variable_attributes() {
+    let src = r#"
+        __attribute__((aligned(16), section(".data.x"))) int x = 1;
+        long y __attribute__((weak)) __attribute__((aligned));
+        int main(void) {
+            int unused_var __attribute__((__unused__));
+            return 0;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── x
+            │   ├── Type
+            │   │   ╰── Int
+            │   ├── Initializer
+            │   │   ╰── <8> Constant Int [1]
+            │   ╰── Attributes
+            │       ├── Aligned
+            │       │   ╰── <1> Constant Int [16]
+            │       ╰── Section ".data.x"
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── y
+            │   ├── Type
+            │   │   ╰── Long
+            │   ╰── Attributes
+            │       ├── Weak
+            │       ╰── Aligned
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── unused_var
+                    │   ├── Type
+                    │   │   ╰── Int
+                    │   ╰── Attributes
+                    │       ╰── Unused
+                    ╰── Return
+                        ╰── <27> Constant Int [0]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_struct_attributes() {
+    let src = r#"
+        struct __attribute__((packed)) s {
+            char c;
+            int i __attribute__((aligned(2)));
+        };
+        struct t {
+            char c;
+            long l;
+        } __attribute__((__packed__));
+        typedef struct __attribute__((packed)) u {
+            char c;
+        } u_t;
+    "#;
+    let expected = r#"
+        Program
+            ├── Struct [s]
+            │   ├── Field
+            │   │   ├── Name
+            │   │   │   ╰── c
+            │   │   ╰── Type
+            │   │       ╰── Char
+            │   ├── Field
+            │   │   ├── Name
+            │   │   │   ╰── i
+            │   │   ├── Type
+            │   │   │   ╰── Int
+            │   │   ╰── Attributes
+            │   │       ╰── Aligned
+            │   │           ╰── <10> Constant Int [2]
+            │   ╰── Attributes
+            │       ╰── Packed
+            ├── Struct [t]
+            │   ├── Field
+            │   │   ├── Name
+            │   │   │   ╰── c
+            │   │   ╰── Type
+            │   │       ╰── Char
+            │   ├── Field
+            │   │   ├── Name
+            │   │   │   ╰── l
+            │   │   ╰── Type
+            │   │       ╰── Long
+            │   ╰── Attributes
+            │       ╰── Packed
+            ├── Struct [u]
+            │   ├── Field
+            │   │   ├── Name
+            │   │   │   ╰── c
+            │   │   ╰── Type
+            │   │       ╰── Char
+            │   ╰── Attributes
+            │       ╰── Packed
+            ╰── TypeDef
+                ├── Name
+                │   ╰── u_t
+                ╰── Type
+                    ╰── Struct [u]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_unsupported_attributes_are_ignored() {
+    let src = r#"
+        int printf(const char *format, ...) __attribute__((format(printf, 1, 2), nonnull((1))));
+        int f(int x __attribute__((unused))) __attribute__((const, ));
+    "#;
+    let expected = r#"
+        Program
+            ├── Function [printf]
+            │   ╰── Parameters
+            │       ├── Param
+            │       │   ├── Name
+            │       │   │   ╰── format
+            │       │   ╰── Type
+            │       │       ╰── Pointer
+            │       │           ╰── Qualified [const]
+            │       │               ╰── Char
+            │       ╰── ...
+            ╰── Function [f]
+                ╰── Parameters
+                    ╰── Param
+                        ├── Name
+                        │   ╰── x
+                        ╰── Type
+                            ╰── Int
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_attribute_arguments() {
+    assert_error(
+        r#"
+        int x __attribute__((packed(1)));
+                                 //^ Wrong number of arguments specified for 'packed' attribute
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_section_name() {
+    assert_error(
+        r#"
+        int x __attribute__((section(data)));
+                                   //^^^^ Expected string literal, but found 'data'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_unclosed_attribute() {
+    assert_error(
+        r#"
+        int x __attribute__((aligned(8));
+                                      //^ Expected ')', but found ';'
+    "#,
+    );
+}
@@ -0,0 +1,50 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_bit_field_declarations() {
+    let src = r#"
+        struct flags {
+            unsigned int ready : 1;
+            int : 0;
+            long count : 2 * 8;
+        };
+    "#;
+    let expected = r#"
+        Program
+            ╰── Struct [flags]
+                ├── Field
+                │   ├── Name
+                │   │   ╰── ready
+                │   ├── Type
+                │   │   ╰── Unsigned Int
+                │   ╰── Width
+                │       ╰── <5> Constant Int [1]
+                ├── Field
+                │   ├── Type
+                │   │   ╰── Int
+                │   ╰── Width
+                │       ╰── <8> Constant Int [0]
+                ╰── Field
+                    ├── Name
+                    │   ╰── count
+                    ├── Type
+                    │   ╰── Long
+                    ╰── Width
+                        ╰── <16>  [*]
+                            ├── <13> Constant Int [2]
+                            ╰── <15> Constant Int [8]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_bit_field_missing_width() {
+    assert_error(
+        r#"
+        struct flags {
+            unsigned int ready : ;
+                               //^ Expected expression, but found ';'
+        };
+    "#,
+    );
+}
@@ -0,0 +1,47 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_bool_declarations() {
+    let src = r#"
+        _Bool flag = 1;
+        _Bool negate(_Bool b) {
+            return (_Bool)!b;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── flag
+            │   ├── Type
+            │   │   ╰── Bool
+            │   ╰── Initializer
+            │       ╰── <4> Constant Int [1]
+            ╰── Function [negate]
+                ├── Parameters
+                │   ╰── Param
+                │       ├── Name
+                │       │   ╰── b
+                │       ╰── Type
+                │           ╰── Bool
+                ╰── Body
+                    ╰── Return
+                        ╰── <21> Cast
+                            ├── Target
+                            │   ╰── Bool
+                            ╰── Expression
+                                ╰── <20> Unary [!]
+                                    ╰── <19> Var [b]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_unsigned_bool() {
+    assert_error(
+        r#"
+        unsigned _Bool b;
+      //^^^^^^^^^^^^^^ Invalid type specifier
+    "#,
+    );
+}
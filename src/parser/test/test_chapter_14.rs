@@ -70,19 +70,6 @@ fn test_invalid_declarations_extra_credit_deref_label() {
     assert_parse(src, expected);
 }
 
-#[test]
-fn test_invalid_parse_abstract_function_declarator() {
-    assert_error(
-        r#"
-        int main(void) {
-            (int (void)) 0;
-                //^^^^ Expected ')', but found 'void'
-            return 0;
-        }
-    "#,
-    );
-}
-
 #[test]
 fn test_invalid_parse_cast_to_declarator() {
     assert_error(
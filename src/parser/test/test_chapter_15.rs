@@ -152,20 +152,6 @@ fn test_invalid_parse_mismatched_subscript() {
     );
 }
 
-#[test]
-fn test_invalid_parse_negative_array_dimension() {
-    assert_error(
-        r#"
-        int main(void)
-        {
-            int arr[-3];
-                  //^^ Array size should be a constant
-            return 0;
-        }
-    "#,
-    );
-}
-
 #[test]
 fn test_invalid_parse_parenthesized_array_of_functions() {
     assert_error(
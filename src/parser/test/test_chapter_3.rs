@@ -36,18 +36,6 @@ fn test_invalid_parse_imbalanced_paren() {
     );
 }
 
-#[test]
-fn test_invalid_parse_malformed_paren() {
-    assert_error(
-        r#"
-        int main(void) {
-            return 2 (- 3);
-                   //^ Expected ';', but found '('
-        }
-    "#,
-    );
-}
-
 #[test]
 fn test_invalid_parse_misplaced_semicolon() {
     assert_error(
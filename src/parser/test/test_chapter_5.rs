@@ -154,7 +154,7 @@ fn test_invalid_parse_malformed_increment() {
         int main(void) {
             int a = 0;
             a + +;
-              //^ Expected expression, but found '+'
+               //^ Expected expression, but found ';'
             return a;
         }
     "#,
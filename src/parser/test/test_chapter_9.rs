@@ -464,18 +464,6 @@ fn test_invalid_labels_extra_credit_goto_function() {
     assert_parse(src, expected);
 }
 
-#[test]
-fn test_invalid_parse_call_non_identifier() {
-    assert_error(
-        r#"
-        int main(void) {
-            return 1();
-                  //^ Expected ';', but found '('
-        }
-    "#,
-    );
-}
-
 #[test]
 fn test_invalid_parse_decl_wrong_closing_delim() {
     assert_error(
@@ -0,0 +1,78 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_compound_literal() {
+    let src = r#"
+        int main(void) {
+            return (struct point){.x = 1, 2}.y + sizeof (int[2]){3};
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ╰── Return
+                        ╰── <30>  [+]
+                            ├── <18> Dot
+                            │   ├── <16> CompoundLiteral
+                            │   │   ├── Type
+                            │   │   │   ╰── Struct [point]
+                            │   │   ╰── Initializer
+                            │   │       ╰── Compound
+                            │   │           ├── Designated
+                            │   │           │   ├── Field
+                            │   │           │   │   ╰── x
+                            │   │           │   ╰── <11> Constant Int [1]
+                            │   │           ╰── <13> Constant Int [2]
+                            │   ╰── y
+                            ╰── <29> SizeOfExpr
+                                ╰── <28> CompoundLiteral
+                                    ├── Type
+                                    │   ╰── Array
+                                    │       ├── 2
+                                    │       ╰── Int
+                                    ╰── Initializer
+                                        ╰── Compound
+                                            ╰── <25> Constant Int [3]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_unsized_array_compound_literal() {
+    let src = r#"
+        int *p = (int[]){1, 2, 3};
+    "#;
+    let expected = r#"
+        Program
+            ╰── VarDeclaration
+                ├── Name
+                │   ╰── p
+                ├── Type
+                │   ╰── Pointer
+                │       ╰── Int
+                ╰── Initializer
+                    ╰── <17> CompoundLiteral
+                        ├── Type
+                        │   ╰── UnsizedArray
+                        │       ╰── Int
+                        ╰── Initializer
+                            ╰── Compound
+                                ├── <10> Constant Int [1]
+                                ├── <12> Constant Int [2]
+                                ╰── <14> Constant Int [3]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_compound_literal_missing_brace() {
+    assert_error(
+        r#"
+        int main(void) {
+            return (struct point){1, 2;
+                                    //^ Expected '}', but found ';'
+        }
+    "#,
+    );
+}
@@ -0,0 +1,45 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_designated_initializer() {
+    let src = r#"
+        int arr[3][2] = {[1][0] = 1, 2, .x = {3}};
+    "#;
+    let expected = r#"
+        Program
+            ╰── VarDeclaration
+                ├── Name
+                │   ╰── arr
+                ├── Type
+                │   ╰── Array
+                │       ├── 3
+                │       ╰── Array
+                │           ├── 2
+                │           ╰── Int
+                ╰── Initializer
+                    ╰── Compound
+                        ├── Designated
+                        │   ├── Index
+                        │   │   ╰── <10> Constant Int [1]
+                        │   ├── Index
+                        │   │   ╰── <12> Constant Int [0]
+                        │   ╰── <14> Constant Int [1]
+                        ├── <16> Constant Int [2]
+                        ╰── Designated
+                            ├── Field
+                            │   ╰── x
+                            ╰── Compound
+                                ╰── <20> Constant Int [3]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_designator_missing_equal() {
+    assert_error(
+        r#"
+        int arr[3] = {[1] 2};
+                        //^ Expected '=', but found '2'
+    "#,
+    );
+}
@@ -0,0 +1,61 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_enum_declarations() {
+    let src = r#"
+        enum color { RED, GREEN = 5, };
+        typedef enum { SMALL = -1 } size;
+        int main(void) {
+            enum color c = RED;
+            return c;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── Enum [color]
+            │   ├── Enumerator [RED]
+            │   ╰── Enumerator [GREEN]
+            │       ╰── <5> Constant Int [5]
+            ├── Enum
+            │   ╰── Enumerator [SMALL]
+            │       ╰── <13> Unary [-]
+            │           ╰── <12> Constant Int [1]
+            ├── TypeDef
+            │   ├── Name
+            │   │   ╰── size
+            │   ╰── Type
+            │       ╰── Enum
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── c
+                    │   ├── Type
+                    │   │   ╰── Enum [color]
+                    │   ╰── Initializer
+                    │       ╰── <30> Var [RED]
+                    ╰── Return
+                        ╰── <34> Var [c]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_empty_enum() {
+    assert_error(
+        r#"
+        enum e {};
+              //^ Expected identifier, but found '}'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_enum_without_declarator() {
+    assert_error(
+        r#"
+        enum x;
+            //^ Expected identifier, but found ';'
+    "#,
+    );
+}
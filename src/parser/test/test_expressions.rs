@@ -0,0 +1,127 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_comma_operator() {
+    let src = r#"
+        int main(void) {
+            for (i = 0, j = 1; i < j; i++, j--)
+                f(i, (i, j));
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ╰── For
+                        ├── Init
+                        │   ╰── <16> Comma
+                        │       ├── <10> Assign [=]
+                        │       │   ├── <7> Var [i]
+                        │       │   ╰── <9> Constant Int [0]
+                        │       ╰── <15> Assign [=]
+                        │           ├── <12> Var [j]
+                        │           ╰── <14> Constant Int [1]
+                        ├── Condition
+                        │   ╰── <22>  [<]
+                        │       ├── <18> Var [i]
+                        │       ╰── <21> Var [j]
+                        ├── Condition
+                        │   ╰── <31> Comma
+                        │       ├── <26> Postfix [++]
+                        │       │   ╰── <24> Var [i]
+                        │       ╰── <30> Postfix [--]
+                        │           ╰── <28> Var [j]
+                        ╰── <41> FunctionCall [f]
+                            ├── <34> Var [i]
+                            ╰── <40> Comma
+                                ├── <36> Var [i]
+                                ╰── <38> Var [j]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_alignof_and_unary_plus() {
+    let src = r#"
+        int main(void) {
+            return +_Alignof(long) + +1;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ╰── Return
+                        ╰── <15>  [+]
+                            ├── <10> Unary [+]
+                            │   ╰── <9> AlignOfType
+                            │       ╰── Long
+                            ╰── <14> Unary [+]
+                                ╰── <13> Constant Int [1]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_static_assert() {
+    let src = r#"
+        _Static_assert(sizeof(int) == 4, "int" " size");
+        int main(void) {
+            _Static_assert(1, "block scope");
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── StaticAssert "int size"
+            │   ╰── <6>  [==]
+            │       ├── <3> SizeOfType
+            │       │   ╰── Int
+            │       ╰── <5> Constant Int [4]
+            ╰── Function [main]
+                ╰── Body
+                    ╰── StaticAssert "block scope"
+                        ╰── <14> Constant Int [1]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_static_assert_in_member_list() {
+    let src = r#"
+        struct s {
+            int a;
+            _Static_assert(sizeof(int) == 4, "int size");
+            long b;
+        };
+    "#;
+    let expected = r#"
+        Program
+            ╰── Struct [s]
+                ├── Field
+                │   ├── Name
+                │   │   ╰── a
+                │   ╰── Type
+                │       ╰── Int
+                ├── Field
+                │   ├── Name
+                │   │   ╰── b
+                │   ╰── Type
+                │       ╰── Long
+                ╰── StaticAssert "int size"
+                    ╰── <11>  [==]
+                        ├── <8> SizeOfType
+                        │   ╰── Int
+                        ╰── <10> Constant Int [4]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_static_assert_without_message() {
+    assert_error(
+        r#"
+        _Static_assert(1, 2);
+                        //^ Expected string literal, but found '2'
+    "#,
+    );
+}
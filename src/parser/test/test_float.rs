@@ -0,0 +1,66 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_float_declarations_and_constants() {
+    let src = r#"
+        float a = 1.5f;
+        float scale(float x, double y) {
+            return (float) y * x + 2.f;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── a
+            │   ├── Type
+            │   │   ╰── Float
+            │   ╰── Initializer
+            │       ╰── <4> Constant Float [+1.5e0]
+            ╰── Function [scale]
+                ├── Parameters
+                │   ├── Param
+                │   │   ├── Name
+                │   │   │   ╰── x
+                │   │   ╰── Type
+                │   │       ╰── Float
+                │   ╰── Param
+                │       ├── Name
+                │       │   ╰── y
+                │       ╰── Type
+                │           ╰── Double
+                ╰── Body
+                    ╰── Return
+                        ╰── <29>  [+]
+                            ├── <26>  [*]
+                            │   ├── <22> Cast
+                            │   │   ├── Target
+                            │   │   │   ╰── Float
+                            │   │   ╰── Expression
+                            │   │       ╰── <21> Var [y]
+                            │   ╰── <25> Var [x]
+                            ╰── <28> Constant Float [+2e0]    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_long_float() {
+    assert_error(
+        r#"
+        long float x;
+      //^^^^^^^^^^ Invalid type specifier
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_float_double() {
+    assert_error(
+        r#"
+        int main(void) {
+            return (float double) 1;
+                  //^^^^^^^^^^^^ Invalid type specifier
+        }
+    "#,
+    );
+}
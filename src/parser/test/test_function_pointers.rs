@@ -0,0 +1,196 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_function_pointer_declarations_and_calls() {
+    let src = r#"
+        int apply(int (*f)(int x), int v) {
+            int (*fps[2])(int x);
+            fps[0] = f;
+            return (*f)(v) + fps[0](v);
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [apply]
+                ├── Parameters
+                │   ├── Param
+                │   │   ├── Name
+                │   │   │   ╰── f
+                │   │   ╰── Type
+                │   │       ╰── Pointer
+                │   │           ╰── FunctionType
+                │   │               ├── Return
+                │   │               │   ╰── Int
+                │   │               ╰── Params
+                │   │                   ╰── Int
+                │   ╰── Param
+                │       ├── Name
+                │       │   ╰── v
+                │       ╰── Type
+                │           ╰── Int
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── fps
+                    │   ╰── Type
+                    │       ╰── Array
+                    │           ├── 2
+                    │           ╰── Pointer
+                    │               ╰── FunctionType
+                    │                   ├── Return
+                    │                   │   ╰── Int
+                    │                   ╰── Params
+                    │                       ╰── Int
+                    ├── <42> Assign [=]
+                    │   ├── <38> Subscript
+                    │   │   ├── <36> Var [fps]
+                    │   │   ╰── <37> Constant Int [0]
+                    │   ╰── <41> Var [f]
+                    ╰── Return
+                        ╰── <59>  [+]
+                            ├── <50> FunctionCall
+                            │   ├── Function
+                            │   │   ╰── <47> Dereference
+                            │   │       ╰── <45> Var [f]
+                            │   ╰── Arguments
+                            │       ╰── <49> Var [v]
+                            ╰── <58> FunctionCall
+                                ├── Function
+                                │   ╰── <55> Subscript
+                                │       ├── <53> Var [fps]
+                                │       ╰── <54> Constant Int [0]
+                                ╰── Arguments
+                                    ╰── <57> Var [v]    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_function_returning_function_pointer() {
+    let src = r#"
+        int (*pick(int i))(int a, int b);
+        int main(void) {
+            return pick(1)(2, 3);
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── Function [pick]
+            │   ╰── Parameters
+            │       ╰── Param
+            │           ├── Name
+            │           │   ╰── i
+            │           ╰── Type
+            │               ╰── Int
+            ╰── Function [main]
+                ╰── Body
+                    ╰── Return
+                        ╰── <31> FunctionCall
+                            ├── Function
+                            │   ╰── <28> FunctionCall [pick]
+                            │       ╰── <27> Constant Int [1]
+                            ╰── Arguments
+                                ├── <29> Constant Int [2]
+                                ╰── <30> Constant Int [3]    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_function_returning_function() {
+    assert_error(
+        r#"
+        int (f(void))(void);
+          //^^^^^^^^^ Can't apply additional derivations to a function type
+    "#,
+    );
+}
+
+#[test]
+fn test_abstract_function_declarators() {
+    let src = r#"
+        int sq(int);
+        long call(void *p, int (*)(int));
+        long call(void *p, int (*f)(int)) {
+            int (*g)(int) = (int (*)(int a))p;
+            return sizeof(int (*)(int)) + f(1) + g(2);
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── Function [sq]
+            │   ╰── Parameters
+            │       ╰── Param
+            │           ╰── Type
+            │               ╰── Int
+            ├── Function [call]
+            │   ╰── Parameters
+            │       ├── Param
+            │       │   ├── Name
+            │       │   │   ╰── p
+            │       │   ╰── Type
+            │       │       ╰── Pointer
+            │       │           ╰── Void
+            │       ╰── Param
+            │           ╰── Type
+            │               ╰── Pointer
+            │                   ╰── FunctionType
+            │                       ├── Return
+            │                       │   ╰── Int
+            │                       ╰── Params
+            │                           ╰── Int
+            ╰── Function [call]
+                ├── Parameters
+                │   ├── Param
+                │   │   ├── Name
+                │   │   │   ╰── p
+                │   │   ╰── Type
+                │   │       ╰── Pointer
+                │   │           ╰── Void
+                │   ╰── Param
+                │       ├── Name
+                │       │   ╰── f
+                │       ╰── Type
+                │           ╰── Pointer
+                │               ╰── FunctionType
+                │                   ├── Return
+                │                   │   ╰── Int
+                │                   ╰── Params
+                │                       ╰── Int
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── g
+                    │   ├── Type
+                    │   │   ╰── Pointer
+                    │   │       ╰── FunctionType
+                    │   │           ├── Return
+                    │   │           │   ╰── Int
+                    │   │           ╰── Params
+                    │   │               ╰── Int
+                    │   ╰── Initializer
+                    │       ╰── <74> Cast
+                    │           ├── Target
+                    │           │   ╰── Pointer
+                    │           │       ╰── FunctionType
+                    │           │           ├── Return
+                    │           │           │   ╰── Int
+                    │           │           ╰── Params
+                    │           │               ╰── Int
+                    │           ╰── Expression
+                    │               ╰── <73> Var [p]
+                    ╰── Return
+                        ╰── <95>  [+]
+                            ├── <90>  [+]
+                            │   ├── <85> SizeOfType
+                            │   │   ╰── Pointer
+                            │   │       ╰── FunctionType
+                            │   │           ├── Return
+                            │   │           │   ╰── Int
+                            │   │           ╰── Params
+                            │   │               ╰── Int
+                            │   ╰── <89> FunctionCall [f]
+                            │       ╰── <88> Constant Int [1]
+                            ╰── <94> FunctionCall [g]
+                                ╰── <93> Constant Int [2]
+    "#;
+    assert_parse(src, expected);
+}
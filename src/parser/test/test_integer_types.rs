@@ -0,0 +1,167 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_short_and_long_long_specifiers() {
+    let src = r#"
+        short a;
+        short int b;
+        signed short c;
+        int short signed d;
+        unsigned short e;
+        short unsigned int f;
+        long long g;
+        long int long h;
+        signed long long i;
+        unsigned long long j;
+        long unsigned long int k;
+    "#;
+    let expected = r#"
+        Program
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── a
+            │   ╰── Type
+            │       ╰── Short
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── b
+            │   ╰── Type
+            │       ╰── Short
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── c
+            │   ╰── Type
+            │       ╰── Short
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── d
+            │   ╰── Type
+            │       ╰── Short
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── e
+            │   ╰── Type
+            │       ╰── Unsigned Short
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── f
+            │   ╰── Type
+            │       ╰── Unsigned Short
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── g
+            │   ╰── Type
+            │       ╰── Long
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── h
+            │   ╰── Type
+            │       ╰── Long
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── i
+            │   ╰── Type
+            │       ╰── Long
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── j
+            │   ╰── Type
+            │       ╰── Unsigned Long
+            ╰── VarDeclaration
+                ├── Name
+                │   ╰── k
+                ╰── Type
+                    ╰── Unsigned Long
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_long_long_suffix_constants() {
+    let src = r#"
+        long long a = 1ll;
+        unsigned long long b = 2ULL;
+        unsigned long long c = 3llu;
+    "#;
+    let expected = r#"
+        Program
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── a
+            │   ├── Type
+            │   │   ╰── Long
+            │   ╰── Initializer
+            │       ╰── <4> Constant Long [1]
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── b
+            │   ├── Type
+            │   │   ╰── Unsigned Long
+            │   ╰── Initializer
+            │       ╰── <10> Constant ULong [2]
+            ╰── VarDeclaration
+                ├── Name
+                │   ╰── c
+                ├── Type
+                │   ╰── Unsigned Long
+                ╰── Initializer
+                    ╰── <16> Constant ULong [3]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_short_long() {
+    assert_error(
+        r#"
+        short long x;
+      //^^^^^^^^^^ Invalid type specifier
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_short_char() {
+    assert_error(
+        r#"
+        int main(void) {
+            unsigned short char c = 0;
+          //^^^^^^^^^^^^^^^^^^^ Invalid type specifier
+            return c;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_long_long_long() {
+    assert_error(
+        r#"
+        long long long x;
+      //^^^^^^^^^^^^^^ Invalid type specifier
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_short_double() {
+    assert_error(
+        r#"
+        int main(void) {
+            return (short double) 1;
+                  //^^^^^^^^^^^^ Invalid type specifier
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_struct_with_int() {
+    assert_error(
+        r#"
+        struct s { int a; };
+        int struct s x;
+      //^^^^^^^^^^ Invalid type specifier
+    "#,
+    );
+}
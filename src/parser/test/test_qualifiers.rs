@@ -0,0 +1,78 @@
+use super::assert_parse;
+
+#[test]
+fn test_qualified_declarations() {
+    let src = r#"
+        int main(void) {
+            const char *const volatile p = 0;
+            volatile int const x = 1;
+            return (const int *) p == &x;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── p
+                    │   ├── Type
+                    │   │   ╰── Qualified [const volatile]
+                    │   │       ╰── Pointer
+                    │   │           ╰── Qualified [const]
+                    │   │               ╰── Char
+                    │   ╰── Initializer
+                    │       ╰── <13> Constant Int [0]
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── x
+                    │   ├── Type
+                    │   │   ╰── Qualified [const volatile]
+                    │   │       ╰── Int
+                    │   ╰── Initializer
+                    │       ╰── <20> Constant Int [1]
+                    ╰── Return
+                        ╰── <34>  [==]
+                            ├── <29> Cast
+                            │   ├── Target
+                            │   │   ╰── Pointer
+                            │   │       ╰── Qualified [const]
+                            │   │           ╰── Int
+                            │   ╰── Expression
+                            │       ╰── <28> Var [p]
+                            ╰── <33> AddressOf
+                                ╰── <32> Var [x]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_qualified_parameters() {
+    let src = r#"
+        struct s;
+        int f(const struct s *s, int *const values[3]);
+    "#;
+    let expected = r#"
+        Program
+            ├── Struct [s]
+            ╰── Function [f]
+                ╰── Parameters
+                    ├── Param
+                    │   ├── Name
+                    │   │   ╰── s
+                    │   ╰── Type
+                    │       ╰── Pointer
+                    │           ╰── Qualified [const]
+                    │               ╰── Struct [s]
+                    ╰── Param
+                        ├── Name
+                        │   ╰── values
+                        ╰── Type
+                            ╰── Array
+                                ├── 3
+                                ╰── Qualified [const]
+                                    ╰── Pointer
+                                        ╰── Int
+    "#;
+    assert_parse(src, expected);
+}
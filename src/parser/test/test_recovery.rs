@@ -0,0 +1,118 @@
+use super::assert_errors;
+
+#[test]
+fn test_recover_at_semicolon() {
+    assert_errors(
+        r#"
+        int main(void) {
+            int a = 1 +;
+            a = (2;
+            return a;
+        }
+    "#,
+        20,
+        &[
+            "2:16: Expected expression, but found ';'",
+            "3:11: Expected ')', but found ';'",
+        ],
+    );
+}
+
+#[test]
+fn test_recover_at_declaration() {
+    assert_errors(
+        r#"
+        int x = ;
+        long y;
+        static int z
+        int main(void) {
+            return 0;
+        }
+        int f(int a int b);
+    "#,
+        20,
+        &[
+            "1:9: Expected expression, but found ';'",
+            "4:1: Expected ';', but found 'int'",
+            "7:13: Expected ')', but found 'int'",
+            "7:18: Expected ';', but found ')'",
+        ],
+    );
+}
+
+#[test]
+fn test_recover_with_cast_after_error() {
+    assert_errors(
+        r#"
+        int x = (1 + @ ) + (int)x + (long)x;
+        int y = sizeof(long) + [;
+        int z = ;
+    "#,
+        20,
+        &[
+            "1:14: Expected expression, but found '@'",
+            "2:24: Expected expression, but found '['",
+            "3:9: Expected expression, but found ';'",
+        ],
+    );
+}
+
+#[test]
+fn test_recover_at_close_brace() {
+    assert_errors(
+        r#"
+        int f(void) {
+            if (1 {
+                return 1;
+            }
+            return 2;
+        }
+        int g(void) {
+            return 3 }
+        int h(void) {
+            return @;
+        }
+    "#,
+        20,
+        &[
+            "2:11: Expected ')', but found '{'",
+            "8:14: Expected ';', but found '}'",
+            "10:12: Expected expression, but found '@'",
+        ],
+    );
+}
+
+#[test]
+fn test_error_limit() {
+    assert_errors(
+        r#"
+        int a = ;
+        int b = ;
+        int c = ;
+        int d = ;
+    "#,
+        2,
+        &[
+            "1:9: Expected expression, but found ';'",
+            "2:9: Expected expression, but found ';'",
+            "2:9: Too many errors, stopping now",
+        ],
+    );
+}
+
+#[test]
+fn test_no_error_limit() {
+    assert_errors(
+        r#"
+        int a = ;
+        int b = ;
+        int c = ;
+    "#,
+        0,
+        &[
+            "1:9: Expected expression, but found ';'",
+            "2:9: Expected expression, but found ';'",
+            "3:9: Expected expression, but found ';'",
+        ],
+    );
+}
@@ -0,0 +1,61 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_thread_local_declarations() {
+    let src = r#"
+        _Thread_local int counter = 1;
+        static __thread long total;
+        extern _Thread_local int shared;
+        int main(void) {
+            static _Thread_local int calls;
+            return calls;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── counter
+            │   ├── Type
+            │   │   ╰── Int
+            │   ├── Initializer
+            │   │   ╰── <4> Constant Int [1]
+            │   ╰── ThreadLocal
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── total
+            │   ├── Type
+            │   │   ╰── Long
+            │   ├── Static
+            │   ╰── ThreadLocal
+            ├── VarDeclaration
+            │   ├── Name
+            │   │   ╰── shared
+            │   ├── Type
+            │   │   ╰── Int
+            │   ├── Extern
+            │   ╰── ThreadLocal
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── calls
+                    │   ├── Type
+                    │   │   ╰── Int
+                    │   ├── Static
+                    │   ╰── ThreadLocal
+                    ╰── Return
+                        ╰── <28> Var [calls]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_duplicated_thread_local() {
+    assert_error(
+        r#"
+        _Thread_local __thread int x;
+                    //^^^^^^^^ Duplicated storage class in declaration
+    "#,
+    );
+}
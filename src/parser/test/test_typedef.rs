@@ -0,0 +1,126 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_typedef_names_and_shadowing() {
+    let src = r#"
+        typedef struct point { int x; } point_t;
+        typedef point_t *handle;
+        int main(void) {
+            handle h = 0;
+            int handle = (int)sizeof(point_t);
+            return handle;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── Struct [point]
+            │   ╰── Field
+            │       ├── Name
+            │       │   ╰── x
+            │       ╰── Type
+            │           ╰── Int
+            ├── TypeDef
+            │   ├── Name
+            │   │   ╰── point_t
+            │   ╰── Type
+            │       ╰── Struct [point]
+            ├── TypeDef
+            │   ├── Name
+            │   │   ╰── handle
+            │   ╰── Type
+            │       ╰── Pointer
+            │           ╰── Named [point_t]
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── h
+                    │   ├── Type
+                    │   │   ╰── Named [handle]
+                    │   ╰── Initializer
+                    │       ╰── <28> Constant Int [0]
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── handle
+                    │   ├── Type
+                    │   │   ╰── Int
+                    │   ╰── Initializer
+                    │       ╰── <40> Cast
+                    │           ├── Target
+                    │           │   ╰── Int
+                    │           ╰── Expression
+                    │               ╰── <39> SizeOfType
+                    │                   ╰── Named [point_t]
+                    ╰── Return
+                        ╰── <44> Var [handle]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_typedef_scope_ends_with_block() {
+    let src = r#"
+        int main(void) {
+            int x = 1;
+            {
+                typedef long x;
+                x y = 2;
+            }
+            return x * 2;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── x
+                    │   ├── Type
+                    │   │   ╰── Int
+                    │   ╰── Initializer
+                    │       ╰── <9> Constant Int [1]
+                    ├── Block
+                    │   ├── TypeDef
+                    │   │   ├── Name
+                    │   │   │   ╰── x
+                    │   │   ╰── Type
+                    │   │       ╰── Long
+                    │   ╰── VarDeclaration
+                    │       ├── Name
+                    │       │   ╰── y
+                    │       ├── Type
+                    │       │   ╰── Named [x]
+                    │       ╰── Initializer
+                    │           ╰── <20> Constant Int [2]
+                    ╰── Return
+                        ╰── <29>  [*]
+                            ├── <26> Var [x]
+                            ╰── <28> Constant Int [2]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_typedef_with_other_specifiers() {
+    assert_error(
+        r#"
+        typedef int T;
+        int main(void) {
+            unsigned T x = 1;
+                     //^ Expected ';', but found 'x'
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_typedef_without_name() {
+    assert_error(
+        r#"
+        typedef int;
+                 //^ Expected identifier, but found ';'
+    "#,
+    );
+}
@@ -0,0 +1,92 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_variable_length_array() {
+    let src = r#"
+        int main(void) {
+            int n = 3;
+            long a[n][n + 1];
+            return sizeof a;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── n
+                    │   ├── Type
+                    │   │   ╰── Int
+                    │   ╰── Initializer
+                    │       ╰── <9> Constant Int [3]
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── a
+                    │   ╰── Type
+                    │       ╰── VariableArray
+                    │           ├── <16> Var [n]
+                    │           ╰── VariableArray
+                    │               ├── <22>  [+]
+                    │               │   ├── <19> Var [n]
+                    │               │   ╰── <21> Constant Int [1]
+                    │               ╰── Long
+                    ╰── Return
+                        ╰── <29> SizeOfExpr
+                            ╰── <28> Var [a]
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_pointer_to_variable_length_array() {
+    let src = r#"
+        int main(void) {
+            int n = 3;
+            int (*p)[n];
+            return sizeof(double[n][2]);
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [main]
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── n
+                    │   ├── Type
+                    │   │   ╰── Int
+                    │   ╰── Initializer
+                    │       ╰── <9> Constant Int [3]
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── p
+                    │   ╰── Type
+                    │       ╰── Pointer
+                    │           ╰── VariableArray
+                    │               ├── <18> Var [n]
+                    │               ╰── Int
+                    ╰── Return
+                        ╰── <31> SizeOfType
+                            ╰── VariableArray
+                                ├── <26> Var [n]
+                                ╰── Array
+                                    ├── 2
+                                    ╰── Double
+    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_variable_length_array_missing_size() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            int a[n + ];
+                    //^ Expected expression, but found ']'
+            return 0;
+        }
+    "#,
+    );
+}
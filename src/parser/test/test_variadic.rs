@@ -0,0 +1,118 @@
+use super::{assert_error, assert_parse};
+
+#[test]
+fn test_variadic_function() {
+    let src = r#"
+        int printf(char *fmt, ...);
+        double sum(int n, ...) {
+            __builtin_va_list ap;
+            __builtin_va_start(ap, n);
+            double d = __builtin_va_arg(ap, double);
+            __builtin_va_end(ap);
+            return d;
+        }
+    "#;
+    let expected = r#"
+        Program
+            ├── Function [printf]
+            │   ╰── Parameters
+            │       ├── Param
+            │       │   ├── Name
+            │       │   │   ╰── fmt
+            │       │   ╰── Type
+            │       │       ╰── Pointer
+            │       │           ╰── Char
+            │       ╰── ...
+            ╰── Function [sum]
+                ├── Parameters
+                │   ├── Param
+                │   │   ├── Name
+                │   │   │   ╰── n
+                │   │   ╰── Type
+                │   │       ╰── Int
+                │   ╰── ...
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── ap
+                    │   ╰── Type
+                    │       ╰── VaList
+                    ├── <28> VaStart
+                    │   ├── <25> Var [ap]
+                    │   ╰── <27> Var [n]
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── d
+                    │   ├── Type
+                    │   │   ╰── Double
+                    │   ╰── Initializer
+                    │       ╰── <37> VaArg
+                    │           ├── <34> Var [ap]
+                    │           ╰── Double
+                    ├── <42> VaEnd
+                    │   ╰── <41> Var [ap]
+                    ╰── Return
+                        ╰── <45> Var [d]    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_va_copy_and_pointer_va_arg() {
+    let src = r#"
+        void f(__builtin_va_list ap) {
+            __builtin_va_list copy;
+            __builtin_va_copy(copy, ap);
+            char *s = __builtin_va_arg(copy, char *);
+        }
+    "#;
+    let expected = r#"
+        Program
+            ╰── Function [f]
+                ├── Parameters
+                │   ╰── Param
+                │       ├── Name
+                │       │   ╰── ap
+                │       ╰── Type
+                │           ╰── VaList
+                ╰── Body
+                    ├── VarDeclaration
+                    │   ├── Name
+                    │   │   ╰── copy
+                    │   ╰── Type
+                    │       ╰── VaList
+                    ├── <17> VaCopy
+                    │   ├── <14> Var [copy]
+                    │   ╰── <16> Var [ap]
+                    ╰── VarDeclaration
+                        ├── Name
+                        │   ╰── s
+                        ├── Type
+                        │   ╰── Pointer
+                        │       ╰── Char
+                        ╰── Initializer
+                            ╰── <29> VaArg
+                                ├── <25> Var [copy]
+                                ╰── Pointer
+                                    ╰── Char    "#;
+    assert_parse(src, expected);
+}
+
+#[test]
+fn test_invalid_ellipsis_without_named_parameter() {
+    assert_error(
+        r#"
+        int f(...);
+            //^^^ A named parameter is required before '...'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_ellipsis_not_last() {
+    assert_error(
+        r#"
+        int f(int a, ..., int b);
+                      //^ Expected ')', but found ','
+    "#,
+    );
+}
@@ -0,0 +1,1712 @@
+#[cfg(test)]
+mod test;
+
+use crate::error::render_snippet;
+use crate::source_map::{Location, SourceMap};
+use crate::symbol::Symbol;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+#[cfg(target_os = "macos")]
+const SYSTEM_INCLUDE_PATHS: [&str; 2] = ["/usr/local/include", "/usr/include"];
+
+#[cfg(not(target_os = "macos"))]
+const SYSTEM_INCLUDE_PATHS: [&str; 3] = [
+    "/usr/local/include",
+    "/usr/include/x86_64-linux-gnu",
+    "/usr/include",
+];
+
+/// Headers provided by the compiler itself, found after the user include paths
+/// but before the system ones.
+const BUILTIN_HEADERS: [(&str, &str); 7] = [
+    (
+        "stdarg.h",
+        "#ifndef __STDARG_H
+#define __STDARG_H
+typedef __builtin_va_list va_list;
+typedef __builtin_va_list __gnuc_va_list;
+#define va_start(ap, param) __builtin_va_start(ap, param)
+#define va_arg(ap, type) __builtin_va_arg(ap, type)
+#define va_end(ap) __builtin_va_end(ap)
+#define va_copy(dest, src) __builtin_va_copy(dest, src)
+#endif
+#undef __need___va_list
+",
+    ),
+    // The C library includes this header with `__need_size_t` and friends
+    // defined to only get some of the definitions, everything is defined the
+    // first time instead.
+    (
+        "stddef.h",
+        "#ifndef __STDDEF_H
+#define __STDDEF_H
+typedef unsigned long size_t;
+typedef long ptrdiff_t;
+typedef int wchar_t;
+typedef struct __max_align_t {
+    long long __max_align_ll __attribute__((aligned(16)));
+    double __max_align_d __attribute__((aligned(16)));
+} max_align_t;
+#define NULL ((void *)0)
+#define offsetof(type, member) ((size_t)&((type *)0)->member)
+#endif
+#undef __need_size_t
+#undef __need_ptrdiff_t
+#undef __need_wchar_t
+#undef __need_NULL
+#undef __need_wint_t
+",
+    ),
+    (
+        "stdbool.h",
+        "#ifndef __STDBOOL_H
+#define __STDBOOL_H
+#define bool _Bool
+#define true 1
+#define false 0
+#define __bool_true_false_are_defined 1
+#endif
+",
+    ),
+    // `_Alignas` isn't supported, `alignas` only takes an alignment.
+    (
+        "stdalign.h",
+        "#ifndef __STDALIGN_H
+#define __STDALIGN_H
+#define alignas(alignment) __attribute__((aligned(alignment)))
+#define alignof _Alignof
+#define __alignas_is_defined 1
+#define __alignof_is_defined 1
+#endif
+",
+    ),
+    (
+        "limits.h",
+        "#ifndef __LIMITS_H
+#define __LIMITS_H
+#define CHAR_BIT 8
+#define MB_LEN_MAX 16
+#define SCHAR_MIN (-128)
+#define SCHAR_MAX 127
+#define UCHAR_MAX 255
+#define CHAR_MIN SCHAR_MIN
+#define CHAR_MAX SCHAR_MAX
+#define SHRT_MIN (-32768)
+#define SHRT_MAX 32767
+#define USHRT_MAX 65535
+#define INT_MIN (-INT_MAX - 1)
+#define INT_MAX 2147483647
+#define UINT_MAX 4294967295U
+#define LONG_MIN (-LONG_MAX - 1L)
+#define LONG_MAX 9223372036854775807L
+#define ULONG_MAX 18446744073709551615UL
+#define LLONG_MIN (-LLONG_MAX - 1LL)
+#define LLONG_MAX 9223372036854775807LL
+#define ULLONG_MAX 18446744073709551615ULL
+#endif
+",
+    ),
+    (
+        "float.h",
+        "#ifndef __FLOAT_H
+#define __FLOAT_H
+#define FLT_RADIX 2
+#define FLT_ROUNDS 1
+#define FLT_EVAL_METHOD 0
+#define DECIMAL_DIG 17
+#define FLT_MANT_DIG 24
+#define FLT_DIG 6
+#define FLT_DECIMAL_DIG 9
+#define FLT_MIN_EXP (-125)
+#define FLT_MIN_10_EXP (-37)
+#define FLT_MAX_EXP 128
+#define FLT_MAX_10_EXP 38
+#define FLT_MAX 3.40282346638528859812e+38F
+#define FLT_MIN 1.17549435082228750797e-38F
+#define FLT_EPSILON 1.19209289550781250000e-7F
+#define FLT_TRUE_MIN 1.40129846432481707092e-45F
+#define FLT_HAS_SUBNORM 1
+#define DBL_MANT_DIG 53
+#define DBL_DIG 15
+#define DBL_DECIMAL_DIG 17
+#define DBL_MIN_EXP (-1021)
+#define DBL_MIN_10_EXP (-307)
+#define DBL_MAX_EXP 1024
+#define DBL_MAX_10_EXP 308
+#define DBL_MAX 1.79769313486231570815e+308
+#define DBL_MIN 2.22507385850720138309e-308
+#define DBL_EPSILON 2.22044604925031308085e-16
+#define DBL_TRUE_MIN 4.94065645841246544177e-324
+#define DBL_HAS_SUBNORM 1
+#endif
+",
+    ),
+    (
+        "stdint.h",
+        "#ifndef __STDINT_H
+#define __STDINT_H
+typedef signed char int8_t;
+typedef short int16_t;
+typedef int int32_t;
+typedef long int64_t;
+typedef unsigned char uint8_t;
+typedef unsigned short uint16_t;
+typedef unsigned int uint32_t;
+typedef unsigned long uint64_t;
+typedef signed char int_least8_t;
+typedef short int_least16_t;
+typedef int int_least32_t;
+typedef long int_least64_t;
+typedef unsigned char uint_least8_t;
+typedef unsigned short uint_least16_t;
+typedef unsigned int uint_least32_t;
+typedef unsigned long uint_least64_t;
+typedef signed char int_fast8_t;
+typedef long int_fast16_t;
+typedef long int_fast32_t;
+typedef long int_fast64_t;
+typedef unsigned char uint_fast8_t;
+typedef unsigned long uint_fast16_t;
+typedef unsigned long uint_fast32_t;
+typedef unsigned long uint_fast64_t;
+typedef long intptr_t;
+typedef unsigned long uintptr_t;
+typedef long intmax_t;
+typedef unsigned long uintmax_t;
+#define INT8_MIN (-128)
+#define INT16_MIN (-32767 - 1)
+#define INT32_MIN (-2147483647 - 1)
+#define INT64_MIN (-9223372036854775807L - 1)
+#define INT8_MAX 127
+#define INT16_MAX 32767
+#define INT32_MAX 2147483647
+#define INT64_MAX 9223372036854775807L
+#define UINT8_MAX 255
+#define UINT16_MAX 65535
+#define UINT32_MAX 4294967295U
+#define UINT64_MAX 18446744073709551615UL
+#define INT_LEAST8_MIN INT8_MIN
+#define INT_LEAST16_MIN INT16_MIN
+#define INT_LEAST32_MIN INT32_MIN
+#define INT_LEAST64_MIN INT64_MIN
+#define INT_LEAST8_MAX INT8_MAX
+#define INT_LEAST16_MAX INT16_MAX
+#define INT_LEAST32_MAX INT32_MAX
+#define INT_LEAST64_MAX INT64_MAX
+#define UINT_LEAST8_MAX UINT8_MAX
+#define UINT_LEAST16_MAX UINT16_MAX
+#define UINT_LEAST32_MAX UINT32_MAX
+#define UINT_LEAST64_MAX UINT64_MAX
+#define INT_FAST8_MIN INT8_MIN
+#define INT_FAST16_MIN INT64_MIN
+#define INT_FAST32_MIN INT64_MIN
+#define INT_FAST64_MIN INT64_MIN
+#define INT_FAST8_MAX INT8_MAX
+#define INT_FAST16_MAX INT64_MAX
+#define INT_FAST32_MAX INT64_MAX
+#define INT_FAST64_MAX INT64_MAX
+#define UINT_FAST8_MAX UINT8_MAX
+#define UINT_FAST16_MAX UINT64_MAX
+#define UINT_FAST32_MAX UINT64_MAX
+#define UINT_FAST64_MAX UINT64_MAX
+#define INTPTR_MIN INT64_MIN
+#define INTPTR_MAX INT64_MAX
+#define UINTPTR_MAX UINT64_MAX
+#define INTMAX_MIN INT64_MIN
+#define INTMAX_MAX INT64_MAX
+#define UINTMAX_MAX UINT64_MAX
+#define PTRDIFF_MIN INT64_MIN
+#define PTRDIFF_MAX INT64_MAX
+#define SIZE_MAX UINT64_MAX
+#define SIG_ATOMIC_MIN INT32_MIN
+#define SIG_ATOMIC_MAX INT32_MAX
+#define WCHAR_MIN INT32_MIN
+#define WCHAR_MAX INT32_MAX
+#define WINT_MIN 0U
+#define WINT_MAX UINT32_MAX
+#define INT8_C(c) c
+#define INT16_C(c) c
+#define INT32_C(c) c
+#define INT64_C(c) c##L
+#define UINT8_C(c) c
+#define UINT16_C(c) c
+#define UINT32_C(c) c##U
+#define UINT64_C(c) c##UL
+#define INTMAX_C(c) c##L
+#define UINTMAX_C(c) c##UL
+#endif
+",
+    ),
+];
+
+/// `__GNUC__` is left undefined so the C library headers stick to standard C
+/// instead of GNU extensions.
+const PREDEFINED_MACROS: [(&str, &str); 6] = [
+    ("__STDC__", "1"),
+    ("__STDC_HOSTED__", "1"),
+    ("__STDC_VERSION__", "201112L"),
+    ("__x86_64__", "1"),
+    ("__LP64__", "1"),
+    (OS_MACRO, "1"),
+];
+
+#[cfg(target_os = "macos")]
+const OS_MACRO: &str = "__APPLE__";
+
+#[cfg(not(target_os = "macos"))]
+const OS_MACRO: &str = "__linux__";
+
+const PUNCTUATORS: [&str; 24] = [
+    "...", "<<=", ">>=", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "*=",
+    "/=", "%=", "+=", "-=", "&=", "^=", "|=", "##", "%:",
+];
+
+#[derive(Debug)]
+pub struct PreprocessorError {
+    pub msg: String,
+    pub location: Location,
+    /// Source lines around the error, rendered like the other diagnostics.
+    pub snippet: String,
+}
+
+impl PreprocessorError {
+    pub fn render(&self) -> String {
+        format!("{self}\n{}", self.snippet)
+    }
+}
+
+impl Display for PreprocessorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: error: {}", self.location, self.msg)
+    }
+}
+
+impl std::error::Error for PreprocessorError {}
+
+type Result<T> = std::result::Result<T, PreprocessorError>;
+
+#[derive(Default)]
+pub struct Options {
+    pub include_paths: Vec<PathBuf>,
+    pub defines: Vec<(String, String)>,
+}
+
+pub struct Preprocessed {
+    pub source: String,
+    pub source_map: SourceMap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PpKind {
+    Identifier,
+    Number,
+    CharLiteral,
+    StringLiteral,
+    Punctuator,
+    Other,
+    Newline,
+    Eof,
+    // Only produced during macro substitution
+    Placemarker,
+    Paste,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pos {
+    file: usize,
+    offset: usize,
+}
+
+#[derive(Debug, Clone)]
+struct PpToken {
+    kind: PpKind,
+    text: Symbol,
+    space: bool,
+    pos: Pos,
+    hideset: Vec<Symbol>,
+    expanded: bool,
+}
+
+impl PpToken {
+    fn is(&self, punctuator: &str) -> bool {
+        self.kind == PpKind::Punctuator && self.text.as_ref() == punctuator
+    }
+
+    fn is_identifier(&self) -> bool {
+        self.kind == PpKind::Identifier
+    }
+}
+
+struct Macro {
+    params: Option<Vec<Symbol>>,
+    variadic: bool,
+    body: Vec<PpToken>,
+}
+
+impl Macro {
+    fn param_index(&self, token: &PpToken) -> Option<usize> {
+        if !token.is_identifier() {
+            return None;
+        }
+        self.params.as_ref()?.iter().position(|p| *p == token.text)
+    }
+}
+
+struct PpLexer<'src> {
+    text: &'src str,
+    // Offsets where a backslash-newline was removed, with the accumulated
+    // number of removed bytes from that point on.
+    splices: Vec<(usize, usize)>,
+    file: usize,
+    offset: usize,
+}
+
+impl<'src> PpLexer<'src> {
+    fn splice(raw: &str) -> (String, Vec<(usize, usize)>) {
+        let mut text = String::with_capacity(raw.len());
+        let mut splices = Vec::new();
+        let mut removed = 0;
+        let mut rest = raw;
+        while let Some(i) = rest.find('\\') {
+            text.push_str(&rest[..i]);
+            let after = &rest[i + 1..];
+            let newline_len = if after.starts_with("\r\n") {
+                2
+            } else if after.starts_with('\n') {
+                1
+            } else {
+                0
+            };
+            if newline_len == 0 {
+                text.push('\\');
+                rest = after;
+            } else {
+                removed += newline_len + 1;
+                splices.push((text.len(), removed));
+                rest = &after[newline_len..];
+            }
+        }
+        text.push_str(rest);
+        (text, splices)
+    }
+
+    fn new(text: &'src str, splices: Vec<(usize, usize)>, file: usize) -> Self {
+        Self {
+            text,
+            splices,
+            file,
+            offset: 0,
+        }
+    }
+
+    fn raw_offset(&self, offset: usize) -> usize {
+        let index = self.splices.partition_point(|&(start, _)| start <= offset);
+        match index.checked_sub(1) {
+            Some(i) => offset + self.splices[i].1,
+            None => offset,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.text[self.offset..].chars().nth(1)
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.offset;
+        loop {
+            match (self.peek(), self.peek_next()) {
+                (Some('\n'), _) => break,
+                (Some(c), _) if c.is_whitespace() => {
+                    self.advance();
+                }
+                (Some('/'), Some('/')) => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.advance();
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    self.offset += 2;
+                    match self.text[self.offset..].find("*/") {
+                        Some(end) => self.offset += end + 2,
+                        None => self.offset = self.text.len(),
+                    }
+                }
+                _ => break,
+            }
+        }
+        self.offset > start
+    }
+
+    fn next(&mut self) -> PpToken {
+        let space = self.skip_whitespace();
+        let start = self.offset;
+        let kind = match self.advance() {
+            None => PpKind::Eof,
+            Some('\n') => PpKind::Newline,
+            Some(c) if c == '_' || c.is_alphabetic() => {
+                while matches!(self.peek(), Some(c) if c == '_' || c.is_alphanumeric()) {
+                    self.advance();
+                }
+                PpKind::Identifier
+            }
+            Some('0'..='9') => self.number(),
+            Some('.') if matches!(self.peek(), Some('0'..='9')) => self.number(),
+            Some(quote @ ('\'' | '"')) => self.quoted(quote),
+            Some(c) => {
+                let rest = &self.text[start..];
+                if let Some(p) = PUNCTUATORS.iter().find(|p| rest.starts_with(*p)) {
+                    self.offset = start + p.len();
+                    PpKind::Punctuator
+                } else if c.is_ascii_punctuation() && !matches!(c, '@' | '$' | '`' | '\\') {
+                    PpKind::Punctuator
+                } else {
+                    PpKind::Other
+                }
+            }
+        };
+        PpToken {
+            kind,
+            text: Symbol::from(&self.text[start..self.offset]),
+            space,
+            pos: Pos {
+                file: self.file,
+                offset: self.raw_offset(start),
+            },
+            hideset: Vec::new(),
+            expanded: false,
+        }
+    }
+
+    fn number(&mut self) -> PpKind {
+        while let Some(c) = self.peek() {
+            if matches!(c, 'e' | 'E' | 'p' | 'P') && matches!(self.peek_next(), Some('+' | '-')) {
+                self.advance();
+                self.advance();
+            } else if c == '.' || c == '_' || c.is_alphanumeric() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        PpKind::Number
+    }
+
+    fn quoted(&mut self, quote: char) -> PpKind {
+        loop {
+            match self.peek() {
+                Some(c) if c == quote => {
+                    self.advance();
+                    break;
+                }
+                Some('\\') => {
+                    self.advance();
+                    if self.peek() != Some('\n') {
+                        self.advance();
+                    }
+                }
+                // An unterminated literal is passed through and reported by the lexer
+                None | Some('\n') => return PpKind::Other,
+                Some(_) => {
+                    self.advance();
+                }
+            }
+        }
+        if quote == '"' {
+            PpKind::StringLiteral
+        } else {
+            PpKind::CharLiteral
+        }
+    }
+}
+
+fn lex_file(text: &str, file: usize) -> Vec<PpToken> {
+    let (spliced, splices) = PpLexer::splice(text);
+    let mut lexer = PpLexer::new(&spliced, splices, file);
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next();
+        let eof = token.kind == PpKind::Eof;
+        tokens.push(token);
+        if eof {
+            return tokens;
+        }
+    }
+}
+
+struct FileCursor {
+    tokens: Vec<PpToken>,
+    index: usize,
+}
+
+impl FileCursor {
+    fn next(&mut self) -> PpToken {
+        let token = self.tokens[self.index].clone();
+        if token.kind != PpKind::Eof {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn peek_significant(&self) -> &PpToken {
+        self.tokens[self.index..]
+            .iter()
+            .find(|t| t.kind != PpKind::Newline)
+            .expect("Token stream should end with Eof")
+    }
+
+    fn next_significant(&mut self) -> Option<PpToken> {
+        while self.tokens[self.index].kind == PpKind::Newline {
+            self.index += 1;
+        }
+        let token = self.next();
+        (token.kind != PpKind::Eof).then_some(token)
+    }
+
+    fn rest_of_line(&mut self) -> Vec<PpToken> {
+        let mut line = Vec::new();
+        loop {
+            let token = self.next();
+            match token.kind {
+                PpKind::Newline | PpKind::Eof => return line,
+                _ => line.push(token),
+            }
+        }
+    }
+}
+
+struct Input<'a> {
+    tokens: VecDeque<PpToken>,
+    file: Option<&'a mut FileCursor>,
+}
+
+impl Input<'_> {
+    fn next(&mut self) -> Option<PpToken> {
+        self.tokens.pop_front()
+    }
+
+    /// Like `next`, but keeps reading from the file when the pending tokens are
+    /// exhausted. Used to read macro arguments, which can span several lines.
+    fn next_or_file(&mut self) -> Option<PpToken> {
+        if let Some(token) = self.tokens.pop_front() {
+            return Some(token);
+        }
+        self.file.as_mut()?.next_significant()
+    }
+
+    fn next_is_open_paren(&self) -> bool {
+        match (self.tokens.front(), &self.file) {
+            (Some(token), _) => token.is("("),
+            (None, Some(file)) => file.peek_significant().is("("),
+            (None, None) => false,
+        }
+    }
+
+    fn push_front(&mut self, tokens: Vec<PpToken>) {
+        for token in tokens.into_iter().rev() {
+            self.tokens.push_front(token);
+        }
+    }
+}
+
+struct Conditional {
+    parent_active: bool,
+    active: bool,
+    taken: bool,
+    seen_else: bool,
+    pos: Pos,
+}
+
+struct Preprocessor {
+    include_paths: Vec<PathBuf>,
+    macros: HashMap<Symbol, Rc<Macro>>,
+    pragma_once: HashSet<PathBuf>,
+    source_map: SourceMap,
+    output: String,
+    output_line: Option<(usize, usize)>,
+    last_end: usize,
+    last_text: Symbol,
+    last_expanded: bool,
+    file: usize,
+    path: PathBuf,
+    presumed_name: Symbol,
+    line_delta: isize,
+    depth: usize,
+}
+
+impl Preprocessor {
+    fn new(options: &Options) -> Result<Self> {
+        let mut preprocessor = Self {
+            include_paths: options.include_paths.clone(),
+            macros: HashMap::new(),
+            pragma_once: HashSet::new(),
+            source_map: SourceMap::default(),
+            output: String::new(),
+            output_line: None,
+            last_end: 0,
+            last_text: Symbol::default(),
+            last_expanded: false,
+            file: 0,
+            path: PathBuf::new(),
+            presumed_name: Symbol::default(),
+            line_delta: 0,
+            depth: 0,
+        };
+        let predefined = PREDEFINED_MACROS
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()));
+        let mut builtins = String::new();
+        for (name, value) in predefined.chain(options.defines.iter().cloned()) {
+            builtins.push_str(&format!("#define {name} {value}\n"));
+        }
+        preprocessor.run(PathBuf::from("<built-in>"), builtins)?;
+        preprocessor.output_line = None;
+        Ok(preprocessor)
+    }
+
+    fn finish(mut self) -> Preprocessed {
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        Preprocessed {
+            source: self.output,
+            source_map: self.source_map,
+        }
+    }
+
+    fn error<T>(&self, pos: Pos, msg: impl Into<String>) -> Result<T> {
+        let location = self.location(pos);
+        let file = self.source_map.file(pos.file);
+        let line_delta = location.line as isize - (file.line_index(pos.offset) + 1) as isize;
+        let mut snippet = String::new();
+        render_snippet(
+            &mut snippet,
+            file,
+            pos.offset,
+            pos.offset + 1,
+            line_delta,
+            '^',
+        );
+        Err(PreprocessorError {
+            msg: msg.into(),
+            location,
+            snippet,
+        })
+    }
+
+    fn location(&self, pos: Pos) -> Location {
+        let file = self.source_map.file(pos.file);
+        let (line, column) = file.line_column(pos.offset);
+        if pos.file == self.file {
+            Location {
+                file: self.presumed_name.clone(),
+                line: (line as isize + self.line_delta) as usize,
+                column,
+            }
+        } else {
+            Location {
+                file: file.name.clone(),
+                line,
+                column,
+            }
+        }
+    }
+
+    fn run_file(&mut self, path: PathBuf, at: Option<Pos>) -> Result<()> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                let msg = format!("{}: {e}", path.display());
+                return match at {
+                    Some(pos) => self.error(pos, msg),
+                    None => Err(PreprocessorError {
+                        msg,
+                        location: Location {
+                            file: Symbol::from(path.display().to_string()),
+                            line: 0,
+                            column: 0,
+                        },
+                        snippet: String::new(),
+                    }),
+                };
+            }
+        };
+        self.run(path, text)
+    }
+
+    fn run(&mut self, path: PathBuf, text: String) -> Result<()> {
+        let name = Symbol::from(path.display().to_string());
+        let file = self.source_map.add_file(name.clone(), text);
+        let tokens = lex_file(&self.source_map.file(file).text, file);
+
+        let saved_file = std::mem::replace(&mut self.file, file);
+        let saved_path = std::mem::replace(&mut self.path, path);
+        let saved_name = std::mem::replace(&mut self.presumed_name, name);
+        let saved_delta = std::mem::replace(&mut self.line_delta, 0);
+
+        let result = self.process(FileCursor { tokens, index: 0 });
+
+        self.file = saved_file;
+        self.path = saved_path;
+        self.presumed_name = saved_name;
+        self.line_delta = saved_delta;
+        result
+    }
+
+    fn process(&mut self, mut cursor: FileCursor) -> Result<()> {
+        let mut conditionals: Vec<Conditional> = Vec::new();
+        let mut at_line_start = true;
+        loop {
+            let token = cursor.next();
+            let active = conditionals.last().is_none_or(|c| c.active);
+            match token.kind {
+                PpKind::Eof => break,
+                PpKind::Newline => {
+                    at_line_start = true;
+                }
+                _ if at_line_start && (token.is("#") || token.is("%:")) => {
+                    let line = cursor.rest_of_line();
+                    self.directive(&token, line, &mut conditionals, active)?;
+                }
+                _ => {
+                    at_line_start = false;
+                    if active {
+                        let mut input = Input {
+                            tokens: VecDeque::from([token]),
+                            file: Some(&mut cursor),
+                        };
+                        let mut expanded = Vec::new();
+                        self.expand(&mut input, &mut expanded)?;
+                        for token in &expanded {
+                            self.emit(token);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(conditional) = conditionals.last() {
+            return self.error(conditional.pos, "unterminated conditional directive");
+        }
+        Ok(())
+    }
+
+    fn directive(
+        &mut self,
+        hash: &PpToken,
+        line: Vec<PpToken>,
+        conditionals: &mut Vec<Conditional>,
+        active: bool,
+    ) -> Result<()> {
+        let Some(name) = line.first() else {
+            // Null directive
+            return Ok(());
+        };
+        let args = &line[1..];
+        match name.text.as_ref() {
+            "if" | "ifdef" | "ifndef" => {
+                let value = active && self.condition(name, args)?;
+                conditionals.push(Conditional {
+                    parent_active: active,
+                    active: value,
+                    taken: value || !active,
+                    seen_else: false,
+                    pos: hash.pos,
+                });
+            }
+            "elif" => {
+                let Some(conditional) = conditionals.last() else {
+                    return self.error(name.pos, "#elif without #if");
+                };
+                if conditional.seen_else {
+                    return self.error(name.pos, "#elif after #else");
+                }
+                let value = !conditional.taken && self.condition(name, args)?;
+                let conditional = conditionals.last_mut().unwrap();
+                conditional.active = value;
+                conditional.taken |= value;
+            }
+            "else" => {
+                let Some(conditional) = conditionals.last_mut() else {
+                    return self.error(name.pos, "#else without #if");
+                };
+                if conditional.seen_else {
+                    return self.error(name.pos, "#else after #else");
+                }
+                conditional.active = conditional.parent_active && !conditional.taken;
+                conditional.taken = true;
+                conditional.seen_else = true;
+            }
+            "endif" => {
+                if conditionals.pop().is_none() {
+                    return self.error(name.pos, "#endif without #if");
+                }
+            }
+            _ if !active => {}
+            "define" => self.define(name, args)?,
+            "undef" => {
+                let Some(macro_name) = args.first().filter(|t| t.is_identifier()) else {
+                    return self.error(name.pos, "macro name missing");
+                };
+                self.macros.remove(&macro_name.text);
+            }
+            "include" => self.include(name, args)?,
+            "line" => self.line(name, args)?,
+            "error" => return self.error(name.pos, format!("#error {}", spelling(args))),
+            "warning" => {
+                eprintln!(
+                    "{}: warning: #warning {}",
+                    self.location(name.pos),
+                    spelling(args)
+                );
+            }
+            "pragma" => {
+                if args.first().is_some_and(|t| t.text.as_ref() == "once") {
+                    let path = fs::canonicalize(&self.path).unwrap_or(self.path.clone());
+                    self.pragma_once.insert(path);
+                }
+            }
+            "ident" | "sccs" => {}
+            _ if name.kind == PpKind::Number => self.line(name, &line)?,
+            other => {
+                return self.error(
+                    name.pos,
+                    format!("invalid preprocessing directive #{other}"),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn define(&mut self, directive: &PpToken, args: &[PpToken]) -> Result<()> {
+        let Some(name) = args.first() else {
+            return self.error(directive.pos, "macro name missing");
+        };
+        if !name.is_identifier() {
+            return self.error(name.pos, "macro names must be identifiers");
+        }
+        if name.text.as_ref() == "defined" {
+            return self.error(name.pos, "'defined' cannot be used as a macro name");
+        }
+        let mut rest = &args[1..];
+        let mut params = None;
+        let mut variadic = false;
+        if let Some(paren) = rest.first()
+            && paren.is("(")
+            && !paren.space
+        {
+            let mut names = Vec::new();
+            let mut i = 1;
+            loop {
+                let Some(token) = rest.get(i) else {
+                    return self.error(paren.pos, "missing ')' in macro parameter list");
+                };
+                match token.kind {
+                    PpKind::Punctuator if token.is(")") && names.is_empty() && i == 1 => break,
+                    PpKind::Punctuator if token.is("...") => {
+                        variadic = true;
+                        names.push(Symbol::from("__VA_ARGS__"));
+                        i += 1;
+                        if !rest.get(i).is_some_and(|t| t.is(")")) {
+                            return self.error(token.pos, "missing ')' in macro parameter list");
+                        }
+                        break;
+                    }
+                    PpKind::Identifier => {
+                        if names.contains(&token.text) {
+                            return self.error(
+                                token.pos,
+                                format!("duplicate macro parameter '{}'", token.text),
+                            );
+                        }
+                        names.push(token.text.clone());
+                        i += 1;
+                        match rest.get(i) {
+                            Some(t) if t.is(",") => i += 1,
+                            Some(t) if t.is(")") => break,
+                            _ => {
+                                return self.error(
+                                    token.pos,
+                                    "expected ',' or ')' in macro parameter list",
+                                );
+                            }
+                        }
+                    }
+                    _ => {
+                        return self
+                            .error(token.pos, "expected parameter name in macro parameter list");
+                    }
+                }
+            }
+            rest = &rest[i + 1..];
+            params = Some(names);
+        }
+
+        let mut body: Vec<PpToken> = rest.to_vec();
+        if let Some(first) = body.first_mut() {
+            first.space = false;
+        }
+        if let Some(paste) = [body.first(), body.last()]
+            .into_iter()
+            .flatten()
+            .find(|t| t.is("##"))
+        {
+            return self.error(
+                paste.pos,
+                "'##' cannot appear at either end of a macro expansion",
+            );
+        }
+        let new_macro = Macro {
+            params,
+            variadic,
+            body,
+        };
+        if new_macro.params.is_some() {
+            for (i, token) in new_macro.body.iter().enumerate() {
+                let next = new_macro.body.get(i + 1);
+                if token.is("#") && next.and_then(|t| new_macro.param_index(t)).is_none() {
+                    return self.error(token.pos, "'#' is not followed by a macro parameter");
+                }
+            }
+        }
+        self.macros.insert(name.text.clone(), Rc::new(new_macro));
+        Ok(())
+    }
+
+    fn include(&mut self, directive: &PpToken, args: &[PpToken]) -> Result<()> {
+        let expanded;
+        let args = if args
+            .first()
+            .is_some_and(|t| t.kind == PpKind::StringLiteral || t.is("<"))
+        {
+            args
+        } else {
+            expanded = self.expand_tokens(args.to_vec())?;
+            &expanded
+        };
+        let (name, quoted) = match args.first() {
+            Some(t) if t.kind == PpKind::StringLiteral => (unquote(&t.text).to_owned(), true),
+            Some(t) if t.is("<") => {
+                let Some(end) = args.iter().position(|t| t.is(">")) else {
+                    return self.error(t.pos, "missing terminating > character");
+                };
+                (spelling(&args[1..end]), false)
+            }
+            _ => {
+                return self.error(directive.pos, "#include expects \"FILENAME\" or <FILENAME>");
+            }
+        };
+
+        let current_dir = self.path.parent().map(Path::to_path_buf);
+        let user_paths = quoted
+            .then_some(current_dir)
+            .flatten()
+            .into_iter()
+            .chain(self.include_paths.iter().cloned());
+        let found = if Path::new(&name).is_absolute() {
+            Some(PathBuf::from(&name)).filter(|p| p.is_file())
+        } else {
+            user_paths
+                .map(|dir| dir.join(&name))
+                .find(|path| path.is_file())
+        };
+        if found.is_none()
+            && let Some((_, text)) = BUILTIN_HEADERS.iter().find(|(header, _)| *header == name)
+        {
+            return self.include_text(directive, PathBuf::from(format!("<{name}>")), text);
+        }
+        let found = found.or_else(|| {
+            SYSTEM_INCLUDE_PATHS
+                .iter()
+                .map(|dir| Path::new(dir).join(&name))
+                .find(|path| path.is_file())
+        });
+        let Some(path) = found else {
+            return self.error(directive.pos, format!("'{name}' file not found"));
+        };
+
+        let canonical = fs::canonicalize(&path).unwrap_or(path.clone());
+        if self.pragma_once.contains(&canonical) {
+            return Ok(());
+        }
+        if self.depth >= MAX_INCLUDE_DEPTH {
+            return self.error(directive.pos, "#include nested too deeply");
+        }
+        self.depth += 1;
+        let result = self.run_file(path, Some(directive.pos));
+        self.depth -= 1;
+        result
+    }
+
+    fn include_text(&mut self, directive: &PpToken, path: PathBuf, text: &str) -> Result<()> {
+        if self.depth >= MAX_INCLUDE_DEPTH {
+            return self.error(directive.pos, "#include nested too deeply");
+        }
+        self.depth += 1;
+        let result = self.run(path, text.to_string());
+        self.depth -= 1;
+        result
+    }
+
+    fn line(&mut self, directive: &PpToken, args: &[PpToken]) -> Result<()> {
+        let args = self.expand_tokens(args.to_vec())?;
+        let Some(line) = args
+            .first()
+            .filter(|t| t.kind == PpKind::Number)
+            .and_then(|t| t.text.as_ref().parse::<usize>().ok())
+        else {
+            return self.error(directive.pos, "#line directive requires a positive integer");
+        };
+        if let Some(name) = args.get(1) {
+            if name.kind != PpKind::StringLiteral {
+                return self.error(name.pos, "invalid filename in #line directive");
+            }
+            self.presumed_name = Symbol::from(unquote(&name.text));
+        }
+        let file = self.source_map.file(directive.pos.file);
+        let next_line = file.line_index(directive.pos.offset) + 2;
+        self.line_delta = line as isize - next_line as isize;
+        Ok(())
+    }
+
+    fn condition(&mut self, directive: &PpToken, args: &[PpToken]) -> Result<bool> {
+        match directive.text.as_ref() {
+            "ifdef" | "ifndef" => {
+                let Some(name) = args.first().filter(|t| t.is_identifier()) else {
+                    return self.error(directive.pos, "macro name missing");
+                };
+                let defined = self.macros.contains_key(&name.text);
+                Ok(defined == (directive.text.as_ref() == "ifdef"))
+            }
+            _ => {
+                if args.is_empty() {
+                    return self.error(directive.pos, "#if with no expression");
+                }
+                let tokens = self.replace_defined(args)?;
+                let tokens = self.expand_tokens(tokens)?;
+                let mut parser = ExprParser {
+                    pp: self,
+                    tokens: &tokens,
+                    index: 0,
+                    end: directive.pos,
+                };
+                let value = parser.expression()?;
+                if let Some(token) = tokens.get(parser.index) {
+                    return self.error(token.pos, "unexpected token in preprocessor expression");
+                }
+                Ok(value.value != 0)
+            }
+        }
+    }
+
+    fn replace_defined(&self, args: &[PpToken]) -> Result<Vec<PpToken>> {
+        let mut result = Vec::with_capacity(args.len());
+        let mut i = 0;
+        while i < args.len() {
+            let token = &args[i];
+            if !(token.is_identifier() && token.text.as_ref() == "defined") {
+                result.push(token.clone());
+                i += 1;
+                continue;
+            }
+            let parenthesized = args.get(i + 1).is_some_and(|t| t.is("("));
+            let name_index = if parenthesized { i + 2 } else { i + 1 };
+            let Some(name) = args.get(name_index).filter(|t| t.is_identifier()) else {
+                return self.error(token.pos, "operator 'defined' requires an identifier");
+            };
+            if parenthesized && !args.get(name_index + 1).is_some_and(|t| t.is(")")) {
+                return self.error(token.pos, "missing ')' after 'defined'");
+            }
+            let value = if self.macros.contains_key(&name.text) {
+                "1"
+            } else {
+                "0"
+            };
+            result.push(PpToken {
+                kind: PpKind::Number,
+                text: Symbol::from(value),
+                ..token.clone()
+            });
+            i = name_index + if parenthesized { 2 } else { 1 };
+        }
+        Ok(result)
+    }
+
+    fn expand_tokens(&mut self, tokens: Vec<PpToken>) -> Result<Vec<PpToken>> {
+        let mut input = Input {
+            tokens: tokens.into(),
+            file: None,
+        };
+        let mut result = Vec::new();
+        self.expand(&mut input, &mut result)?;
+        Ok(result)
+    }
+
+    fn expand(&mut self, input: &mut Input, output: &mut Vec<PpToken>) -> Result<()> {
+        while let Some(token) = input.next() {
+            if !token.is_identifier() || token.hideset.contains(&token.text) {
+                output.push(token);
+                continue;
+            }
+            match token.text.as_ref() {
+                "__FILE__" => {
+                    let name = self.location(token.pos).file;
+                    output.push(PpToken {
+                        kind: PpKind::StringLiteral,
+                        text: Symbol::from(format!("\"{}\"", escape(name.as_ref()))),
+                        ..token
+                    });
+                    continue;
+                }
+                "__LINE__" => {
+                    let line = self.location(token.pos).line;
+                    output.push(PpToken {
+                        kind: PpKind::Number,
+                        text: Symbol::from(line.to_string()),
+                        ..token
+                    });
+                    continue;
+                }
+                _ => {}
+            }
+            let Some(m) = self.macros.get(&token.text).cloned() else {
+                output.push(token);
+                continue;
+            };
+            let mut hideset = token.hideset.clone();
+            let args = if m.params.is_some() {
+                if !input.next_is_open_paren() {
+                    output.push(token);
+                    continue;
+                }
+                input.next_or_file();
+                let (args, close_paren) = self.collect_args(&token, &m, input)?;
+                hideset.retain(|name| close_paren.hideset.contains(name));
+                args
+            } else {
+                Vec::new()
+            };
+            hideset.push(token.text.clone());
+            let substituted = self.substitute(&m, &token, args, &hideset)?;
+            input.push_front(substituted);
+        }
+        Ok(())
+    }
+
+    fn collect_args(
+        &self,
+        name: &PpToken,
+        m: &Macro,
+        input: &mut Input,
+    ) -> Result<(Vec<Vec<PpToken>>, PpToken)> {
+        let param_count = m.params.as_ref().map_or(0, Vec::len);
+        let mut args = vec![Vec::new()];
+        let mut depth = 0;
+        let close_paren = loop {
+            let Some(token) = input.next_or_file() else {
+                return self.error(
+                    name.pos,
+                    format!("unterminated argument list invoking macro '{}'", name.text),
+                );
+            };
+            if token.is("(") {
+                depth += 1;
+            } else if token.is(")") {
+                if depth == 0 {
+                    break token;
+                }
+                depth -= 1;
+            } else if token.is(",") && depth == 0 && !(m.variadic && args.len() == param_count) {
+                args.push(Vec::new());
+                continue;
+            }
+            args.last_mut().unwrap().push(token);
+        };
+
+        if param_count == 0 && args.len() == 1 && args[0].is_empty() {
+            args.clear();
+        }
+        if m.variadic && args.len() == param_count - 1 {
+            args.push(Vec::new());
+        }
+        if args.len() != param_count {
+            let msg = if args.len() > param_count {
+                format!(
+                    "macro '{}' passed {} arguments, but takes just {param_count}",
+                    name.text,
+                    args.len()
+                )
+            } else {
+                format!(
+                    "macro '{}' requires {param_count} arguments, but only {} given",
+                    name.text,
+                    args.len()
+                )
+            };
+            return self.error(name.pos, msg);
+        }
+        Ok((args, close_paren))
+    }
+
+    fn substitute(
+        &mut self,
+        m: &Macro,
+        name: &PpToken,
+        args: Vec<Vec<PpToken>>,
+        hideset: &[Symbol],
+    ) -> Result<Vec<PpToken>> {
+        let body = &m.body;
+        let mut substituted = Vec::with_capacity(body.len());
+        let mut i = 0;
+        while i < body.len() {
+            let token = &body[i];
+            if m.params.is_some()
+                && token.is("#")
+                && let Some(param) = body.get(i + 1).and_then(|t| m.param_index(t))
+            {
+                substituted.push(PpToken {
+                    kind: PpKind::StringLiteral,
+                    text: Symbol::from(stringize(&args[param])),
+                    ..token.clone()
+                });
+                i += 2;
+                continue;
+            }
+            if token.is("##") {
+                substituted.push(PpToken {
+                    kind: PpKind::Paste,
+                    ..token.clone()
+                });
+            } else if let Some(param) = m.param_index(token) {
+                let next_to_paste =
+                    (i > 0 && body[i - 1].is("##")) || body.get(i + 1).is_some_and(|t| t.is("##"));
+                let mut arg = if next_to_paste {
+                    args[param].clone()
+                } else {
+                    self.expand_tokens(args[param].clone())?
+                };
+                match arg.first_mut() {
+                    Some(first) => first.space = token.space,
+                    None if next_to_paste => arg.push(PpToken {
+                        kind: PpKind::Placemarker,
+                        ..token.clone()
+                    }),
+                    None => {}
+                }
+                substituted.extend(arg);
+            } else {
+                substituted.push(token.clone());
+            }
+            i += 1;
+        }
+
+        let mut pasted: Vec<PpToken> = Vec::with_capacity(substituted.len());
+        let mut tokens = substituted.into_iter();
+        while let Some(token) = tokens.next() {
+            if token.kind == PpKind::Paste
+                && let Some(left) = pasted.pop()
+                && let Some(right) = tokens.next()
+            {
+                pasted.push(self.paste(name, left, right)?);
+            } else {
+                pasted.push(token);
+            }
+        }
+
+        let mut result: Vec<PpToken> = pasted
+            .into_iter()
+            .filter(|t| t.kind != PpKind::Placemarker)
+            .map(|mut t| {
+                t.pos = name.pos;
+                t.expanded = true;
+                t.hideset.extend(hideset.iter().cloned());
+                t
+            })
+            .collect();
+        if let Some(first) = result.first_mut() {
+            first.space = name.space;
+        }
+        Ok(result)
+    }
+
+    fn paste(&self, name: &PpToken, left: PpToken, right: PpToken) -> Result<PpToken> {
+        if left.kind == PpKind::Placemarker {
+            return Ok(PpToken {
+                space: left.space,
+                ..right
+            });
+        }
+        if right.kind == PpKind::Placemarker {
+            return Ok(left);
+        }
+        let text = format!("{}{}", left.text, right.text);
+        let mut lexer = PpLexer::new(&text, Vec::new(), left.pos.file);
+        let token = lexer.next();
+        if token.space || lexer.next().kind != PpKind::Eof {
+            return self.error(
+                name.pos,
+                format!(
+                    "pasting \"{}\" and \"{}\" does not give a valid preprocessing token",
+                    left.text, right.text
+                ),
+            );
+        }
+        Ok(PpToken {
+            kind: token.kind,
+            text: token.text,
+            space: left.space,
+            pos: left.pos,
+            hideset: Vec::new(),
+            expanded: true,
+        })
+    }
+
+    fn emit(&mut self, token: &PpToken) {
+        let file = self.source_map.file(token.pos.file);
+        let line = file.line_index(token.pos.offset);
+        if self.output_line != Some((token.pos.file, line)) {
+            if !self.output.is_empty() {
+                self.output.push('\n');
+            }
+            let column = token.pos.offset - file.line_start(line);
+            self.output.extend(std::iter::repeat_n(' ', column));
+            self.output_line = Some((token.pos.file, line));
+        } else if token.space {
+            // Keep the original columns between tokens that were not expanded
+            let gap = if token.expanded || self.last_expanded {
+                1
+            } else {
+                token.pos.offset.saturating_sub(self.last_end).max(1)
+            };
+            self.output.extend(std::iter::repeat_n(' ', gap));
+        } else if needs_separation(&self.last_text, &token.text) {
+            self.output.push(' ');
+        }
+        self.source_map.add_segment(
+            self.output.len(),
+            token.pos.file,
+            token.pos.offset,
+            &self.presumed_name,
+            self.line_delta,
+        );
+        self.output.push_str(token.text.as_ref());
+        self.last_end = token.pos.offset + token.text.len();
+        self.last_text = token.text.clone();
+        self.last_expanded = token.expanded;
+    }
+}
+
+/// Whether two adjacent tokens would be lexed differently if printed without
+/// whitespace between them.
+fn needs_separation(left: &Symbol, right: &Symbol) -> bool {
+    let (Some(l), Some(r)) = (left.as_ref().chars().last(), right.as_ref().chars().next()) else {
+        return false;
+    };
+    let word = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    let operator = |c: char| "+-*/%&|^<>=!#:.".contains(c);
+    (word(l) && word(r)) || (operator(l) && operator(r))
+}
+
+fn spelling(tokens: &[PpToken]) -> String {
+    let mut result = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && token.space {
+            result.push(' ');
+        }
+        result.push_str(token.text.as_ref());
+    }
+    result
+}
+
+fn unquote(literal: &Symbol) -> &str {
+    let literal = literal.as_ref();
+    &literal[1..literal.len() - 1]
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn stringize(tokens: &[PpToken]) -> String {
+    let mut result = String::from("\"");
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && token.space {
+            result.push(' ');
+        }
+        match token.kind {
+            PpKind::StringLiteral | PpKind::CharLiteral => {
+                result.push_str(&escape(token.text.as_ref()))
+            }
+            _ => result.push_str(token.text.as_ref()),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[derive(Clone, Copy)]
+struct Value {
+    value: i64,
+    unsigned: bool,
+}
+
+struct ExprParser<'a> {
+    pp: &'a Preprocessor,
+    tokens: &'a [PpToken],
+    index: usize,
+    end: Pos,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&PpToken> {
+        self.tokens.get(self.index)
+    }
+
+    fn error<T>(&self, msg: &str) -> Result<T> {
+        let pos = self.peek().map_or(self.end, |t| t.pos);
+        self.pp.error(pos, msg)
+    }
+
+    fn expect(&mut self, punctuator: &str) -> Result<()> {
+        if self.peek().is_some_and(|t| t.is(punctuator)) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(&format!(
+                "expected '{punctuator}' in preprocessor expression"
+            ))
+        }
+    }
+
+    fn expression(&mut self) -> Result<Value> {
+        let cond = self.binary(1)?;
+        if !self.peek().is_some_and(|t| t.is("?")) {
+            return Ok(cond);
+        }
+        self.index += 1;
+        let then_value = self.expression()?;
+        self.expect(":")?;
+        let else_value = self.expression()?;
+        let unsigned = then_value.unsigned || else_value.unsigned;
+        let value = if cond.value != 0 {
+            then_value.value
+        } else {
+            else_value.value
+        };
+        Ok(Value { value, unsigned })
+    }
+
+    fn precedence(token: &PpToken) -> Option<u8> {
+        if token.kind != PpKind::Punctuator {
+            return None;
+        }
+        let precedence = match token.text.as_ref() {
+            "||" => 1,
+            "&&" => 2,
+            "|" => 3,
+            "^" => 4,
+            "&" => 5,
+            "==" | "!=" => 6,
+            "<" | ">" | "<=" | ">=" => 7,
+            "<<" | ">>" => 8,
+            "+" | "-" => 9,
+            "*" | "/" | "%" => 10,
+            _ => return None,
+        };
+        Some(precedence)
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<Value> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.peek().cloned()
+            && let Some(precedence) = Self::precedence(&op)
+            && precedence >= min_precedence
+        {
+            self.index += 1;
+            let right = self.binary(precedence + 1)?;
+            left = self.apply(&op, left, right)?;
+        }
+        Ok(left)
+    }
+
+    fn apply(&self, op: &PpToken, left: Value, right: Value) -> Result<Value> {
+        let unsigned = left.unsigned || right.unsigned;
+        let (l, r) = (left.value, right.value);
+        let boolean = |b: bool| Value {
+            value: b as i64,
+            unsigned: false,
+        };
+        let compare = |signed: bool, unsigned_result: bool| {
+            if unsigned { unsigned_result } else { signed }
+        };
+        let value = match op.text.as_ref() {
+            "||" => return Ok(boolean(l != 0 || r != 0)),
+            "&&" => return Ok(boolean(l != 0 && r != 0)),
+            "==" => return Ok(boolean(l == r)),
+            "!=" => return Ok(boolean(l != r)),
+            "<" => return Ok(boolean(compare(l < r, (l as u64) < (r as u64)))),
+            ">" => return Ok(boolean(compare(l > r, (l as u64) > (r as u64)))),
+            "<=" => return Ok(boolean(compare(l <= r, (l as u64) <= (r as u64)))),
+            ">=" => return Ok(boolean(compare(l >= r, (l as u64) >= (r as u64)))),
+            "|" => l | r,
+            "^" => l ^ r,
+            "&" => l & r,
+            "<<" => l.wrapping_shl(r as u32),
+            ">>" if left.unsigned => ((l as u64).wrapping_shr(r as u32)) as i64,
+            ">>" => l.wrapping_shr(r as u32),
+            "+" => l.wrapping_add(r),
+            "-" => l.wrapping_sub(r),
+            "*" => l.wrapping_mul(r),
+            "/" | "%" if r == 0 => {
+                return self.pp.error(op.pos, "division by zero in #if");
+            }
+            "/" if unsigned => ((l as u64) / (r as u64)) as i64,
+            "/" => l.wrapping_div(r),
+            "%" if unsigned => ((l as u64) % (r as u64)) as i64,
+            "%" => l.wrapping_rem(r),
+            _ => unreachable!(),
+        };
+        Ok(Value { value, unsigned })
+    }
+
+    fn unary(&mut self) -> Result<Value> {
+        let Some(token) = self.peek().cloned() else {
+            return self.error("expected value in preprocessor expression");
+        };
+        self.index += 1;
+        match token.kind {
+            PpKind::Punctuator => match token.text.as_ref() {
+                "(" => {
+                    let value = self.expression()?;
+                    self.expect(")")?;
+                    Ok(value)
+                }
+                "-" => {
+                    let v = self.unary()?;
+                    Ok(Value {
+                        value: v.value.wrapping_neg(),
+                        ..v
+                    })
+                }
+                "+" => self.unary(),
+                "~" => {
+                    let v = self.unary()?;
+                    Ok(Value {
+                        value: !v.value,
+                        ..v
+                    })
+                }
+                "!" => {
+                    let v = self.unary()?;
+                    Ok(Value {
+                        value: (v.value == 0) as i64,
+                        unsigned: false,
+                    })
+                }
+                _ => self.pp.error(
+                    token.pos,
+                    format!(
+                        "token '{}' is not valid in preprocessor expressions",
+                        token.text
+                    ),
+                ),
+            },
+            PpKind::Number => self.number(&token),
+            PpKind::CharLiteral => Ok(Value {
+                value: char_value(token.text.as_ref()),
+                unsigned: false,
+            }),
+            // Identifiers remaining after macro expansion evaluate to zero
+            PpKind::Identifier => Ok(Value {
+                value: 0,
+                unsigned: false,
+            }),
+            _ => self.pp.error(
+                token.pos,
+                format!(
+                    "token '{}' is not valid in preprocessor expressions",
+                    token.text
+                ),
+            ),
+        }
+    }
+
+    fn number(&self, token: &PpToken) -> Result<Value> {
+        let text = token.text.as_ref();
+        let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+        let suffix = &text[digits.len()..];
+        let parsed = if let Some(hex) = digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+            u64::from_str_radix(hex, 16)
+        } else if let Some(binary) = digits.strip_prefix("0b").or(digits.strip_prefix("0B")) {
+            u64::from_str_radix(binary, 2)
+        } else if digits.len() > 1 && digits.starts_with('0') {
+            u64::from_str_radix(&digits[1..], 8)
+        } else {
+            digits.parse::<u64>()
+        };
+        let Ok(value) = parsed else {
+            return self.pp.error(
+                token.pos,
+                format!("invalid integer constant '{text}' in preprocessor expression"),
+            );
+        };
+        Ok(Value {
+            value: value as i64,
+            unsigned: suffix.contains(['u', 'U']) || value > i64::MAX as u64,
+        })
+    }
+}
+
+fn char_value(literal: &str) -> i64 {
+    let inner = &literal[1..literal.len() - 1];
+    let mut chars = inner.chars();
+    let value = match chars.next() {
+        Some('\\') => match chars.next() {
+            Some('n') => '\n' as u32,
+            Some('t') => '\t' as u32,
+            Some('r') => '\r' as u32,
+            Some('a') => 0x07,
+            Some('b') => 0x08,
+            Some('f') => 0x0c,
+            Some('v') => 0x0b,
+            Some('x') => u32::from_str_radix(chars.as_str(), 16).unwrap_or(0),
+            Some(c @ '0'..='7') => {
+                let octal = format!("{c}{}", chars.as_str());
+                u32::from_str_radix(&octal, 8).unwrap_or(0)
+            }
+            Some(c) => c as u32,
+            None => 0,
+        },
+        Some(c) => c as u32,
+        None => 0,
+    };
+    // Plain char is signed
+    value as u8 as i8 as i64
+}
+
+pub fn preprocess(path: &Path, options: &Options) -> Result<Preprocessed> {
+    let mut preprocessor = Preprocessor::new(options)?;
+    preprocessor.run_file(path.to_path_buf(), None)?;
+    Ok(preprocessor.finish())
+}
+
+#[allow(dead_code)]
+pub fn preprocess_source(name: &str, source: &str, options: &Options) -> Result<Preprocessed> {
+    let mut preprocessor = Preprocessor::new(options)?;
+    preprocessor.run(PathBuf::from(name), source.to_owned())?;
+    Ok(preprocessor.finish())
+}
@@ -0,0 +1,509 @@
+use crate::preprocessor::{Options, Preprocessed, preprocess, preprocess_source};
+use crate::pretty::dedent;
+use std::fs;
+use std::path::PathBuf;
+
+fn run(src: &str) -> Preprocessed {
+    preprocess_source("test.c", &dedent(src), &Options::default()).unwrap()
+}
+
+fn assert_preprocess(src: &str, expected: &str) {
+    assert_eq!(run(src).source.trim_end(), dedent(expected));
+}
+
+fn assert_error(src: &str, expected: &str) {
+    let Err(error) = preprocess_source("test.c", &dedent(src), &Options::default()) else {
+        panic!("Preprocessing should have failed");
+    };
+    assert_eq!(error.to_string(), expected);
+}
+
+fn assert_rendered_error(src: &str, expected: &str) {
+    let Err(error) = preprocess_source("test.c", &dedent(src), &Options::default()) else {
+        panic!("Preprocessing should have failed");
+    };
+    assert_eq!(error.render().trim_end(), dedent(expected));
+}
+
+fn location(preprocessed: &Preprocessed, needle: &str) -> String {
+    let offset = preprocessed.source.find(needle).unwrap();
+    preprocessed.source_map.location(offset).to_string()
+}
+
+#[test]
+fn test_object_like_macro() {
+    assert_preprocess(
+        r#"
+        #define SIZE 10
+        #define DOUBLE_SIZE (SIZE * 2)
+        int a[DOUBLE_SIZE];
+    "#,
+        r#"
+        int a[(10 * 2)];
+    "#,
+    );
+}
+
+#[test]
+fn test_function_like_macro() {
+    assert_preprocess(
+        r#"
+        #define max(a, b) ((a) > (b) ? (a) : (b))
+        #define inc(x) x + 1
+        int x = max(1, inc(inc(2)));
+        int max = max;
+    "#,
+        r#"
+        int x = ((1) > (2 + 1 + 1) ? (1) : (2 + 1 + 1));
+        int max = max;
+    "#,
+    );
+}
+
+#[test]
+fn test_recursive_macro_is_not_expanded() {
+    assert_preprocess(
+        r#"
+        #define foo foo + 1
+        #define a b
+        #define b a
+        int foo = a;
+    "#,
+        r#"
+        int foo + 1 = a;
+    "#,
+    );
+}
+
+#[test]
+fn test_rescanning_with_following_tokens() {
+    assert_preprocess(
+        r#"
+        #define f(x) g(x)
+        #define g(x) x * 2
+        #define h f
+        int y = h(3);
+    "#,
+        r#"
+        int y = 3 * 2;
+    "#,
+    );
+}
+
+#[test]
+fn test_arguments_spanning_lines() {
+    assert_preprocess(
+        r#"
+        #define add(a, b) a + b
+        int x = add(1,
+                    2);
+        int y;
+    "#,
+        r#"
+        int x = 1 + 2
+                      ;
+        int y;
+    "#,
+    );
+}
+
+#[test]
+fn test_stringizing() {
+    assert_preprocess(
+        r#"
+        #define str(x) #x
+        char *s = str(a   +  "b\n");
+        char *e = str();
+    "#,
+        r#"
+        char *s = "a + \"b\\n\"";
+        char *e = "";
+    "#,
+    );
+}
+
+#[test]
+fn test_token_pasting() {
+    assert_preprocess(
+        r#"
+        #define cat(a, b) a ## b
+        #define var(n) x ## n ## _value
+        int cat(foo, 1) = cat(, 2) + cat(3,);
+        int var(7);
+    "#,
+        r#"
+        int foo1 = 2 + 3;
+        int x7_value;
+    "#,
+    );
+}
+
+#[test]
+fn test_pasted_arguments_are_not_expanded() {
+    assert_preprocess(
+        r#"
+        #define N 5
+        #define cat(a, b) a ## b
+        #define xcat(a, b) cat(a, b)
+        int cat(N, 1) = xcat(N, 1);
+    "#,
+        r#"
+        int N1 = 51;
+    "#,
+    );
+}
+
+#[test]
+fn test_variadic_macro() {
+    assert_preprocess(
+        r#"
+        #define call(f, ...) f(__VA_ARGS__)
+        int x = call(sum, 1, 2, 3) + call(zero);
+    "#,
+        r#"
+        int x = sum(1, 2, 3) + zero();
+    "#,
+    );
+}
+
+#[test]
+fn test_conditionals() {
+    assert_preprocess(
+        r#"
+        #define VERSION 3
+        #if VERSION > 4
+        int a;
+        #elif defined(VERSION) && VERSION * 2 == 6
+        int b;
+        #else
+        int c;
+        #endif
+        #if !defined UNDEFINED || UNDEFINED
+        int d;
+        #endif
+        #if (0x10 >> 2) == 4 && -1 < 0 && -1 > 0u && 'a' == 97
+        int e;
+        #endif
+    "#,
+        r#"
+        int b;
+        int d;
+        int e;
+    "#,
+    );
+}
+
+#[test]
+fn test_nested_conditionals() {
+    assert_preprocess(
+        r#"
+        #define A
+        #ifdef B
+        #  ifdef A
+        int a;
+        #  else
+        #    error "not reached"
+        #  endif
+        #elif 1
+        #  ifndef B
+        int b;
+        #  endif
+        #endif
+        #if 0
+        don't care about 'unterminated literals here
+        #bogus directive
+        #endif
+    "#,
+        r#"
+        int b;
+    "#,
+    );
+}
+
+#[test]
+fn test_undef() {
+    assert_preprocess(
+        r#"
+        #define X 1
+        int a = X;
+        #undef X
+        int b = X;
+    "#,
+        r#"
+        int a = 1;
+        int b = X;
+    "#,
+    );
+}
+
+#[test]
+fn test_comments_and_line_splicing() {
+    assert_preprocess(
+        r#"
+        #define LONG 1 + \
+            2 /* a comment
+            over lines */ + 3 // trailing
+        int x = LONG;
+        int /* inline */ y;
+    "#,
+        r#"
+        int x = 1 + 2 + 3;
+        int              y;
+    "#,
+    );
+}
+
+#[test]
+fn test_line_and_file() {
+    assert_preprocess(
+        r#"
+        int a = __LINE__;
+        #line 100 "other.c"
+        int b = __LINE__;
+        char *f = __FILE__;
+    "#,
+        r#"
+        int a = 1;
+        int b = 100;
+        char *f = "other.c";
+    "#,
+    );
+}
+
+#[test]
+fn test_source_map() {
+    let preprocessed = run(r#"
+        #define ZERO 0
+
+        int main(void) {
+            return ZERO;
+        }
+        #line 20 "renamed.c"
+        int after;
+    "#);
+    assert_eq!(location(&preprocessed, "main"), "test.c:3:5");
+    assert_eq!(location(&preprocessed, "return"), "test.c:4:5");
+    assert_eq!(location(&preprocessed, "0"), "test.c:4:12");
+    assert_eq!(location(&preprocessed, "after"), "renamed.c:20:5");
+}
+
+#[test]
+fn test_include() {
+    let dir = std::env::temp_dir().join(format!("preprocessor_test_{}", std::process::id()));
+    let include_dir = dir.join("include");
+    fs::create_dir_all(&include_dir).unwrap();
+    fs::write(
+        dir.join("local.h"),
+        "#pragma once\n#define LOCAL 1\nint local;\n",
+    )
+    .unwrap();
+    fs::write(include_dir.join("system.h"), "int system_value;\n").unwrap();
+    let main = dir.join("main.c");
+    fs::write(
+        &main,
+        "#include \"local.h\"\n#include \"local.h\"\n#include <system.h>\nint x = LOCAL;\n",
+    )
+    .unwrap();
+
+    let options = Options {
+        include_paths: vec![PathBuf::from(&include_dir)],
+        defines: vec![],
+    };
+    let preprocessed = preprocess(&main, &options).unwrap();
+    assert_eq!(
+        preprocessed.source,
+        "int local;\nint system_value;\nint x = 1;\n"
+    );
+    let system_location = location(&preprocessed, "system_value");
+    assert!(system_location.ends_with("system.h:1:5"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_command_line_defines() {
+    let options = Options {
+        include_paths: vec![],
+        defines: vec![("DEBUG".into(), "1".into()), ("LEVEL".into(), "2".into())],
+    };
+    let preprocessed = preprocess_source(
+        "test.c",
+        "#if DEBUG\nint level = LEVEL;\n#endif\n",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(preprocessed.source, "int level = 2;\n");
+}
+
+#[test]
+fn test_builtin_stdarg() {
+    assert_preprocess(
+        r#"
+        #include <stdarg.h>
+        #include <stdarg.h>
+        void f(int n, ...) {
+            va_list ap;
+            va_start(ap, n);
+            va_end(ap);
+        }
+    "#,
+        r#"
+        typedef __builtin_va_list va_list;
+        typedef __builtin_va_list __gnuc_va_list;
+        void f(int n, ...) {
+            va_list ap;
+            __builtin_va_start(ap, n);
+            __builtin_va_end(ap);
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_builtin_stddef_with_need_macros() {
+    assert_preprocess(
+        r#"
+        #define __need_size_t
+        #include <stddef.h>
+        #ifdef __need_size_t
+        int leaked;
+        #endif
+        #include <stddef.h>
+        size_t n = offsetof(struct s, m);
+    "#,
+        r#"
+        typedef unsigned long size_t;
+        typedef long ptrdiff_t;
+        typedef int wchar_t;
+        typedef struct __max_align_t {
+            long long __max_align_ll __attribute__((aligned(16)));
+            double __max_align_d __attribute__((aligned(16)));
+        } max_align_t;
+        size_t n = ((size_t)&((struct s *)0)->m);
+    "#,
+    );
+}
+
+#[test]
+fn test_builtin_limits_and_stdint() {
+    let preprocessed = run(r#"
+        #include <limits.h>
+        #include <stdint.h>
+        #include <stdbool.h>
+        #if INT_MAX == INT32_MAX && SIZE_MAX == ULONG_MAX && true
+        int64_t x = INT64_C(1);
+        #endif
+    "#);
+    assert!(
+        preprocessed
+            .source
+            .starts_with("typedef signed char int8_t;")
+    );
+    assert!(
+        preprocessed
+            .source
+            .trim_end()
+            .ends_with("\nint64_t x = 1L;")
+    );
+}
+
+#[test]
+fn test_error_directive() {
+    assert_error(
+        r#"
+        int a;
+        #error "unsupported platform"
+    "#,
+        r#"test.c:2:2: error: #error "unsupported platform""#,
+    );
+}
+
+#[test]
+fn test_unterminated_conditional() {
+    assert_error(
+        r#"
+        #ifdef X
+        int a;
+    "#,
+        "test.c:1:1: error: unterminated conditional directive",
+    );
+}
+
+#[test]
+fn test_else_without_if() {
+    assert_error(
+        r#"
+        int a;
+        #else
+    "#,
+        "test.c:2:2: error: #else without #if",
+    );
+}
+
+#[test]
+fn test_wrong_number_of_arguments() {
+    assert_error(
+        r#"
+        #define f(a, b) a + b
+        int x = f(1);
+    "#,
+        "test.c:2:9: error: macro 'f' requires 2 arguments, but only 1 given",
+    );
+}
+
+#[test]
+fn test_unterminated_macro_call() {
+    assert_error(
+        r#"
+        #define f(a) a
+        int x = f(1;
+    "#,
+        "test.c:2:9: error: unterminated argument list invoking macro 'f'",
+    );
+}
+
+#[test]
+fn test_invalid_paste() {
+    assert_error(
+        r#"
+        #define cat(a, b) a ## b
+        int x = cat(+, /);
+    "#,
+        "test.c:2:9: error: pasting \"+\" and \"/\" does not give a valid preprocessing token",
+    );
+}
+
+#[test]
+fn test_missing_include() {
+    assert_error(
+        r#"
+        #include "does_not_exist.h"
+    "#,
+        "test.c:1:2: error: 'does_not_exist.h' file not found",
+    );
+}
+
+#[test]
+fn test_invalid_directive() {
+    assert_error(
+        r#"
+        #foo
+    "#,
+        "test.c:1:2: error: invalid preprocessing directive #foo",
+    );
+}
+
+#[test]
+fn test_error_snippet() {
+    assert_rendered_error(
+        r#"
+        int a;
+        #include <missing.h>
+    "#,
+        r#"
+        test.c:2:2: error: 'missing.h' file not found
+         2 | #include <missing.h>
+           |  ^
+    "#,
+    );
+}
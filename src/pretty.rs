@@ -1,22 +1,32 @@
 use crate::ast;
+use crate::optimization::{self, OptimizationFlags};
 use crate::parser;
 use crate::semantic;
+use crate::source_map::SourceFile;
 use crate::tacky;
 
 #[allow(dead_code)]
 pub fn dump_ast(src: &str) -> String {
-    let ast = parser::parse(src).unwrap();
+    let ast = parser::parse(src, parser::DEFAULT_ERROR_LIMIT).unwrap();
     ast::pretty::dump(&ast).unwrap()
 }
 
 #[allow(dead_code)]
 pub fn dump_tacky(src: &str) -> String {
-    let ast = parser::parse(src).unwrap();
+    let ast = parser::parse(src, parser::DEFAULT_ERROR_LIMIT).unwrap();
     let (ast, semantic_data) = semantic::validate(ast).unwrap();
     let tacky = tacky::emit(&ast, semantic_data);
     tacky::pretty::pp(&tacky).unwrap().trim().to_owned()
 }
 
+#[allow(dead_code)]
+pub fn dump_optimized_tacky(src: &str, flags: &OptimizationFlags) -> String {
+    let ast = parser::parse(src, parser::DEFAULT_ERROR_LIMIT).unwrap();
+    let (ast, semantic_data) = semantic::validate(ast).unwrap();
+    let tacky = optimization::optimize(tacky::emit(&ast, semantic_data), flags);
+    tacky::pretty::pp(&tacky).unwrap().trim().to_owned()
+}
+
 #[allow(dead_code)]
 pub fn dedent(tree: &str) -> String {
     tree.trim()
@@ -34,6 +44,21 @@ pub fn remove_annotation(src: &str) -> String {
         .join("\n")
 }
 
+/// Lists diagnostics as `line:column: message`, sorted by location.
+#[allow(dead_code)]
+pub fn diagnostics(src: &str, errors: &[crate::error::CompilerError]) -> Vec<String> {
+    let file = SourceFile::new("test.c".into(), src.to_owned());
+    let mut errors: Vec<_> = errors.iter().collect();
+    errors.sort_by_key(|error| error.span.0);
+    errors
+        .iter()
+        .map(|error| {
+            let (line, column) = file.line_column(error.span.0);
+            format!("{line}:{column}: {}", error.msg)
+        })
+        .collect()
+}
+
 #[allow(dead_code)]
 pub fn annotate(src: &str, error: &crate::error::CompilerError) -> String {
     let mut result = String::new();
@@ -1,9 +1,13 @@
-use crate::ast::{Constant, Expression, FunctionTypeSpec, Node, NodeId, Program, TypeSpec};
-use crate::error::Result;
+use crate::ast::{
+    Constant, Expression, FunctionTypeSpec, Node, NodeId, Program, Qualifiers, TypeSpec,
+};
+use crate::error::{CompilerError, Result};
 use crate::symbol::Symbol;
 use crate::tacky;
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 
+mod constant_eval;
 mod id_resolution;
 mod label_check;
 mod type_check;
@@ -13,28 +17,58 @@ mod test;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Type {
+    Bool,
     Char,
     SChar,
     UChar,
+    Short,
+    UShort,
     Int,
     UInt,
     Long,
     ULong,
     Double,
+    Float,
     Function(FunctionType),
     Pointer(Box<Type>),
     Array(Box<Type>, usize),
+    VariableArray(Box<Type>, VariableLength),
     Struct(Symbol),
     Union(Symbol),
     Void,
+    /// Never wraps another qualified type nor an array, the qualifiers of an
+    /// array apply to its elements.
+    Qualified(Qualifiers, Box<Type>),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct FunctionType {
     pub params: Vec<Type>,
     pub ret: Box<Type>,
+    pub variadic: bool,
 }
 
+/// Hidden local variable holding the length of a variable length array,
+/// computed when its declaration is reached. Lengths are only known at
+/// runtime, so any two of them compare equal.
+#[derive(Debug, Clone, Eq)]
+pub struct VariableLength(pub Symbol);
+
+impl VariableLength {
+    pub fn of(size: &Node<Expression>) -> Self {
+        VariableLength(format!("vla_length.{}", size.id).into())
+    }
+}
+
+impl PartialEq for VariableLength {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Tag of the structure behind `__builtin_va_list`, following the System V ABI.
+pub const VA_LIST_TAG: &str = "__va_list_tag";
+
 #[derive(Debug, Clone)]
 pub struct AggregateType {
     pub kind: AggregateKind,
@@ -54,19 +88,83 @@ pub struct Field {
     pub name: Symbol,
     pub ty: Type,
     pub offset: usize,
+    pub bit_field: Option<BitField>,
+}
+
+/// Bit-fields live in a storage unit of the size of their type, placed at
+/// the offset of the field.
+#[derive(Debug, Clone, Copy)]
+pub struct BitField {
+    /// Position of the first bit inside the storage unit.
+    pub offset: u8,
+    pub width: u8,
 }
 
 impl Type {
+    pub fn qualified(self, qualifiers: Qualifiers) -> Type {
+        if qualifiers.is_empty() {
+            return self;
+        }
+        match self {
+            Type::Qualified(inner_qualifiers, inner) => {
+                Type::Qualified(inner_qualifiers.union(qualifiers), inner)
+            }
+            Type::Array(inner, size) => Type::Array(inner.qualified(qualifiers).into(), size),
+            Type::VariableArray(inner, length) => {
+                Type::VariableArray(inner.qualified(qualifiers).into(), length)
+            }
+            ty => Type::Qualified(qualifiers, ty.into()),
+        }
+    }
+
+    /// The type without its top level qualifiers.
+    pub fn unqualified(&self) -> &Type {
+        match self {
+            Type::Qualified(_, inner) => inner,
+            ty => ty,
+        }
+    }
+
+    pub fn qualifiers(&self) -> Qualifiers {
+        match self {
+            Type::Qualified(qualifiers, _) => *qualifiers,
+            _ => Qualifiers::default(),
+        }
+    }
+
+    pub fn is_const(&self) -> bool {
+        self.qualifiers().is_const
+    }
+
+    pub fn is_volatile(&self) -> bool {
+        self.qualifiers().is_volatile
+    }
+
+    pub fn is_pointer_to_volatile(&self) -> bool {
+        matches!(self.unqualified(), Type::Pointer(inner) if inner.is_volatile())
+    }
+
     pub fn is_char(&self) -> bool {
-        matches!(self, Type::Char | Type::SChar | Type::UChar)
+        matches!(self.unqualified(), Type::Char | Type::SChar | Type::UChar)
+    }
+
+    /// Types smaller than `int`, which are promoted to `int` in arithmetic.
+    pub fn is_promotable(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Type::Bool | Type::Char | Type::SChar | Type::UChar | Type::Short | Type::UShort
+        )
     }
 
     pub fn is_int(&self) -> bool {
         matches!(
-            self,
-            Type::Char
+            self.unqualified(),
+            Type::Bool
+                | Type::Char
                 | Type::SChar
                 | Type::UChar
+                | Type::Short
+                | Type::UShort
                 | Type::Int
                 | Type::UInt
                 | Type::Long
@@ -75,19 +173,23 @@ impl Type {
     }
 
     pub fn is_double(&self) -> bool {
-        matches!(self, Type::Double)
+        matches!(self.unqualified(), Type::Double)
+    }
+
+    pub fn is_floating(&self) -> bool {
+        matches!(self.unqualified(), Type::Double | Type::Float)
     }
 
     pub fn is_void(&self) -> bool {
-        matches!(self, Type::Void)
+        matches!(self.unqualified(), Type::Void)
     }
 
     pub fn is_function(&self) -> bool {
-        matches!(self, Type::Function(_))
+        matches!(self.unqualified(), Type::Function(_))
     }
 
     pub fn is_aggregate(&self) -> bool {
-        matches!(self, Type::Struct(_) | Type::Union(_))
+        matches!(self.unqualified(), Type::Struct(_) | Type::Union(_))
     }
 
     pub fn is_incomplete_aggregate(&self, semantics: &SemanticData) -> bool {
@@ -96,36 +198,44 @@ impl Type {
 
     pub fn is_arithmetic(&self) -> bool {
         matches!(
-            self,
-            Type::Char
+            self.unqualified(),
+            Type::Bool
+                | Type::Char
                 | Type::SChar
                 | Type::UChar
+                | Type::Short
+                | Type::UShort
                 | Type::Int
                 | Type::UInt
                 | Type::Long
                 | Type::ULong
                 | Type::Double
+                | Type::Float
         )
     }
 
     pub fn is_scalar(&self) -> bool {
         matches!(
-            self,
-            Type::Char
+            self.unqualified(),
+            Type::Bool
+                | Type::Char
                 | Type::SChar
                 | Type::UChar
+                | Type::Short
+                | Type::UShort
                 | Type::Int
                 | Type::UInt
                 | Type::Long
                 | Type::ULong
                 | Type::Double
+                | Type::Float
                 | Type::Pointer(_)
         )
     }
 
     pub fn is_complete(&self, semantics: &SemanticData) -> bool {
-        match self {
-            Type::Void => false,
+        match self.unqualified() {
+            Type::Void | Type::Function(_) => false,
             Type::Struct(name) | Type::Union(name) => {
                 matches!(semantics.type_defs.get(name), Some(TypeEntry::Complete(_)))
             }
@@ -134,63 +244,173 @@ impl Type {
     }
 
     pub fn is_pointer(&self) -> bool {
-        matches!(self, Type::Pointer(_))
-    }
-
-    pub fn is_pointer_to_void(&self) -> bool {
-        matches!(self, Type::Pointer(inner) if inner.is_void())
+        matches!(self.unqualified(), Type::Pointer(_))
     }
 
     pub fn is_pointer_to_incomplete(&self, semantics: &SemanticData) -> bool {
-        matches!(self, Type::Pointer(inner) if !inner.is_complete(semantics))
+        matches!(self.unqualified(), Type::Pointer(inner) if !inner.is_complete(semantics))
     }
 
     pub fn is_array(&self) -> bool {
-        matches!(self, Type::Array(_, _))
+        matches!(
+            self.unqualified(),
+            Type::Array(_, _) | Type::VariableArray(_, _)
+        )
+    }
+
+    /// Arrays whose size is only known at runtime, which includes arrays of
+    /// variable length arrays.
+    pub fn is_variable_length_array(&self) -> bool {
+        match self.unqualified() {
+            Type::VariableArray(..) => true,
+            Type::Array(inner, _) => inner.is_variable_length_array(),
+            _ => false,
+        }
+    }
+
+    /// Types derived from a variable length array.
+    pub fn is_variably_modified(&self) -> bool {
+        match self.unqualified() {
+            Type::VariableArray(..) => true,
+            Type::Array(inner, _) | Type::Pointer(inner) => inner.is_variably_modified(),
+            _ => false,
+        }
+    }
+
+    /// Like equality, except that a variable length array is compatible
+    /// with any array of compatible elements, since its length is only
+    /// known at runtime.
+    pub fn is_compatible(&self, other: &Type) -> bool {
+        match (self, other) {
+            (
+                Type::VariableArray(inner, _),
+                Type::Array(other, _) | Type::VariableArray(other, _),
+            )
+            | (Type::Array(inner, _), Type::VariableArray(other, _))
+            | (Type::Pointer(inner), Type::Pointer(other)) => inner.is_compatible(other),
+            (Type::Qualified(qualifiers, inner), Type::Qualified(other_qualifiers, other)) => {
+                qualifiers == other_qualifiers && inner.is_compatible(other)
+            }
+            _ => self == other,
+        }
     }
 
     pub fn is_signed(&self) -> bool {
-        match self {
-            Type::Int | Type::Long | Type::Char | Type::SChar => true,
-            Type::UInt | Type::ULong | Type::Double | Type::Pointer(_) | Type::UChar => false,
+        match self.unqualified() {
+            Type::Int | Type::Long | Type::Char | Type::SChar | Type::Short => true,
+            Type::UInt
+            | Type::ULong
+            | Type::Double
+            | Type::Float
+            | Type::Pointer(_)
+            | Type::Bool
+            | Type::UChar
+            | Type::UShort => false,
+            // Variables of variable length array type hold an address
+            ty if ty.is_variable_length_array() => false,
             _ => panic!("{self:?} does not have a sign"),
         }
     }
 }
 
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Bool => write!(f, "_Bool"),
+            Type::Char => write!(f, "char"),
+            Type::SChar => write!(f, "signed char"),
+            Type::UChar => write!(f, "unsigned char"),
+            Type::Short => write!(f, "short"),
+            Type::UShort => write!(f, "unsigned short"),
+            Type::Int => write!(f, "int"),
+            Type::UInt => write!(f, "unsigned int"),
+            Type::Long => write!(f, "long"),
+            Type::ULong => write!(f, "unsigned long"),
+            Type::Double => write!(f, "double"),
+            Type::Float => write!(f, "float"),
+            Type::Void => write!(f, "void"),
+            Type::Pointer(inner) => write!(f, "{inner} *"),
+            Type::Qualified(qualifiers, inner) if inner.is_pointer() => {
+                write!(f, "{inner}{qualifiers}")
+            }
+            Type::Qualified(qualifiers, inner) => write!(f, "{qualifiers} {inner}"),
+            Type::Array(inner, size) => write!(f, "{inner}[{size}]"),
+            Type::VariableArray(inner, _) => write!(f, "{inner}[*]"),
+            Type::Struct(tag) => write!(f, "struct {}", source_name(tag)),
+            Type::Union(tag) => write!(f, "union {}", source_name(tag)),
+            Type::Function(function) => {
+                let mut params: Vec<_> = function.params.iter().map(|p| p.to_string()).collect();
+                if function.variadic {
+                    params.push("...".to_string());
+                }
+                write!(f, "{} ({})", function.ret, params.join(", "))
+            }
+        }
+    }
+}
+
 impl TypeSpec {
-    pub fn ty(&self) -> Type {
+    pub fn ty(&self, semantics: &SemanticData) -> Type {
         match self {
+            TypeSpec::Bool => Type::Bool,
             TypeSpec::Char => Type::Char,
             TypeSpec::SChar => Type::SChar,
             TypeSpec::UChar => Type::UChar,
+            TypeSpec::Short => Type::Short,
+            TypeSpec::UShort => Type::UShort,
             TypeSpec::Int => Type::Int,
             TypeSpec::UInt => Type::UInt,
             TypeSpec::Long => Type::Long,
             TypeSpec::ULong => Type::ULong,
             TypeSpec::Double => Type::Double,
-            TypeSpec::Function(ty) => Type::Function(ty.ty()),
-            TypeSpec::Pointer(ty) => Type::Pointer(ty.ty().into()),
-            TypeSpec::Array(ty, size) => Type::Array(ty.ty().into(), *size),
+            TypeSpec::Float => Type::Float,
+            TypeSpec::Function(ty) => Type::Function(ty.ty(semantics)),
+            TypeSpec::Pointer(ty) => Type::Pointer(ty.ty(semantics).into()),
+            TypeSpec::Array(ty, size) => Type::Array(ty.ty(semantics).into(), *size),
+            TypeSpec::VariableArray(ty, size) => match semantics.array_sizes.get(&size.id) {
+                Some(length) => Type::Array(ty.ty(semantics).into(), *length),
+                None => Type::VariableArray(ty.ty(semantics).into(), VariableLength::of(size)),
+            },
+            // Empty until the initializer is checked
+            TypeSpec::UnsizedArray(ty) => Type::Array(
+                ty.ty(semantics).into(),
+                semantics.array_sizes.get(&ty.id).copied().unwrap_or(0),
+            ),
             TypeSpec::Struct(tag) => Type::Struct(tag.symbol.clone()),
             TypeSpec::Union(tag) => Type::Union(tag.symbol.clone()),
+            TypeSpec::Enum(_) => Type::Int,
+            TypeSpec::Named(name) => semantics.type_aliases[&name.symbol].clone(),
             TypeSpec::Void => Type::Void,
+            TypeSpec::Qualified(qualifiers, ty) => ty.ty(semantics).qualified(*qualifiers),
+            TypeSpec::VaList => Type::Array(Type::Struct(VA_LIST_TAG.into()).into(), 1),
         }
     }
 }
 
 impl FunctionTypeSpec {
-    fn ty(&self) -> FunctionType {
+    fn ty(&self, semantics: &SemanticData) -> FunctionType {
         FunctionType {
-            params: self.params.iter().map(|t| t.ty()).collect(),
-            ret: self.ret.ty().into(),
+            params: (0..self.params.len())
+                .map(|i| self.param_ty(i, semantics))
+                .collect(),
+            ret: self.ret.ty(semantics).unqualified().clone().into(),
+            variadic: self.variadic,
+        }
+    }
+
+    pub fn param_ty(&self, index: usize, semantics: &SemanticData) -> Type {
+        // Array and function parameters are adjusted to pointers
+        match self.params[index].ty(semantics) {
+            Type::Array(inner, _) | Type::VariableArray(inner, _) => Type::Pointer(inner),
+            ty @ Type::Function(_) => Type::Pointer(ty.into()),
+            ty => ty.unqualified().clone(),
         }
     }
 }
 
 impl Constant {
     pub fn from_char(c: char, ty: &Type) -> Constant {
-        match ty {
+        match ty.unqualified() {
             Type::Char | Type::SChar => Constant::Char(c as i8),
             Type::UChar => Constant::UChar(c as u8),
             _ => panic!("Cannot convert char to {ty:?}"),
@@ -201,18 +421,25 @@ impl Constant {
         match self {
             Constant::Char(_) => Type::Char,
             Constant::UChar(_) => Type::UChar,
+            Constant::Short(_) => Type::Short,
+            Constant::UShort(_) => Type::UShort,
             Constant::Int(_) => Type::Int,
             Constant::UInt(_) => Type::UInt,
             Constant::Long(_) => Type::Long,
             Constant::ULong(_) => Type::ULong,
             Constant::Double(_) => Type::Double,
+            Constant::Float(_) => Type::Float,
         }
     }
 
     pub fn cast(&self, target: &Type) -> Option<Constant> {
-        let c = match (self, target) {
+        let c = match (self, target.unqualified()) {
+            // Booleans are stored as a byte holding 0 or 1
+            (c, Type::Bool) => Constant::UChar(!c.is_zero() as u8),
             (c, Type::Char | Type::SChar) if c.is_int() => Constant::Char(c.as_u64() as i8),
             (c, Type::UChar) if c.is_int() => Constant::UChar(c.as_u64() as u8),
+            (c, Type::Short) if c.is_int() => Constant::Short(c.as_u64() as i16),
+            (c, Type::UShort) if c.is_int() => Constant::UShort(c.as_u64() as u16),
             (c, Type::Int) if c.is_int() => Constant::Int(c.as_u64() as i32),
             (c, Type::UInt) if c.is_int() => Constant::UInt(c.as_u64() as u32),
             (c, Type::Long) if c.is_int() => Constant::Long(c.as_u64() as i64),
@@ -220,18 +447,43 @@ impl Constant {
 
             (Constant::Char(value), Type::Double) => Constant::Double(*value as f64),
             (Constant::UChar(value), Type::Double) => Constant::Double(*value as f64),
+            (Constant::Short(value), Type::Double) => Constant::Double(*value as f64),
+            (Constant::UShort(value), Type::Double) => Constant::Double(*value as f64),
             (Constant::Int(value), Type::Double) => Constant::Double(*value as f64),
             (Constant::UInt(value), Type::Double) => Constant::Double(*value as f64),
             (Constant::Long(value), Type::Double) => Constant::Double(*value as f64),
             (Constant::ULong(value), Type::Double) => Constant::Double(*value as f64),
 
+            (Constant::Char(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::UChar(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::Short(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::UShort(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::Int(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::UInt(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::Long(value), Type::Float) => Constant::Float(*value as f32),
+            (Constant::ULong(value), Type::Float) => Constant::Float(*value as f32),
+
             (Constant::Double(value), Type::Int) => Constant::Int(*value as i32),
             (Constant::Double(value), Type::Char | Type::SChar) => Constant::Char(*value as i8),
             (Constant::Double(value), Type::UChar) => Constant::UChar(*value as u8),
+            (Constant::Double(value), Type::Short) => Constant::Short(*value as i16),
+            (Constant::Double(value), Type::UShort) => Constant::UShort(*value as u16),
             (Constant::Double(value), Type::UInt) => Constant::UInt(*value as u32),
             (Constant::Double(value), Type::Long) => Constant::Long(*value as i64),
             (Constant::Double(value), Type::ULong) => Constant::ULong(*value as u64),
             (Constant::Double(value), Type::Double) => Constant::Double(*value),
+            (Constant::Double(value), Type::Float) => Constant::Float(*value as f32),
+
+            (Constant::Float(value), Type::Char | Type::SChar) => Constant::Char(*value as i8),
+            (Constant::Float(value), Type::UChar) => Constant::UChar(*value as u8),
+            (Constant::Float(value), Type::Short) => Constant::Short(*value as i16),
+            (Constant::Float(value), Type::UShort) => Constant::UShort(*value as u16),
+            (Constant::Float(value), Type::Int) => Constant::Int(*value as i32),
+            (Constant::Float(value), Type::UInt) => Constant::UInt(*value as u32),
+            (Constant::Float(value), Type::Long) => Constant::Long(*value as i64),
+            (Constant::Float(value), Type::ULong) => Constant::ULong(*value as u64),
+            (Constant::Float(value), Type::Double) => Constant::Double(*value as f64),
+            (Constant::Float(value), Type::Float) => Constant::Float(*value),
             (
                 Constant::Int(0) | Constant::Long(0) | Constant::UInt(0) | Constant::ULong(0),
                 Type::Pointer(_),
@@ -248,8 +500,11 @@ impl Constant {
             Constant::Long(v) => StaticInit::Long(*v),
             Constant::ULong(v) => StaticInit::ULong(*v),
             Constant::Double(v) => StaticInit::Double(*v),
+            Constant::Float(v) => StaticInit::Float(*v),
             Constant::Char(v) => StaticInit::Char(*v),
             Constant::UChar(v) => StaticInit::UChar(*v),
+            Constant::Short(v) => StaticInit::Short(*v),
+            Constant::UShort(v) => StaticInit::UShort(*v),
         }
     }
 }
@@ -258,6 +513,19 @@ impl Constant {
 pub enum TypeEntry {
     Incomplete(AggregateKind),
     Complete(AggregateType),
+    Enum,
+}
+
+/// Initializer of a local variable after resolving designators and elided
+/// braces, shaped like the initialized type.
+#[derive(Debug, Clone)]
+pub enum InitTree {
+    /// Initializes a scalar, a whole struct or union, or a char array from a
+    /// string literal.
+    Single(NodeId),
+    /// Elements of an array or fields of a struct or union, missing ones are
+    /// zero initialized. At most one field of a union is initialized.
+    Aggregate(Vec<Option<InitTree>>),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -267,15 +535,50 @@ pub struct SemanticData {
     pub expression_types: HashMap<NodeId, Type>,
     pub assignment_common_types: HashMap<NodeId, Type>,
     pub type_defs: HashMap<Symbol, TypeEntry>,
+    pub type_aliases: HashMap<Symbol, Type>,
     pub implicit_casts: HashMap<NodeId, Type>,
     pub pointer_decays: HashMap<NodeId, Type>,
     pub switch_cases: HashMap<NodeId, SwitchCases>,
+    pub initializers: HashMap<NodeId, InitTree>,
+    pub compound_literals: HashMap<NodeId, Symbol>,
+    /// Lengths of the arrays whose size is an integer constant expression
+    /// other than a literal, by the id of the expression. Arrays sized by
+    /// their initializer are found by the id of their element type instead.
+    pub array_sizes: HashMap<NodeId, usize>,
+    pub enumerators: HashMap<Symbol, i32>,
+    pub warnings: Vec<CompilerError>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SymbolData {
     pub ty: Type,
     pub attrs: Attributes,
+    pub decl_attrs: DeclAttributes,
+}
+
+/// Attributes given with `_Noreturn` or `__attribute__`, accumulated over
+/// all the declarations of a symbol.
+#[derive(Debug, Clone, Default)]
+pub struct DeclAttributes {
+    pub noreturn: bool,
+    pub aligned: Option<u8>,
+    pub packed: bool,
+    pub unused: bool,
+    pub weak: bool,
+    pub section: Option<Symbol>,
+}
+
+impl DeclAttributes {
+    pub fn merge(&mut self, other: DeclAttributes) {
+        self.noreturn |= other.noreturn;
+        self.aligned = self.aligned.max(other.aligned);
+        self.packed |= other.packed;
+        self.unused |= other.unused;
+        self.weak |= other.weak;
+        if other.section.is_some() {
+            self.section = other.section;
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -287,6 +590,8 @@ pub enum Attributes {
     Static {
         initial_value: InitialValue,
         global: bool,
+        /// Declared `_Thread_local`, each thread has its own instance.
+        thread_local: bool,
     },
     Const {
         init: StaticInit,
@@ -311,17 +616,23 @@ impl InitialValue {
 pub enum StaticInit {
     Char(i8),
     UChar(u8),
+    Short(i16),
+    UShort(u16),
     Int(i32),
     UInt(u32),
     Long(i64),
     ULong(u64),
     Double(f64),
+    Float(f32),
     ZeroInit(usize),
     String {
         symbol: Symbol,
         null_terminated: bool,
     },
-    Pointer(Symbol),
+    /// Address of a string constant, plus an offset in bytes.
+    Pointer(Symbol, i64),
+    /// Address of a static object, plus an offset in bytes.
+    Address(Symbol, i64),
 }
 
 #[derive(Debug, Clone)]
@@ -374,6 +685,7 @@ impl SemanticData {
                                 null_terminated: true,
                             },
                         },
+                        decl_attrs: DeclAttributes::default(),
                     },
                 );
                 name
@@ -381,6 +693,19 @@ impl SemanticData {
         }
     }
 
+    /// Thread local variables defined in another translation unit, which
+    /// are only accessed through their address.
+    pub fn is_external_thread_local(&self, symbol: &Symbol) -> bool {
+        matches!(
+            self.symbols.get(symbol).map(|data| &data.attrs),
+            Some(Attributes::Static {
+                initial_value: InitialValue::NoInitializer,
+                thread_local: true,
+                ..
+            })
+        )
+    }
+
     pub fn symbol_ty(&self, symbol: &Symbol) -> &Type {
         &self.symbols.get(symbol).expect("Symbol without type").ty
     }
@@ -394,24 +719,28 @@ impl SemanticData {
 
     pub fn is_signed(&self, val: &tacky::Val) -> bool {
         match val {
-            tacky::Val::Constant(Constant::Int(_) | Constant::Long(_) | Constant::Char(_)) => true,
-            tacky::Val::Constant(Constant::UInt(_) | Constant::ULong(_) | Constant::UChar(_)) => {
-                false
-            }
-            tacky::Val::Constant(Constant::Double(_)) => true,
+            tacky::Val::Constant(
+                Constant::Int(_) | Constant::Long(_) | Constant::Char(_) | Constant::Short(_),
+            ) => true,
+            tacky::Val::Constant(
+                Constant::UInt(_) | Constant::ULong(_) | Constant::UChar(_) | Constant::UShort(_),
+            ) => false,
+            tacky::Val::Constant(Constant::Double(_) | Constant::Float(_)) => true,
             tacky::Val::Var(name) => self.symbol_ty(name).is_signed(),
         }
     }
 
     pub fn common_type(&self, ty1: &Type, ty2: &Type) -> Type {
-        // Char types are treated as ints
-        let ty1 = if ty1.is_char() { &Type::Int } else { ty1 };
-        let ty2 = if ty2.is_char() { &Type::Int } else { ty2 };
+        // Char and short types are treated as ints
+        let ty1 = if ty1.is_promotable() { &Type::Int } else { ty1 };
+        let ty2 = if ty2.is_promotable() { &Type::Int } else { ty2 };
 
         let result = if ty1 == ty2 {
             ty1
         } else if ty1.is_double() || ty2.is_double() {
             &Type::Double
+        } else if ty1.is_floating() || ty2.is_floating() {
+            &Type::Float
         } else if ty1.size(self) == ty2.size(self) {
             if ty1.is_signed() { ty2 } else { ty1 }
         } else if ty1.size(self) > ty2.size(self) {
@@ -425,7 +754,15 @@ impl SemanticData {
 
 pub fn validate(ast: Node<Program>) -> Result<(Node<Program>, SemanticData)> {
     let ast = id_resolution::check(ast)?;
-    let ast = label_check::check(ast)?;
-    let semantic_data = type_check::check(&ast)?;
+    let (ast, label_warnings) = label_check::check(ast)?;
+    let mut semantic_data = type_check::check(&ast)?;
+    semantic_data.warnings.extend(label_warnings);
     Ok((ast, semantic_data))
 }
+
+/// Name of a symbol as written in the source, before it was made unique by
+/// the identifier resolution pass.
+pub fn source_name(symbol: &Symbol) -> &str {
+    let name: &str = symbol.as_ref();
+    name.split('.').next().unwrap_or(name)
+}
@@ -0,0 +1,447 @@
+use crate::ast::{BinaryOp, Constant, Expression, Node, UnaryOp};
+use crate::error::{CompilerError, ErrorKind, Result, Warning};
+use crate::lexer::Span;
+use crate::semantic::{Attributes, SemanticData, StaticInit, Type};
+use crate::symbol::Symbol;
+
+/// Value of a constant expression.
+#[derive(Debug, Clone)]
+pub enum ConstantValue {
+    Arithmetic(Constant),
+    /// Address of a static object or function, plus an offset in bytes.
+    Address(Symbol, i64),
+}
+
+impl ConstantValue {
+    pub fn to_static_init(&self, semantics: &SemanticData) -> StaticInit {
+        match self {
+            ConstantValue::Arithmetic(c) => c.to_static_init(),
+            ConstantValue::Address(name, offset) => match semantics.symbols[name].attrs {
+                Attributes::Const { .. } => StaticInit::Pointer(name.clone(), *offset),
+                _ => StaticInit::Address(name.clone(), *offset),
+            },
+        }
+    }
+}
+
+/// Evaluates type checked expressions, applying the implicit conversions
+/// recorded for them. Expressions that are not constant evaluate to `None`,
+/// which leaves the choice of error to the caller.
+pub struct ConstantEvaluator<'a> {
+    semantics: &'a mut SemanticData,
+}
+
+impl<'a> ConstantEvaluator<'a> {
+    pub fn new(semantics: &'a mut SemanticData) -> Self {
+        ConstantEvaluator { semantics }
+    }
+
+    pub fn evaluate(&mut self, expr: &Node<Expression>) -> Result<Option<ConstantValue>> {
+        let value = if self.semantics.pointer_decays.contains_key(&expr.id) {
+            self.address(expr)?
+                .map(|(name, offset)| ConstantValue::Address(name, offset))
+        } else {
+            self.value(expr)?
+        };
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        match self.semantics.implicit_casts.get(&expr.id) {
+            Some(target) => Ok(convert(value, target)),
+            None => Ok(Some(value)),
+        }
+    }
+
+    /// Value of an integer constant expression, `None` when the expression
+    /// isn't one.
+    pub fn integer(&mut self, expr: &Node<Expression>) -> Result<Option<i128>> {
+        match self.evaluate(expr)? {
+            Some(ConstantValue::Arithmetic(c)) if c.is_int() => Ok(Some(integer_value(&c))),
+            _ => Ok(None),
+        }
+    }
+
+    fn value(&mut self, expr: &Node<Expression>) -> Result<Option<ConstantValue>> {
+        let value = match expr.as_ref() {
+            Expression::Constant(c) => c.clone(),
+            Expression::Var(name) => match self.semantics.enumerators.get(name) {
+                Some(value) => Constant::Int(*value),
+                None => return Ok(None),
+            },
+            Expression::Unary { op, expr: operand } => {
+                let Some(operand) = self.evaluate(operand)? else {
+                    return Ok(None);
+                };
+                let ConstantValue::Arithmetic(operand) = operand else {
+                    return Ok(match op.as_ref() {
+                        UnaryOp::Not => Some(ConstantValue::Arithmetic(Constant::Int(0))),
+                        _ => None,
+                    });
+                };
+                match self.unary(op, operand, expr.span) {
+                    Some(c) => c,
+                    None => return Ok(None),
+                }
+            }
+            Expression::Binary { op, left, right }
+                if matches!(op.as_ref(), BinaryOp::And | BinaryOp::Or) =>
+            {
+                // The right operand is only evaluated when it decides the result
+                let Some(left) = self.evaluate(left)? else {
+                    return Ok(None);
+                };
+                let is_or = matches!(op.as_ref(), BinaryOp::Or);
+                if is_true(&left) == is_or {
+                    return Ok(Some(ConstantValue::Arithmetic(Constant::Int(is_or as i32))));
+                }
+                let Some(right) = self.evaluate(right)? else {
+                    return Ok(None);
+                };
+                Constant::Int(is_true(&right) as i32)
+            }
+            Expression::Binary { op, left, right }
+                if self.semantics.expr_type(expr).is_pointer()
+                    || self.semantics.expr_type(left).is_pointer() =>
+            {
+                return self.pointer_arithmetic(op.as_ref(), left, right);
+            }
+            Expression::Binary { op, left, right } => {
+                let (Some(left), Some(right)) = (self.evaluate(left)?, self.evaluate(right)?)
+                else {
+                    return Ok(None);
+                };
+                let (ConstantValue::Arithmetic(left), ConstantValue::Arithmetic(right)) =
+                    (left, right)
+                else {
+                    return Ok(None);
+                };
+                match self.binary(op, left, right, expr.span)? {
+                    Some(c) => c,
+                    None => return Ok(None),
+                }
+            }
+            Expression::Conditional {
+                cond,
+                then_expr,
+                else_expr,
+            } => {
+                let Some(cond) = self.evaluate(cond)? else {
+                    return Ok(None);
+                };
+                return if is_true(&cond) {
+                    self.evaluate(then_expr)
+                } else {
+                    self.evaluate(else_expr)
+                };
+            }
+            Expression::Cast {
+                target,
+                expr: inner,
+            } => {
+                let target = target.ty(self.semantics);
+                if target.is_void() {
+                    return Ok(None);
+                }
+                let Some(value) = self.evaluate(inner)? else {
+                    return Ok(None);
+                };
+                return Ok(convert(value, &target));
+            }
+            Expression::AddressOf(inner) => {
+                return Ok(self
+                    .address(inner)?
+                    .map(|(name, offset)| ConstantValue::Address(name, offset)));
+            }
+            Expression::SizeOfType(target) => {
+                let ty = target.ty(self.semantics);
+                if ty.is_variable_length_array() {
+                    return Ok(None);
+                }
+                Constant::ULong(ty.size(self.semantics) as u64)
+            }
+            Expression::SizeOfExpr(inner) => {
+                let ty = self.semantics.expr_type(inner);
+                if ty.is_variable_length_array() {
+                    return Ok(None);
+                }
+                Constant::ULong(ty.size(self.semantics) as u64)
+            }
+            Expression::AlignOfType(target) => {
+                let ty = target.ty(self.semantics);
+                Constant::ULong(ty.alignment(self.semantics) as u64)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(ConstantValue::Arithmetic(value)))
+    }
+
+    /// Static address designated by an lvalue.
+    fn address(&mut self, expr: &Node<Expression>) -> Result<Option<(Symbol, i64)>> {
+        let address = match expr.as_ref() {
+            Expression::Var(name) => match self.semantics.symbols.get(name) {
+                Some(data) if is_static(&data.attrs) => (name.clone(), 0),
+                _ => return Ok(None),
+            },
+            Expression::String(s) => (self.semantics.make_string(s), 0),
+            Expression::CompoundLiteral { .. } => {
+                let name = &self.semantics.compound_literals[&expr.id];
+                if !is_static(&self.semantics.symbols[name].attrs) {
+                    return Ok(None);
+                }
+                (name.clone(), 0)
+            }
+            Expression::Dereference(pointer) => match self.evaluate(pointer)? {
+                Some(ConstantValue::Address(name, offset)) => (name, offset),
+                _ => return Ok(None),
+            },
+            Expression::Subscript(left, right) => {
+                let (pointer, index) = if self.semantics.expr_type(left).is_pointer() {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                match self.pointer_arithmetic(&BinaryOp::Add, pointer, index)? {
+                    Some(ConstantValue::Address(name, offset)) => (name, offset),
+                    _ => return Ok(None),
+                }
+            }
+            Expression::Dot { aggregate, field } => {
+                let Some((name, offset)) = self.address(aggregate)? else {
+                    return Ok(None);
+                };
+                let ty = self.semantics.expr_type(aggregate).clone();
+                (name, offset + self.field_offset(&ty, &field.symbol))
+            }
+            Expression::Arrow { pointer, field } => {
+                let Some(ConstantValue::Address(name, offset)) = self.evaluate(pointer)? else {
+                    return Ok(None);
+                };
+                let Type::Pointer(ty) = self.semantics.expr_type(pointer).clone() else {
+                    unreachable!("Arrow operands are pointers");
+                };
+                (name, offset + self.field_offset(&ty, &field.symbol))
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(address))
+    }
+
+    fn field_offset(&self, ty: &Type, field: &Symbol) -> i64 {
+        let (Type::Struct(tag) | Type::Union(tag)) = ty.unqualified() else {
+            unreachable!("Fields belong to aggregates");
+        };
+        let aggregate = self.semantics.get_aggregate(tag);
+        let field = aggregate.fields.iter().find(|f| &f.name == field);
+        field.expect("Field was checked").offset as i64
+    }
+
+    /// Adds an integer to or subtracts it from an address, or subtracts two
+    /// addresses of the same object.
+    fn pointer_arithmetic(
+        &mut self,
+        op: &BinaryOp,
+        left: &Node<Expression>,
+        right: &Node<Expression>,
+    ) -> Result<Option<ConstantValue>> {
+        let (pointer, index) = if self.semantics.expr_type(left).is_pointer() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        let Type::Pointer(inner) = self.semantics.expr_type(pointer).clone() else {
+            unreachable!("Pointer arithmetic on a non-pointer");
+        };
+        if inner.is_variable_length_array() || !matches!(op, BinaryOp::Add | BinaryOp::Subtract) {
+            return Ok(None);
+        }
+        let scale = inner.size(self.semantics) as i64;
+        let (Some(ConstantValue::Address(name, offset)), Some(index)) =
+            (self.evaluate(pointer)?, self.evaluate(index)?)
+        else {
+            return Ok(None);
+        };
+        let value = match (op, index) {
+            (BinaryOp::Subtract, ConstantValue::Address(other, other_offset)) => {
+                if name != other {
+                    return Ok(None);
+                }
+                ConstantValue::Arithmetic(Constant::Long((offset - other_offset) / scale))
+            }
+            (BinaryOp::Add, ConstantValue::Arithmetic(c)) => {
+                ConstantValue::Address(name, offset + integer_value(&c) as i64 * scale)
+            }
+            (BinaryOp::Subtract, ConstantValue::Arithmetic(c)) => {
+                ConstantValue::Address(name, offset - integer_value(&c) as i64 * scale)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(value))
+    }
+
+    fn unary(&mut self, op: &Node<UnaryOp>, operand: Constant, span: Span) -> Option<Constant> {
+        let ty = operand.ty();
+        match (op.as_ref(), operand) {
+            (UnaryOp::Not, operand) => Some(Constant::Int(operand.is_zero() as i32)),
+            (UnaryOp::Plus, operand) => Some(operand),
+            (UnaryOp::Negate, Constant::Double(value)) => Some(Constant::Double(-value)),
+            (UnaryOp::Negate, Constant::Float(value)) => Some(Constant::Float(-value)),
+            (UnaryOp::Negate, operand) => Some(self.wrap(-integer_value(&operand), &ty, span)),
+            (UnaryOp::Complement, operand) if operand.is_int() => {
+                Some(self.wrap(!integer_value(&operand), &ty, span))
+            }
+            _ => None,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        op: &Node<BinaryOp>,
+        left: Constant,
+        right: Constant,
+        span: Span,
+    ) -> Result<Option<Constant>> {
+        if !left.is_int() {
+            return Ok(floating_binary(op, left, right));
+        }
+        let ty = left.ty();
+        let (left, right) = (integer_value(&left), integer_value(&right));
+        let value = match op.as_ref() {
+            BinaryOp::Add => left.wrapping_add(right),
+            BinaryOp::Subtract => left.wrapping_sub(right),
+            BinaryOp::Multiply => left.wrapping_mul(right),
+            BinaryOp::Divide | BinaryOp::Reminder if right == 0 => {
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Division by zero in constant expression",
+                    span,
+                ));
+            }
+            BinaryOp::Divide => left / right,
+            BinaryOp::Reminder => left % right,
+            BinaryOp::ShiftLeft | BinaryOp::ShiftRight => {
+                let bits = ty.size(self.semantics) as i128 * 8;
+                let count = if (0..bits).contains(&right) {
+                    right
+                } else {
+                    self.semantics.warnings.push(CompilerError::warning(
+                        Warning::Overflow,
+                        format!("Shift count is out of range for type '{ty}'"),
+                        span,
+                    ));
+                    right & (bits - 1)
+                };
+                if let BinaryOp::ShiftLeft = op.as_ref() {
+                    left.wrapping_shl(count as u32)
+                } else {
+                    left >> count
+                }
+            }
+            BinaryOp::BinAnd => left & right,
+            BinaryOp::BinOr => left | right,
+            BinaryOp::BinXor => left ^ right,
+            BinaryOp::Equal => return Ok(Some(Constant::Int((left == right) as i32))),
+            BinaryOp::NotEqual => return Ok(Some(Constant::Int((left != right) as i32))),
+            BinaryOp::LessThan => return Ok(Some(Constant::Int((left < right) as i32))),
+            BinaryOp::LessOrEqualThan => return Ok(Some(Constant::Int((left <= right) as i32))),
+            BinaryOp::GreaterThan => return Ok(Some(Constant::Int((left > right) as i32))),
+            BinaryOp::GreaterOrEqualThan => {
+                return Ok(Some(Constant::Int((left >= right) as i32)));
+            }
+            BinaryOp::And | BinaryOp::Or => unreachable!("Logical operators short-circuit"),
+        };
+        Ok(Some(self.wrap(value, &ty, span)))
+    }
+
+    /// Converts an exact result to the integer type of the operation. Signed
+    /// results that don't fit overflow, unsigned ones wrap around.
+    fn wrap(&mut self, value: i128, ty: &Type, span: Span) -> Constant {
+        let bits = ty.size(self.semantics) as u32 * 8;
+        let (min, max) = (-(1i128 << (bits - 1)), 1i128 << (bits - 1));
+        if ty.is_signed() && !(min..max).contains(&value) {
+            self.semantics.warnings.push(CompilerError::warning(
+                Warning::Overflow,
+                format!("Integer overflow in constant expression of type '{ty}'"),
+                span,
+            ));
+        }
+        Constant::ULong(value as u64)
+            .cast(ty)
+            .expect("Integers convert to each other")
+    }
+}
+
+fn floating_binary(op: &Node<BinaryOp>, left: Constant, right: Constant) -> Option<Constant> {
+    let ty = left.ty();
+    let (left, right) = (floating_value(&left), floating_value(&right));
+    let value = match op.as_ref() {
+        BinaryOp::Add => left + right,
+        BinaryOp::Subtract => left - right,
+        BinaryOp::Multiply => left * right,
+        BinaryOp::Divide => left / right,
+        BinaryOp::Equal => return Some(Constant::Int((left == right) as i32)),
+        BinaryOp::NotEqual => return Some(Constant::Int((left != right) as i32)),
+        BinaryOp::LessThan => return Some(Constant::Int((left < right) as i32)),
+        BinaryOp::LessOrEqualThan => return Some(Constant::Int((left <= right) as i32)),
+        BinaryOp::GreaterThan => return Some(Constant::Int((left > right) as i32)),
+        BinaryOp::GreaterOrEqualThan => return Some(Constant::Int((left >= right) as i32)),
+        _ => return None,
+    };
+    Constant::Double(value).cast(&ty)
+}
+
+fn convert(value: ConstantValue, target: &Type) -> Option<ConstantValue> {
+    match value {
+        ConstantValue::Arithmetic(c) if target.is_pointer() && c.is_int() => Some(
+            ConstantValue::Arithmetic(Constant::ULong(integer_value(&c) as u64)),
+        ),
+        ConstantValue::Arithmetic(c) => c.cast(target).map(ConstantValue::Arithmetic),
+        ConstantValue::Address(..) if target.is_pointer() => Some(value),
+        ConstantValue::Address(..) if let Type::Bool = target.unqualified() => {
+            Some(ConstantValue::Arithmetic(Constant::UChar(1)))
+        }
+        ConstantValue::Address(..) => None,
+    }
+}
+
+fn is_true(value: &ConstantValue) -> bool {
+    match value {
+        ConstantValue::Arithmetic(c) => !c.is_zero(),
+        ConstantValue::Address(..) => true,
+    }
+}
+
+/// Whether the address of an object or function is a constant, which isn't
+/// the case for thread local objects.
+fn is_static(attrs: &Attributes) -> bool {
+    matches!(
+        attrs,
+        Attributes::Static {
+            thread_local: false,
+            ..
+        } | Attributes::Function { .. }
+    )
+}
+
+/// Integer value of a constant, following the signedness of its type.
+fn integer_value(c: &Constant) -> i128 {
+    match c {
+        Constant::Char(v) => *v as i128,
+        Constant::UChar(v) => *v as i128,
+        Constant::Short(v) => *v as i128,
+        Constant::UShort(v) => *v as i128,
+        Constant::Int(v) => *v as i128,
+        Constant::UInt(v) => *v as i128,
+        Constant::Long(v) => *v as i128,
+        Constant::ULong(v) => *v as i128,
+        Constant::Double(v) => *v as i128,
+        Constant::Float(v) => *v as i128,
+    }
+}
+
+fn floating_value(c: &Constant) -> f64 {
+    match c {
+        Constant::Double(v) => *v,
+        Constant::Float(v) => *v as f64,
+        c => integer_value(c) as f64,
+    }
+}
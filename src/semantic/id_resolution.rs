@@ -1,16 +1,20 @@
 use crate::ast::{
-    Block, BlockItem, Declaration, Expression, ForInit, FunctionDeclaration, Identifier,
-    Initializer, InnerRef, NameAndFields, Node, Program, Statement, StorageClass, TypeSpec,
-    VarDeclaration,
+    Block, BlockItem, Declaration, Designator, EnumDeclaration, Expression, ForInit,
+    FunctionDeclaration, Identifier, Initializer, InnerRef, NameAndFields, Node, Program,
+    Statement, StaticAssert, StorageClass, TypeDefDeclaration, TypeSpec, VarDeclaration,
 };
 use crate::error::{CompilerError, ErrorKind, Result};
+use crate::lexer::Span;
 use crate::symbol::Symbol;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Default)]
 struct Resolver {
     scopes: VecDeque<Scope>,
     structs_scopes: VecDeque<StructScope>,
+    // Typedefs of function types, which declare functions rather than
+    // variables
+    function_types: HashSet<Symbol>,
     counter: usize,
 }
 
@@ -20,6 +24,15 @@ type StructScope = HashMap<Symbol, Symbol>;
 struct Resolution {
     name: Symbol,
     linked: bool,
+    kind: NameKind,
+    span: Span,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum NameKind {
+    Object,
+    TypeName,
+    Enumerator,
 }
 
 impl Resolver {
@@ -32,6 +45,9 @@ impl Resolver {
                 Declaration::Struct(d) | Declaration::Union(d) => {
                     self.resolve_type_declaration(d)?
                 }
+                Declaration::Enum(d) => self.resolve_enum_declaration(d)?,
+                Declaration::TypeDef(d) => self.resolve_typedef_declaration(d)?,
+                Declaration::StaticAssert(d) => self.resolve_static_assert(d)?,
             };
         }
         self.end_scope();
@@ -55,19 +71,36 @@ impl Resolver {
             Declaration::Struct(decl) | Declaration::Union(decl) => {
                 self.resolve_type_declaration(decl)
             }
+            Declaration::Enum(decl) => self.resolve_enum_declaration(decl),
+            Declaration::TypeDef(decl) => self.resolve_typedef_declaration(decl),
+            Declaration::StaticAssert(decl) => self.resolve_static_assert(decl),
         }
     }
 
+    fn resolve_static_assert(&mut self, decl: &mut StaticAssert) -> Result<()> {
+        self.resolve_expression(&mut decl.condition)
+    }
+
     fn resolve_file_var_declaration(&mut self, decl: &mut VarDeclaration) -> Result<()> {
         self.resolve_type(&mut decl.type_spec)?;
         let scope = self.scopes.front_mut().expect("Invalid scope state");
+        if let Some(entry) = scope.get(&decl.name.symbol)
+            && entry.kind != NameKind::Object
+        {
+            return Err(different_kind_error(&decl.name, entry));
+        }
         scope.insert(
             decl.name.symbol.clone(),
             Resolution {
                 name: decl.name.symbol.clone(),
                 linked: true,
+                kind: NameKind::Object,
+                span: decl.name.span,
             },
         );
+        if let Some(init) = &mut decl.init {
+            self.resolve_initializer(init)?;
+        }
         Ok(())
     }
 
@@ -75,23 +108,27 @@ impl Resolver {
         self.resolve_type(&mut decl.type_spec)?;
         let symbol = &decl.name.symbol;
         let unique_name = self.make_name(symbol);
+        let linked = matches!(decl.storage_class.inner_ref(), Some(StorageClass::Extern))
+            || self.is_function_type(&decl.type_spec);
         let scope = self.scopes.front_mut().expect("Invalid scope state");
-        let storage = decl.storage_class.inner_ref();
         if let Some(entry) = scope.get(symbol)
-            && !(entry.linked && matches!(storage, Some(StorageClass::Extern)))
+            && !(entry.linked && linked)
         {
-            return Err(CompilerError {
-                kind: ErrorKind::Resolve,
-                msg: format!("Variable '{symbol}' was already declared"),
-                span: decl.name.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Resolve,
+                format!("Variable '{symbol}' was already declared"),
+                decl.name.span,
+            )
+            .with_label(entry.span, "previous declaration is here"));
         }
-        if let Some(StorageClass::Extern) = storage {
+        if linked {
             scope.insert(
                 symbol.clone(),
                 Resolution {
                     name: decl.name.symbol.clone(),
                     linked: true,
+                    kind: NameKind::Object,
+                    span: decl.name.span,
                 },
             );
         } else {
@@ -100,6 +137,8 @@ impl Resolver {
                 Resolution {
                     name: unique_name.clone(),
                     linked: false,
+                    kind: NameKind::Object,
+                    span: decl.name.span,
                 },
             );
             decl.name.symbol = unique_name;
@@ -113,9 +152,14 @@ impl Resolver {
     fn resolve_initializer(&mut self, init: &mut Node<Initializer>) -> Result<()> {
         match init.as_mut() {
             Initializer::Single(expr) => self.resolve_expression(expr),
-            Initializer::Compound(initializers) => {
-                for initializer in initializers {
-                    self.resolve_initializer(initializer)?;
+            Initializer::Compound(items) => {
+                for item in items {
+                    for designator in &mut item.designators {
+                        if let Designator::Index(index) = designator.as_mut() {
+                            self.resolve_expression(index)?;
+                        }
+                    }
+                    self.resolve_initializer(&mut item.init)?;
                 }
                 Ok(())
             }
@@ -124,19 +168,17 @@ impl Resolver {
 
     fn resolve_function_declaration(&mut self, decl: &mut FunctionDeclaration) -> Result<()> {
         self.resolve_type(&mut decl.type_spec.ret)?;
-        for param in &mut decl.type_spec.params {
-            self.resolve_type(param)?;
-        }
         let symbol = &decl.name.symbol;
         let scope = self.scopes.front_mut().expect("Invalid scope state");
         if let Some(resolution) = scope.get(symbol)
             && !resolution.linked
         {
-            return Err(CompilerError {
-                kind: ErrorKind::Resolve,
-                msg: format!("Variable '{symbol}' was already declared"),
-                span: decl.name.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Resolve,
+                format!("Variable '{symbol}' was already declared"),
+                decl.name.span,
+            )
+            .with_label(resolution.span, "previous declaration is here"));
         }
 
         scope.insert(
@@ -144,10 +186,29 @@ impl Resolver {
             Resolution {
                 name: symbol.clone(),
                 linked: true,
+                kind: NameKind::Object,
+                span: decl.name.span,
             },
         );
         self.begin_scope();
-        for param in &mut decl.params {
+        // Parameters are in scope for the types of the following ones, like
+        // in `int f(int n, int a[n])`
+        for (i, ty) in decl.type_spec.params.iter_mut().enumerate() {
+            self.resolve_type(ty)?;
+            let Some(param) = decl.params.get_mut(i) else {
+                continue;
+            };
+            if param.symbol.as_ref().is_empty() {
+                // Only prototypes may omit parameter names
+                if decl.body.is_some() {
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        "Parameter name omitted",
+                        ty.span,
+                    ));
+                }
+                continue;
+            }
             self.resolve_param(param)?
         }
         if let Some(body) = &mut decl.body {
@@ -161,12 +222,13 @@ impl Resolver {
         let symbol = param.symbol.clone();
         let unique_name = self.make_name(&symbol).clone();
         let scope = self.scopes.front_mut().expect("Invalid scope state");
-        if scope.contains_key(&symbol) {
-            return Err(CompilerError {
-                kind: ErrorKind::Resolve,
-                msg: format!("Parameter '{symbol}' was already declared"),
-                span: param.span,
-            });
+        if let Some(resolution) = scope.get(&symbol) {
+            return Err(CompilerError::new(
+                ErrorKind::Resolve,
+                format!("Parameter '{symbol}' was already declared"),
+                param.span,
+            )
+            .with_label(resolution.span, "previous declaration is here"));
         }
         param.symbol = unique_name.clone();
         scope.insert(
@@ -174,13 +236,29 @@ impl Resolver {
             Resolution {
                 name: unique_name.clone(),
                 linked: false,
+                kind: NameKind::Object,
+                span: param.span,
             },
         );
         Ok(())
     }
 
     fn resolve_type_declaration(&mut self, decl: &mut NameAndFields) -> Result<()> {
-        let tag = &decl.name.symbol;
+        self.declare_tag(&mut decl.name);
+        for field in &mut decl.fields {
+            self.resolve_type(&mut field.type_spec)?;
+            if let Some(width) = &mut field.width {
+                self.resolve_expression(width)?;
+            }
+        }
+        for static_assert in &mut decl.static_asserts {
+            self.resolve_static_assert(static_assert)?;
+        }
+        Ok(())
+    }
+
+    fn declare_tag(&mut self, name: &mut Node<Identifier>) {
+        let tag = &name.symbol;
         let unique_name = self.make_name(tag);
         let scope = self
             .structs_scopes
@@ -194,15 +272,113 @@ impl Resolver {
             unique_name
         };
 
-        decl.name.symbol = unique_name;
-        for field in &mut decl.fields {
-            self.resolve_type(&mut field.type_spec)?;
+        name.symbol = unique_name;
+    }
+
+    fn resolve_enum_declaration(&mut self, decl: &mut EnumDeclaration) -> Result<()> {
+        if let Some(name) = &mut decl.name {
+            self.declare_tag(name);
+        }
+        for enumerator in &mut decl.enumerators {
+            if let Some(value) = &mut enumerator.value {
+                self.resolve_expression(value)?;
+            }
+            let unique_name = self.make_name(&enumerator.name.symbol);
+            let name = &mut enumerator.name;
+            let scope = self.scopes.front_mut().expect("Invalid scope state");
+            if let Some(entry) = scope.get(&name.symbol) {
+                return Err(match entry.kind {
+                    NameKind::Enumerator => CompilerError::new(
+                        ErrorKind::Resolve,
+                        format!("Enumerator '{}' was already declared", name.symbol),
+                        name.span,
+                    )
+                    .with_label(entry.span, "previous declaration is here"),
+                    _ => different_kind_error(name, entry),
+                });
+            }
+            scope.insert(
+                name.symbol.clone(),
+                Resolution {
+                    name: unique_name.clone(),
+                    linked: false,
+                    kind: NameKind::Enumerator,
+                    span: name.span,
+                },
+            );
+            name.symbol = unique_name;
         }
         Ok(())
     }
 
+    fn resolve_typedef_declaration(&mut self, decl: &mut TypeDefDeclaration) -> Result<()> {
+        self.resolve_type(&mut decl.type_spec)?;
+        let symbol = &decl.name.symbol;
+        let unique_name = self.make_name(symbol);
+        let scope = self.scopes.front_mut().expect("Invalid scope state");
+        // Repeating a typedef in the same scope is fine as long as the type is
+        // the same, which is checked during type checking.
+        let unique_name = match scope.get(symbol) {
+            Some(entry) if entry.kind == NameKind::TypeName => entry.name.clone(),
+            Some(entry) => return Err(different_kind_error(&decl.name, entry)),
+            None => {
+                scope.insert(
+                    symbol.clone(),
+                    Resolution {
+                        name: unique_name.clone(),
+                        linked: false,
+                        kind: NameKind::TypeName,
+                        span: decl.name.span,
+                    },
+                );
+                unique_name
+            }
+        };
+        if self.is_function_type(&decl.type_spec) {
+            self.function_types.insert(unique_name.clone());
+        }
+        decl.name.symbol = unique_name;
+        Ok(())
+    }
+
+    fn is_function_type(&self, ty: &TypeSpec) -> bool {
+        match ty {
+            TypeSpec::Function(_) => true,
+            TypeSpec::Named(name) => self.function_types.contains(&name.symbol),
+            TypeSpec::Qualified(_, inner) => self.is_function_type(inner),
+            _ => false,
+        }
+    }
+
     fn resolve_type(&mut self, ty: &mut Node<TypeSpec>) -> Result<()> {
         match ty.as_mut() {
+            TypeSpec::Named(name) => {
+                let resolution = self.scopes.iter().find_map(|s| s.get(&name.symbol));
+                if let Some(resolution) = resolution
+                    && resolution.kind == NameKind::TypeName
+                {
+                    name.symbol = resolution.name.clone();
+                    return Ok(());
+                }
+                Err(CompilerError::new(
+                    ErrorKind::Resolve,
+                    format!("Unknown type name '{}'", name.symbol),
+                    ty.span,
+                ))
+            }
+            TypeSpec::Enum(Some(tag)) => {
+                for scope in &self.structs_scopes {
+                    if let Some(declared) = scope.get(&tag.symbol) {
+                        tag.symbol = declared.clone();
+                        return Ok(());
+                    }
+                }
+                Err(CompilerError::new(
+                    ErrorKind::Resolve,
+                    format!("Undeclared enum type '{}'", tag.symbol),
+                    ty.span,
+                ))
+            }
             TypeSpec::Struct(tag) | TypeSpec::Union(tag) => {
                 for scope in &self.structs_scopes {
                     if let Some(declared) = scope.get(&tag.symbol) {
@@ -210,13 +386,20 @@ impl Resolver {
                         return Ok(());
                     }
                 }
-                Err(CompilerError {
-                    kind: ErrorKind::Resolve,
-                    msg: format!("Undeclared structure type '{}'", tag.symbol),
-                    span: ty.span,
-                })
+                Err(CompilerError::new(
+                    ErrorKind::Resolve,
+                    format!("Undeclared structure type '{}'", tag.symbol),
+                    ty.span,
+                ))
             }
-            TypeSpec::Pointer(inner) | TypeSpec::Array(inner, _) => self.resolve_type(inner),
+            TypeSpec::VariableArray(inner, size) => {
+                self.resolve_type(inner)?;
+                self.resolve_expression(size)
+            }
+            TypeSpec::Pointer(inner)
+            | TypeSpec::Array(inner, _)
+            | TypeSpec::UnsizedArray(inner)
+            | TypeSpec::Qualified(_, inner) => self.resolve_type(inner),
             TypeSpec::Function(f) => {
                 self.resolve_type(&mut f.ret)?;
                 for param in &mut f.params {
@@ -249,9 +432,11 @@ impl Resolver {
                     self.resolve_statement(else_stmt)?;
                 }
             }
-            Statement::Labeled { body, .. }
-            | Statement::Case { body, .. }
-            | Statement::Default { body, .. } => {
+            Statement::Labeled { body, .. } | Statement::Default { body, .. } => {
+                self.resolve_statement(body)?;
+            }
+            Statement::Case { value, body, .. } => {
+                self.resolve_expression(value)?;
                 self.resolve_statement(body)?;
             }
             Statement::Compound(block) => {
@@ -304,19 +489,20 @@ impl Resolver {
     }
 
     fn resolve_expression(&mut self, expr: &mut Node<Expression>) -> Result<()> {
+        let span = expr.span;
         match expr.as_mut() {
             Expression::Var(name) => {
-                for scope in &self.scopes {
-                    if let Some(declared) = scope.get(name) {
-                        *name = declared.name.clone();
-                        return Ok(());
-                    }
+                let Some(declared) = self.scopes.iter().find_map(|scope| scope.get(name)) else {
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        format!("Undeclared variable '{name}'"),
+                        span,
+                    ));
+                };
+                match declared.kind {
+                    NameKind::Object | NameKind::Enumerator => *name = declared.name.clone(),
+                    NameKind::TypeName => return Err(type_name_error(name, span)),
                 }
-                return Err(CompilerError {
-                    kind: ErrorKind::Resolve,
-                    msg: format!("Undeclared variable '{name}'"),
-                    span: expr.span,
-                });
             }
             Expression::Assignment { left, right, .. } => {
                 self.resolve_expression(left)?;
@@ -341,43 +527,69 @@ impl Resolver {
                 self.resolve_expression(then_expr)?;
                 self.resolve_expression(else_expr)?;
             }
-            Expression::FunctionCall { name, args } => {
-                let symbol = &name.symbol;
-                for scope in &self.scopes {
-                    if let Some(resolution) = scope.get(symbol) {
-                        name.symbol = resolution.name.clone();
-                        for arg in args {
-                            self.resolve_expression(arg)?;
+            Expression::FunctionCall { function, args } => {
+                let span = function.span;
+                if let Expression::Var(symbol) = function.as_mut() {
+                    let Some(resolution) = self.scopes.iter().find_map(|s| s.get(symbol)) else {
+                        return Err(CompilerError::new(
+                            ErrorKind::Resolve,
+                            format!("Undeclared function '{symbol}'"),
+                            span,
+                        ));
+                    };
+                    match resolution.kind {
+                        NameKind::Object => *symbol = resolution.name.clone(),
+                        NameKind::TypeName => return Err(type_name_error(symbol, span)),
+                        NameKind::Enumerator => {
+                            return Err(CompilerError::new(
+                                ErrorKind::Resolve,
+                                format!("Called object '{symbol}' is not a function"),
+                                span,
+                            ));
                         }
-                        return Ok(());
                     }
+                } else {
+                    self.resolve_expression(function)?;
+                }
+                for arg in args {
+                    self.resolve_expression(arg)?;
                 }
-                return Err(CompilerError {
-                    kind: ErrorKind::Resolve,
-                    msg: format!("Undeclared function '{symbol}'"),
-                    span: name.span,
-                });
             }
 
             Expression::Cast { target, expr } => {
                 self.resolve_type(target)?;
                 self.resolve_expression(expr)?;
             }
+            Expression::CompoundLiteral { type_spec, init } => {
+                self.resolve_type(type_spec)?;
+                self.resolve_initializer(init)?;
+            }
 
-            Expression::SizeOfType(ty) => {
+            Expression::SizeOfType(ty) | Expression::AlignOfType(ty) => {
+                self.resolve_type(ty)?;
+            }
+            Expression::Comma(left, right) => {
+                self.resolve_expression(left)?;
+                self.resolve_expression(right)?;
+            }
+            Expression::VaArg(ap, ty) => {
+                self.resolve_expression(ap)?;
                 self.resolve_type(ty)?;
             }
 
             Expression::Dereference(expr)
             | Expression::AddressOf(expr)
             | Expression::SizeOfExpr(expr)
+            | Expression::VaEnd(expr)
             | Expression::Arrow { pointer: expr, .. }
             | Expression::Dot {
                 aggregate: expr, ..
             } => {
                 self.resolve_expression(expr)?;
             }
-            Expression::Subscript(expr1, expr2) => {
+            Expression::Subscript(expr1, expr2)
+            | Expression::VaStart(expr1, expr2)
+            | Expression::VaCopy(expr1, expr2) => {
                 self.resolve_expression(expr1)?;
                 self.resolve_expression(expr2)?;
             }
@@ -403,6 +615,23 @@ impl Resolver {
     }
 }
 
+fn different_kind_error(name: &Node<Identifier>, previous: &Resolution) -> CompilerError {
+    CompilerError::new(
+        ErrorKind::Resolve,
+        format!("'{}' redeclared as a different kind of symbol", name.symbol),
+        name.span,
+    )
+    .with_label(previous.span, "previous declaration is here")
+}
+
+fn type_name_error(name: &Symbol, span: Span) -> CompilerError {
+    CompilerError::new(
+        ErrorKind::Resolve,
+        format!("Unexpected type name '{name}'"),
+        span,
+    )
+}
+
 pub fn check(program: Node<Program>) -> Result<Node<Program>> {
     Resolver::default().resolve(program)
 }
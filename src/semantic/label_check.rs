@@ -1,13 +1,16 @@
 use crate::ast::{Block, BlockItem, Declaration, FunctionDeclaration, Node, Program, Statement};
-use crate::error::{CompilerError, ErrorKind};
+use crate::error::{CompilerError, ErrorKind, Warning};
+use crate::lexer::Span;
 use crate::symbol::Symbol;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Default)]
 struct LabelChecker {
-    labels: HashMap<Symbol, Symbol>,
+    labels: HashMap<Symbol, (Symbol, Span)>,
+    used_labels: HashSet<Symbol>,
     label_stack: VecDeque<LabelKind>,
     counter: usize,
+    warnings: Vec<CompilerError>,
 }
 
 enum LabelKind {
@@ -16,16 +19,37 @@ enum LabelKind {
 }
 
 impl LabelChecker {
-    fn check(mut self, mut program: Node<Program>) -> crate::error::Result<Node<Program>> {
+    fn check(
+        mut self,
+        mut program: Node<Program>,
+    ) -> crate::error::Result<(Node<Program>, Vec<CompilerError>)> {
         for decl in &mut program.declarations {
             self.labels.clear();
+            self.used_labels.clear();
             debug_assert!(self.label_stack.is_empty());
             if let Declaration::Function(d) = decl.as_mut() {
-                self.check_function_declaration(d)?
+                self.check_function_declaration(d)?;
+                self.check_unused_labels();
             }
         }
 
-        Ok(program)
+        Ok((program, self.warnings))
+    }
+
+    fn check_unused_labels(&mut self) {
+        let mut unused: Vec<_> = self
+            .labels
+            .iter()
+            .filter(|(name, _)| !self.used_labels.contains(*name))
+            .collect();
+        unused.sort_by_key(|(_, (_, span))| span.0);
+        for (name, (_, span)) in unused {
+            self.warnings.push(CompilerError::warning(
+                Warning::UnusedLabel,
+                format!("Label '{name}' defined but not used"),
+                *span,
+            ));
+        }
     }
     fn check_statement(&mut self, stmt: &mut Node<Statement>) -> crate::error::Result<()> {
         match stmt.as_mut() {
@@ -40,15 +64,17 @@ impl LabelChecker {
                 }
             }
             Statement::Labeled { name, body: stmt } => {
-                if self.labels.contains_key(&name.symbol) {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: format!("Label '{}' was already defined", name.symbol),
-                        span: name.span,
-                    });
+                if let Some((_, previous)) = self.labels.get(&name.symbol) {
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        format!("Label '{}' was already defined", name.symbol),
+                        name.span,
+                    )
+                    .with_label(*previous, "previous definition is here"));
                 }
                 let new_label = self.make_label(name.symbol.as_ref());
-                self.labels.insert(name.symbol.clone(), new_label.clone());
+                self.labels
+                    .insert(name.symbol.clone(), (new_label.clone(), name.span));
                 name.symbol = new_label;
                 self.check_statement(stmt)?;
             }
@@ -81,11 +107,11 @@ impl LabelChecker {
                 {
                     *label = enclosing_label.clone();
                 } else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: "'break' statement not in loop or switch statement".to_owned(),
-                        span: stmt.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        "'break' statement not in loop or switch statement",
+                        stmt.span,
+                    ));
                 }
             }
             Statement::Continue(label) => {
@@ -93,11 +119,11 @@ impl LabelChecker {
                     LabelKind::Loop(label) => Some(label),
                     LabelKind::Switch(_) => None,
                 }) else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: "'continue' statement not in loop statement".to_owned(),
-                        span: stmt.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        "'continue' statement not in loop statement",
+                        stmt.span,
+                    ));
                 };
                 *label = enclosing_label.clone();
             }
@@ -106,11 +132,11 @@ impl LabelChecker {
                     LabelKind::Loop(_) => None,
                     LabelKind::Switch(label) => Some(label),
                 }) else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: "case label not within a switch statement".to_owned(),
-                        span: stmt.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        "case label not within a switch statement",
+                        stmt.span,
+                    ));
                 };
                 *label = self.make_label(&format!("{enclosing_label}_case_"));
                 self.check_statement(body)?;
@@ -120,11 +146,11 @@ impl LabelChecker {
                     LabelKind::Loop(_) => None,
                     LabelKind::Switch(label) => Some(label),
                 }) else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: "default label not within a switch statement".to_owned(),
-                        span: stmt.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        "default label not within a switch statement",
+                        stmt.span,
+                    ));
                 };
                 *label = self.make_label(&format!("{enclosing_label}_default"));
                 self.check_statement(body)?;
@@ -158,7 +184,7 @@ impl LabelChecker {
         Ok(())
     }
 
-    fn check_gotos_block(&self, block: &mut Node<Block>) -> crate::error::Result<()> {
+    fn check_gotos_block(&mut self, block: &mut Node<Block>) -> crate::error::Result<()> {
         for block_item in &mut block.items {
             if let BlockItem::Stmt(stmt) = block_item {
                 self.check_gotos_stmt(stmt)?
@@ -167,17 +193,18 @@ impl LabelChecker {
         Ok(())
     }
 
-    fn check_gotos_stmt(&self, stmt: &mut Node<Statement>) -> crate::error::Result<()> {
+    fn check_gotos_stmt(&mut self, stmt: &mut Node<Statement>) -> crate::error::Result<()> {
         match stmt.as_mut() {
             Statement::Goto(name) => {
-                if let Some(new_name) = self.labels.get(&name.symbol) {
+                if let Some((new_name, _)) = self.labels.get(&name.symbol) {
+                    self.used_labels.insert(name.symbol.clone());
                     name.symbol = new_name.clone();
                 } else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: format!("Undefined label '{}'", name.symbol),
-                        span: name.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        format!("Undefined label '{}'", name.symbol),
+                        name.span,
+                    ));
                 }
             }
             Statement::If {
@@ -219,6 +246,6 @@ impl LabelChecker {
     }
 }
 
-pub fn check(program: Node<Program>) -> crate::error::Result<Node<Program>> {
+pub fn check(program: Node<Program>) -> crate::error::Result<(Node<Program>, Vec<CompilerError>)> {
     LabelChecker::default().check(program)
 }
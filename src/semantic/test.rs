@@ -1,10 +1,10 @@
-use crate::parser::parse;
+use crate::parser::{parse, DEFAULT_ERROR_LIMIT};
 use crate::semantic::validate;
-use crate::pretty::{annotate, remove_annotation};
+use crate::pretty::{annotate, dedent, diagnostics, remove_annotation};
 
 fn assert_error(expected_annotated: &str) {
     let clean_source = remove_annotation(expected_annotated);
-    let ast = parse(&clean_source).expect("Parse error");
+    let ast = parse(&clean_source, DEFAULT_ERROR_LIMIT).expect("Parse error");
     let Err(error) = validate(ast) else {
         panic!("No error produced!")
     };
@@ -12,6 +12,13 @@ fn assert_error(expected_annotated: &str) {
     assert_eq!(actual_annotated, expected_annotated);
 }
 
+fn assert_warnings(src: &str, expected: &[&str]) {
+    let src = dedent(src);
+    let ast = parse(&src, DEFAULT_ERROR_LIMIT).expect("Parse error");
+    let (_, semantics) = validate(ast).expect("Validation error");
+    assert_eq!(diagnostics(&src, &semantics.warnings), expected);
+}
+
 mod test_chapter_5;
 mod test_chapter_6;
 mod test_chapter_7;
@@ -26,3 +33,18 @@ mod test_chapter_15;
 mod test_chapter_16;
 mod test_chapter_17;
 mod test_chapter_18;
+mod test_enum;
+mod test_typedef;
+mod test_warnings;
+mod test_qualifiers;
+mod test_bit_fields;
+mod test_designated_initializers;
+mod test_compound_literals;
+mod test_variable_length_arrays;
+mod test_constant_expressions;
+mod test_expressions;
+mod test_function_pointers;
+mod test_variadic;
+mod test_attributes;
+mod test_thread_local;
+mod test_bool;
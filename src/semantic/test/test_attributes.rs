@@ -0,0 +1,159 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_packed_and_aligned_layout() {
+    assert_warnings(
+        r#"
+        struct __attribute__((packed)) p {
+            char c;
+            int i;
+            long l;
+        };
+        struct q {
+            char c;
+            int i __attribute__((aligned(16)));
+        } __attribute__((aligned(32)));
+        struct r {
+            char c;
+            long l __attribute__((packed));
+        };
+        _Static_assert(sizeof(struct p) == 13 && _Alignof(struct p) == 1, "packed");
+        _Static_assert(sizeof(struct q) == 32 && _Alignof(struct q) == 32, "aligned");
+        _Static_assert(sizeof(struct r) == 9 && _Alignof(struct r) == 1, "packed field");
+    "#,
+        &[],
+    );
+}
+
+#[test]
+fn test_unused_attribute_silences_warning() {
+    assert_warnings(
+        r#"
+        int main(void) {
+            int a __attribute__((unused));
+            static int b __attribute__((unused));
+            int c;
+            return 0;
+        }
+    "#,
+        &["4:9: Unused variable 'c'"],
+    );
+}
+
+#[test]
+fn test_invalid_noreturn_variable() {
+    assert_error(
+        r#"
+        _Noreturn int x;
+      //^^^^^^^^^ Only functions can be declared noreturn
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_section_on_local_variable() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x __attribute__((section(".data.x"))) = 0;
+                               //^^^^^^^^^^^^^^^^^^ Section attribute can't be specified for local variables
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_weak_static_function() {
+    assert_error(
+        r#"
+        static int f(void) __attribute__((weak));
+                 //^ Weak declaration of 'f' must be public
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_weak_local_variable() {
+    assert_error(
+        r#"
+        int main(void) {
+            static int x __attribute__((weak));
+                     //^ Weak declaration of 'x' must be public
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_conflicting_sections() {
+    assert_error(
+        r#"
+        extern int x __attribute__((section(".data.a")));
+        int x __attribute__((section(".data.b"))) = 1;
+          //^ Section of 'x' conflicts with previous declaration
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_alignment_not_power_of_two() {
+    assert_error(
+        r#"
+        int x __attribute__((aligned(12)));
+                                   //^^ Requested alignment is not a positive power of 2
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_alignment_too_large() {
+    assert_error(
+        r#"
+        int x __attribute__((aligned(1 << 10)));
+                                   //^^^^^^^ Requested alignment is too large
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_alignment_not_constant() {
+    assert_error(
+        r#"
+        int n = 8;
+        int x __attribute__((aligned(n)));
+                                   //^ Requested alignment is not an integer constant
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_over_aligned_local_variable() {
+    assert_error(
+        r#"
+        int main(void) {
+            static char ok[8] __attribute__((aligned(64)));
+            __attribute__((aligned(64))) char buf[8];
+                                            //^^^ Alignment above 16 bytes isn't supported for local variables
+            return buf[0] + ok[0];
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_local_of_over_aligned_type() {
+    assert_error(
+        r#"
+        struct s {
+            char c __attribute__((aligned(32)));
+        };
+        int main(void) {
+            struct s value;
+                   //^^^^^ Alignment above 16 bytes isn't supported for local variables
+            return value.c;
+        }
+    "#,
+    );
+}
@@ -0,0 +1,121 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_invalid_bit_field_type() {
+    assert_error(
+        r#"
+        struct s {
+            double d : 3;
+          //^^^^^^ Bit-field has invalid type 'double'
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_bit_field_width_exceeds_type() {
+    assert_error(
+        r#"
+        struct s {
+            char c : 9;
+                   //^ Bit-field width exceeds the width of its type
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_negative_bit_field_width() {
+    assert_error(
+        r#"
+        struct s {
+            int i : -1;
+                  //^^ Bit-field has negative width
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_named_zero_width_bit_field() {
+    assert_error(
+        r#"
+        struct s {
+            int i : 0;
+                  //^ Named bit-field has zero width
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_non_constant_bit_field_width() {
+    assert_error(
+        r#"
+        int width = 3;
+        struct s {
+            int i : width;
+                  //^^^^^ Bit-field width is not an integer constant
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_address_of_bit_field() {
+    assert_error(
+        r#"
+        struct s {
+            int i : 3;
+        };
+        int *get(struct s *p) {
+            return &p->i;
+                 //^^^^^ Can't take address of a bit-field
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_sizeof_bit_field() {
+    assert_error(
+        r#"
+        struct s {
+            int i : 3;
+        };
+        unsigned long size(struct s v) {
+            return sizeof v.i;
+                        //^^^ Cannot get size of a bit-field
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_narrow_bit_fields_promote_to_int() {
+    // Only the operands that aren't promoted to int compare with different
+    // signs.
+    assert_warnings(
+        r#"
+        struct s {
+            unsigned u : 3;
+            int i : 3;
+            unsigned w : 32;
+            long l : 40;
+        };
+        int main(void) {
+            struct s x = {7, -4, 5, -3};
+            int a = x.i > x.u;
+            int b = x.u - 8 < x.i;
+            int c = x.i > x.w;
+            int d = x.w - 8 < x.i;
+            int e = x.l - 1 < x.u;
+            return a + b + c + d + e;
+        }
+    "#,
+        &[
+            "11:13: Comparison of integers of different signs: 'int' and 'unsigned int'",
+            "12:13: Comparison of integers of different signs: 'unsigned int' and 'int'",
+        ],
+    );
+}
@@ -0,0 +1,43 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_valid_bool_conversions() {
+    assert_warnings(
+        r#"
+        static int x;
+        _Bool from_int = 256;
+        _Bool from_double = 0.5;
+        _Bool from_address = &x;
+        _Static_assert(sizeof(_Bool) == 1 && _Alignof(_Bool) == 1, "size");
+        _Static_assert((_Bool)2 == 1 && (_Bool)0.0 == 0, "normalized");
+        struct flags {
+            _Bool a : 1;
+            _Bool b : 1;
+        };
+        int main(void) {
+            _Bool b = &x;
+            long l = 1L << 40;
+            b = l;
+            b++;
+            switch (b) {
+            case 1:
+                return b + from_int;
+            }
+            return 0;
+        }
+    "#,
+        &[],
+    );
+}
+
+#[test]
+fn test_invalid_bool_bit_field_width() {
+    assert_error(
+        r#"
+        struct s {
+            _Bool b : 2;
+                    //^ Bit-field width exceeds the width of its type
+        };
+    "#,
+    );
+}
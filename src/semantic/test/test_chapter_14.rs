@@ -35,6 +35,19 @@ fn test_invalid_declarations_extra_credit_deref_label() {
     );
 }
 
+#[test]
+fn test_invalid_types_abstract_function_declarator() {
+    assert_error(
+        r#"
+        int main(void) {
+            (int (void)) 0;
+               //^^^^^^ Cannot cast a value to a non-scalar type
+            return 0;
+        }
+    "#,
+    );
+}
+
 #[test]
 fn test_invalid_types_address_of_address() {
     assert_error(
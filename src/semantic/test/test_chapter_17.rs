@@ -251,7 +251,7 @@ fn test_invalid_types_incomplete_types_sizeof_function() {
         r#"
         int x(void) { return 0; }
         int main(void) { return sizeof x; }
-                                     //^ Function used as variable
+                                     //^ Cannot get size of a function type
     "#,
     );
 }
@@ -1493,7 +1493,7 @@ fn test_invalid_types_initializers_nested_struct_initializer_wrong_type() {
         int main(void) {
           struct outer x = {{1, 2}};
           struct outer y = {1, x};
-                         //^^^^^^ Too many elements in the initializer
+                             //^ Cannot convert type for assignment!
           return 0;
         }
     "#,
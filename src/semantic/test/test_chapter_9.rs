@@ -239,7 +239,7 @@ fn test_invalid_types_assign_fun_to_variable() {
         int main(void) {
             int a = 10;
             a = x;
-              //^ Function used as variable
+              //^ Cannot convert type for assignment!
             return 0;
         }
     "#,
@@ -253,7 +253,7 @@ fn test_invalid_types_assign_value_to_function() {
         int main(void) {
             int x(void);
             x = 3;
-          //^ Function used as variable
+          //^ Type is not assignable
             return 0;
         }
     "#,
@@ -315,7 +315,7 @@ fn test_invalid_types_divide_by_function() {
         int x(void);
         int main(void) {
             int a = 10 / x;
-                       //^ Function used as variable
+                       //^ Operator is invalid
             return 0;
         }
     "#,
@@ -329,7 +329,7 @@ fn test_invalid_types_extra_credit_bitwise_op_function() {
         int x(void);
         int main(void) {
             x >> 2;
-          //^ Function used as variable
+          //^ Operator requires integer operands
             return 0;
         }
     "#,
@@ -343,7 +343,7 @@ fn test_invalid_types_extra_credit_compound_assign_function_lhs() {
         int x(void);
         int main(void) {
             x += 3;
-          //^ Function used as variable
+          //^ Type is not assignable
             return 0;
         }
     "#,
@@ -358,7 +358,7 @@ fn test_invalid_types_extra_credit_compound_assign_function_rhs() {
         int main(void) {
             int a = 3;
             a += x;
-               //^ Function used as variable
+               //^ Assign compound operator cannot be a function
             return 0;
         }
     "#,
@@ -372,7 +372,7 @@ fn test_invalid_types_extra_credit_postfix_incr_fun_name() {
         int x(void);
         int main(void) {
             x++;
-          //^ Function used as variable
+          //^ Type is not assignable
             return 0;
         }
     "#,
@@ -386,7 +386,7 @@ fn test_invalid_types_extra_credit_prefix_decr_fun_name() {
         int x(void);
         int main(void){
             --x;
-            //^ Function used as variable
+            //^ Type is not assignable
             return 0;
         }
     "#,
@@ -400,7 +400,7 @@ fn test_invalid_types_extra_credit_switch_on_function() {
         int main(void) {
             int f(void);
             switch (f)
-                  //^ Function used as variable
+                  //^ Switch statement requires an integer expression
                 return 0;
         }
     "#,
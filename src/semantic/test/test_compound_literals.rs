@@ -0,0 +1,95 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_unsized_array_compound_literal_length() {
+    assert_warnings(
+        r#"
+        struct point {
+            int x;
+            int y;
+        };
+        _Static_assert(sizeof((int[]){1, 2, 3}) == 3 * sizeof(int), "list");
+        _Static_assert(sizeof((char[]){"abc"}) == 4, "string");
+        _Static_assert(sizeof((long[]){[4] = 1, 2}) == 6 * sizeof(long), "designator");
+        _Static_assert(sizeof((int[][2]){1, 2, 3}) == 4 * sizeof(int), "elided braces");
+        _Static_assert(sizeof((struct point[]){{1, 2}, {3}}) == 2 * sizeof(struct point), "structs");
+        int *p = (int[]){1, 2, 3};
+    "#,
+        &[],
+    );
+}
+
+#[test]
+fn test_invalid_unsized_array_type() {
+    assert_error(
+        r#"
+        int main(void) {
+            return sizeof(int[]);
+                           //^^ Array size is missing
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_void_compound_literal() {
+    assert_error(
+        r#"
+        int main(void) {
+            (void){0};
+           //^^^^ Compound literal has incomplete type
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_incomplete_compound_literal() {
+    assert_error(
+        r#"
+        struct s;
+        int main(void) {
+            (struct s){0};
+           //^^^^^^^^ Compound literal has incomplete type
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_block_scope_compound_literal_in_static_initializer() {
+    assert_error(
+        r#"
+        int main(void) {
+            static int *p = (int[2]){1, 2};
+                          //^^^^^^^^^^^^^^ Non-constant initializer on local static variable
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_compound_literal_wrong_pointer_type() {
+    assert_error(
+        r#"
+        long *p = (int[2]){1, 2};
+                //^^^^^^^^^^^^^^ Cannot convert type for assignment!
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_to_const_compound_literal() {
+    assert_error(
+        r#"
+        int main(void) {
+            (const int){1} = 2;
+          //^^^^^^^^^^^^^^ Cannot assign to a const-qualified lvalue
+            return 0;
+        }
+    "#,
+    );
+}
@@ -0,0 +1,178 @@
+use super::{assert_error, assert_warnings};
+use crate::parser::{DEFAULT_ERROR_LIMIT, parse};
+use crate::semantic::{Attributes, InitialValue, StaticInit, validate};
+use crate::symbol::Symbol;
+
+#[test]
+fn test_invalid_division_by_zero_in_constant() {
+    assert_error(
+        r#"
+        int x = 10 / (5 - 5);
+              //^^^^^^^^^^^^ Division by zero in constant expression
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_initializer_address_of_local() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x = 0;
+            static int *p = &x;
+                          //^^ Non-constant initializer on local static variable
+            return *p;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_initializer_reads_variable() {
+    assert_error(
+        r#"
+        int three = 3;
+        int x = 1 + three;
+              //^^^^^^^^^ Non-constant initializer on local static variable
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_case_not_constant() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x = 1;
+            switch (x) {
+            case x + 1:
+               //^^^^^ case label does not reduce to an integer constant
+                return 0;
+            }
+            return 1;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_duplicate_folded_case() {
+    assert_error(
+        r#"
+        int main(void) {
+            switch (4) {
+            case 2 * 2:
+                return 0;
+            case sizeof(int):
+               //^^^^^^^^^^^ duplicate case value
+                return 1;
+            }
+            return 2;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_assert_sizeof() {
+    assert_error(
+        r#"
+        struct s { char c; long l; };
+        _Static_assert(sizeof(struct s) == 9, "packed");
+                     //^^^^^^^^^^^^^^^^^^^^^ Static assertion failed: packed
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_negative_folded_array_size() {
+    assert_error(
+        r#"
+        enum { N = 2 };
+        int arr[N - 3];
+              //^^^^^ Array size is negative
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_comma_in_array_designator() {
+    assert_error(
+        r#"
+        int arr[3] = { [(1, 2)] = 1 };
+                      //^^^^^^ Array designator is not an integer constant
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_floating_bit_field_width() {
+    assert_error(
+        r#"
+        struct s {
+            int a : 2.0;
+                  //^^^ Bit-field width is not an integer constant
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_enumerator_out_of_range_after_folding() {
+    assert_error(
+        r#"
+        enum e { A = 1 << 30, B = A * 2 - 1, C };
+                                           //^ Value of enumerator 'C' is out of range of 'int'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_to_enumerator() {
+    assert_error(
+        r#"
+        enum e { A };
+        int main(void) {
+            A = 1;
+          //^ Expression is not assignable
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_overflow_warnings() {
+    assert_warnings(
+        r#"
+        int a = 2147483647 + 1;
+        long b = -(-9223372036854775807l - 1);
+        int c = 1 << 32;
+        unsigned d = 4294967295u + 1;
+        int main(void) {
+            return a;
+        }
+    "#,
+        &[
+            "1:9: Integer overflow in constant expression of type 'int'",
+            "2:10: Integer overflow in constant expression of type 'long'",
+            "3:9: Shift count is out of range for type 'int'",
+        ],
+    );
+}
+
+#[test]
+fn test_unsigned_long_multiplication_wraps() {
+    let src = "unsigned long x = 18446744073709551615UL * 18446744073709551615UL;";
+    let ast = parse(src, DEFAULT_ERROR_LIMIT).expect("Parse error");
+    let (_, semantics) = validate(ast).expect("Validation error");
+    let Attributes::Static {
+        initial_value: InitialValue::Initial(init),
+        ..
+    } = &semantics.symbols[&Symbol::from("x")].attrs
+    else {
+        panic!("Not a static variable");
+    };
+    assert!(matches!(init[..], [StaticInit::ULong(1)]));
+    assert!(semantics.warnings.is_empty());
+}
@@ -0,0 +1,134 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_invalid_unknown_field_designator() {
+    assert_error(
+        r#"
+        struct point {
+            int x;
+            int y;
+        };
+        int main(void) {
+            struct point p = {.z = 1};
+                            //^^ Aggregate type 'point' does not have field 'z'
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_array_designator_out_of_bounds() {
+    assert_error(
+        r#"
+        int arr[3] = {[3] = 1};
+                    //^^^ Array designator index is out of bounds
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_negative_array_designator() {
+    assert_error(
+        r#"
+        int arr[3] = {[-1] = 1};
+                    //^^^^ Array designator index is out of bounds
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_array_designator_not_constant() {
+    assert_error(
+        r#"
+        int main(void) {
+            int i = 0;
+            int arr[3] = {[i] = 1};
+                         //^ Array designator is not an integer constant
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_field_designator_for_array() {
+    assert_error(
+        r#"
+        int arr[3] = {.x = 1};
+                    //^^ Field designator used to initialize type 'int[3]'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_nested_designator_for_scalar() {
+    assert_error(
+        r#"
+        struct point {
+            int x;
+            int y;
+        };
+        struct point p = {.x[0] = 1};
+                          //^^^ Array designator used to initialize type 'int'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_too_many_elements_after_designator() {
+    assert_error(
+        r#"
+        int main(void) {
+            int arr[3] = {[1] = 1, 2, 3};
+                       //^^^^^^^^^^^^^^^ Too many elements in the initializer
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_union_initialized_twice() {
+    assert_error(
+        r#"
+        union u {
+            int i;
+            double d;
+        };
+        union u x = {.d = 1.0, 2};
+                  //^^^^^^^^^^^^^ Too many elements in the initializer
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_elided_string_for_non_char_array() {
+    assert_error(
+        r#"
+        struct s {
+            int arr[4];
+        };
+        struct s x = {"abc"};
+                    //^^^^^ Can't initialize a non-character type with a string literal
+    "#,
+    );
+}
+
+#[test]
+fn test_elided_string_for_array_of_char_arrays() {
+    assert_warnings(
+        r#"
+        struct t {
+            int a;
+            char s[2][4];
+        };
+        struct t v = {1, "ab", "cd"};
+        int main(void) {
+            struct t w = {1, "ab", "cd"};
+            return v.s[1][0] + w.s[1][0];
+        }
+    "#,
+        &[],
+    );
+}
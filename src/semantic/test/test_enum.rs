@@ -0,0 +1,123 @@
+use super::assert_error;
+
+#[test]
+fn test_invalid_duplicated_enumerator() {
+    assert_error(
+        r#"
+        enum e { A, B, A };
+                     //^ Enumerator 'A' was already declared
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_enumerator_redeclares_variable() {
+    assert_error(
+        r#"
+        int main(void) {
+            int A = 0;
+            enum { A };
+                 //^ 'A' redeclared as a different kind of symbol
+            return A;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_enum_redefinition() {
+    assert_error(
+        r#"
+        enum e { A };
+        enum e { B };
+           //^ Redefinition of enum 'e'
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_enum_tag_used_as_struct() {
+    assert_error(
+        r#"
+        enum e { A };
+        struct e { int x; };
+             //^ Tag does not match previous declaration
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_struct_tag_used_as_enum() {
+    assert_error(
+        r#"
+        struct s { int x; };
+        int main(void) {
+            enum s value = 0;
+          //^^^^^^ Type is not an enum type
+            return value;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_undeclared_enum() {
+    assert_error(
+        r#"
+        int main(void) {
+            enum e value = 0;
+          //^^^^^^ Undeclared enum type 'e'
+            return value;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_non_constant_enumerator() {
+    assert_error(
+        r#"
+        int x = 1;
+        enum e { A = x };
+                   //^ Enumerator value is not an integer constant
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_enumerator_out_of_range() {
+    assert_error(
+        r#"
+        enum e { A = 2147483647, B };
+                               //^ Value of enumerator 'B' is out of range of 'int'
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_call_enumerator() {
+    assert_error(
+        r#"
+        enum e { A };
+        int main(void) {
+            return A();
+                 //^ Called object 'A' is not a function
+        }
+    "#,
+    );
+}
@@ -0,0 +1,93 @@
+use super::assert_error;
+
+#[test]
+fn test_invalid_static_assert_failed() {
+    assert_error(
+        r#"
+        _Static_assert(2 < 1, "two is less than one");
+                     //^^^^^ Static assertion failed: two is less than one
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_assert_failed_in_member_list() {
+    assert_error(
+        r#"
+        struct s {
+            char c;
+            _Static_assert(sizeof(long) == 4, "long size");
+                         //^^^^^^^^^^^^^^^^^ Static assertion failed: long size
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_assert_incomplete_in_member_list() {
+    assert_error(
+        r#"
+        union u {
+            int i;
+            _Static_assert(sizeof(union u) == 4, "union size");
+                                //^^^^^^^ Cannot get size of an incomplete type
+        };
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_assert_not_constant() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x = 1;
+            _Static_assert(x, "x");
+                         //^ Static assertion condition is not an integer constant
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_alignof_incomplete_type() {
+    assert_error(
+        r#"
+        struct s;
+        int main(void) {
+            return _Alignof(struct s);
+                          //^^^^^^^^ Cannot get alignment of an incomplete type
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_unary_plus_on_pointer() {
+    assert_error(
+        r#"
+        int main(void) {
+            int *p = 0;
+            +p;
+           //^ Unary operator requires an arithmetic operator
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_comma_result_not_lvalue() {
+    assert_error(
+        r#"
+        int main(void) {
+            int a = 0;
+            int b = 0;
+            (a, b) = 1;
+          //^^^^^^ Expression is not assignable
+            return 0;
+        }
+    "#,
+    );
+}
@@ -0,0 +1,105 @@
+use super::assert_error;
+
+#[test]
+fn test_invalid_call_constant() {
+    assert_error(
+        r#"
+        int main(void) {
+            return 1();
+                 //^ Called object is not a function
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_call_parenthesized_constant() {
+    assert_error(
+        r#"
+        int main(void) {
+            return 2 (- 3);
+                 //^ Called object is not a function
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_call_through_data_pointer() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x = 0;
+            int *p = &x;
+            return (*p)();
+                 //^^^^ Called object is not a function
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_function_pointer_arithmetic() {
+    assert_error(
+        r#"
+        int f(void);
+        int main(void) {
+            int (*fp)(void) = f;
+            fp = fp + 1;
+               //^^ Cannot add pointers to incomplete types
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_incompatible_function_pointer() {
+    assert_error(
+        r#"
+        long f(long x);
+        int main(void) {
+            int (*fp)(int x) = f;
+                             //^ Cannot convert type for assignment!
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_function_pointer_wrong_argument_count() {
+    assert_error(
+        r#"
+        int apply(int (*f)(int x, int y)) {
+            return f(1);
+                 //^ Function called with the wrong number of arguments
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_sizeof_function_type() {
+    assert_error(
+        r#"
+        typedef int fn(void);
+        int main(void) {
+            return sizeof(fn);
+                        //^^ Cannot get size of a function type
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_unnamed_parameter_in_definition() {
+    assert_error(
+        r#"
+        int f(int a, long) {
+                   //^^^^ Parameter name omitted
+            return a;
+        }
+    "#,
+    );
+}
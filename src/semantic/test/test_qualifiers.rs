@@ -0,0 +1,115 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_invalid_assign_to_const_variable() {
+    assert_error(
+        r#"
+        int main(void) {
+            const int x = 1;
+            x = 2;
+          //^ Cannot assign to a const-qualified lvalue
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_through_pointer_to_const() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x = 1;
+            const int *p = &x;
+            *p = 2;
+          //^^ Cannot assign to a const-qualified lvalue
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_increment_const_parameter() {
+    assert_error(
+        r#"
+        int f(const int count) {
+            count++;
+          //^^^^^ Cannot assign to a const-qualified lvalue
+            return count;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_to_member_of_const_struct() {
+    assert_error(
+        r#"
+        struct s {
+            int a;
+        };
+        int main(void) {
+            const struct s value = {1};
+            value.a = 2;
+          //^^^^^^^ Cannot assign to a const-qualified lvalue
+            return value.a;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_struct_with_const_member() {
+    assert_error(
+        r#"
+        struct s {
+            const int a;
+        };
+        void copy(struct s *dst, struct s *src) {
+            *dst = *src;
+          //^^^^ Cannot assign to a const-qualified lvalue
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_to_const_pointer() {
+    assert_error(
+        r#"
+        int main(void) {
+            int x = 1;
+            int *const p = &x;
+            p = 0;
+          //^ Cannot assign to a const-qualified lvalue
+            return *p;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_discarded_qualifiers() {
+    assert_warnings(
+        r#"
+        int take(char *s);
+        int main(void) {
+            const char *s = "text";
+            volatile int v = 0;
+            char *t = s;
+            int *p = &v;
+            const volatile int *q = p;
+            void *any = q;
+            take(s);
+            return *t + *q + (any == q);
+        }
+    "#,
+        &[
+            "5:15: Conversion from 'const char *' to 'char *' discards qualifiers",
+            "6:14: Conversion from 'volatile int *' to 'int *' discards qualifiers",
+            "8:17: Conversion from 'const volatile int *' to 'void *' discards qualifiers",
+            "9:10: Conversion from 'const char *' to 'char *' discards qualifiers",
+        ],
+    );
+}
@@ -0,0 +1,77 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_valid_thread_local_declarations() {
+    assert_warnings(
+        r#"
+        _Thread_local int counter = 1;
+        extern _Thread_local int counter;
+        extern __thread long total;
+        int main(void) {
+            static _Thread_local int calls;
+            extern _Thread_local int counter;
+            return calls + counter + (int)total;
+        }
+    "#,
+        &[],
+    );
+}
+
+#[test]
+fn test_invalid_automatic_thread_local() {
+    assert_error(
+        r#"
+        int main(void) {
+            _Thread_local int x = 0;
+                            //^ Thread local variables in a block must be static or extern
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_thread_local_function() {
+    assert_error(
+        r#"
+        _Thread_local int f(void);
+                        //^ Functions can't be thread local
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_thread_local_after_non_thread_local() {
+    assert_error(
+        r#"
+        int x;
+        extern _Thread_local int x;
+                               //^ Thread local declaration of 'x' follows non thread local declaration
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_non_thread_local_after_thread_local() {
+    assert_error(
+        r#"
+        _Thread_local int x;
+        int main(void) {
+            extern int x;
+                     //^ Non thread local declaration of 'x' follows thread local declaration
+            return x;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_thread_local_address_constant() {
+    assert_error(
+        r#"
+        _Thread_local int x;
+        int *p = &x;
+               //^^ Non-constant initializer on local static variable
+    "#,
+    );
+}
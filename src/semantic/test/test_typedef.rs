@@ -0,0 +1,148 @@
+use super::assert_error;
+use crate::parser::{DEFAULT_ERROR_LIMIT, parse};
+use crate::pretty::dedent;
+use crate::semantic::{Attributes, FunctionType, Type, validate};
+
+#[test]
+fn test_typedef_resolves_to_aliased_type() {
+    let src = dedent(
+        r#"
+        typedef unsigned long size;
+        typedef size *size_ptr;
+        int main(void) {
+            typedef int size;
+            size s = 0;
+            return s;
+        }
+    "#,
+    );
+    let ast = parse(&src, DEFAULT_ERROR_LIMIT).expect("Parse error");
+    let (_, semantics) = validate(ast).expect("Validation error");
+    let mut aliases: Vec<_> = semantics
+        .type_aliases
+        .iter()
+        .map(|(name, ty)| (name.to_string(), ty.clone()))
+        .collect();
+    aliases.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        aliases,
+        vec![
+            ("size.0".to_string(), Type::ULong),
+            ("size.2".to_string(), Type::Int),
+            ("size_ptr.1".to_string(), Type::Pointer(Type::ULong.into())),
+        ]
+    );
+}
+
+#[test]
+fn test_typedef_of_function_type_declares_function() {
+    let src = dedent(
+        r#"
+        typedef int F(int x);
+        F sq;
+        static F twice;
+        int main(void) {
+            F sq;
+            return sq(3) + twice(2);
+        }
+        int sq(int x) { return x * x; }
+        static int twice(int x) { return 2 * x; }
+    "#,
+    );
+    let ast = parse(&src, DEFAULT_ERROR_LIMIT).expect("Parse error");
+    let (_, semantics) = validate(ast).expect("Validation error");
+    let function_ty = Type::Function(FunctionType {
+        params: vec![Type::Int],
+        ret: Type::Int.into(),
+        variadic: false,
+    });
+    for (name, is_global) in [("sq", true), ("twice", false)] {
+        let data = &semantics.symbols[&name.into()];
+        assert_eq!(data.ty, function_ty);
+        assert!(matches!(
+            data.attrs,
+            Attributes::Function { defined: true, global } if global == is_global
+        ));
+    }
+}
+
+#[test]
+fn test_invalid_initialized_typedef_of_function_type() {
+    assert_error(
+        r#"
+        typedef int F(int x);
+        F sq = 0;
+             //^ Functions can't be initialized
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_typedef_conflicting_types() {
+    assert_error(
+        r#"
+        typedef int T;
+        typedef long T;
+                   //^ Conflicting types for typedef 'T': 'long' and 'int'
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_typedef_redeclared_as_variable() {
+    assert_error(
+        r#"
+        typedef int T;
+        int T;
+          //^ 'T' redeclared as a different kind of symbol
+        int main(void) {
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_variable_redeclared_as_typedef() {
+    assert_error(
+        r#"
+        int main(void) {
+            int T = 0;
+            typedef int T;
+                      //^ 'T' redeclared as a different kind of symbol
+            return T;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_typedef_used_as_variable() {
+    assert_error(
+        r#"
+        typedef int T;
+        int main(void) {
+            return T;
+                 //^ Unexpected type name 'T'
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_typedef_incomplete_struct_variable() {
+    assert_error(
+        r#"
+        struct s;
+        typedef struct s S;
+        int main(void) {
+            S value;
+          //^ Incomplete type
+            return 0;
+        }
+    "#,
+    );
+}
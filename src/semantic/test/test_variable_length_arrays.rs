@@ -0,0 +1,181 @@
+use super::{assert_error, assert_warnings};
+
+#[test]
+fn test_invalid_negative_array_dimension() {
+    assert_error(
+        r#"
+        int main(void) {
+            int arr[-3];
+                  //^^ Array size is negative
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_file_scope_variable_length_array() {
+    assert_error(
+        r#"
+        int n = 3;
+        int arr[n];
+      //^^^^^^^^^^ Variable length array declared outside of a function
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_static_variable_length_array() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            static int arr[n];
+          //^^^^^^^^^^^^^^^^^ Variably modified types can't be static or extern
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_initialized_variable_length_array() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            int arr[n] = {1, 2, 3};
+                       //^^^^^^^^^ Variable length arrays can't be initialized
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_non_integer_array_size() {
+    assert_error(
+        r#"
+        int main(void) {
+            double d = 3.0;
+            int arr[d];
+                  //^ Array size should be an integer
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_variable_length_compound_literal() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            (int[n]){0};
+              //^^^ Compound literal has variable length array type
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_variable_length_array_field() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            struct s {
+                int arr[n];
+              //^^^^^^^^^^ Fields can't have variably modified type
+            };
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_multi_dimension_variable_length_array_parameter() {
+    assert_warnings(
+        r#"
+        int sum(int n, int m, int a[n][m]);
+        int sum(int n, int m, int a[n][m]) {
+            int (*row)[m] = a + 1;
+            return a[0][m - 1] + (*row)[0] + (int)sizeof(a[0]);
+        }
+        int main(void) {
+            int x[2][3] = {{1, 2, 3}, {4, 5, 6}};
+            return sum(2, 3, x);
+        }
+    "#,
+        &[],
+    );
+}
+
+#[test]
+fn test_invalid_variable_length_array_parameter_before_length() {
+    assert_error(
+        r#"
+        int foo(int arr[n], int n);
+                      //^ Undeclared variable 'n'
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_assign_to_variable_length_array() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            int a[n];
+            int b[n];
+            a = b;
+          //^ Type is not assignable
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_goto_into_variable_length_array_scope() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            for (int i = 0; i < 2; i = i + 1) {
+                if (i == 1)
+                    goto l;
+                  //^^^^^^^ Jump into scope of identifier with variably modified type
+                int a[n];
+            l:
+                a[0] = i;
+            }
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_case_in_variable_length_array_scope() {
+    assert_error(
+        r#"
+        int main(void) {
+            int n = 3;
+            switch (n) {
+            case 1:;
+                int a[n];
+            case 3:
+          //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ Switch jumps into scope of identifier with variably modified type
+                a[0] = 1;
+                return a[0];
+            }
+            return 0;
+        }
+    "#,
+    );
+}
@@ -0,0 +1,66 @@
+use super::assert_error;
+
+#[test]
+fn test_invalid_va_start_in_fixed_arguments_function() {
+    assert_error(
+        r#"
+        int f(int n) {
+            __builtin_va_list ap;
+            __builtin_va_start(ap, n);
+          //^^^^^^^^^^^^^^^^^^^^^^^^^ 'va_start' used in function with fixed arguments
+            return 0;
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_variadic_call_missing_fixed_arguments() {
+    assert_error(
+        r#"
+        int f(int n, long m, ...);
+        int main(void) {
+            return f(1);
+                 //^ Function called with the wrong number of arguments
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_va_arg_on_non_va_list() {
+    assert_error(
+        r#"
+        int f(int n, ...) {
+            int *p = &n;
+            return __builtin_va_arg(p, int);
+                                  //^ Expression is not a 'va_list'
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_va_arg_void_type() {
+    assert_error(
+        r#"
+        void f(int n, ...) {
+            __builtin_va_list ap;
+            __builtin_va_start(ap, n);
+            __builtin_va_arg(ap, void);
+                               //^^^^ Invalid type 'void' for 'va_arg'
+        }
+    "#,
+    );
+}
+
+#[test]
+fn test_invalid_conflicting_variadic_declaration() {
+    assert_error(
+        r#"
+        int f(int n, ...);
+        int f(int n);
+          //^ Conflicting declaration types for 'f'
+    "#,
+    );
+}
@@ -0,0 +1,115 @@
+use super::assert_warnings;
+
+#[test]
+fn test_narrowing_conversion() {
+    assert_warnings(
+        r#"
+        long get(void);
+        int take(char c);
+        int main(void) {
+            long l = get();
+            int i = l;
+            unsigned char c = 255;
+            char d = -1;
+            int e = 2.0;
+            int f = 2.5;
+            double g = l;
+            i = 3000000000;
+            take(i);
+            return i + c + d + e + f + (int) g;
+        }
+    "#,
+        &[
+            "5:13: Conversion from 'long' to 'int' may change value",
+            "9:13: Conversion from 'double' to 'int' may change value",
+            "11:9: Conversion from 'long' to 'int' may change value",
+            "12:10: Conversion from 'int' to 'char' may change value",
+        ],
+    );
+}
+
+#[test]
+fn test_float_narrowing_conversion() {
+    assert_warnings(
+        r#"
+        double get(void);
+        int main(void) {
+            double d = get();
+            float f = d;
+            float g = 0.5;
+            float h = 0.1;
+            float i = 16777216;
+            float j = 16777217;
+            int k = f;
+            return (int) (f + g + h + i + j) + k;
+        }
+    "#,
+        &[
+            "4:15: Conversion from 'double' to 'float' may change value",
+            "6:15: Conversion from 'double' to 'float' may change value",
+            "9:13: Conversion from 'float' to 'int' may change value",
+        ],
+    );
+}
+
+#[test]
+fn test_unused_variables() {
+    assert_warnings(
+        r#"
+        int global;
+        int main(void) {
+            int used = 1;
+            int unused;
+            static int unused_static;
+            extern int global;
+            for (int i = 0; i < 10; i = i + 1) {
+                int inner = 2;
+            }
+            return used;
+        }
+    "#,
+        &[
+            "4:9: Unused variable 'unused'",
+            "5:16: Unused variable 'unused_static'",
+            "8:13: Unused variable 'inner'",
+        ],
+    );
+}
+
+#[test]
+fn test_unused_labels() {
+    assert_warnings(
+        r#"
+        int main(void) {
+            goto used;
+        unused:
+            return 1;
+        used:
+            return 0;
+        }
+    "#,
+        &["3:1: Label 'unused' defined but not used"],
+    );
+}
+
+#[test]
+fn test_sign_compare() {
+    assert_warnings(
+        r#"
+        int main(void) {
+            int i = -1;
+            unsigned int u = 1;
+            unsigned long ul = 2;
+            long l = 3;
+            unsigned char c = 4;
+            return (i < u) + (u == i) + (u > 0) + (l < u) + (ul >= l) + (c < i) + (u != -1);
+        }
+    "#,
+        &[
+            "7:12: Comparison of integers of different signs: 'int' and 'unsigned int'",
+            "7:22: Comparison of integers of different signs: 'unsigned int' and 'int'",
+            "7:53: Comparison of integers of different signs: 'unsigned long' and 'long'",
+            "7:75: Comparison of integers of different signs: 'unsigned int' and 'int'",
+        ],
+    );
+}
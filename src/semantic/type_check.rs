@@ -1,32 +1,40 @@
 use crate::alignment::align_offset;
+use crate::ast;
 use crate::ast::{
-    AssignOp, BinaryOp, Block, BlockItem, Constant, Declaration, Expression, ForInit,
-    FunctionDeclaration, Identifier, Initializer, InnerRef, NameAndFields, Node, Program,
-    Statement, StorageClass, TypeSpec, UnaryOp, VarDeclaration,
+    AssignOp, Attribute, BinaryOp, Block, BlockItem, Constant, Declaration, Designator,
+    EnumDeclaration, Expression, ForInit, FunctionDeclaration, Identifier, Initializer,
+    InitializerItem, InnerRef, NameAndFields, Node, NodeId, Program, Statement, StaticAssert,
+    StorageClass, TypeDefDeclaration, TypeSpec, UnaryOp, VarDeclaration,
 };
-use crate::error::{CompilerError, ErrorKind, Result};
+use crate::error::{CompilerError, ErrorKind, Result, Warning};
 use crate::lexer::Span;
+use crate::semantic::constant_eval::{ConstantEvaluator, ConstantValue};
 use crate::semantic::{
-    AggregateKind, AggregateType, Attributes, Field, FunctionType, InitialValue, SemanticData,
-    StaticInit, SwitchCases, SymbolData, Type, TypeEntry,
+    AggregateKind, AggregateType, Attributes, BitField, DeclAttributes, Field, FunctionType,
+    InitTree, InitialValue, SemanticData, StaticInit, SwitchCases, SymbolData, Type, TypeEntry,
+    VA_LIST_TAG, VariableLength, source_name,
 };
+use crate::symbol::Symbol;
 use std::cmp;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 impl SymbolData {
     fn local(ty: Type) -> Self {
         SymbolData {
             ty,
             attrs: Attributes::Local,
+            decl_attrs: DeclAttributes::default(),
         }
     }
-    fn global(ty: Type) -> Self {
+    fn global(ty: Type, thread_local: bool) -> Self {
         SymbolData {
             ty,
             attrs: Attributes::Static {
                 initial_value: InitialValue::NoInitializer,
                 global: true,
+                thread_local,
             },
+            decl_attrs: DeclAttributes::default(),
         }
     }
 }
@@ -35,6 +43,18 @@ impl SymbolData {
 struct TypeChecker {
     semantics: SemanticData,
     switch_stack: VecDeque<SwitchCases>,
+    declarations: HashMap<Symbol, Span>,
+    locals: Vec<(Symbol, Span)>,
+    used_variables: HashSet<Symbol>,
+    in_variadic_function: bool,
+    in_function: bool,
+    /// Identifiers with a variably modified type in scope, which can't be
+    /// jumped over with a `goto` or a `switch`.
+    variably_modified: Vec<Symbol>,
+    /// Number of those identifiers in scope at each enclosing `switch`.
+    switch_scopes: Vec<usize>,
+    label_scopes: HashMap<Symbol, Vec<Symbol>>,
+    gotos: Vec<(Symbol, Vec<Symbol>, Span)>,
 }
 
 impl TypeChecker {
@@ -45,42 +65,97 @@ impl TypeChecker {
                 Declaration::Function(f) => self.check_function_declaration(f, true)?,
                 Declaration::Struct(d) => self.check_type_declaration(d, false)?,
                 Declaration::Union(d) => self.check_type_declaration(d, true)?,
+                Declaration::Enum(d) => self.check_enum_declaration(d)?,
+                Declaration::TypeDef(d) => self.check_typedef_declaration(d)?,
+                Declaration::StaticAssert(d) => self.check_static_assert(d)?,
             }
         }
         Ok(())
     }
 
     fn check_local_var_declaration(&mut self, decl: &VarDeclaration) -> Result<()> {
+        if let Type::Function(function_ty) = decl.type_spec.ty(&self.semantics) {
+            return self.check_function_typedef_declaration(decl, &function_ty, false);
+        }
         let name = decl.name.symbol.clone();
+        let automatic = decl.storage_class.is_none();
+        Self::error_if(
+            automatic && decl.is_thread_local,
+            decl.name.span,
+            "Thread local variables in a block must be static or extern",
+        )?;
+        let decl_attrs = self.variable_attributes(decl, automatic)?;
+        Self::error_if(
+            decl_attrs.weak
+                && !matches!(decl.storage_class.inner_ref(), Some(StorageClass::Extern)),
+            decl.name.span,
+            &format!(
+                "Weak declaration of '{}' must be public",
+                source_name(&name)
+            ),
+        )?;
         self.validate_type_specifier(&decl.type_spec)?;
-        let ty = decl.type_spec.ty();
+        let ty = decl.type_spec.ty(&self.semantics);
         let ty_span = decl.type_spec.span;
         Self::error_if(
-            decl.type_spec.ty().is_void(),
+            decl.type_spec.ty(&self.semantics).is_void(),
             ty_span,
             "Illegal void variable",
         )?;
+        Self::error_if(
+            ty.is_function(),
+            ty_span,
+            "Variables can't have function type",
+        )?;
+        Self::error_if(
+            ty.is_variably_modified() && decl.storage_class.is_some(),
+            ty_span,
+            "Variably modified types can't be static or extern",
+        )?;
+        if ty.is_variable_length_array()
+            && let Some(init) = &decl.init
+        {
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Variable length arrays can't be initialized",
+                init.span,
+            ));
+        }
         if let Some(StorageClass::Extern) = decl.storage_class.inner_ref() {
             if let Some(init) = &decl.init {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: "Initializers are not allowed in local extern variable declarations"
-                        .into(),
-                    span: init.span,
-                });
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Initializers are not allowed in local extern variable declarations",
+                    init.span,
+                ));
             }
             if let Some(data) = self.semantics.symbols.get(&name) {
                 if data.ty != ty {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: format!("Name '{name}' was already declared"),
-                        span: decl.name.span,
-                    });
+                    return Err(self.redeclaration_error(
+                        &name,
+                        format!("Name '{name}' was already declared"),
+                        decl.name.span,
+                    ));
+                }
+                self.check_thread_local_redeclaration(&name, &data.attrs, decl)?;
+                let decl_attrs =
+                    self.merge_attributes(&name, &data.decl_attrs, decl_attrs, decl.name.span)?;
+                if let Some(data) = self.semantics.symbols.get_mut(&name) {
+                    data.decl_attrs = decl_attrs;
                 }
             } else {
+                self.declarations
+                    .entry(name.clone())
+                    .or_insert(decl.name.span);
                 self.semantics.symbols.insert(
                     decl.name.symbol.clone(),
-                    SymbolData::global(decl.type_spec.ty()),
+                    SymbolData {
+                        decl_attrs,
+                        ..SymbolData::global(
+                            decl.type_spec.ty(&self.semantics),
+                            decl.is_thread_local,
+                        )
+                    },
                 );
             }
         } else if let Some(StorageClass::Static) = decl.storage_class.inner_ref() {
@@ -90,19 +165,24 @@ impl TypeChecker {
                 "Incomplete aggregate type",
             )?;
             let initial_value = if let Some(init) = &decl.init {
-                InitialValue::Initial(self.check_static_initializer(init, &decl.type_spec.ty())?)
+                InitialValue::Initial(
+                    self.check_static_initializer(init, &decl.type_spec.ty(&self.semantics))?,
+                )
             } else {
                 InitialValue::single(StaticInit::ZeroInit(
-                    decl.type_spec.ty().size(&self.semantics),
+                    decl.type_spec.ty(&self.semantics).size(&self.semantics),
                 ))
             };
+            if !decl_attrs.unused {
+                self.locals.push((name.clone(), decl.name.span));
+            }
             if let Some(data) = self.semantics.symbols.get(&name) {
-                if data.ty != decl.type_spec.ty() {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: format!("Name '{name}' was previously declared with a different type"),
-                        span: decl.name.span,
-                    });
+                if data.ty != decl.type_spec.ty(&self.semantics) {
+                    return Err(self.redeclaration_error(
+                        &name,
+                        format!("Name '{name}' was previously declared with a different type"),
+                        decl.name.span,
+                    ));
                 }
             } else {
                 Self::error_if(
@@ -110,14 +190,19 @@ impl TypeChecker {
                     ty_span,
                     "Incomplete type",
                 )?;
+                self.declarations
+                    .entry(name.clone())
+                    .or_insert(decl.name.span);
                 self.semantics.symbols.insert(
                     decl.name.symbol.clone(),
                     SymbolData {
-                        ty: decl.type_spec.ty(),
+                        ty: decl.type_spec.ty(&self.semantics),
                         attrs: Attributes::Static {
                             initial_value,
                             global: false,
+                            thread_local: decl.is_thread_local,
                         },
+                        decl_attrs,
                     },
                 );
             }
@@ -127,12 +212,27 @@ impl TypeChecker {
                 ty_span,
                 "Incomplete type",
             )?;
+            // Stack frames are only aligned to 16 bytes
+            Self::error_if(
+                ty.alignment(&self.semantics)
+                    .max(decl_attrs.aligned.unwrap_or(1))
+                    > 16,
+                decl.name.span,
+                "Alignment above 16 bytes isn't supported for local variables",
+            )?;
+            let unused = decl_attrs.unused;
             self.semantics.symbols.insert(
                 decl.name.symbol.clone(),
-                SymbolData::local(decl.type_spec.ty()),
+                SymbolData {
+                    decl_attrs,
+                    ..SymbolData::local(decl.type_spec.ty(&self.semantics))
+                },
             );
+            if !unused {
+                self.locals.push((name.clone(), decl.name.span));
+            }
             if let Some(init) = &decl.init {
-                self.check_initializer(init, &decl.type_spec.ty())?;
+                self.check_initializer(init, &decl.type_spec.ty(&self.semantics))?;
             }
         }
         Ok(())
@@ -143,286 +243,566 @@ impl TypeChecker {
         init: &Node<Initializer>,
         target: &Type,
     ) -> Result<Vec<StaticInit>> {
-        match init.as_ref() {
-            Initializer::Single(expr) => match expr.as_ref() {
-                Expression::String(s) => {
-                    match target {
-                        Type::Array(inner_ty, size) => {
-                            if !inner_ty.is_char() {
-                                return Err(CompilerError {
-                                        kind: ErrorKind::Type,
-                                        msg: "Can't initialize a non-character type with a string literal".into(),
-                                        span: init.span,
-                                    });
-                            }
+        let tree = self.initializer_tree(init, target)?;
+        let mut static_inits = Vec::new();
+        self.check_static_init_tree(&tree, target, &init.expressions(), &mut static_inits)?;
+        Ok(static_inits)
+    }
 
-                            if s.len() > *size {
-                                return Err(CompilerError {
-                                        kind: ErrorKind::Type,
-                                        msg: "Initializer string has more characters than destination array".into(),
-                                        span: init.span,
-                                    });
-                            }
-                            let padding = size - s.len();
-                            let mut result = vec![StaticInit::String {
-                                symbol: s.clone(),
-                                null_terminated: padding > 0,
-                            }];
-                            // We check padding > 1 here because we need to account for the null
-                            // terminator
-                            if padding > 1 {
-                                result.push(StaticInit::ZeroInit(padding - 1));
-                            }
-                            Ok(result)
-                        }
-                        Type::Pointer(inner_ty) => {
-                            if let Type::Char = **inner_ty {
-                                let name = self.semantics.make_string(s);
-                                Ok(vec![StaticInit::Pointer(name)])
-                            } else {
-                                Err(CompilerError {
-                                        kind: ErrorKind::Type,
-                                        msg: "Can't initialize a non-character pointer to a string literal".into(),
-                                        span: init.span,
-                                    })
-                            }
-                        }
-                        _ => Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Non-string initializer on local static variable".to_string(),
-                            span: init.span,
-                        }),
-                    }
+    fn check_static_init_tree(
+        &mut self,
+        tree: &InitTree,
+        target: &Type,
+        exprs: &HashMap<NodeId, &Node<Expression>>,
+        static_inits: &mut Vec<StaticInit>,
+    ) -> Result<()> {
+        let elements = match tree {
+            InitTree::Single(id) => {
+                static_inits.extend(self.check_static_single_initializer(exprs[id], target)?);
+                return Ok(());
+            }
+            InitTree::Aggregate(elements) => elements,
+        };
+        match target.unqualified() {
+            Type::Array(inner_ty, _) => {
+                let size = inner_ty.size(&self.semantics);
+                let mut missing = 0;
+                for element in elements {
+                    let Some(element) = element else {
+                        missing += 1;
+                        continue;
+                    };
+                    Self::push_zero_init(static_inits, missing * size);
+                    missing = 0;
+                    self.check_static_init_tree(element, inner_ty, exprs, static_inits)?;
                 }
-                Expression::Constant(c) => {
-                    let Some(c) = c.cast(target) else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Invalid type of static declaration".into(),
-                            span: init.span,
-                        });
+                Self::push_zero_init(static_inits, missing * size);
+            }
+            Type::Struct(tag) | Type::Union(tag) => {
+                let Some(TypeEntry::Complete(aggregate)) = self.semantics.type_defs.get(tag) else {
+                    unreachable!("Initialized aggregates are complete");
+                };
+                let size = aggregate.size;
+                let fields = aggregate.fields.clone();
+                let mut offset = 0;
+                let mut bit_fields = Vec::new();
+                for (element, field) in elements.iter().zip(fields) {
+                    let Some(element) = element else {
+                        continue;
                     };
-                    Ok(vec![c.to_static_init()])
+                    if let Some(bit_field) = field.bit_field {
+                        let InitTree::Single(id) = element else {
+                            unreachable!("Bit-fields are scalars");
+                        };
+                        let value = self.check_static_bit_field(exprs[id], &field.ty)?;
+                        let position = field.offset * 8 + bit_field.offset as usize;
+                        bit_fields.push((position, bit_field.width, value));
+                        continue;
+                    }
+                    offset = Self::pack_bit_fields(&mut bit_fields, offset, static_inits);
+                    Self::push_zero_init(static_inits, field.offset - offset);
+                    self.check_static_init_tree(element, &field.ty, exprs, static_inits)?;
+                    offset = field.offset + field.ty.size(&self.semantics);
                 }
-                _ => Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: "Non-constant initializer on local static variable".to_string(),
-                    span: init.span,
-                }),
-            },
-            Initializer::Compound(initializers) => match target {
+                offset = Self::pack_bit_fields(&mut bit_fields, offset, static_inits);
+                Self::push_zero_init(static_inits, size - offset);
+            }
+            _ => unreachable!("Only aggregates have compound initializers"),
+        }
+        Ok(())
+    }
+
+    fn push_zero_init(static_inits: &mut Vec<StaticInit>, size: usize) {
+        if size > 0 {
+            static_inits.push(StaticInit::ZeroInit(size));
+        }
+    }
+
+    fn check_static_single_initializer(
+        &mut self,
+        expr: &Node<Expression>,
+        target: &Type,
+    ) -> Result<Vec<StaticInit>> {
+        match expr.as_ref() {
+            Expression::String(s) => match target.unqualified() {
                 Type::Array(inner_ty, size) => {
-                    if initializers.len() > *size {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Too many elements in the initializer".into(),
-                            span: init.span,
-                        });
+                    if !inner_ty.is_char() {
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Can't initialize a non-character type with a string literal",
+                            expr.span,
+                        ));
                     }
-                    let mut static_inits = Vec::with_capacity(*size);
-                    for initializer in initializers {
-                        let inner_inits = self.check_static_initializer(initializer, inner_ty)?;
-                        static_inits.extend(inner_inits);
+
+                    if s.len() > *size {
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Initializer string has more characters than destination array",
+                            expr.span,
+                        ));
                     }
-                    let padding = (size - initializers.len()) * inner_ty.size(&self.semantics);
-                    if padding > 0 {
-                        static_inits.push(StaticInit::ZeroInit(padding));
+                    let padding = size - s.len();
+                    let mut result = vec![StaticInit::String {
+                        symbol: s.clone(),
+                        null_terminated: padding > 0,
+                    }];
+                    // We check padding > 1 here because we need to account for the null
+                    // terminator
+                    if padding > 1 {
+                        result.push(StaticInit::ZeroInit(padding - 1));
                     }
-                    Ok(static_inits)
+                    Ok(result)
                 }
-                Type::Struct(tag) => {
-                    let Some(TypeEntry::Complete(aggregate)) = self.semantics.type_defs.get(tag)
-                    else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Unknown structure type to initialize".into(),
-                            span: init.span,
-                        });
-                    };
-                    if initializers.len() > aggregate.fields.len() {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Too many elements in the initializer".into(),
-                            span: init.span,
-                        });
-                    }
-                    let mut offset = 0;
-                    let mut static_inits = Vec::new();
-                    let struct_size = aggregate.size;
-                    let fields = aggregate.fields.clone();
-                    for (init, field) in initializers.iter().zip(fields) {
-                        if field.offset != offset {
-                            static_inits.push(StaticInit::ZeroInit(field.offset - offset));
-                        }
-                        let inner_inits = self.check_static_initializer(init, &field.ty)?;
-                        static_inits.extend(inner_inits);
-                        offset = field.offset + field.ty.size(&self.semantics);
-                    }
-                    if struct_size != offset {
-                        static_inits.push(StaticInit::ZeroInit(struct_size - offset));
+                Type::Pointer(inner_ty) => {
+                    if let Type::Char = inner_ty.unqualified() {
+                        let name = self.semantics.make_string(s);
+                        Ok(vec![StaticInit::Pointer(name, 0)])
+                    } else {
+                        Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Can't initialize a non-character pointer to a string literal",
+                            expr.span,
+                        ))
                     }
-                    Ok(static_inits)
                 }
-                Type::Union(tag) => {
-                    let Some(TypeEntry::Complete(union_def)) = self.semantics.type_defs.get(tag)
-                    else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Unknown union type to initialize".into(),
-                            span: init.span,
-                        });
-                    };
-                    let [single_init] = initializers.as_slice() else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Too many elements in the initializer".into(),
-                            span: init.span,
-                        });
-                    };
-                    let union_size = union_def.size;
-
-                    let first_field = union_def
-                        .fields
-                        .first()
-                        .expect("Complete unions should always have fields")
-                        .clone();
-                    let mut static_inits =
-                        self.check_static_initializer(single_init, &first_field.ty)?;
-                    let field_size = first_field.ty.size(&self.semantics);
-                    if union_size != field_size {
-                        static_inits.push(StaticInit::ZeroInit(union_size - field_size));
-                    }
-                    Ok(static_inits)
+                _ => Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Non-string initializer on local static variable",
+                    expr.span,
+                )),
+            },
+            _ => {
+                let value = self.static_value(expr, target)?;
+                Ok(vec![value.to_static_init(&self.semantics)])
+            }
+        }
+    }
+
+    fn check_static_bit_field(&mut self, expr: &Node<Expression>, ty: &Type) -> Result<u64> {
+        match self.static_value(expr, ty)? {
+            ConstantValue::Arithmetic(c) => Ok(c.as_u64()),
+            ConstantValue::Address(..) => unreachable!("Bit-fields are integers"),
+        }
+    }
+
+    /// Value of a constant expression initializing an object of static
+    /// storage duration, converted to the type of the object.
+    fn static_value(&mut self, expr: &Node<Expression>, target: &Type) -> Result<ConstantValue> {
+        let ty = self.check_initializer_expr(expr)?;
+        // Pointers report why they don't convert like in any other assignment
+        self.convert_by_assignment(expr, &ty, target)
+            .map_err(|err| {
+                if ty.is_pointer() {
+                    return err;
                 }
+                CompilerError::new(
+                    ErrorKind::Type,
+                    "Invalid type of static declaration",
+                    expr.span,
+                )
+            })?;
+        ConstantEvaluator::new(&mut self.semantics)
+            .evaluate(expr)?
+            .ok_or_else(|| {
+                CompilerError::new(
+                    ErrorKind::Type,
+                    "Non-constant initializer on local static variable",
+                    expr.span,
+                )
+            })
+    }
 
-                _ => Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: "Cannot initialize a scalar value with a compound initializer".into(),
-                    span: init.span,
-                }),
-            },
+    /// Checks an integer constant expression, `what` names it in errors.
+    fn integer_constant(&mut self, expr: &Node<Expression>, what: &str) -> Result<i128> {
+        let ty = self.check_and_convert_expr(expr)?;
+        let value = if ty.is_int() {
+            ConstantEvaluator::new(&mut self.semantics).integer(expr)?
+        } else {
+            None
+        };
+        value.ok_or_else(|| {
+            CompilerError::new(
+                ErrorKind::Type,
+                format!("{what} is not an integer constant"),
+                expr.span,
+            )
+        })
+    }
+
+    /// Bit-fields can share bytes, so their static initializers are emitted
+    /// byte by byte. Takes the `(bit position, width, value)` of each
+    /// bit-field and returns the offset after the last written byte.
+    fn pack_bit_fields(
+        bit_fields: &mut Vec<(usize, u8, u64)>,
+        offset: usize,
+        static_inits: &mut Vec<StaticInit>,
+    ) -> usize {
+        let Some(first) = bit_fields.iter().map(|(position, ..)| position / 8).min() else {
+            return offset;
+        };
+        let end = bit_fields
+            .iter()
+            .map(|(position, width, _)| (position + *width as usize).div_ceil(8))
+            .max()
+            .unwrap_or(first);
+        let mut bytes = vec![0u8; end - first];
+        for (position, width, value) in bit_fields.drain(..) {
+            for bit in 0..width as usize {
+                if value >> bit & 1 == 1 {
+                    let target = position + bit - first * 8;
+                    bytes[target / 8] |= 1 << (target % 8);
+                }
+            }
         }
+        if first != offset {
+            static_inits.push(StaticInit::ZeroInit(first - offset));
+        }
+        static_inits.extend(bytes.into_iter().map(StaticInit::UChar));
+        end
     }
 
     fn check_initializer(&mut self, init: &Node<Initializer>, target: &Type) -> Result<()> {
-        match init.as_ref() {
-            Initializer::Single(expr) => {
-                let init_ty = if let Expression::String(s) = expr.as_ref()
-                    && let Type::Array(inner_ty, size) = target
-                {
-                    if !inner_ty.is_char() {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Can't initialize a non-character type with a string literal"
-                                .into(),
-                            span: init.span,
-                        });
-                    }
+        let tree = self.initializer_tree(init, target)?;
+        self.check_init_tree(&tree, target, &init.expressions())?;
+        self.semantics.initializers.insert(init.id, tree);
+        Ok(())
+    }
 
-                    if s.len() > *size {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Initializer string has more characters than destination array"
-                                .into(),
-                            span: init.span,
-                        });
+    fn check_init_tree(
+        &mut self,
+        tree: &InitTree,
+        target: &Type,
+        exprs: &HashMap<NodeId, &Node<Expression>>,
+    ) -> Result<()> {
+        match tree {
+            InitTree::Single(id) => self.check_single_initializer(exprs[id], target),
+            InitTree::Aggregate(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    if let Some(element) = element {
+                        let element_ty = self.element_type(target, index);
+                        self.check_init_tree(element, &element_ty, exprs)?;
                     }
-                    self.semantics
-                        .expression_types
-                        .insert(expr.id, target.clone());
-                    target.clone()
-                } else {
-                    self.check_and_convert_expr(expr)?
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn check_single_initializer(&mut self, expr: &Node<Expression>, target: &Type) -> Result<()> {
+        let init_ty = if let Expression::String(s) = expr.as_ref()
+            && let Type::Array(inner_ty, size) = target
+        {
+            if !inner_ty.is_char() {
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Can't initialize a non-character type with a string literal",
+                    expr.span,
+                ));
+            }
+
+            if s.len() > *size {
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Initializer string has more characters than destination array",
+                    expr.span,
+                ));
+            }
+            self.semantics
+                .expression_types
+                .insert(expr.id, target.clone());
+            target.clone()
+        } else {
+            self.check_initializer_expr(expr)?
+        };
+        self.convert_by_assignment(expr, &init_ty, target)?;
+        Ok(())
+    }
+
+    /// Expressions in initializers may already be checked while eliding braces.
+    fn check_initializer_expr(&mut self, expr: &Node<Expression>) -> Result<Type> {
+        match self.semantics.expression_types.get(&expr.id) {
+            Some(ty) => Ok(ty.clone()),
+            None => self.check_and_convert_expr(expr),
+        }
+    }
+
+    /// Resolves designators and elided braces, shaping the initializer like
+    /// the initialized type.
+    fn initializer_tree(&mut self, init: &Node<Initializer>, target: &Type) -> Result<InitTree> {
+        match init.as_ref() {
+            Initializer::Single(expr) => Ok(InitTree::Single(expr.id)),
+            // Initializers of scalars and strings for char arrays may be
+            // enclosed in braces, as in `(int){1}`
+            Initializer::Compound(items)
+                if let [item] = items.as_slice()
+                    && item.designators.is_empty()
+                    && let Initializer::Single(expr) = item.init.as_ref()
+                    && (target.is_scalar()
+                        || matches!(target.unqualified(), Type::Array(inner, _) if inner.is_char())
+                            && matches!(expr.as_ref(), Expression::String(_))) =>
+            {
+                Ok(InitTree::Single(expr.id))
+            }
+            Initializer::Compound(items) => {
+                Self::error_if(
+                    !target.is_array() && !target.is_aggregate(),
+                    init.span,
+                    "Cannot initialize a scalar value with a compound initializer",
+                )?;
+                let mut elements = self.initializer_elements(target, init.span)?;
+                let mut next = 0;
+                self.fill_initializer(
+                    target,
+                    &mut elements,
+                    items,
+                    &mut next,
+                    None,
+                    Some(init.span),
+                )?;
+                Ok(InitTree::Aggregate(elements))
+            }
+        }
+    }
+
+    /// The length of an array sized by its initializer, which is one past
+    /// the last initialized element.
+    fn initializer_length(&mut self, init: &Node<Initializer>, element: &Type) -> Result<usize> {
+        let Initializer::Compound(items) = init.as_ref() else {
+            unreachable!("Compound literals have an initializer list");
+        };
+        if let [item] = items.as_slice()
+            && item.designators.is_empty()
+            && let Initializer::Single(expr) = item.init.as_ref()
+            && let Expression::String(s) = expr.as_ref()
+            && element.is_char()
+        {
+            return Ok(s.len() + 1);
+        }
+        // Every item initializes at least one element, either after the
+        // previous one or at its designator
+        let mut bound = items.len();
+        for item in items {
+            if let Some(designator) = item.designators.first()
+                && let Designator::Index(index) = designator.as_ref()
+            {
+                let position = self.integer_constant(index, "Array designator")?;
+                bound = bound.max(position.max(0) as usize + items.len());
+            }
+        }
+        let array = Type::Array(element.clone().into(), bound);
+        let InitTree::Aggregate(elements) = self.initializer_tree(init, &array)? else {
+            unreachable!("Array initializer lists are aggregates");
+        };
+        Ok(elements
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |last| last + 1))
+    }
+
+    fn initializer_elements(&self, ty: &Type, span: Span) -> Result<Vec<Option<InitTree>>> {
+        match ty.unqualified() {
+            Type::Array(_, size) => Ok(vec![None; *size]),
+            Type::Struct(tag) | Type::Union(tag) => match self.semantics.type_defs.get(tag) {
+                Some(TypeEntry::Complete(aggregate)) => Ok(vec![None; aggregate.fields.len()]),
+                _ if matches!(ty.unqualified(), Type::Struct(_)) => Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Unknown structure type to initialize",
+                    span,
+                )),
+                _ => Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Unknown union type to initialize",
+                    span,
+                )),
+            },
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn element_type(&self, ty: &Type, index: usize) -> Type {
+        match ty.unqualified() {
+            Type::Array(inner_ty, _) => inner_ty.as_ref().clone(),
+            Type::Struct(tag) | Type::Union(tag) => {
+                let Some(TypeEntry::Complete(aggregate)) = self.semantics.type_defs.get(tag) else {
+                    unreachable!("Initialized aggregates are complete");
                 };
-                self.convert_by_assignment(expr, &init_ty, target)?;
+                aggregate.fields[index].ty.clone()
             }
-            Initializer::Compound(initializers) => match target {
-                Type::Array(inner_ty, size) => {
-                    if initializers.len() > *size {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Too many elements in the initializer".into(),
-                            span: init.span,
-                        });
-                    }
-                    for inner_innit in initializers {
-                        self.check_initializer(inner_innit, inner_ty)?;
-                    }
-                }
-                Type::Struct(tag) => {
-                    let Some(TypeEntry::Complete(aggregate)) = self.semantics.type_defs.get(tag)
-                    else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Unknown structure type to initialize".into(),
-                            span: init.span,
-                        });
-                    };
-                    if initializers.len() > aggregate.fields.len() {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Too many elements in the initializer".into(),
-                            span: init.span,
-                        });
-                    }
-                    let field_tys = aggregate
-                        .fields
-                        .iter()
-                        .map(|f| f.ty.clone())
-                        .collect::<Vec<_>>();
-                    for (inner_init, field_ty) in initializers.iter().zip(field_tys.iter()) {
-                        self.check_initializer(inner_init, field_ty)?;
+            _ => unreachable!("Only aggregates have elements"),
+        }
+    }
+
+    /// Initializes the elements of `ty` from `items[*next..]`. A list with
+    /// elided braces (`braced` is `None`) stops once it is full or at the next
+    /// designator, which belongs to an enclosing braced list. `applied` holds
+    /// the designators left once those of the current item were applied.
+    fn fill_initializer<'a>(
+        &mut self,
+        ty: &Type,
+        elements: &mut [Option<InitTree>],
+        items: &'a [InitializerItem],
+        next: &mut usize,
+        mut applied: Option<&'a [Node<Designator>]>,
+        braced: Option<Span>,
+    ) -> Result<()> {
+        let is_union = matches!(ty.unqualified(), Type::Union(_));
+        let mut position = 0;
+        while let Some(item) = items.get(*next) {
+            let mut designators = match applied.take() {
+                Some(designators) => designators,
+                None if item.designators.is_empty() => &[],
+                None if braced.is_none() => break,
+                None => &item.designators,
+            };
+            if let Some((designator, rest)) = designators.split_first() {
+                position = self.designator_position(ty, designator)?;
+                designators = rest;
+            } else if position >= elements.len() {
+                match braced {
+                    Some(span) => {
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Too many elements in the initializer",
+                            span,
+                        ));
                     }
+                    None => break,
                 }
-                Type::Union(tag) => {
-                    let Some(TypeEntry::Complete(union_def)) = self.semantics.type_defs.get(tag)
-                    else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Unknown union type to initialize".into(),
-                            span: init.span,
-                        });
-                    };
-                    let [single_init] = initializers.as_slice() else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Too many elements in the initializer".into(),
-                            span: init.span,
-                        });
-                    };
+            }
+            let element_ty = self.element_type(ty, position);
+            let element = if !designators.is_empty() {
+                let mut inner = match elements[position].take() {
+                    Some(InitTree::Aggregate(inner)) => inner,
+                    _ => self.initializer_elements(&element_ty, item.init.span)?,
+                };
+                self.fill_initializer(
+                    &element_ty,
+                    &mut inner,
+                    items,
+                    next,
+                    Some(designators),
+                    None,
+                )?;
+                InitTree::Aggregate(inner)
+            } else if let Initializer::Single(expr) = item.init.as_ref()
+                && self.is_brace_elided(&element_ty, expr)?
+            {
+                let mut inner = self.initializer_elements(&element_ty, item.init.span)?;
+                self.fill_initializer(&element_ty, &mut inner, items, next, Some(&[]), None)?;
+                InitTree::Aggregate(inner)
+            } else {
+                *next += 1;
+                self.initializer_tree(&item.init, &element_ty)?
+            };
+            if is_union {
+                elements.fill(None);
+            }
+            elements[position] = Some(element);
+            position = if is_union {
+                elements.len()
+            } else {
+                position + 1
+            };
+        }
+        Ok(())
+    }
 
-                    let first_ty = union_def
-                        .fields
-                        .first()
-                        .expect("Complete unions should always have fields")
-                        .ty
-                        .clone();
-                    self.check_initializer(single_init, &first_ty)?;
-                }
-                _ => {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Cannot initialize a scalar value with a compound initializer".into(),
-                        span: init.span,
-                    });
+    fn designator_position(&mut self, ty: &Type, designator: &Node<Designator>) -> Result<usize> {
+        match (designator.as_ref(), ty.unqualified()) {
+            (Designator::Index(index), Type::Array(_, size)) => {
+                let position = self.integer_constant(index, "Array designator")?;
+                Self::error_if(
+                    position < 0 || position >= *size as i128,
+                    designator.span,
+                    "Array designator index is out of bounds",
+                )?;
+                Ok(position as usize)
+            }
+            (Designator::Field(name), Type::Struct(tag) | Type::Union(tag)) => {
+                let Some(TypeEntry::Complete(aggregate)) = self.semantics.type_defs.get(tag) else {
+                    unreachable!("Initialized aggregates are complete");
+                };
+                aggregate
+                    .fields
+                    .iter()
+                    .position(|field| field.name == name.symbol)
+                    .ok_or_else(|| {
+                        CompilerError::new(
+                            ErrorKind::Type,
+                            format!(
+                                "Aggregate type '{}' does not have field '{}'",
+                                source_name(tag),
+                                name.symbol
+                            ),
+                            designator.span,
+                        )
+                    })
+            }
+            (Designator::Index(_), _) => Err(CompilerError::new(
+                ErrorKind::Type,
+                format!("Array designator used to initialize type '{ty}'"),
+                designator.span,
+            )),
+            (Designator::Field(_), _) => Err(CompilerError::new(
+                ErrorKind::Type,
+                format!("Field designator used to initialize type '{ty}'"),
+                designator.span,
+            )),
+        }
+    }
+
+    /// An expression initializing an aggregate element starts the
+    /// initializer of its first scalar, unless it initializes the whole
+    /// element: a struct or union of the same type, or a string literal for
+    /// an array of characters. A string literal for an array of arrays of
+    /// characters initializes the first of them, for any other array it is
+    /// reported as initializing the whole array.
+    fn is_brace_elided(&mut self, element_ty: &Type, expr: &Node<Expression>) -> Result<bool> {
+        match element_ty.unqualified() {
+            Type::Array(inner, _) if matches!(expr.as_ref(), Expression::String(_)) => {
+                let mut innermost = inner.as_ref();
+                while let Type::Array(element, _) = innermost.unqualified() {
+                    innermost = element;
                 }
-            },
+                Ok(!inner.is_char() && innermost.is_char())
+            }
+            Type::Array(..) => Ok(true),
+            Type::Struct(_) | Type::Union(_) => {
+                let expr_ty = self.check_initializer_expr(expr)?;
+                Ok(expr_ty.unqualified() != element_ty.unqualified())
+            }
+            _ => Ok(false),
         }
-        Ok(())
     }
 
     fn check_file_var_declaration(&mut self, decl: &VarDeclaration) -> Result<()> {
+        if let Type::Function(function_ty) = decl.type_spec.ty(&self.semantics) {
+            return self.check_function_typedef_declaration(decl, &function_ty, true);
+        }
+        let mut decl_attrs = self.variable_attributes(decl, false)?;
         self.validate_type_specifier(&decl.type_spec)?;
-        let ty = decl.type_spec.ty();
+        let ty = decl.type_spec.ty(&self.semantics);
         let ty_span = decl.type_spec.span;
+        Self::error_if(
+            ty.is_variably_modified(),
+            ty_span,
+            "Variable length array declared outside of a function",
+        )?;
         Self::error_if(ty.is_void(), ty_span, "Illegal void variable")?;
+        Self::error_if(
+            ty.is_function(),
+            ty_span,
+            "Variables can't have function type",
+        )?;
         let mut initial_value = if let Some(init) = &decl.init {
             Self::error_if(
                 ty.is_incomplete_aggregate(&self.semantics),
                 ty_span,
                 "Incomplete aggregate type",
             )?;
-            InitialValue::Initial(self.check_static_initializer(init, &decl.type_spec.ty())?)
+            InitialValue::Initial(
+                self.check_static_initializer(init, &decl.type_spec.ty(&self.semantics))?,
+            )
         } else if let Some(StorageClass::Extern) = decl.storage_class.inner_ref() {
             InitialValue::NoInitializer
         } else {
@@ -436,40 +816,42 @@ impl TypeChecker {
         let mut global = !matches!(decl.storage_class.inner_ref(), Some(StorageClass::Static));
         let name = decl.name.symbol.clone();
         if let Some(data) = self.semantics.symbols.get(&name) {
-            if data.ty != decl.type_spec.ty() {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Variable '{name}' is already declared with a different type"),
-                    span: decl.name.span,
-                });
+            if data.ty != decl.type_spec.ty(&self.semantics) {
+                return Err(self.redeclaration_error(
+                    &name,
+                    format!("Variable '{name}' is already declared with a different type"),
+                    decl.name.span,
+                ));
             }
+            self.check_thread_local_redeclaration(&name, &data.attrs, decl)?;
             let Attributes::Static {
                 initial_value: old_initial,
                 global: old_global,
+                ..
             } = &data.attrs
             else {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Variable '{name}' does not have variable attributes"),
-                    span: decl.name.span,
-                });
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    format!("Variable '{name}' does not have variable attributes"),
+                    decl.name.span,
+                ));
             };
             if matches!(decl.storage_class.inner_ref(), Some(StorageClass::Extern)) {
                 global = *old_global
             } else if *old_global != global {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Variable '{name}' has conflicting linkage"),
-                    span: decl.name.span,
-                });
+                return Err(self.redeclaration_error(
+                    &name,
+                    format!("Variable '{name}' has conflicting linkage"),
+                    decl.name.span,
+                ));
             }
             initial_value = match (old_initial, initial_value) {
                 (InitialValue::Initial(_), InitialValue::Initial(_)) => {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: format!("Variable '{name}' has conflicting definitions"),
-                        span: decl.name.span,
-                    });
+                    return Err(self.redeclaration_error(
+                        &name,
+                        format!("Variable '{name}' has conflicting definitions"),
+                        decl.name.span,
+                    ));
                 }
                 (InitialValue::Initial(_), _) => old_initial.clone(),
                 (
@@ -478,14 +860,26 @@ impl TypeChecker {
                 ) => InitialValue::Tentative,
                 (_, initial_value) => initial_value,
             };
+            decl_attrs =
+                self.merge_attributes(&name, &data.decl_attrs, decl_attrs, decl.name.span)?;
         }
+        Self::error_if(
+            decl_attrs.weak && !global,
+            decl.name.span,
+            &format!("Weak declaration of '{name}' must be public"),
+        )?;
         let data = SymbolData {
-            ty: decl.type_spec.ty(),
+            ty: decl.type_spec.ty(&self.semantics),
             attrs: Attributes::Static {
                 initial_value,
                 global,
+                thread_local: decl.is_thread_local,
             },
+            decl_attrs,
         };
+        self.declarations
+            .entry(name.clone())
+            .or_insert(decl.name.span);
         self.semantics.symbols.insert(name, data);
         Ok(())
     }
@@ -495,8 +889,26 @@ impl TypeChecker {
         decl: &FunctionDeclaration,
         top_level: bool,
     ) -> Result<()> {
-        let name = decl.name.symbol.clone();
-        let mut function_ty = decl.type_spec.ty();
+        Self::error_if(
+            decl.is_thread_local,
+            decl.name.span,
+            "Functions can't be thread local",
+        )?;
+        let decl_attrs = self.decl_attributes(&decl.attributes)?;
+        self.validate_type_specifier(&decl.type_spec.ret)?;
+        for (i, param) in decl.type_spec.params.iter().enumerate() {
+            self.validate_type_specifier(param)?;
+            // The lengths of the following parameters can refer to this one
+            if let Some(name) = decl.params.get(i)
+                && !name.symbol.as_ref().is_empty()
+            {
+                let ty = decl.type_spec.param_ty(i, &self.semantics);
+                self.semantics
+                    .symbols
+                    .insert(name.symbol.clone(), SymbolData::local(ty));
+            }
+        }
+        let function_ty = decl.type_spec.ty(&self.semantics);
 
         Self::error_if(
             function_ty.ret.is_array(),
@@ -504,106 +916,258 @@ impl TypeChecker {
             "A function cannot return array",
         )?;
 
-        self.validate_type_specifier(&decl.type_spec.ret)?;
-        for param in &decl.type_spec.params {
-            self.validate_type_specifier(param)?;
-        }
+        self.declare_function(
+            &decl.name,
+            &function_ty,
+            &decl.storage_class,
+            decl_attrs,
+            decl.body.is_some(),
+            top_level,
+        )?;
+        if let Some(body) = &decl.body {
+            if !top_level {
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Nested function definitions are not allowed ",
+                    decl.name.span,
+                ));
+            }
 
-        // Replace array params with pointers
-        for param in &mut function_ty.params {
-            Self::error_if(param.is_void(), decl.name.span, "Illegal void parameter")?;
-            if let Type::Array(inner, _) = param {
-                *param = Type::Pointer(inner.clone());
+            for i in 0..decl.params.len() {
+                let param_name = &decl.params[i];
+                let param_ty = &function_ty.params[i];
+                let param_ty_spec = &decl.type_spec.params[i];
+
+                Self::error_if(
+                    param_ty.is_incomplete_aggregate(&self.semantics),
+                    param_ty_spec.span,
+                    "Aggregate type is not complete",
+                )?;
+                // Qualifiers don't take part in the function type, but they
+                // still apply to the parameter inside the body.
+                let qualifiers = param_ty_spec.ty(&self.semantics).qualifiers();
+                self.semantics.symbols.insert(
+                    param_name.symbol.clone(),
+                    SymbolData::local(param_ty.clone().qualified(qualifiers)),
+                );
             }
+            Self::error_if(
+                function_ty.ret.is_incomplete_aggregate(&self.semantics),
+                decl.type_spec.ret.span,
+                "Aggregate type is not complete",
+            )?;
+            self.in_variadic_function = function_ty.variadic;
+            self.in_function = true;
+            self.check_block(&decl.type_spec.ty(&self.semantics), body)?;
+            self.in_function = false;
+            self.in_variadic_function = false;
+            self.check_unused_variables();
+            self.check_gotos()?;
+        }
+        Ok(())
+    }
+
+    /// Declarators whose typedef names a function type declare functions,
+    /// like `F f;` after `typedef int F(int);`.
+    fn check_function_typedef_declaration(
+        &mut self,
+        decl: &VarDeclaration,
+        function_ty: &FunctionType,
+        top_level: bool,
+    ) -> Result<()> {
+        Self::error_if(
+            decl.is_thread_local,
+            decl.name.span,
+            "Functions can't be thread local",
+        )?;
+        if let Some(init) = &decl.init {
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Functions can't be initialized",
+                init.span,
+            ));
+        }
+        let decl_attrs = self.decl_attributes(&decl.attributes)?;
+        self.declare_function(
+            &decl.name,
+            function_ty,
+            &decl.storage_class,
+            decl_attrs,
+            false,
+            top_level,
+        )
+    }
+
+    /// Declares a function, checking it against its previous declarations.
+    fn declare_function(
+        &mut self,
+        identifier: &Node<Identifier>,
+        function_ty: &FunctionType,
+        storage_class: &Option<Node<StorageClass>>,
+        mut decl_attrs: DeclAttributes,
+        has_body: bool,
+        top_level: bool,
+    ) -> Result<()> {
+        let name = identifier.symbol.clone();
+        for param in &function_ty.params {
+            Self::error_if(param.is_void(), identifier.span, "Illegal void parameter")?;
         }
 
         let this_ty = Type::Function(function_ty.clone());
 
-        let has_body = decl.body.is_some();
         let mut already_defined = false;
-        let is_static = matches!(decl.storage_class.inner_ref(), Some(StorageClass::Static));
+        let is_static = matches!(storage_class.inner_ref(), Some(StorageClass::Static));
 
         if !top_level && is_static {
-            return Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: "Block scoped function declarations can't be static".into(),
-                span: decl.storage_class.as_ref().unwrap().span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Block scoped function declarations can't be static",
+                storage_class.as_ref().unwrap().span,
+            ));
         }
 
         let mut is_global = !is_static;
 
         if let Some(data) = self.semantics.symbols.get(&name) {
             if data.ty != this_ty {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Conflicting declaration types for '{name}'"),
-                    span: decl.name.span,
-                });
+                return Err(self.redeclaration_error(
+                    &name,
+                    format!("Conflicting declaration types for '{name}'"),
+                    identifier.span,
+                ));
             };
             let Attributes::Function { defined, global } = data.attrs else {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Function '{name}' does not have function attributes"),
-                    span: decl.name.span,
-                });
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    format!("Function '{name}' does not have function attributes"),
+                    identifier.span,
+                ));
             };
             already_defined = defined;
             if already_defined && has_body {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Function '{name}' is defined more than once"),
-                    span: decl.name.span,
-                });
+                return Err(self.redeclaration_error(
+                    &name,
+                    format!("Function '{name}' is defined more than once"),
+                    identifier.span,
+                ));
             }
             if global && is_static {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!("Function '{name}' was previously declared as non-static"),
-                    span: decl.name.span,
-                });
+                return Err(self
+                    .redeclaration_error(
+                        &name,
+                        format!("Function '{name}' was previously declared as non-static"),
+                        identifier.span,
+                    )
+                    .with_note(
+                        "a static declaration must come before any other declaration of the \
+                         function",
+                    ));
             }
-            is_global = global
+            is_global = global;
+            decl_attrs =
+                self.merge_attributes(&name, &data.decl_attrs, decl_attrs, identifier.span)?;
         }
+        Self::error_if(
+            decl_attrs.weak && !is_global,
+            identifier.span,
+            &format!("Weak declaration of '{name}' must be public"),
+        )?;
         let data = SymbolData {
             ty: this_ty,
             attrs: Attributes::Function {
                 defined: already_defined || has_body,
                 global: is_global,
             },
+            decl_attrs,
         };
+        self.declarations
+            .entry(name.clone())
+            .or_insert(identifier.span);
         self.semantics.symbols.insert(name, data);
-        if let Some(body) = &decl.body {
-            if !top_level {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: "Nested function definitions are not allowed ".to_owned(),
-                    span: decl.name.span,
-                });
+        Ok(())
+    }
+
+    fn check_unused_variables(&mut self) {
+        for (name, span) in std::mem::take(&mut self.locals) {
+            if !self.used_variables.contains(&name) {
+                self.semantics.warnings.push(CompilerError::warning(
+                    Warning::UnusedVariable,
+                    format!("Unused variable '{}'", source_name(&name)),
+                    span,
+                ));
             }
+        }
+    }
 
-            for i in 0..decl.params.len() {
-                let param_name = &decl.params[i];
-                let param_ty = &function_ty.params[i];
-                let param_ty_spec = &decl.type_spec.params[i];
+    /// Enumerators without a value take the one following the previous
+    /// enumerator.
+    fn check_enum_declaration(&mut self, decl: &EnumDeclaration) -> Result<()> {
+        let mut next_value = 0;
+        for enumerator in &decl.enumerators {
+            if let Some(value) = &enumerator.value {
+                next_value = self.integer_constant(value, "Enumerator value")?;
+            }
+            let name = &enumerator.name.symbol;
+            let value = i32::try_from(next_value).map_err(|_| {
+                CompilerError::new(
+                    ErrorKind::Type,
+                    format!(
+                        "Value of enumerator '{}' is out of range of 'int'",
+                        source_name(name)
+                    ),
+                    enumerator.span,
+                )
+            })?;
+            self.semantics.enumerators.insert(name.clone(), value);
+            next_value += 1;
+        }
+        let Some(name) = &decl.name else {
+            return Ok(());
+        };
+        match self.semantics.type_defs.get(&name.symbol) {
+            None => {
+                self.semantics
+                    .type_defs
+                    .insert(name.symbol.clone(), TypeEntry::Enum);
+                Ok(())
+            }
+            Some(TypeEntry::Enum) => Err(CompilerError::new(
+                ErrorKind::Type,
+                format!("Redefinition of enum '{}'", source_name(&name.symbol)),
+                name.span,
+            )),
+            Some(_) => Err(CompilerError::new(
+                ErrorKind::Type,
+                "Tag does not match previous declaration",
+                name.span,
+            )),
+        }
+    }
 
-                Self::error_if(
-                    param_ty.is_incomplete_aggregate(&self.semantics),
-                    param_ty_spec.span,
-                    "Aggregate type is not complete",
-                )?;
-                self.semantics.symbols.insert(
-                    param_name.symbol.clone(),
-                    SymbolData::local(param_ty.clone()),
-                );
+    fn check_typedef_declaration(&mut self, decl: &TypeDefDeclaration) -> Result<()> {
+        self.validate_type_specifier(&decl.type_spec)?;
+        let name = &decl.name.symbol;
+        let ty = decl.type_spec.ty(&self.semantics);
+        Self::error_if(
+            !self.in_function && ty.is_variably_modified(),
+            decl.type_spec.span,
+            "Variable length array declared outside of a function",
+        )?;
+        if let Some(previous) = self.semantics.type_aliases.get(name) {
+            if *previous != ty {
+                return Err(self.redeclaration_error(
+                    name,
+                    format!(
+                        "Conflicting types for typedef '{}': '{ty}' and '{previous}'",
+                        source_name(name)
+                    ),
+                    decl.name.span,
+                ));
             }
-            Self::error_if(
-                function_ty.ret.is_incomplete_aggregate(&self.semantics),
-                decl.type_spec.ret.span,
-                "Aggregate type is not complete",
-            )?;
-            self.check_block(&decl.type_spec.ty(), body)?;
+            return Ok(());
         }
+        self.declarations.insert(name.clone(), decl.name.span);
+        self.semantics.type_aliases.insert(name.clone(), ty);
         Ok(())
     }
 
@@ -621,69 +1185,108 @@ impl TypeChecker {
         }
 
         let old = self.semantics.type_defs.get(&decl.name.symbol);
-        if let Some(TypeEntry::Incomplete(k)) = old
-            && *k != kind
-        {
-            return Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: "Tag does not match previous declaration".to_string(),
-                span: decl.name.span,
-            });
+        let mismatch = match old {
+            Some(TypeEntry::Incomplete(k)) => *k != kind,
+            Some(TypeEntry::Enum) => true,
+            _ => false,
+        };
+        if mismatch {
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Tag does not match previous declaration",
+                decl.name.span,
+            ));
         }
         if let Some(TypeEntry::Complete(t)) = old
             && t.kind != kind
         {
-            return Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: format!(
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                format!(
                     "Tag `{}` does not match previous declaration",
                     decl.name.symbol
                 ),
-                span: decl.name.span,
-            });
+                decl.name.span,
+            ));
         }
 
         // Incomplete declaration do nothing
         if decl.fields.is_empty() {
             return Ok(());
         }
+        // The aggregate is still incomplete inside its member list
+        for static_assert in &decl.static_asserts {
+            self.check_static_assert(static_assert)?;
+        }
 
+        let aggregate_attrs = self.decl_attributes(&decl.attributes)?;
         let mut fields: Vec<Field> = Vec::new();
+        // Counted in bits, bit-fields don't have to end on a byte boundary
         let mut size = 0;
-        let mut alignment = 1;
+        let mut alignment = aggregate_attrs.aligned.unwrap_or(1);
         for field_node in &decl.fields {
             self.validate_type_specifier(&field_node.type_spec)?;
-            let ty = field_node.type_spec.ty();
-            let field_alignment = ty.alignment(&self.semantics);
-            let field_offset = if is_union {
-                0
+            let ty = field_node.type_spec.ty(&self.semantics);
+            Self::error_if(
+                ty.is_variably_modified(),
+                field_node.type_spec.span,
+                "Fields can't have variably modified type",
+            )?;
+            let field_attrs = self.decl_attributes(&field_node.attributes)?;
+            let field_alignment = if aggregate_attrs.packed || field_attrs.packed {
+                1
+            } else {
+                ty.alignment(&self.semantics)
+            };
+            let field_alignment = field_alignment.max(field_attrs.aligned.unwrap_or(1));
+            let start = if is_union { 0 } else { size };
+            let (field_offset, bit_field, end) = if let Some(width) = &field_node.width {
+                let width = self.bit_field_width(field_node, width, &ty)?;
+                // A bit-field is moved to the next storage unit when it would
+                // straddle a unit boundary, zero width ones always are.
+                let unit_bits = ty.size(&self.semantics) * 8;
+                let start = if width == 0 || start / unit_bits != (start + width - 1) / unit_bits {
+                    align_offset(start, unit_bits as u8)
+                } else {
+                    start
+                };
+                let unit_start = start / unit_bits * unit_bits;
+                let bit_field = BitField {
+                    offset: (start - unit_start) as u8,
+                    width: width as u8,
+                };
+                (unit_start / 8, Some(bit_field), start + width)
             } else {
-                align_offset(size, field_alignment)
+                let field_offset = align_offset(start.div_ceil(8), field_alignment);
+                let end = (field_offset + ty.size(&self.semantics)) * 8;
+                (field_offset, None, end)
+            };
+            size = if is_union { cmp::max(size, end) } else { end };
+
+            // Unnamed bit-fields only take space
+            let Some(name) = &field_node.name else {
+                continue;
             };
             let field = Field {
-                name: field_node.name.symbol.clone(),
+                name: name.symbol.clone(),
                 ty: ty.clone(),
                 offset: field_offset,
+                bit_field,
             };
             if fields.iter().any(|f| f.name == field.name) {
-                return Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: format!(
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    format!(
                         "Field name `{}` already exists in the type definition",
                         field.name
                     ),
-                    span: field_node.name.span,
-                });
+                    name.span,
+                ));
             }
             fields.push(field);
             alignment = cmp::max(alignment, field_alignment);
-            size = if is_union {
-                cmp::max(size, ty.size(&self.semantics))
-            } else {
-                field_offset + ty.size(&self.semantics)
-            };
         }
-        let size = align_offset(size, alignment);
+        let size = align_offset(size.div_ceil(8), alignment);
         let tag = decl.name.symbol.clone();
         let old = self.semantics.type_defs.insert(
             decl.name.symbol.clone(),
@@ -695,27 +1298,91 @@ impl TypeChecker {
             }),
         );
         if let Some(TypeEntry::Complete(_)) = old {
-            Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: format!("Aggregate type '{tag}' was already declared"),
-                span: decl.name.span,
-            })
+            Err(CompilerError::new(
+                ErrorKind::Type,
+                format!("Aggregate type '{tag}' was already declared"),
+                decl.name.span,
+            ))
         } else {
             Ok(())
         }
     }
 
-    fn validate_type_specifier(&self, ty: &Node<TypeSpec>) -> Result<()> {
+    fn bit_field_width(
+        &mut self,
+        field: &ast::Field,
+        width: &Node<Expression>,
+        ty: &Type,
+    ) -> Result<usize> {
+        Self::error_if(
+            !ty.is_int(),
+            field.type_spec.span,
+            &format!("Bit-field has invalid type '{ty}'"),
+        )?;
+        let value = self.integer_constant(width, "Bit-field width")?;
+        Self::error_if(value < 0, width.span, "Bit-field has negative width")?;
+        // Only the value bit of a _Bool is usable
+        let max_width = match ty.unqualified() {
+            Type::Bool => 1,
+            _ => ty.size(&self.semantics) as i128 * 8,
+        };
+        Self::error_if(
+            value > max_width,
+            width.span,
+            "Bit-field width exceeds the width of its type",
+        )?;
+        Self::error_if(
+            value == 0 && field.name.is_some(),
+            width.span,
+            "Named bit-field has zero width",
+        )?;
+        Ok(value as usize)
+    }
+
+    fn validate_type_specifier(&mut self, ty: &Node<TypeSpec>) -> Result<()> {
         match ty.as_ref() {
             TypeSpec::Array(inner, _) => {
                 Self::error_if(
-                    !inner.ty().is_complete(&self.semantics),
+                    !inner.ty(&self.semantics).is_complete(&self.semantics),
+                    ty.span,
+                    "Illegal array of incomplete types",
+                )?;
+                self.validate_type_specifier(inner)?;
+            }
+            TypeSpec::VariableArray(inner, size) => {
+                let size_ty = self.check_and_convert_expr(size)?;
+                Self::error_if(
+                    !size_ty.is_int(),
+                    size.span,
+                    "Array size should be an integer",
+                )?;
+                Self::error_if(
+                    !inner.ty(&self.semantics).is_complete(&self.semantics),
                     ty.span,
                     "Illegal array of incomplete types",
                 )?;
                 self.validate_type_specifier(inner)?;
+                // Sizes given by a constant expression make fixed size arrays
+                if let Some(length) = ConstantEvaluator::new(&mut self.semantics).integer(size)? {
+                    Self::error_if(length < 0, size.span, "Array size is negative")?;
+                    self.semantics.array_sizes.insert(size.id, length as usize);
+                    return Ok(());
+                }
+                self.cast_if_needed(size, &size_ty, &Type::ULong);
+                let VariableLength(length) = VariableLength::of(size);
+                self.semantics
+                    .symbols
+                    .insert(length, SymbolData::local(Type::ULong));
+            }
+            TypeSpec::UnsizedArray(_) => {
+                // Only compound literals get a length from their initializer
+                return Err(CompilerError::new(
+                    ErrorKind::Type,
+                    "Array size is missing",
+                    ty.span,
+                ));
             }
-            TypeSpec::Pointer(inner) => {
+            TypeSpec::Pointer(inner) | TypeSpec::Qualified(_, inner) => {
                 self.validate_type_specifier(inner)?;
             }
             TypeSpec::Function(f) => {
@@ -729,19 +1396,19 @@ impl TypeChecker {
                 match self.semantics.type_defs.get(name) {
                     Some(TypeEntry::Complete(type_def)) => {
                         if let AggregateKind::Union = type_def.kind {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Type,
-                                msg: "Type is not a struct type".to_string(),
-                                span: ty.span,
-                            });
+                            return Err(CompilerError::new(
+                                ErrorKind::Type,
+                                "Type is not a struct type",
+                                ty.span,
+                            ));
                         }
                     }
-                    Some(TypeEntry::Incomplete(AggregateKind::Union)) | None => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Type is not a struct type".to_string(),
-                            span: ty.span,
-                        });
+                    Some(TypeEntry::Incomplete(AggregateKind::Union) | TypeEntry::Enum) | None => {
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Type is not a struct type",
+                            ty.span,
+                        ));
                     }
                     _ => {}
                 }
@@ -751,58 +1418,111 @@ impl TypeChecker {
                 match self.semantics.type_defs.get(name) {
                     Some(TypeEntry::Complete(type_def)) => {
                         if let AggregateKind::Struct = type_def.kind {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Type,
-                                msg: "Type is not a union type".to_string(),
-                                span: ty.span,
-                            });
+                            return Err(CompilerError::new(
+                                ErrorKind::Type,
+                                "Type is not a union type",
+                                ty.span,
+                            ));
                         }
                     }
-                    Some(TypeEntry::Incomplete(AggregateKind::Struct)) | None => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Type is not a union type".to_string(),
-                            span: ty.span,
-                        });
+                    Some(TypeEntry::Incomplete(AggregateKind::Struct) | TypeEntry::Enum) | None => {
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Type is not a union type",
+                            ty.span,
+                        ));
                     }
                     _ => {}
                 }
             }
+            TypeSpec::Enum(Some(name)) => {
+                Self::error_if(
+                    !matches!(
+                        self.semantics.type_defs.get(&name.symbol),
+                        Some(TypeEntry::Enum)
+                    ),
+                    ty.span,
+                    "Type is not an enum type",
+                )?;
+            }
             _ => {}
         }
         Ok(())
     }
 
     fn check_block(&mut self, function: &FunctionType, block: &Block) -> Result<()> {
+        let in_scope = self.variably_modified.len();
         for item in &block.items {
             match item {
                 BlockItem::Stmt(stmt) => self.check_statement(function, stmt)?,
-                BlockItem::Decl(decl) => self.check_declaration(decl)?,
+                BlockItem::Decl(decl) => {
+                    self.check_declaration(decl)?;
+                    match decl.as_ref() {
+                        Declaration::Var(d) => {
+                            self.declare_if_variably_modified(&d.name.symbol, &d.type_spec)
+                        }
+                        Declaration::TypeDef(d) => {
+                            self.declare_if_variably_modified(&d.name.symbol, &d.type_spec)
+                        }
+                        _ => {}
+                    }
+                }
             }
         }
+        self.variably_modified.truncate(in_scope);
+        Ok(())
+    }
+
+    fn declare_if_variably_modified(&mut self, name: &Symbol, type_spec: &Node<TypeSpec>) {
+        if type_spec.ty(&self.semantics).is_variably_modified() {
+            self.variably_modified.push(name.clone());
+        }
+    }
+
+    /// A `goto` can't jump to a label in the scope of an identifier with a
+    /// variably modified type, unless the `goto` is in its scope too.
+    fn check_gotos(&mut self) -> Result<()> {
+        let label_scopes = std::mem::take(&mut self.label_scopes);
+        for (label, in_scope, span) in std::mem::take(&mut self.gotos) {
+            Self::error_if(
+                label_scopes[&label]
+                    .iter()
+                    .any(|name| !in_scope.contains(name)),
+                span,
+                "Jump into scope of identifier with variably modified type",
+            )?;
+        }
         Ok(())
     }
 
+    fn check_switch_scope(&self, span: Span) -> Result<()> {
+        let switch_scope = *self.switch_scopes.last().expect("Case without switch");
+        Self::error_if(
+            self.variably_modified.len() > switch_scope,
+            span,
+            "Switch jumps into scope of identifier with variably modified type",
+        )
+    }
+
     fn check_statement(&mut self, function: &FunctionType, stmt: &Node<Statement>) -> Result<()> {
         match stmt.as_ref() {
             Statement::Return(expr) => {
                 if let Some(expr) = expr {
                     if function.ret.is_void() {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Return statement with an expression in a void function"
-                                .to_string(),
-                            span: stmt.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Return statement with an expression in a void function",
+                            stmt.span,
+                        ));
                     }
                     let expr_ty = self.check_and_convert_expr(expr)?;
                     self.convert_by_assignment(expr, &expr_ty, &function.ret)?;
                 } else if !function.ret.is_void() {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Return statement without an expression ".to_string(),
-                        span: stmt.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Return statement without an expression ",
+                        stmt.span,
+                    ));
                 }
             }
             Statement::Expression(expr) => {
@@ -824,14 +1544,14 @@ impl TypeChecker {
                 let expr_ty = self.check_and_convert_expr(expr)?;
 
                 if !expr_ty.is_int() {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Switch statement requires an integer expression".to_owned(),
-                        span: expr.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Switch statement requires an integer expression",
+                        expr.span,
+                    ));
                 }
 
-                if expr_ty.is_char() {
+                if expr_ty.is_promotable() {
                     self.cast_if_needed(expr, &expr_ty, &Type::Int);
                 }
 
@@ -840,28 +1560,37 @@ impl TypeChecker {
                     values: vec![],
                     default: None,
                 });
+                self.switch_scopes.push(self.variably_modified.len());
                 self.check_statement(function, body)?;
+                self.switch_scopes.pop();
                 self.semantics
                     .switch_cases
                     .insert(expr.id, self.switch_stack.pop_front().unwrap());
             }
             Statement::Case { value, body, label } => {
-                let switch_cases = self.switch_stack.front_mut().expect("Case without switch");
-                let constant = match value.as_ref() {
-                    Expression::Constant(constant) if constant.ty().is_int() => constant,
-                    _ => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "case label does not reduce to an integer constant".to_owned(),
-                            span: value.span,
-                        });
-                    }
+                let ty = self.check_and_convert_expr(value)?;
+                let constant = if ty.is_int() {
+                    ConstantEvaluator::new(&mut self.semantics).integer(value)?
+                } else {
+                    None
+                };
+                let Some(constant) = constant else {
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "case label does not reduce to an integer constant",
+                        value.span,
+                    ));
                 };
-                let case_constant = constant.as_u64();
+                let switch_cases = self.switch_stack.front_mut().expect("Case without switch");
+                let case_constant = constant as u64;
                 let case_value = match &switch_cases.expr_ty {
-                    Type::Int | Type::Char | Type::SChar | Type::UChar => {
-                        Constant::Int(case_constant as i32)
-                    }
+                    Type::Int
+                    | Type::Bool
+                    | Type::Char
+                    | Type::SChar
+                    | Type::UChar
+                    | Type::Short
+                    | Type::UShort => Constant::Int(case_constant as i32),
                     Type::UInt => Constant::UInt(case_constant as u32),
                     Type::Long => Constant::Long(case_constant as i64),
                     Type::ULong => Constant::ULong(case_constant),
@@ -870,25 +1599,27 @@ impl TypeChecker {
                 };
 
                 if switch_cases.values.iter().any(|(v, _)| *v == case_value) {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "duplicate case value".to_owned(),
-                        span: value.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "duplicate case value",
+                        value.span,
+                    ));
                 }
                 switch_cases.values.push((case_value, label.clone()));
+                self.check_switch_scope(stmt.span)?;
                 self.check_statement(function, body)?;
             }
             Statement::Default { label, body } => {
                 let switch_cases = self.switch_stack.front_mut().expect("Case without switch");
                 if switch_cases.default.is_some() {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Resolve,
-                        msg: "multiple default labels in one switch".to_owned(),
-                        span: stmt.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Resolve,
+                        "multiple default labels in one switch",
+                        stmt.span,
+                    ));
                 }
                 switch_cases.default = Some(label.clone());
+                self.check_switch_scope(stmt.span)?;
                 self.check_statement(function, body)?
             }
             Statement::While { cond, body, .. } | Statement::DoWhile { cond, body, .. } => {
@@ -896,7 +1627,11 @@ impl TypeChecker {
                 Self::error_if(!cond_ty.is_scalar(), cond.span, "Invalid condition type")?;
                 self.check_statement(function, body)?;
             }
-            Statement::Labeled { body, .. } => self.check_statement(function, body)?,
+            Statement::Labeled { name, body } => {
+                self.label_scopes
+                    .insert(name.symbol.clone(), self.variably_modified.clone());
+                self.check_statement(function, body)?
+            }
             Statement::Compound(block) => self.check_block(function, block)?,
             Statement::For {
                 init,
@@ -905,17 +1640,18 @@ impl TypeChecker {
                 body,
                 ..
             } => {
+                let in_scope = self.variably_modified.len();
                 match init {
                     ForInit::Decl(d) => {
                         if let Some(storage) = &d.storage_class {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Type,
-                                msg: "Declarations inside for loops can't have storage class"
-                                    .into(),
-                                span: storage.span,
-                            });
+                            return Err(CompilerError::new(
+                                ErrorKind::Type,
+                                "Declarations inside for loops can't have storage class",
+                                storage.span,
+                            ));
                         }
-                        self.check_local_var_declaration(d)?
+                        self.check_local_var_declaration(d)?;
+                        self.declare_if_variably_modified(&d.name.symbol, &d.type_spec);
                     }
                     ForInit::Expr(e) => {
                         self.check_and_convert_expr(e)?;
@@ -930,9 +1666,16 @@ impl TypeChecker {
                     self.check_and_convert_expr(post)?;
                 }
                 self.check_statement(function, body)?;
+                self.variably_modified.truncate(in_scope);
             }
-            Statement::Break(_) | Statement::Continue(_) | Statement::Goto(_) | Statement::Null => {
+            Statement::Goto(label) => {
+                self.gotos.push((
+                    label.symbol.clone(),
+                    self.variably_modified.clone(),
+                    stmt.span,
+                ));
             }
+            Statement::Break(_) | Statement::Continue(_) | Statement::Null => {}
         }
 
         Ok(())
@@ -944,15 +1687,38 @@ impl TypeChecker {
             Declaration::Function(d) => self.check_function_declaration(d, false),
             Declaration::Struct(d) => self.check_type_declaration(d, false),
             Declaration::Union(d) => self.check_type_declaration(d, true),
+            Declaration::Enum(d) => self.check_enum_declaration(d),
+            Declaration::TypeDef(d) => self.check_typedef_declaration(d),
+            Declaration::StaticAssert(d) => self.check_static_assert(d),
         }
     }
 
+    fn check_static_assert(&mut self, decl: &StaticAssert) -> Result<()> {
+        let value = self.integer_constant(&decl.condition, "Static assertion condition")?;
+        Self::error_if(
+            value == 0,
+            decl.condition.span,
+            &format!("Static assertion failed: {}", decl.message),
+        )
+    }
+
     fn check_and_convert_expr(&mut self, expr: &Node<Expression>) -> Result<Type> {
         let ty = self.check_expression(expr)?;
-        match ty {
-            Type::Array(inner, ..) => {
+        match ty.unqualified() {
+            Type::Array(inner, ..) | Type::VariableArray(inner, ..) => {
                 // Pointer decay
-                let pointer_ty = Type::Pointer(inner);
+                let pointer_ty = Type::Pointer(inner.clone());
+                self.semantics
+                    .pointer_decays
+                    .insert(expr.id, pointer_ty.clone());
+                self.semantics
+                    .expression_types
+                    .insert(expr.id, pointer_ty.clone());
+                Ok(pointer_ty)
+            }
+            Type::Function(_) => {
+                // Function designators decay to function pointers
+                let pointer_ty = Type::Pointer(ty.clone().into());
                 self.semantics
                     .pointer_decays
                     .insert(expr.id, pointer_ty.clone());
@@ -961,14 +1727,26 @@ impl TypeChecker {
                     .insert(expr.id, pointer_ty.clone());
                 Ok(pointer_ty)
             }
-            Type::Struct(_) | Type::Union(_) if !ty.is_complete(&self.semantics) => {
-                Err(CompilerError {
-                    kind: ErrorKind::Type,
-                    msg: "Incomplete aggregate type".to_string(),
-                    span: expr.span,
-                })
+            Type::Struct(_) | Type::Union(_) if !ty.is_complete(&self.semantics) => Err(
+                CompilerError::new(ErrorKind::Type, "Incomplete aggregate type", expr.span),
+            ),
+            _ => {
+                let promoted = self.promoted_bit_field_type(expr, &ty);
+                Ok(self.cast_if_needed(expr, &ty, &promoted))
             }
-            _ => Ok(ty),
+        }
+    }
+
+    /// Bit-fields whose values all fit in an `int` are promoted to `int`,
+    /// like the types smaller than it.
+    fn promoted_bit_field_type(&self, expr: &Node<Expression>, ty: &Type) -> Type {
+        let fits_int = self.bit_field(expr).is_some_and(|bit_field| {
+            bit_field.width < 32 || (bit_field.width == 32 && ty.is_signed())
+        });
+        if fits_int && ty.is_int() {
+            Type::Int
+        } else {
+            ty.clone()
         }
     }
 
@@ -976,20 +1754,18 @@ impl TypeChecker {
         let ty = match expr.as_ref() {
             Expression::Constant(c) => c.ty(),
             Expression::String(s) => Type::Array(Type::Char.into(), s.len() + 1),
+            Expression::Var(name) if self.semantics.enumerators.contains_key(name) => Type::Int,
             Expression::Var(name) => {
                 let Some(data) = self.semantics.symbols.get(name) else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Unknown type of expression".to_string(),
-                        span: expr.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Unknown type of expression",
+                        expr.span,
+                    ));
                 };
-                Self::error_if(
-                    data.ty.is_function(),
-                    expr.span,
-                    "Function used as variable",
-                )?;
-                data.ty.clone()
+                let ty = data.ty.clone();
+                self.used_variables.insert(name.clone());
+                ty
             }
             Expression::Unary { op, expr } => match op.as_ref() {
                 UnaryOp::Increment | UnaryOp::Decrement => {
@@ -1001,16 +1777,21 @@ impl TypeChecker {
                     )?;
                     Self::error_if(!operand_ty.is_scalar(), expr.span, "Type is not assignable")?;
                     Self::error_if(
-                        !Self::is_lvalue(expr),
+                        !self.is_lvalue(expr),
                         expr.span,
                         "Expression is not assignable",
                     )?;
+                    Self::error_if(
+                        !self.is_modifiable(&operand_ty),
+                        expr.span,
+                        "Cannot assign to a const-qualified lvalue",
+                    )?;
                     Self::error_if(
                         operand_ty.is_pointer_to_incomplete(&self.semantics),
                         expr.span,
                         "Illegal operation on pointer to void type",
                     )?;
-                    operand_ty
+                    operand_ty.unqualified().clone()
                 }
                 UnaryOp::Complement => {
                     let operand_ty = self.check_and_convert_expr(expr)?;
@@ -1019,20 +1800,20 @@ impl TypeChecker {
                         expr.span,
                         "Unary operator requires an integer type",
                     )?;
-                    if operand_ty.is_char() {
+                    if operand_ty.is_promotable() {
                         self.cast_if_needed(expr, &operand_ty, &Type::Int)
                     } else {
                         operand_ty
                     }
                 }
-                UnaryOp::Negate => {
+                UnaryOp::Negate | UnaryOp::Plus => {
                     let operand_ty = self.check_and_convert_expr(expr)?;
                     Self::error_if(
                         !operand_ty.is_arithmetic(),
                         expr.span,
                         "Unary operator requires an arithmetic operator",
                     )?;
-                    if operand_ty.is_char() {
+                    if operand_ty.is_promotable() {
                         self.cast_if_needed(expr, &operand_ty, &Type::Int)
                     } else {
                         operand_ty
@@ -1057,16 +1838,21 @@ impl TypeChecker {
                 )?;
                 Self::error_if(!operand_ty.is_scalar(), expr.span, "Type is not assignable")?;
                 Self::error_if(
-                    !Self::is_lvalue(expr),
+                    !self.is_lvalue(expr),
                     expr.span,
                     "Expression is not assignable",
                 )?;
+                Self::error_if(
+                    !self.is_modifiable(&operand_ty),
+                    expr.span,
+                    "Cannot assign to a const-qualified lvalue",
+                )?;
                 Self::error_if(
                     operand_ty.is_pointer_to_incomplete(&self.semantics),
                     expr.span,
                     "Illegal operation on pointer to void type",
                 )?;
-                operand_ty
+                operand_ty.unqualified().clone()
             }
             Expression::Binary { left, right, op } => {
                 let left_ty = self.check_and_convert_expr(left)?;
@@ -1131,7 +1917,7 @@ impl TypeChecker {
                                 "Incomplete pointer type",
                             )?;
                             Self::error_if(
-                                left_inner != right_inner,
+                                left_inner.unqualified() != right_inner.unqualified(),
                                 right.span,
                                 "Invalid pointer operator type",
                             )?;
@@ -1145,11 +1931,11 @@ impl TypeChecker {
                             Self::error_if(!right_ty.is_int(), right.span, "Operator is invalid")?;
                         }
                         (_, Type::Pointer(_)) => {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Type,
-                                msg: "Cannot substract a pointer from an int".to_string(),
-                                span: right.span,
-                            });
+                            return Err(CompilerError::new(
+                                ErrorKind::Type,
+                                "Cannot substract a pointer from an int",
+                                right.span,
+                            ));
                         }
                         _ => {}
                     },
@@ -1160,18 +1946,18 @@ impl TypeChecker {
                         if !left_ty.is_arithmetic() || !left_ty.is_arithmetic() =>
                     {
                         let Type::Pointer(left_inner) = left_ty.clone() else {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Type,
-                                msg: "Operator is invalid".to_string(),
-                                span: left.span,
-                            });
+                            return Err(CompilerError::new(
+                                ErrorKind::Type,
+                                "Operator is invalid",
+                                left.span,
+                            ));
                         };
                         let Type::Pointer(right_inner) = right_ty.clone() else {
-                            return Err(CompilerError {
-                                kind: ErrorKind::Type,
-                                msg: "Operator is invalid".to_string(),
-                                span: right.span,
-                            });
+                            return Err(CompilerError::new(
+                                ErrorKind::Type,
+                                "Operator is invalid",
+                                right.span,
+                            ));
                         };
                         Self::error_if(
                             !left_inner.is_complete(&self.semantics),
@@ -1179,7 +1965,7 @@ impl TypeChecker {
                             "Operator is invalid",
                         )?;
                         Self::error_if(
-                            left_inner != right_inner,
+                            left_inner.unqualified() != right_inner.unqualified(),
                             right.span,
                             "Operator is invalid",
                         )?;
@@ -1190,7 +1976,7 @@ impl TypeChecker {
                 match op.as_ref() {
                     BinaryOp::And | BinaryOp::Or => Type::Int,
                     BinaryOp::ShiftRight | BinaryOp::ShiftLeft => {
-                        if let Type::Char | Type::SChar | Type::UChar = left_ty {
+                        if left_ty.is_promotable() {
                             self.cast_if_needed(left, &left_ty, &Type::Int)
                         } else {
                             left_ty
@@ -1208,6 +1994,7 @@ impl TypeChecker {
                             )?;
                             self.semantics.common_type(&left_ty, &right_ty)
                         };
+                        self.check_sign_compare(expr.span, (left, &left_ty), (right, &right_ty));
                         self.cast_if_needed(left, &left_ty, &common);
                         self.cast_if_needed(right, &right_ty, &common);
                         Type::Int
@@ -1233,7 +2020,14 @@ impl TypeChecker {
                             BinaryOp::LessThan
                             | BinaryOp::LessOrEqualThan
                             | BinaryOp::GreaterThan
-                            | BinaryOp::GreaterOrEqualThan => Type::Int,
+                            | BinaryOp::GreaterOrEqualThan => {
+                                self.check_sign_compare(
+                                    expr.span,
+                                    (left, &left_ty),
+                                    (right, &right_ty),
+                                );
+                                Type::Int
+                            }
                             _ => common,
                         }
                     }
@@ -1253,10 +2047,16 @@ impl TypeChecker {
                     "Type is not assignable",
                 )?;
                 Self::error_if(
-                    !Self::is_lvalue(left),
+                    !self.is_lvalue(left),
                     left.span,
                     "Expression is not assignable",
                 )?;
+                Self::error_if(
+                    !self.is_modifiable(&left_ty),
+                    left.span,
+                    "Cannot assign to a const-qualified lvalue",
+                )?;
+                let left_ty = left_ty.unqualified().clone();
                 let right_ty = self.check_and_convert_expr(right)?;
                 Self::error_if(
                     !right_ty.is_aggregate() && !right_ty.is_scalar(),
@@ -1305,6 +2105,14 @@ impl TypeChecker {
                             left.span,
                             "Cannot assign to a pointer of an incomplete type",
                         )?;
+                        let is_function = self.semantics.pointer_decays.get(&right.id).is_some_and(
+                            |ty| matches!(ty, Type::Pointer(inner) if inner.is_function()),
+                        );
+                        Self::error_if(
+                            is_function,
+                            right.span,
+                            "Assign compound operator cannot be a function",
+                        )?;
                         Self::error_if(
                             right_ty.is_pointer(),
                             right.span,
@@ -1328,8 +2136,9 @@ impl TypeChecker {
                     AssignOp::Equal => self.convert_by_assignment(right, &right_ty, &left_ty)?,
                     AssignOp::ShiftLeftEqual | AssignOp::ShiftRightEqual => {
                         // This doesn't seem to make sense, but it serves to cast
-                        // possible Char | UChar to Int
-                        let common = self.semantics.common_type(&left_ty, &left_ty);
+                        // possible Char | UChar | Short | UShort to Int
+                        let promoted = self.promoted_bit_field_type(left, &left_ty);
+                        let common = self.semantics.common_type(&promoted, &promoted);
                         self.cast_if_needed(left, &left_ty, &common);
                         self.cast_if_needed(right, &right_ty, &common);
                         self.cast_if_needed(expr, &common, &left_ty);
@@ -1339,7 +2148,8 @@ impl TypeChecker {
                         left_ty
                     }
                     _ => {
-                        let common = self.semantics.common_type(&left_ty, &right_ty);
+                        let promoted = self.promoted_bit_field_type(left, &left_ty);
+                        let common = self.semantics.common_type(&promoted, &right_ty);
                         self.cast_if_needed(left, &left_ty, &common);
                         self.cast_if_needed(right, &right_ty, &common);
                         self.semantics
@@ -1369,11 +2179,11 @@ impl TypeChecker {
                     } else if then_ty == else_ty {
                         then_ty.clone()
                     } else {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Invalid types of the branches".to_string(),
-                            span: expr.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Invalid types of the branches",
+                            expr.span,
+                        ));
                     };
                     self.cast_if_needed(then_expr, &then_ty, &common);
                     self.cast_if_needed(else_expr, &else_ty, &common);
@@ -1381,48 +2191,146 @@ impl TypeChecker {
                 }
             }
 
-            Expression::FunctionCall { name, args } => {
-                let symbol = name.symbol.clone();
-                let Some(data) = self.semantics.symbols.get(&symbol) else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Unknown type of expression".to_string(),
-                        span: expr.span,
-                    });
-                };
-                let Type::Function(function_ty) = data.ty.clone() else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Variable used as function name".to_string(),
-                        span: expr.span,
-                    });
+            Expression::FunctionCall { function, args } => {
+                let function_ty = match self.check_and_convert_expr(function)? {
+                    Type::Pointer(inner) if inner.is_function() => {
+                        let Type::Function(function_ty) = *inner else {
+                            unreachable!()
+                        };
+                        function_ty
+                    }
+                    _ => {
+                        let msg = if let Expression::Var(_) = function.as_ref() {
+                            "Variable used as function name"
+                        } else {
+                            "Called object is not a function"
+                        };
+                        return Err(CompilerError::new(ErrorKind::Type, msg, expr.span));
+                    }
                 };
-                if function_ty.params.len() != args.len() {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Function called with the wrong number of arguments".to_string(),
-                        span: expr.span,
-                    });
+                if function_ty.params.len() != args.len()
+                    && !(function_ty.variadic && args.len() > function_ty.params.len())
+                {
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Function called with the wrong number of arguments",
+                        expr.span,
+                    ));
                 }
                 for (arg, param_ty) in args.iter().zip(&function_ty.params) {
                     let arg_ty = self.check_and_convert_expr(arg)?;
                     self.convert_by_assignment(arg, &arg_ty, param_ty)?;
                 }
+                // Default argument promotions for the variable arguments
+                for arg in &args[function_ty.params.len()..] {
+                    let arg_ty = self.check_and_convert_expr(arg)?;
+                    Self::error_if(arg_ty.is_void(), arg.span, "Invalid use of void expression")?;
+                    if arg_ty.is_promotable() {
+                        self.cast_if_needed(arg, &arg_ty, &Type::Int);
+                    } else if arg_ty == Type::Float {
+                        self.cast_if_needed(arg, &arg_ty, &Type::Double);
+                    }
+                }
                 *function_ty.ret.clone()
             }
+            Expression::VaStart(ap, param) => {
+                Self::error_if(
+                    !self.in_variadic_function,
+                    expr.span,
+                    "'va_start' used in function with fixed arguments",
+                )?;
+                self.check_va_list(ap)?;
+                self.check_and_convert_expr(param)?;
+                Type::Void
+            }
+            Expression::VaArg(ap, target) => {
+                self.check_va_list(ap)?;
+                self.validate_type_specifier(target)?;
+                let target_ty = target.ty(&self.semantics);
+                Self::error_if(
+                    target_ty.is_void() || target_ty.is_array() || target_ty.is_function(),
+                    target.span,
+                    &format!("Invalid type '{target_ty}' for 'va_arg'"),
+                )?;
+                Self::error_if(
+                    !target_ty.is_complete(&self.semantics),
+                    target.span,
+                    "Type is not complete",
+                )?;
+                target_ty.unqualified().clone()
+            }
+            Expression::VaEnd(ap) => {
+                self.check_va_list(ap)?;
+                Type::Void
+            }
+            Expression::VaCopy(dst, src) => {
+                self.check_va_list(dst)?;
+                self.check_va_list(src)?;
+                Type::Void
+            }
+            Expression::CompoundLiteral { type_spec, init } => {
+                if let TypeSpec::UnsizedArray(inner) = type_spec.as_ref() {
+                    self.validate_type_specifier(inner)?;
+                    let element = inner.ty(&self.semantics);
+                    Self::error_if(
+                        !element.is_complete(&self.semantics),
+                        type_spec.span,
+                        "Illegal array of incomplete types",
+                    )?;
+                    let length = self.initializer_length(init, &element)?;
+                    self.semantics.array_sizes.insert(inner.id, length);
+                } else {
+                    self.validate_type_specifier(type_spec)?;
+                }
+                let ty = type_spec.ty(&self.semantics);
+                Self::error_if(
+                    ty.is_void() || ty.is_function() || ty.is_incomplete_aggregate(&self.semantics),
+                    type_spec.span,
+                    "Compound literal has incomplete type",
+                )?;
+                Self::error_if(
+                    ty.is_variable_length_array(),
+                    type_spec.span,
+                    "Compound literal has variable length array type",
+                )?;
+                // Compound literals are unnamed objects, static at file scope
+                let attrs = if self.in_function {
+                    self.check_initializer(init, &ty)?;
+                    Attributes::Local
+                } else {
+                    Attributes::Static {
+                        initial_value: InitialValue::Initial(
+                            self.check_static_initializer(init, &ty)?,
+                        ),
+                        global: false,
+                        thread_local: false,
+                    }
+                };
+                let name = Symbol::from(format!("compound_literal.{}", expr.id));
+                self.semantics.symbols.insert(
+                    name.clone(),
+                    SymbolData {
+                        ty: ty.clone(),
+                        attrs,
+                        decl_attrs: DeclAttributes::default(),
+                    },
+                );
+                self.semantics.compound_literals.insert(expr.id, name);
+                ty
+            }
             Expression::Cast { target, expr } => {
                 self.validate_type_specifier(target)?;
-                let target_ty = target.ty();
+                let target_ty = target.ty(&self.semantics);
                 let ty = self.check_and_convert_expr(expr)?;
                 Self::error_if(
-                    target_ty.is_pointer() && ty.is_double(),
+                    target_ty.is_pointer() && ty.is_floating(),
                     expr.span,
-                    "Cannot cast a double to a pointer",
+                    &format!("Cannot cast a {ty} to a pointer"),
                 )?;
                 Self::error_if(
-                    target_ty.is_double() && ty.is_pointer(),
+                    target_ty.is_floating() && ty.is_pointer(),
                     expr.span,
-                    "Cannot cast a pointer to a double",
+                    &format!("Cannot cast a pointer to a {target_ty}"),
                 )?;
                 Self::error_if(
                     target_ty.is_array(),
@@ -1440,7 +2348,7 @@ impl TypeChecker {
                     expr.span,
                     "Cannot cast non-scalar expression",
                 )?;
-                target_ty
+                target_ty.unqualified().clone()
             }
             Expression::Dereference(inner) => {
                 let inner_ty = self.check_and_convert_expr(inner)?;
@@ -1454,24 +2362,29 @@ impl TypeChecker {
                         *referenced.clone()
                     }
                     _ => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Cannot dereference a non-pointer".to_string(),
-                            span: expr.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Cannot dereference a non-pointer",
+                            expr.span,
+                        ));
                     }
                 }
             }
             Expression::AddressOf(inner) => {
-                if Self::is_lvalue(inner) {
+                if self.is_lvalue(inner) {
                     let inner_ty = self.check_expression(inner)?;
+                    Self::error_if(
+                        self.is_bit_field(inner),
+                        expr.span,
+                        "Can't take address of a bit-field",
+                    )?;
                     Type::Pointer(inner_ty.clone().into())
                 } else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Can't take address of non-lvalue!".to_string(),
-                        span: expr.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Can't take address of non-lvalue!",
+                        expr.span,
+                    ));
                 }
             }
             Expression::Subscript(expr1, expr2) => {
@@ -1495,38 +2408,72 @@ impl TypeChecker {
                         *referenced.clone()
                     }
                     _ => {
-                        return Err(CompilerError {
-                            kind: ErrorKind::Type,
-                            msg: "Subscript requires integer and pointer types".to_string(),
-                            span: expr.span,
-                        });
+                        return Err(CompilerError::new(
+                            ErrorKind::Type,
+                            "Subscript requires integer and pointer types",
+                            expr.span,
+                        ));
                     }
                 }
             }
             Expression::SizeOfType(target) => {
                 self.validate_type_specifier(target)?;
                 Self::error_if(
-                    !target.ty().is_complete(&self.semantics),
+                    target.ty(&self.semantics).is_function(),
+                    target.span,
+                    "Cannot get size of a function type",
+                )?;
+                Self::error_if(
+                    !target.ty(&self.semantics).is_complete(&self.semantics),
                     target.span,
                     "Cannot get size of an incomplete type",
                 )?;
                 Type::ULong
             }
+            Expression::AlignOfType(target) => {
+                self.validate_type_specifier(target)?;
+                Self::error_if(
+                    !target.ty(&self.semantics).is_complete(&self.semantics),
+                    target.span,
+                    "Cannot get alignment of an incomplete type",
+                )?;
+                Type::ULong
+            }
+            Expression::Comma(left, right) => {
+                self.check_and_convert_expr(left)?;
+                self.check_and_convert_expr(right)?
+            }
             Expression::SizeOfExpr(expr) => {
                 let ty = self.check_expression(expr)?;
+                Self::error_if(
+                    ty.is_function(),
+                    expr.span,
+                    "Cannot get size of a function type",
+                )?;
                 Self::error_if(
                     !ty.is_complete(&self.semantics),
                     expr.span,
                     "Cannot get size of an incomplete type",
                 )?;
+                Self::error_if(
+                    self.is_bit_field(expr),
+                    expr.span,
+                    "Cannot get size of a bit-field",
+                )?;
                 Type::ULong
             }
             Expression::Dot {
                 aggregate,
                 field: member,
             } => {
-                let ty = self.check_and_convert_expr(aggregate)?;
-                self.check_field(aggregate, &ty, member)?
+                let ty = self.check_expression(aggregate)?;
+                Self::error_if(
+                    ty.is_incomplete_aggregate(&self.semantics),
+                    aggregate.span,
+                    "Incomplete aggregate type",
+                )?;
+                self.check_field(aggregate, ty.unqualified(), member)?
+                    .qualified(ty.qualifiers())
             }
             Expression::Arrow {
                 pointer,
@@ -1534,16 +2481,19 @@ impl TypeChecker {
             } => {
                 let ty = self.check_and_convert_expr(pointer)?;
                 let Type::Pointer(inner_ty) = ty else {
-                    return Err(CompilerError {
-                        kind: ErrorKind::Type,
-                        msg: "Type is not a pointer".to_string(),
-                        span: pointer.span,
-                    });
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Type is not a pointer",
+                        pointer.span,
+                    ));
                 };
-                self.check_field(pointer, &inner_ty, member)?
+                self.check_field(pointer, inner_ty.unqualified(), member)?
+                    .qualified(inner_ty.qualifiers())
             }
         };
-        self.semantics.expression_types.insert(expr.id, ty.clone());
+        self.semantics
+            .expression_types
+            .insert(expr.id, ty.unqualified().clone());
         Ok(ty)
     }
 
@@ -1554,52 +2504,226 @@ impl TypeChecker {
         member: &Node<Identifier>,
     ) -> Result<Type> {
         let (Type::Struct(name) | Type::Union(name)) = ty else {
-            return Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: "Type is not a struct or union".to_string(),
-                span: aggregate.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Type is not a struct or union",
+                aggregate.span,
+            ));
         };
         let Some(TypeEntry::Complete(type_def)) = self.semantics.type_defs.get(name) else {
-            return Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: "Type is not a struct or union".to_string(),
-                span: aggregate.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Type is not a struct or union",
+                aggregate.span,
+            ));
         };
         let field_name = &member.symbol;
         let field = type_def
             .fields
             .iter()
             .find(|&f| &f.name == field_name)
-            .ok_or_else(|| CompilerError {
-                kind: ErrorKind::Type,
-                msg: format!("Aggregate type '{name}' does not have field '{field_name}'"),
-                span: aggregate.span,
+            .ok_or_else(|| {
+                CompilerError::new(
+                    ErrorKind::Type,
+                    format!("Aggregate type '{name}' does not have field '{field_name}'"),
+                    aggregate.span,
+                )
             })?;
         Ok(field.ty.clone())
     }
 
-    fn is_lvalue(expr: &Expression) -> bool {
+    /// Whether an already checked member access refers to a bit-field.
+    fn is_bit_field(&self, expr: &Node<Expression>) -> bool {
+        self.bit_field(expr).is_some()
+    }
+
+    fn bit_field(&self, expr: &Node<Expression>) -> Option<BitField> {
+        let (aggregate_ty, member) = match expr.as_ref() {
+            Expression::Dot { aggregate, field } => {
+                (self.semantics.expr_type(aggregate).clone(), field)
+            }
+            Expression::Arrow { pointer, field } => match self.semantics.expr_type(pointer) {
+                Type::Pointer(inner) => (inner.unqualified().clone(), field),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let (Type::Struct(name) | Type::Union(name)) = aggregate_ty else {
+            return None;
+        };
+        self.semantics
+            .get_aggregate(&name)
+            .fields
+            .iter()
+            .find(|f| f.name == member.symbol)
+            .and_then(|f| f.bit_field)
+    }
+
+    fn check_va_list(&mut self, ap: &Node<Expression>) -> Result<()> {
+        let ty = self.check_and_convert_expr(ap)?;
+        let va_list_ptr = Type::Pointer(Type::Struct(VA_LIST_TAG.into()).into());
+        Self::error_if(ty != va_list_ptr, ap.span, "Expression is not a 'va_list'")
+    }
+
+    fn is_lvalue(&self, expr: &Expression) -> bool {
         match expr {
-            Expression::Var(_)
-            | Expression::Dereference(_)
+            Expression::Var(name) => !self.semantics.enumerators.contains_key(name),
+            Expression::Dereference(_)
             | Expression::Subscript(_, _)
             | Expression::String(_)
+            | Expression::CompoundLiteral { .. }
             | Expression::Arrow { .. } => true,
 
-            Expression::Dot { aggregate, .. } => Self::is_lvalue(aggregate),
+            Expression::Dot { aggregate, .. } => self.is_lvalue(aggregate),
             _ => false,
         }
     }
 
+    /// Whether an lvalue of this type can be assigned to, which excludes const
+    /// objects and aggregates with a const member.
+    fn is_modifiable(&self, ty: &Type) -> bool {
+        if ty.is_const() {
+            return false;
+        }
+        match ty.unqualified() {
+            Type::Array(inner, _) | Type::VariableArray(inner, _) => self.is_modifiable(inner),
+            Type::Struct(name) | Type::Union(name) => match self.semantics.type_defs.get(name) {
+                Some(TypeEntry::Complete(aggregate)) => {
+                    aggregate.fields.iter().all(|f| self.is_modifiable(&f.ty))
+                }
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+
+    fn decl_attributes(&mut self, attributes: &[Node<Attribute>]) -> Result<DeclAttributes> {
+        let mut decl_attrs = DeclAttributes::default();
+        for attribute in attributes {
+            match attribute.as_ref() {
+                Attribute::Noreturn => decl_attrs.noreturn = true,
+                Attribute::Aligned(alignment) => {
+                    let alignment = self.requested_alignment(alignment)?;
+                    decl_attrs.aligned = decl_attrs.aligned.max(Some(alignment));
+                }
+                Attribute::Packed => decl_attrs.packed = true,
+                Attribute::Unused => decl_attrs.unused = true,
+                Attribute::Weak => decl_attrs.weak = true,
+                Attribute::Section(section) => decl_attrs.section = Some(section.clone()),
+            }
+        }
+        Ok(decl_attrs)
+    }
+
+    /// Attributes of a variable declaration, `automatic` variables live on
+    /// the stack and can't be placed in a section.
+    fn variable_attributes(
+        &mut self,
+        decl: &VarDeclaration,
+        automatic: bool,
+    ) -> Result<DeclAttributes> {
+        for attribute in &decl.attributes {
+            match attribute.as_ref() {
+                Attribute::Noreturn => {
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Only functions can be declared noreturn",
+                        attribute.span,
+                    ));
+                }
+                Attribute::Section(_) if automatic => {
+                    return Err(CompilerError::new(
+                        ErrorKind::Type,
+                        "Section attribute can't be specified for local variables",
+                        attribute.span,
+                    ));
+                }
+                _ => {}
+            }
+        }
+        self.decl_attributes(&decl.attributes)
+    }
+
+    /// Alignment given to the `aligned` attribute, the largest alignment of
+    /// any type when omitted.
+    fn requested_alignment(&mut self, alignment: &Option<Node<Expression>>) -> Result<u8> {
+        let Some(alignment) = alignment else {
+            return Ok(16);
+        };
+        let value = self.integer_constant(alignment, "Requested alignment")?;
+        Self::error_if(
+            value <= 0 || value & (value - 1) != 0,
+            alignment.span,
+            "Requested alignment is not a positive power of 2",
+        )?;
+        Self::error_if(
+            value > u8::MAX as i128,
+            alignment.span,
+            "Requested alignment is too large",
+        )?;
+        Ok(value as u8)
+    }
+
+    fn merge_attributes(
+        &self,
+        name: &Symbol,
+        old: &DeclAttributes,
+        new: DeclAttributes,
+        span: Span,
+    ) -> Result<DeclAttributes> {
+        if let (Some(old_section), Some(new_section)) = (&old.section, &new.section)
+            && old_section != new_section
+        {
+            return Err(self.redeclaration_error(
+                name,
+                format!("Section of '{name}' conflicts with previous declaration"),
+                span,
+            ));
+        }
+        let mut merged = old.clone();
+        merged.merge(new);
+        Ok(merged)
+    }
+
+    fn check_thread_local_redeclaration(
+        &self,
+        name: &Symbol,
+        previous: &Attributes,
+        decl: &VarDeclaration,
+    ) -> Result<()> {
+        let Attributes::Static { thread_local, .. } = previous else {
+            return Ok(());
+        };
+        match (*thread_local, decl.is_thread_local) {
+            (false, true) => Err(self.redeclaration_error(
+                name,
+                format!(
+                    "Thread local declaration of '{name}' follows non thread local declaration"
+                ),
+                decl.name.span,
+            )),
+            (true, false) => Err(self.redeclaration_error(
+                name,
+                format!(
+                    "Non thread local declaration of '{name}' follows thread local declaration"
+                ),
+                decl.name.span,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn redeclaration_error(&self, name: &Symbol, msg: String, span: Span) -> CompilerError {
+        let error = CompilerError::new(ErrorKind::Type, msg, span);
+        match self.declarations.get(name) {
+            Some(previous) => error.with_label(*previous, "previous declaration is here"),
+            None => error,
+        }
+    }
+
     fn error_if(condition: bool, span: Span, err: &str) -> Result<()> {
         if condition {
-            Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: err.to_string(),
-                span,
-            })
+            Err(CompilerError::new(ErrorKind::Type, err, span))
         } else {
             Ok(())
         }
@@ -1611,23 +2735,131 @@ impl TypeChecker {
         ty: &Type,
         target: &Type,
     ) -> Result<Type> {
+        let target = target.unqualified();
         if ty == target {
             return Ok(ty.clone());
         }
 
+        if ty.is_arithmetic() && target.is_arithmetic() {
+            self.check_narrowing(expr, ty, target);
+        }
+        // Any scalar converts to _Bool by comparing it against zero
         if ty.is_arithmetic() && target.is_arithmetic()
             || expr.is_null_constant() && target.is_pointer()
-            || target.is_pointer_to_void() && ty.is_pointer()
-            || target.is_pointer() && ty.is_pointer_to_void()
+            || ty.is_pointer() && matches!(target, Type::Bool)
+        {
+            return Ok(self.cast_if_needed(expr, ty, target));
+        }
+        if let (Type::Pointer(inner), Type::Pointer(target_inner)) = (ty, target)
+            && (inner
+                .unqualified()
+                .is_compatible(target_inner.unqualified())
+                || inner.is_void()
+                || target_inner.is_void())
         {
+            if !target_inner.qualifiers().contains(inner.qualifiers()) {
+                self.semantics.warnings.push(CompilerError::warning(
+                    Warning::DiscardedQualifiers,
+                    format!("Conversion from '{ty}' to '{target}' discards qualifiers"),
+                    expr.span,
+                ));
+            }
             return Ok(self.cast_if_needed(expr, ty, target));
         }
 
-        Err(CompilerError {
-            kind: ErrorKind::Type,
-            msg: "Cannot convert type for assignment!".to_string(),
-            span: expr.span,
-        })
+        Err(CompilerError::new(
+            ErrorKind::Type,
+            "Cannot convert type for assignment!",
+            expr.span,
+        ))
+    }
+
+    fn check_narrowing(&mut self, expr: &Node<Expression>, ty: &Type, target: &Type) {
+        let narrowing = if let Type::Bool = target.unqualified() {
+            false
+        } else if ty.is_floating() {
+            !target.is_floating() || target.size(&self.semantics) < ty.size(&self.semantics)
+        } else {
+            !target.is_floating() && target.size(&self.semantics) < ty.size(&self.semantics)
+        };
+        if narrowing && !self.literal_fits(expr, target) {
+            self.semantics.warnings.push(CompilerError::warning(
+                Warning::Conversion,
+                format!("Conversion from '{ty}' to '{target}' may change value"),
+                expr.span,
+            ));
+        }
+    }
+
+    fn check_sign_compare(
+        &mut self,
+        span: Span,
+        (left, left_ty): (&Node<Expression>, &Type),
+        (right, right_ty): (&Node<Expression>, &Type),
+    ) {
+        if !left_ty.is_int() || !right_ty.is_int() || left_ty.is_signed() == right_ty.is_signed() {
+            return;
+        }
+        let common = self.semantics.common_type(left_ty, right_ty);
+        let signed = if left_ty.is_signed() { left } else { right };
+        // Non negative literals keep their value when converted to unsigned
+        if common.is_signed() || self.literal_fits(signed, &common) {
+            return;
+        }
+        self.semantics.warnings.push(CompilerError::warning(
+            Warning::SignCompare,
+            format!("Comparison of integers of different signs: '{left_ty}' and '{right_ty}'"),
+            span,
+        ));
+    }
+
+    /// Whether `expr` is a literal, possibly negated, whose value can be
+    /// represented by the integer type `target`.
+    fn literal_fits(&self, expr: &Node<Expression>, target: &Type) -> bool {
+        let (negated, literal) = match expr.as_ref() {
+            Expression::Unary { op, expr } if matches!(op.as_ref(), UnaryOp::Negate) => {
+                (true, expr.as_ref())
+            }
+            literal => (false, literal),
+        };
+        let Expression::Constant(constant) = literal else {
+            return false;
+        };
+        if target.is_floating() {
+            // The value fits when converting it back gives the same constant
+            return constant
+                .cast(target)
+                .and_then(|converted| converted.cast(&constant.ty()))
+                .is_some_and(|round_trip| round_trip == *constant);
+        }
+        let value = match constant {
+            Constant::Double(value) => {
+                if value.fract() != 0.0 || value.abs() >= 2f64.powi(64) {
+                    return false;
+                }
+                *value as i128
+            }
+            Constant::Float(value) => {
+                if value.fract() != 0.0 || value.abs() >= 2f32.powi(64) {
+                    return false;
+                }
+                *value as i128
+            }
+            Constant::Int(_) | Constant::Long(_) | Constant::Char(_) | Constant::Short(_) => {
+                constant.as_u64() as i64 as i128
+            }
+            Constant::UInt(_) | Constant::ULong(_) | Constant::UChar(_) | Constant::UShort(_) => {
+                constant.as_u64() as i128
+            }
+        };
+        let value = if negated { -value } else { value };
+        let bits = target.size(&self.semantics) * 8;
+        let (min, max) = if target.is_signed() {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        };
+        (min..=max).contains(&value)
     }
 
     fn cast_if_needed(&mut self, expr: &Node<Expression>, ty: &Type, expected: &Type) -> Type {
@@ -1654,16 +2886,25 @@ impl TypeChecker {
             ty2
         } else if e2.is_null_constant() {
             ty1
-        } else if ty1.is_pointer_to_void() && ty2.is_pointer()
-            || ty1.is_pointer() && ty2.is_pointer_to_void()
+        } else if let (Type::Pointer(inner1), Type::Pointer(inner2)) = (ty1, ty2)
+            && (inner1.unqualified() == inner2.unqualified()
+                || inner1.is_void()
+                || inner2.is_void())
         {
-            &Type::Pointer(Type::Void.into())
+            // The pointee of the common type gets the qualifiers of both sides
+            let pointee = if inner1.unqualified() == inner2.unqualified() {
+                inner1.unqualified().clone()
+            } else {
+                Type::Void
+            };
+            let qualifiers = inner1.qualifiers().union(inner2.qualifiers());
+            &Type::Pointer(pointee.qualified(qualifiers).into())
         } else {
-            return Err(CompilerError {
-                kind: ErrorKind::Type,
-                msg: "Expressions have incompatible types".to_string(),
-                span: e1.span + e2.span,
-            });
+            return Err(CompilerError::new(
+                ErrorKind::Type,
+                "Expressions have incompatible types",
+                e1.span + e2.span,
+            ));
         };
         Ok(result.clone())
     }
@@ -1671,6 +2912,40 @@ impl TypeChecker {
 
 pub fn check(program: &Node<Program>) -> Result<SemanticData> {
     let mut type_checker = TypeChecker::default();
+    type_checker.semantics.type_defs.insert(
+        VA_LIST_TAG.into(),
+        TypeEntry::Complete(AggregateType {
+            kind: AggregateKind::Struct,
+            alignment: 8,
+            size: 24,
+            fields: vec![
+                Field {
+                    name: "gp_offset".into(),
+                    ty: Type::UInt,
+                    offset: 0,
+                    bit_field: None,
+                },
+                Field {
+                    name: "fp_offset".into(),
+                    ty: Type::UInt,
+                    offset: 4,
+                    bit_field: None,
+                },
+                Field {
+                    name: "overflow_arg_area".into(),
+                    ty: Type::Pointer(Type::Void.into()),
+                    offset: 8,
+                    bit_field: None,
+                },
+                Field {
+                    name: "reg_save_area".into(),
+                    ty: Type::Pointer(Type::Void.into()),
+                    offset: 16,
+                    bit_field: None,
+                },
+            ],
+        }),
+    );
     type_checker.check(program)?;
     Ok(type_checker.semantics)
 }
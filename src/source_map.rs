@@ -0,0 +1,146 @@
+use crate::symbol::Symbol;
+use std::fmt::{Display, Formatter};
+
+pub struct SourceFile {
+    pub name: Symbol,
+    pub text: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: Symbol, text: String) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self {
+            name,
+            text,
+            line_starts,
+        }
+    }
+
+    /// Zero based index of the line containing `offset`.
+    pub fn line_index(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    pub fn line_start(&self, index: usize) -> usize {
+        self.line_starts[index]
+    }
+
+    /// Text of the line at `index`, without the line terminator.
+    pub fn line(&self, index: usize) -> &str {
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.text.len());
+        self.text[self.line_starts[index]..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// One based line and column of `offset`.
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let index = self.line_index(offset);
+        (index + 1, offset - self.line_starts[index] + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: Symbol,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// A contiguous range of the preprocessed output that was copied from an
+/// original file. `#line` directives are represented by the presumed name and
+/// a delta applied to physical line numbers.
+struct Segment {
+    start: usize,
+    file: usize,
+    offset: usize,
+    presumed_name: Symbol,
+    line_delta: isize,
+}
+
+/// Maps byte offsets of the preprocessed source (the ones stored in every
+/// `Span`) back to the original files.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    segments: Vec<Segment>,
+}
+
+impl SourceMap {
+    pub fn add_file(&mut self, name: Symbol, text: String) -> usize {
+        self.files.push(SourceFile::new(name, text));
+        self.files.len() - 1
+    }
+
+    pub fn file(&self, id: usize) -> &SourceFile {
+        &self.files[id]
+    }
+
+    pub fn add_segment(
+        &mut self,
+        start: usize,
+        file: usize,
+        offset: usize,
+        presumed_name: &Symbol,
+        line_delta: isize,
+    ) {
+        if let Some(last) = self.segments.last() {
+            let contiguous = last.file == file
+                && last.presumed_name == *presumed_name
+                && last.line_delta == line_delta
+                && offset >= last.offset
+                && start - last.start == offset - last.offset;
+            if contiguous {
+                return;
+            }
+        }
+        self.segments.push(Segment {
+            start,
+            file,
+            offset,
+            presumed_name: presumed_name.clone(),
+            line_delta,
+        });
+    }
+
+    fn segment(&self, offset: usize) -> Option<&Segment> {
+        let index = self.segments.partition_point(|s| s.start <= offset);
+        index.checked_sub(1).map(|i| &self.segments[i])
+    }
+
+    /// Original file and byte offset inside it of an output `offset`.
+    pub fn resolve(&self, offset: usize) -> Option<(&SourceFile, usize)> {
+        let segment = self.segment(offset)?;
+        let file = &self.files[segment.file];
+        let file_offset = (segment.offset + offset - segment.start).min(file.text.len());
+        Some((file, file_offset))
+    }
+
+    pub fn location(&self, offset: usize) -> Location {
+        let (Some(segment), Some((file, file_offset))) =
+            (self.segment(offset), self.resolve(offset))
+        else {
+            return Location {
+                file: Symbol::from("<unknown>"),
+                line: 0,
+                column: 0,
+            };
+        };
+        let (line, column) = file.line_column(file_offset);
+        Location {
+            file: segment.presumed_name.clone(),
+            line: (line as isize + segment.line_delta) as usize,
+            column,
+        }
+    }
+}
@@ -5,8 +5,16 @@ pub mod pretty;
 mod test;
 
 use crate::ast;
-use crate::semantic::{Attributes, InitialValue, SemanticData, StaticInit, SymbolData, Type};
+use crate::error::{CompilerError, Warning};
+use crate::lexer::Span;
+use crate::optimization::cfg::{GenericInstruction, InstructionKind, NodeId};
+use crate::semantic::{
+    Attributes, BitField, DeclAttributes, Field, InitTree, InitialValue, SemanticData, StaticInit,
+    SymbolData, Type, VA_LIST_TAG, VariableLength,
+};
 use crate::symbol::Symbol;
+use crate::tacky::cfg::Cfg;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 
 #[derive(Debug, Clone)]
@@ -34,6 +42,7 @@ pub struct Function {
 pub struct StaticVariable {
     pub name: Symbol,
     pub global: bool,
+    pub thread_local: bool,
     pub ty: Type,
     pub init: Vec<StaticInit>,
 }
@@ -80,6 +89,11 @@ pub enum Instruction {
         args: Vec<Val>,
         dst: Option<Val>,
     },
+    IndirectCall {
+        ptr: Val,
+        args: Vec<Val>,
+        dst: Option<Val>,
+    },
     SignExtend {
         src: Val,
         dst: Val,
@@ -108,6 +122,14 @@ pub enum Instruction {
         src: Val,
         dst: Val,
     },
+    FloatToDouble {
+        src: Val,
+        dst: Val,
+    },
+    DoubleToFloat {
+        src: Val,
+        dst: Val,
+    },
     GetAddress {
         src: Val,
         dst: Val,
@@ -136,6 +158,29 @@ pub enum Instruction {
         dst: Val,
         offset: i64,
     },
+    VaStart {
+        ap: Val,
+    },
+    VaArg {
+        ap: Val,
+        dst: Val,
+    },
+    AllocateStack {
+        size: Val,
+        dst: Val,
+    },
+    SaveStack {
+        dst: Val,
+    },
+    RestoreStack {
+        src: Val,
+    },
+    /// Only present while the function is in SSA form, picks the value
+    /// coming from the predecessor block that was executed.
+    Phi {
+        dst: Val,
+        args: Vec<(NodeId, Val)>,
+    },
 }
 
 pub type Constant = ast::Constant;
@@ -179,7 +224,16 @@ pub enum BinaryOp {
 enum ExprResult {
     Operand(Val),
     Dereference(Val),
-    SubObject { base: Symbol, offset: i64 },
+    SubObject {
+        base: Symbol,
+        offset: i64,
+    },
+    /// A bit-field inside the storage unit `unit`, of type `ty`.
+    BitField {
+        unit: Box<ExprResult>,
+        ty: Type,
+        bit_field: BitField,
+    },
 }
 
 struct TackyGenerator {
@@ -187,35 +241,256 @@ struct TackyGenerator {
     instructions: Vec<Instruction>,
     tmp_counter: u32,
     label_counter: u32,
+    /// Stack pointers saved by the enclosing blocks that allocate variable
+    /// length arrays, restored when leaving them.
+    stack_saves: Vec<Val>,
+    /// Number of saved stack pointers when entering a loop or a switch.
+    jump_depths: HashMap<Symbol, usize>,
 }
 
 impl TackyGenerator {
-    fn emit_instructions(&mut self, function: &ast::Block) -> Vec<Instruction> {
-        self.emit_block(function);
+    fn emit_instructions(
+        &mut self,
+        function: &ast::FunctionDeclaration,
+        body: &ast::Node<ast::Block>,
+    ) -> Vec<Instruction> {
+        for param in &function.type_spec.params {
+            self.emit_variable_lengths(param);
+        }
+        self.emit_block(body);
+        self.check_missing_return(function, body);
         self.instructions
             .push(Instruction::Return(Some(Val::Constant(Constant::Int(0)))));
         self.instructions.clone()
     }
 
+    /// A `return`, possibly behind labels.
+    fn is_return(stmt: &ast::Statement) -> bool {
+        match stmt {
+            ast::Statement::Return(_) => true,
+            ast::Statement::Labeled { body, .. }
+            | ast::Statement::Case { body, .. }
+            | ast::Statement::Default { body, .. } => Self::is_return(body),
+            _ => false,
+        }
+    }
+
+    /// Warns when the end of a non-void function can be reached, that is
+    /// when the exit of the function is reached without a return.
+    fn check_missing_return(
+        &mut self,
+        function: &ast::FunctionDeclaration,
+        body: &ast::Node<ast::Block>,
+    ) {
+        let name = &function.name.symbol;
+        if function.type_spec.ret.ty(&self.semantics).is_void() || name.as_ref() == "main" {
+            return;
+        }
+        let cfg = Cfg::new(&self.instructions);
+        let mut reachable = HashSet::new();
+        let mut queue = VecDeque::from([cfg.entry_id()]);
+        while let Some(node_id) = queue.pop_front() {
+            if !reachable.insert(node_id) {
+                continue;
+            }
+            let node = cfg.get_node(node_id);
+            // Loops without condition jump on constants, only one of the
+            // successors (jump target first, then fall through) is taken.
+            let taken = match node.instructions.last() {
+                Some(Instruction::JumpIfZero {
+                    cond: Val::Constant(c),
+                    ..
+                }) => Some(c.is_zero()),
+                Some(Instruction::JumpIfNotZero {
+                    cond: Val::Constant(c),
+                    ..
+                }) => Some(!c.is_zero()),
+                _ => None,
+            };
+            match taken {
+                Some(true) => queue.push_back(node.successors[0]),
+                Some(false) => queue.push_back(node.successors[1]),
+                None => queue.extend(node.successors.iter()),
+            }
+        }
+        let falls_through = cfg
+            .get_node(cfg.exit_id())
+            .predecessors
+            .iter()
+            .filter(|id| reachable.contains(*id))
+            .any(|id| {
+                let last = cfg.get_node(*id).instructions.last();
+                !matches!(last.map(|i| i.kind()), Some(InstructionKind::Return))
+            });
+        if falls_through {
+            let closing_brace = Span(body.span.1 - 1, body.span.1);
+            self.semantics.warnings.push(CompilerError::warning(
+                Warning::ReturnType,
+                format!("Non-void function '{name}' does not return a value in all control paths"),
+                closing_brace,
+            ));
+        }
+    }
+
+    /// Warns about statements following a `return` in the same block. Only
+    /// the `return` statements directly in the block are followed, not the
+    /// ones nested in compound statements or other control flow.
     fn emit_block(&mut self, block: &ast::Block) {
+        let mut after_return = false;
+        let mut saved_stack = None;
         for block_item in &block.items {
+            if let ast::BlockItem::Stmt(stmt) = block_item
+                && after_return
+            {
+                match stmt.as_ref() {
+                    // Labels can be reached with jumps
+                    ast::Statement::Labeled { .. }
+                    | ast::Statement::Case { .. }
+                    | ast::Statement::Default { .. } => after_return = false,
+                    ast::Statement::Null => {}
+                    _ => {
+                        self.semantics.warnings.push(CompilerError::warning(
+                            Warning::UnreachableCode,
+                            "Code will never be executed",
+                            stmt.span,
+                        ));
+                        after_return = false;
+                    }
+                }
+            }
+            if let ast::BlockItem::Stmt(stmt) = block_item
+                && Self::is_return(stmt)
+            {
+                after_return = true;
+            }
             match block_item {
                 ast::BlockItem::Stmt(stmt) => self.emit_statement(stmt),
-                ast::BlockItem::Decl(decl) => {
-                    if let ast::Declaration::Var(decl) = decl.as_ref() {
+                ast::BlockItem::Decl(decl) => match decl.as_ref() {
+                    ast::Declaration::Var(decl) => {
+                        // Jumps into the scope of a variable length array are
+                        // not allowed, so the stack is saved right before the
+                        // first one.
+                        if saved_stack.is_none() && Self::allocates_stack(decl, &self.semantics) {
+                            let saved = self.make_temp(&Type::Pointer(Type::Void.into()));
+                            self.instructions
+                                .push(Instruction::SaveStack { dst: saved.clone() });
+                            self.stack_saves.push(saved.clone());
+                            saved_stack = Some(saved);
+                        }
                         self.emit_var_declaration(decl)
                     }
-                }
+                    ast::Declaration::TypeDef(decl) => self.emit_variable_lengths(&decl.type_spec),
+                    _ => {}
+                },
             }
         }
+        if let Some(saved) = saved_stack {
+            self.stack_saves.pop();
+            self.instructions
+                .push(Instruction::RestoreStack { src: saved });
+        }
+    }
+
+    fn allocates_stack(decl: &ast::VarDeclaration, semantics: &SemanticData) -> bool {
+        decl.storage_class.is_none() && decl.type_spec.ty(semantics).is_variable_length_array()
+    }
+
+    /// Leaving blocks with a jump frees the variable length arrays allocated
+    /// in them.
+    fn restore_stack(&mut self, label: &Symbol) {
+        if let Some(saved) = self.stack_saves.get(self.jump_depths[label]) {
+            self.instructions
+                .push(Instruction::RestoreStack { src: saved.clone() });
+        }
+    }
+
+    /// Computes the lengths of the variable length arrays in a type, when
+    /// its declaration is reached.
+    fn emit_variable_lengths(&mut self, type_spec: &ast::Node<ast::TypeSpec>) {
+        match type_spec.as_ref() {
+            ast::TypeSpec::VariableArray(inner, size)
+                if !self.semantics.array_sizes.contains_key(&size.id) =>
+            {
+                self.emit_variable_lengths(inner);
+                let length = self.emit_expr(size);
+                let VariableLength(name) = VariableLength::of(size);
+                self.instructions.push(Instruction::Copy {
+                    src: length,
+                    dst: Val::Var(name),
+                });
+            }
+            ast::TypeSpec::Array(inner, _)
+            | ast::TypeSpec::VariableArray(inner, _)
+            | ast::TypeSpec::UnsizedArray(inner)
+            | ast::TypeSpec::Pointer(inner)
+            | ast::TypeSpec::Qualified(_, inner) => self.emit_variable_lengths(inner),
+            _ => {}
+        }
+    }
+
+    /// Size of a type in bytes, computed at runtime for variable length
+    /// arrays.
+    fn runtime_size(&mut self, ty: &Type) -> Val {
+        let (length, inner) = match ty.unqualified() {
+            Type::VariableArray(inner, VariableLength(length)) => (Val::Var(length.clone()), inner),
+            Type::Array(inner, length) if ty.is_variable_length_array() => {
+                (Val::Constant(Constant::ULong(*length as u64)), inner)
+            }
+            ty => return Val::Constant(Constant::ULong(ty.size(&self.semantics) as u64)),
+        };
+        let element_size = self.runtime_size(inner);
+        let dst = self.make_temp(&Type::ULong);
+        self.instructions.push(Instruction::Binary {
+            op: BinaryOp::Multiply,
+            src1: length,
+            src2: element_size,
+            dst: dst.clone(),
+        });
+        dst
+    }
+
+    /// Index and scale of pointer arithmetic over elements of type `ty`.
+    /// Variable length arrays are scaled at runtime, multiplying the index.
+    fn scaled_index(&mut self, index: Val, ty: &Type) -> (Val, usize) {
+        if !ty.is_variable_length_array() {
+            return (index, ty.size(&self.semantics));
+        }
+        let size = self.runtime_size(ty);
+        let size = self.cast(size, &Type::Long);
+        let index = self.cast(index, &Type::Long);
+        let dst = self.make_temp(&Type::Long);
+        self.instructions.push(Instruction::Binary {
+            op: BinaryOp::Multiply,
+            src1: index,
+            src2: size,
+            dst: dst.clone(),
+        });
+        (dst, 1)
     }
 
     fn emit_var_declaration(&mut self, decl: &ast::VarDeclaration) {
         if decl.storage_class.is_some() {
             return;
         }
+        self.emit_variable_lengths(&decl.type_spec);
+        let ty = decl.type_spec.ty(&self.semantics);
+        if ty.is_variable_length_array() {
+            let size = self.runtime_size(&ty);
+            self.instructions.push(Instruction::AllocateStack {
+                size,
+                dst: Val::Var(decl.name.symbol.clone()),
+            });
+        }
         if let Some(init) = &decl.init {
-            self.emit_initializer(0, 0, &decl.name.symbol, init, &decl.type_spec.ty());
+            let tree = self.semantics.initializers[&init.id].clone();
+            self.emit_initializer(
+                0,
+                0,
+                &decl.name.symbol,
+                &tree,
+                &init.expressions(),
+                &decl.type_spec.ty(&self.semantics),
+            );
         }
     }
 
@@ -224,11 +499,13 @@ impl TackyGenerator {
         level: usize,
         offset: usize,
         name: &Symbol,
-        initializer: &ast::Initializer,
+        tree: &InitTree,
+        exprs: &HashMap<ast::NodeId, &ast::Node<ast::Expression>>,
         ty: &Type,
     ) {
-        match initializer {
-            ast::Initializer::Single(init) => {
+        match tree {
+            InitTree::Single(id) => {
+                let init = exprs[id];
                 if let ast::Expression::String(s) = init.as_ref()
                     && ty.is_array()
                 {
@@ -266,16 +543,17 @@ impl TackyGenerator {
                     }
                 }
             }
-            ast::Initializer::Compound(initializers) => match ty {
-                Type::Array(inner, len) => {
-                    for i in 0..*len {
-                        let size = inner.size(&self.semantics);
-                        if let Some(initializer) = initializers.get(i) {
+            InitTree::Aggregate(elements) => match ty.unqualified() {
+                Type::Array(inner, _) => {
+                    let size = inner.size(&self.semantics);
+                    for (i, element) in elements.iter().enumerate() {
+                        if let Some(element) = element {
                             self.emit_initializer(
                                 level + 1,
                                 offset + i * size,
                                 name,
-                                initializer,
+                                element,
+                                exprs,
                                 inner,
                             )
                         } else {
@@ -286,13 +564,16 @@ impl TackyGenerator {
                 Type::Struct(struct_name) => {
                     let aggregate = self.semantics.get_aggregate(struct_name);
                     let fields = aggregate.fields.clone();
-                    for (i, field) in fields.iter().enumerate() {
-                        if let Some(initializer) = initializers.get(i) {
+                    for (field, element) in fields.iter().zip(elements) {
+                        if field.bit_field.is_some() {
+                            self.emit_bit_field_initializer(offset, name, field, element, exprs);
+                        } else if let Some(element) = element {
                             self.emit_initializer(
                                 level + 1,
                                 offset + field.offset,
                                 name,
-                                initializer,
+                                element,
+                                exprs,
                                 &field.ty,
                             )
                         } else {
@@ -301,34 +582,69 @@ impl TackyGenerator {
                     }
                 }
                 Type::Union(union_name) => {
-                    let struct_def = self.semantics.get_aggregate(union_name);
-                    let first_field_ty = struct_def
-                        .fields
-                        .first()
-                        .expect("Union without fields")
-                        .ty
-                        .clone();
-                    let [initializer] = initializers.as_slice() else {
-                        panic!("Union initializer with multiple initializers");
+                    let union_def = self.semantics.get_aggregate(union_name);
+                    let fields = union_def.fields.clone();
+                    let Some((field, element)) = fields
+                        .iter()
+                        .zip(elements)
+                        .find(|(_, element)| element.is_some())
+                    else {
+                        self.emit_zero_initializer(offset, name, ty);
+                        return;
                     };
-                    self.emit_initializer(level + 1, offset, name, initializer, &first_field_ty);
+                    if field.bit_field.is_some() {
+                        self.emit_bit_field_initializer(offset, name, field, element, exprs);
+                    } else if let Some(element) = element {
+                        self.emit_initializer(level + 1, offset, name, element, exprs, &field.ty);
+                    }
                 }
                 _ => panic!("Compound initializer used with a non-array type"),
             },
         }
     }
 
+    /// Bit-fields share their storage unit, so they are initialized without
+    /// overwriting their neighbours. Missing initializers set them to zero.
+    fn emit_bit_field_initializer(
+        &mut self,
+        offset: usize,
+        name: &Symbol,
+        field: &Field,
+        initializer: &Option<InitTree>,
+        exprs: &HashMap<ast::NodeId, &ast::Node<ast::Expression>>,
+    ) {
+        let bit_field = field.bit_field.expect("Field is a bit-field");
+        let value = match initializer {
+            Some(InitTree::Single(id)) => self.emit_expr(exprs[id]),
+            Some(InitTree::Aggregate(_)) => {
+                panic!("Compound initializer used with a bit-field")
+            }
+            None => Val::Constant(Constant::Int(0).cast(&field.ty).unwrap()),
+        };
+        let unit = ExprResult::SubObject {
+            base: name.clone(),
+            offset: (offset + field.offset) as i64,
+        };
+        self.store_bit_field(&unit, &field.ty, bit_field, value);
+    }
+
     fn emit_zero_initializer(&mut self, offset: usize, name: &Symbol, ty: &Type) {
         let constant = match ty {
             Type::Char | Type::SChar => Constant::Char(0),
-            Type::UChar => Constant::UChar(0),
+            Type::Bool | Type::UChar => Constant::UChar(0),
+            Type::Short => Constant::Short(0),
+            Type::UShort => Constant::UShort(0),
             Type::Int => Constant::Int(0),
             Type::UInt => Constant::UInt(0),
             Type::Long => Constant::Long(0),
             Type::ULong => Constant::ULong(0),
             Type::Double => Constant::Double(0.0),
+            Type::Float => Constant::Float(0.0),
             Type::Pointer(_) => Constant::ULong(0),
-            Type::Void | Type::Function(_) => panic!("Zero initializer for invalid type"),
+            Type::Void | Type::Function(_) | Type::VariableArray(..) => {
+                panic!("Zero initializer for invalid type")
+            }
+            Type::Qualified(_, inner) => return self.emit_zero_initializer(offset, name, inner),
             Type::Array(inner, size) => {
                 let ty_size = inner.size(&self.semantics);
                 for i in 0..*size {
@@ -339,7 +655,17 @@ impl TackyGenerator {
             Type::Struct(struct_name) | Type::Union(struct_name) => {
                 let struct_def = self.semantics.get_aggregate(struct_name);
                 for field in struct_def.fields.clone() {
-                    self.emit_zero_initializer(offset + field.offset, name, &field.ty)
+                    if field.bit_field.is_some() {
+                        self.emit_bit_field_initializer(
+                            offset,
+                            name,
+                            &field,
+                            &None,
+                            &HashMap::new(),
+                        );
+                    } else {
+                        self.emit_zero_initializer(offset + field.offset, name, &field.ty)
+                    }
                 }
                 return;
             }
@@ -414,6 +740,8 @@ impl TackyGenerator {
             ast::Statement::Null => {}
 
             ast::Statement::DoWhile { cond, body, label } => {
+                self.jump_depths
+                    .insert(label.clone(), self.stack_saves.len());
                 let start_label = Symbol::from(format!("start_{label}"));
                 self.instructions
                     .push(Instruction::Label(start_label.clone()));
@@ -430,6 +758,8 @@ impl TackyGenerator {
                     .push(Instruction::Label(format!("break_{label}").into()));
             }
             ast::Statement::While { cond, body, label } => {
+                self.jump_depths
+                    .insert(label.clone(), self.stack_saves.len());
                 let continue_label = Symbol::from(format!("continue_{label}"));
                 let break_label = Symbol::from(format!("break_{label}"));
                 self.instructions
@@ -452,6 +782,8 @@ impl TackyGenerator {
                 body,
                 label,
             } => {
+                self.jump_depths
+                    .insert(label.clone(), self.stack_saves.len());
                 match init {
                     ast::ForInit::Decl(decl) => self.emit_var_declaration(decl),
                     ast::ForInit::Expr(expr) => {
@@ -484,6 +816,8 @@ impl TackyGenerator {
                 self.instructions.push(Instruction::Label(break_label));
             }
             ast::Statement::Switch { expr, body, label } => {
+                self.jump_depths
+                    .insert(label.clone(), self.stack_saves.len());
                 let cond = self.emit_expr(expr);
                 let expr_ty = self.semantics.expr_type(expr).clone();
                 let switch_cases = self.semantics.switch_cases(expr).clone();
@@ -514,10 +848,12 @@ impl TackyGenerator {
                 self.instructions.push(Instruction::Label(break_label))
             }
             ast::Statement::Break(label) => {
+                self.restore_stack(label);
                 let target = Symbol::from(format!("break_{label}"));
                 self.instructions.push(Instruction::Jump { target });
             }
             ast::Statement::Continue(label) => {
+                self.restore_stack(label);
                 let target = Symbol::from(format!("continue_{label}"));
                 self.instructions.push(Instruction::Jump { target });
             }
@@ -532,12 +868,13 @@ impl TackyGenerator {
     fn make_cond(&mut self, cond: &ast::Node<ast::Expression>) -> Val {
         let cond_val = self.emit_expr(cond);
         let cond_ty = self.semantics.expr_type(cond).clone();
-        if cond_ty.is_double() {
+        if cond_ty.is_floating() {
             let dst = self.make_temp(&Type::Int);
+            let zero = Constant::Int(0).cast(&cond_ty).unwrap();
             self.instructions.push(Instruction::Binary {
                 op: BinaryOp::NotEqual,
                 src1: cond_val,
-                src2: Val::Constant(Constant::Double(0.0)),
+                src2: Val::Constant(zero),
                 dst: dst.clone(),
             });
             dst
@@ -587,6 +924,14 @@ impl TackyGenerator {
                     self.check_implicit_cast(dst, expr)
                 }
             }
+            ExprResult::BitField {
+                unit,
+                ty,
+                bit_field,
+            } => {
+                let val = self.load_bit_field(&unit, &ty, bit_field);
+                self.check_implicit_cast(val, expr)
+            }
         }
     }
 
@@ -595,7 +940,44 @@ impl TackyGenerator {
         let result = match expr.as_ref() {
             ast::Expression::Constant(value) => Val::Constant(value.clone()),
             ast::Expression::String(s) => self.make_string_const(s),
+            ast::Expression::Var(name)
+                if let Some(value) = self.semantics.enumerators.get(name) =>
+            {
+                Val::Constant(Constant::Int(*value))
+            }
+            // Variables of variable length array type hold the address of
+            // their storage
+            ast::Expression::Var(name)
+                if self.semantics.symbol_ty(name).is_variable_length_array() =>
+            {
+                return ExprResult::Dereference(Val::Var(name.clone()));
+            }
+            ast::Expression::Var(name) if self.semantics.is_external_thread_local(name) => {
+                let ptr_ty = Type::Pointer(self.semantics.symbol_ty(name).clone().into());
+                let ptr = self.make_temp(&ptr_ty);
+                self.instructions.push(Instruction::GetAddress {
+                    src: Val::Var(name.clone()),
+                    dst: ptr.clone(),
+                });
+                return ExprResult::Dereference(ptr);
+            }
             ast::Expression::Var(name) => Val::Var(name.clone()),
+            ast::Expression::CompoundLiteral { init, .. } => {
+                let name = self.semantics.compound_literals[&expr.id].clone();
+                if let Some(tree) = self.semantics.initializers.get(&init.id).cloned() {
+                    let ty = self.semantics.symbols[&name].ty.clone();
+                    self.emit_initializer(0, 0, &name, &tree, &init.expressions(), &ty);
+                }
+                Val::Var(name)
+            }
+            ast::Expression::Unary { op, expr } if matches!(op.as_ref(), ast::UnaryOp::Plus) => {
+                let operand = self.expression(expr);
+                self.get_or_load(&operand, expr)
+            }
+            ast::Expression::Comma(left, right) => {
+                self.emit_expr(left);
+                self.emit_expr(right)
+            }
             ast::Expression::Unary { op, expr } => {
                 let lvalue = self.expression(expr);
                 let val = self.get_or_load(&lvalue, expr);
@@ -610,17 +992,23 @@ impl TackyGenerator {
                         _ => unreachable!(),
                     };
                     let index = Val::Constant(Constant::Long(index));
-                    let scale = inner.size(&self.semantics);
+                    let (index, scale) = self.scaled_index(index, &inner);
                     self.instructions.push(Instruction::AddPtr {
                         ptr: val,
                         index,
                         scale,
                         dst: dst.clone(),
                     });
+                } else if let Type::Bool = expr_ty.unqualified()
+                    && let ast::UnaryOp::Decrement | ast::UnaryOp::Increment = op.as_ref()
+                {
+                    let increment = matches!(op.as_ref(), ast::UnaryOp::Increment);
+                    self.step_bool(increment, val, dst.clone());
                 } else {
                     let tacky_op = match op.as_ref() {
                         ast::UnaryOp::Complement => UnaryOp::Complement,
                         ast::UnaryOp::Negate => UnaryOp::Negate,
+                        ast::UnaryOp::Plus => unreachable!(),
                         ast::UnaryOp::Not => UnaryOp::Not,
                         ast::UnaryOp::Increment => UnaryOp::Increment,
                         ast::UnaryOp::Decrement => UnaryOp::Decrement,
@@ -654,13 +1042,16 @@ impl TackyGenerator {
                         ast::PostfixOp::Decrement => -1,
                     };
                     let index = Val::Constant(Constant::Long(index));
-                    let scale = inner.size(&self.semantics);
+                    let (index, scale) = self.scaled_index(index, &inner);
                     self.instructions.push(Instruction::AddPtr {
                         ptr: val,
                         index,
                         scale,
                         dst: decremented.clone(),
                     });
+                } else if let Type::Bool = expr_ty.unqualified() {
+                    let increment = matches!(op.as_ref(), ast::PostfixOp::Increment);
+                    self.step_bool(increment, val, decremented.clone());
                 } else {
                     let tacky_op = match op.as_ref() {
                         ast::PostfixOp::Increment => UnaryOp::Increment,
@@ -687,7 +1078,7 @@ impl TackyGenerator {
                         if let Type::Pointer(inner) = left_ty {
                             let ptr = src1;
                             let index = self.emit_expr(right);
-                            let scale = inner.size(&self.semantics);
+                            let (index, scale) = self.scaled_index(index, &inner);
                             self.instructions.push(Instruction::AddPtr {
                                 ptr,
                                 index,
@@ -697,8 +1088,7 @@ impl TackyGenerator {
                             return ExprResult::Operand(dst);
                         } else if let Type::Pointer(inner) = right_ty {
                             let ptr = self.emit_expr(right);
-                            let index = src1;
-                            let scale = inner.size(&self.semantics);
+                            let (index, scale) = self.scaled_index(src1, &inner);
                             self.instructions.push(Instruction::AddPtr {
                                 ptr,
                                 index,
@@ -726,9 +1116,12 @@ impl TackyGenerator {
                                 dst: diff.clone(),
                             });
 
-                            let ty = inner1.as_ref();
-                            let size =
-                                Val::Constant(Constant::Long(ty.size(&self.semantics) as i64));
+                            let size = if inner1.is_variable_length_array() {
+                                let size = self.runtime_size(inner1);
+                                self.cast(size, &Type::Long)
+                            } else {
+                                Val::Constant(Constant::Long(inner1.size(&self.semantics) as i64))
+                            };
                             self.instructions.push(Instruction::Binary {
                                 op: BinaryOp::Divide,
                                 src1: diff,
@@ -745,10 +1138,10 @@ impl TackyGenerator {
                                 src: index,
                                 dst: negated.clone(),
                             });
-                            let scale = inner.size(&self.semantics);
+                            let (index, scale) = self.scaled_index(negated, &inner);
                             self.instructions.push(Instruction::AddPtr {
                                 ptr,
-                                index: negated,
+                                index,
                                 scale,
                                 dst: dst.clone(),
                             });
@@ -874,7 +1267,7 @@ impl TackyGenerator {
                                 }
                                 _ => unreachable!(),
                             };
-                            let scale = inner.size(&self.semantics);
+                            let (index, scale) = self.scaled_index(index, &inner);
                             self.instructions.push(Instruction::AddPtr {
                                 ptr: src1,
                                 index,
@@ -942,7 +1335,7 @@ impl TackyGenerator {
                 result
             }
 
-            ast::Expression::FunctionCall { name, args } => {
+            ast::Expression::FunctionCall { function, args } => {
                 let args: Vec<Val> = args.iter().map(|a| self.emit_expr(a)).collect();
                 let (result, dst) = if expr_ty.is_void() {
                     (Val::Var("DUMMY".into()), None)
@@ -950,23 +1343,65 @@ impl TackyGenerator {
                     let dst = self.make_temp(&expr_ty);
                     (dst.clone(), Some(dst))
                 };
-                self.instructions.push(Instruction::FnCall {
-                    name: name.symbol.clone(),
-                    args,
-                    dst,
-                });
+                let instruction = match function.as_ref() {
+                    ast::Expression::Var(name) if self.semantics.symbol_ty(name).is_function() => {
+                        Instruction::FnCall {
+                            name: name.clone(),
+                            args,
+                            dst,
+                        }
+                    }
+                    _ => Instruction::IndirectCall {
+                        ptr: self.emit_expr(function),
+                        args,
+                        dst,
+                    },
+                };
+                self.instructions.push(instruction);
                 result
             }
 
+            ast::Expression::VaStart(ap, _) => {
+                let ap = self.emit_expr(ap);
+                self.instructions.push(Instruction::VaStart { ap });
+                Val::Var("DUMMY".into())
+            }
+            ast::Expression::VaArg(ap, _) => {
+                let ap = self.emit_expr(ap);
+                let dst = self.make_temp(&expr_ty);
+                self.instructions.push(Instruction::VaArg {
+                    ap,
+                    dst: dst.clone(),
+                });
+                dst
+            }
+            ast::Expression::VaEnd(ap) => {
+                self.emit_expr(ap);
+                Val::Var("DUMMY".into())
+            }
+            ast::Expression::VaCopy(dst, src) => {
+                let dst = self.emit_expr(dst);
+                let src = self.emit_expr(src);
+                let tmp = self.make_temp(&Type::Struct(VA_LIST_TAG.into()));
+                self.instructions.push(Instruction::Load {
+                    ptr: src,
+                    dst: tmp.clone(),
+                });
+                self.instructions
+                    .push(Instruction::Store { src: tmp, ptr: dst });
+                Val::Var("DUMMY".into())
+            }
+
             ast::Expression::Cast {
                 target,
                 expr: inner,
             } => {
+                self.emit_variable_lengths(target);
                 let result = self.emit_expr(inner);
-                if target.ty().is_void() {
+                if target.ty(&self.semantics).is_void() {
                     return ExprResult::Operand(Val::Var("DUMMY".into()));
                 } else {
-                    self.cast(result, &target.ty())
+                    self.cast(result, &target.ty(&self.semantics))
                 }
             }
 
@@ -1000,6 +1435,7 @@ impl TackyGenerator {
                     }
                     dst
                 }
+                ExprResult::BitField { .. } => panic!("Cannot take the address of a bit-field"),
             },
             ast::Expression::Subscript(expr1, expr2) => {
                 let ty1 = self.semantics.expr_type(expr1).clone();
@@ -1017,7 +1453,7 @@ impl TackyGenerator {
                 let Type::Pointer(inner) = &ptr_ty else {
                     unreachable!();
                 };
-                let scale = inner.size(&self.semantics);
+                let (index, scale) = self.scaled_index(index, inner);
                 let dst = self.make_temp(&ptr_ty);
                 self.instructions.push(Instruction::AddPtr {
                     ptr,
@@ -1028,12 +1464,24 @@ impl TackyGenerator {
                 return ExprResult::Dereference(dst);
             }
             ast::Expression::SizeOfType(ty) => {
-                let ty1 = &ty.ty();
+                let ty1 = &ty.ty(&self.semantics);
+                if ty1.is_variable_length_array() {
+                    self.emit_variable_lengths(ty);
+                    return ExprResult::Operand(self.runtime_size(ty1));
+                }
                 return ExprResult::Operand(Val::Constant(Constant::ULong(
                     ty1.size(&self.semantics) as u64,
                 )));
             }
+            ast::Expression::AlignOfType(ty) => {
+                let alignment = ty.ty(&self.semantics).alignment(&self.semantics);
+                return ExprResult::Operand(Val::Constant(Constant::ULong(alignment as u64)));
+            }
             ast::Expression::SizeOfExpr(e) => {
+                let ty = self.semantics.expr_type(e).clone();
+                if ty.is_variable_length_array() {
+                    return ExprResult::Operand(self.runtime_size(&ty));
+                }
                 let size = if let Some(target) = self.semantics.implicit_casts.get(&e.id).cloned() {
                     target.size(&self.semantics)
                 } else if let Some(target) = self.semantics.pointer_decays.get(&e.id).cloned() {
@@ -1051,13 +1499,14 @@ impl TackyGenerator {
                     panic!("Expected an aggregate type in dot expression");
                 };
                 let aggregate_def = self.semantics.get_aggregate(&aggregate_name);
-                let field_offset = aggregate_def
+                let field = aggregate_def
                     .fields
                     .iter()
                     .find(|f| f.name == field.symbol)
-                    .expect("Field not found in struct")
-                    .offset as i64;
-                return match self.expression(aggregate) {
+                    .expect("Field not found in struct");
+                let field_offset = field.offset as i64;
+                let bit_field = field.bit_field;
+                let result = match self.expression(aggregate) {
                     ExprResult::Operand(Val::Var(base)) => ExprResult::SubObject {
                         base,
                         offset: field_offset,
@@ -1083,6 +1532,7 @@ impl TackyGenerator {
                     }
                     _ => panic!("Invalid dot expression"),
                 };
+                return Self::member(result, expr_ty, bit_field);
             }
             ast::Expression::Arrow { pointer, field } => {
                 let pointer_ty = self.semantics.expr_type(pointer).clone();
@@ -1090,19 +1540,21 @@ impl TackyGenerator {
                 let Type::Pointer(aggregate_ty) = pointer_ty else {
                     panic!("Expected a pointer to agregate type in dot expression");
                 };
-                let (Type::Struct(aggregate_name) | Type::Union(aggregate_name)) = &*aggregate_ty
+                let (Type::Struct(aggregate_name) | Type::Union(aggregate_name)) =
+                    aggregate_ty.unqualified()
                 else {
                     panic!("Expected a struct in dot expression");
                 };
                 let aggregate = self.semantics.get_aggregate(aggregate_name);
-                let field_offset = aggregate
+                let field = aggregate
                     .fields
                     .iter()
                     .find(|f| f.name == field.symbol)
-                    .expect("Field not found in struct")
-                    .offset as i64;
+                    .expect("Field not found in struct");
+                let field_offset = field.offset as i64;
+                let bit_field = field.bit_field;
                 let ptr = self.emit_expr(pointer);
-                return if field_offset != 0 {
+                let result = if field_offset != 0 {
                     let dst = self.make_temp(&Type::Pointer(aggregate_ty));
                     self.instructions.push(Instruction::AddPtr {
                         ptr,
@@ -1114,11 +1566,142 @@ impl TackyGenerator {
                 } else {
                     ExprResult::Dereference(ptr)
                 };
+                return Self::member(result, expr_ty, bit_field);
             }
         };
         ExprResult::Operand(result)
     }
 
+    fn member(result: ExprResult, ty: Type, bit_field: Option<BitField>) -> ExprResult {
+        match bit_field {
+            Some(bit_field) => ExprResult::BitField {
+                unit: result.into(),
+                ty,
+                bit_field,
+            },
+            None => result,
+        }
+    }
+
+    /// Type in which the bits of a storage unit are shifted and masked.
+    fn bit_field_work_type(&self, ty: &Type) -> Type {
+        match (ty.size(&self.semantics) <= 4, ty.is_signed()) {
+            (true, true) => Type::Int,
+            (true, false) => Type::UInt,
+            (false, true) => Type::Long,
+            (false, false) => Type::ULong,
+        }
+    }
+
+    /// The storage unit of a `_Bool` bit-field is shared with other fields,
+    /// so it is handled as a plain byte to avoid normalizing it.
+    fn bit_field_unit_type(ty: &Type) -> Type {
+        match ty.unqualified() {
+            Type::Bool => Type::UChar,
+            _ => ty.clone(),
+        }
+    }
+
+    fn bit_field_mask(bit_field: BitField) -> u64 {
+        let mask = if bit_field.width == 64 {
+            u64::MAX
+        } else {
+            (1u64 << bit_field.width) - 1
+        };
+        mask << bit_field.offset
+    }
+
+    fn shift(&mut self, op: BinaryOp, src: Val, count: u8, ty: &Type) -> Val {
+        if count == 0 {
+            return src;
+        }
+        let dst = self.make_temp(ty);
+        self.instructions.push(Instruction::Binary {
+            op,
+            src1: src,
+            src2: Val::Constant(Constant::Int(count as i32)),
+            dst: dst.clone(),
+        });
+        dst
+    }
+
+    fn load_bit_field(&mut self, unit: &ExprResult, ty: &Type, bit_field: BitField) -> Val {
+        let work_ty = self.bit_field_work_type(ty);
+        let bits = (work_ty.size(&self.semantics) * 8) as u8;
+        let unit_val = self.get_unit(unit, ty);
+        let value = self.cast(unit_val, &work_ty);
+        // Shifting the field to the top and back extends its sign when the
+        // type is signed.
+        let left = bits - bit_field.offset - bit_field.width;
+        let value = self.shift(BinaryOp::ShiftLeft, value, left, &work_ty);
+        let value = self.shift(
+            BinaryOp::ShiftRight,
+            value,
+            bits - bit_field.width,
+            &work_ty,
+        );
+        self.cast(value, ty)
+    }
+
+    fn store_bit_field(&mut self, unit: &ExprResult, ty: &Type, bit_field: BitField, rvalue: Val) {
+        let work_ty = self.bit_field_work_type(ty);
+        let mask = Self::bit_field_mask(bit_field);
+        let constant = |value: u64| {
+            let constant = Constant::ULong(value).cast(&work_ty);
+            Val::Constant(constant.expect("Masks are integer constants"))
+        };
+        let (mask, inverted_mask) = (constant(mask), constant(!mask));
+
+        let unit_val = self.get_unit(unit, ty);
+        let unit_val = self.cast(unit_val, &work_ty);
+        let cleared = self.make_temp(&work_ty);
+        self.instructions.push(Instruction::Binary {
+            op: BinaryOp::BinAnd,
+            src1: unit_val,
+            src2: inverted_mask,
+            dst: cleared.clone(),
+        });
+
+        let value = self.cast(rvalue, &work_ty);
+        let value = self.shift(BinaryOp::ShiftLeft, value, bit_field.offset, &work_ty);
+        let masked = self.make_temp(&work_ty);
+        self.instructions.push(Instruction::Binary {
+            op: BinaryOp::BinAnd,
+            src1: value,
+            src2: mask,
+            dst: masked.clone(),
+        });
+
+        let combined = self.make_temp(&work_ty);
+        self.instructions.push(Instruction::Binary {
+            op: BinaryOp::BinOr,
+            src1: cleared,
+            src2: masked,
+            dst: combined.clone(),
+        });
+        let combined = self.cast(combined, &Self::bit_field_unit_type(ty));
+        self.copy_or_store(unit, combined);
+    }
+
+    fn get_unit(&mut self, unit: &ExprResult, ty: &Type) -> Val {
+        let dst = self.make_temp(&Self::bit_field_unit_type(ty));
+        match unit {
+            ExprResult::Dereference(ptr) => self.instructions.push(Instruction::Load {
+                ptr: ptr.clone(),
+                dst: dst.clone(),
+            }),
+            ExprResult::SubObject { base, offset } => {
+                self.instructions.push(Instruction::CopyFromOffset {
+                    src: base.clone(),
+                    dst: dst.clone(),
+                    offset: *offset,
+                })
+            }
+            _ => panic!("Bit-fields are always stored inside an aggregate"),
+        }
+        dst
+    }
+
     fn get_or_load(&mut self, lvalue: &ExprResult, expr: &ast::Node<ast::Expression>) -> Val {
         let expr_ty = self.semantics.expr_type(expr).clone();
         let val = match lvalue {
@@ -1140,6 +1723,11 @@ impl TackyGenerator {
                 });
                 dst
             }
+            ExprResult::BitField {
+                unit,
+                ty,
+                bit_field,
+            } => self.load_bit_field(unit, ty, *bit_field),
         };
         self.check_implicit_cast(val, expr)
     }
@@ -1165,6 +1753,11 @@ impl TackyGenerator {
                     offset: *offset,
                 })
             }
+            ExprResult::BitField {
+                unit,
+                ty,
+                bit_field,
+            } => self.store_bit_field(unit, ty, *bit_field, rvalue),
         }
     }
 
@@ -1188,12 +1781,38 @@ impl TackyGenerator {
 
     fn cast(&mut self, src: Val, target: &Type) -> Val {
         let src_ty = self.semantics.val_ty(&src);
-        if target == &src_ty {
+        if target.unqualified() == src_ty.unqualified() {
             src
         } else {
             let dst = self.make_temp(target);
 
-            if src_ty.is_double() {
+            // Any nonzero value converts to 1
+            if let Type::Bool = target.unqualified() {
+                let zero = Constant::Int(0)
+                    .cast(&src_ty)
+                    .expect("Casting from a scalar");
+                self.instructions.push(Instruction::Binary {
+                    op: BinaryOp::NotEqual,
+                    src1: src,
+                    src2: Val::Constant(zero),
+                    dst: dst.clone(),
+                });
+                return dst;
+            }
+
+            // Conversions between integers and floating types use the same
+            // instructions for float and double.
+            if src_ty.is_double() && target.is_floating() {
+                self.instructions.push(Instruction::DoubleToFloat {
+                    src,
+                    dst: dst.clone(),
+                });
+            } else if src_ty.is_floating() && target.is_floating() {
+                self.instructions.push(Instruction::FloatToDouble {
+                    src,
+                    dst: dst.clone(),
+                });
+            } else if src_ty.is_floating() {
                 if target.is_signed() {
                     self.instructions.push(Instruction::DoubleToInt {
                         src,
@@ -1205,7 +1824,7 @@ impl TackyGenerator {
                         dst: dst.clone(),
                     });
                 }
-            } else if target.is_double() {
+            } else if target.is_floating() {
                 if src_ty.is_signed() {
                     self.instructions.push(Instruction::IntToDouble {
                         src,
@@ -1242,6 +1861,22 @@ impl TackyGenerator {
         }
     }
 
+    /// Incrementing a `_Bool` always sets it, decrementing flips it.
+    fn step_bool(&mut self, increment: bool, val: Val, dst: Val) {
+        if increment {
+            self.instructions.push(Instruction::Copy {
+                src: Val::Constant(Constant::UChar(1)),
+                dst,
+            });
+        } else {
+            self.instructions.push(Instruction::Unary {
+                op: UnaryOp::Not,
+                src: val,
+                dst,
+            });
+        }
+    }
+
     fn make_temp(&mut self, ty: &Type) -> Val {
         assert!(!matches!(ty, Type::Void));
         let name = Symbol::from(format!("tmp.{i}", i = self.tmp_counter));
@@ -1251,6 +1886,7 @@ impl TackyGenerator {
             SymbolData {
                 ty: ty.clone(),
                 attrs: Attributes::Local,
+                decl_attrs: DeclAttributes::default(),
             },
         );
         self.tmp_counter += 1;
@@ -1275,6 +1911,8 @@ pub fn emit(program: &ast::Program, semantics: SemanticData) -> Program {
         instructions: vec![],
         tmp_counter: 0,
         label_counter: 0,
+        stack_saves: vec![],
+        jump_depths: HashMap::new(),
     };
     for decl in &program.declarations {
         generator.instructions.clear();
@@ -1293,7 +1931,7 @@ pub fn emit(program: &ast::Program, semantics: SemanticData) -> Program {
                 name,
                 global,
                 params: function.params.iter().map(|i| i.symbol.clone()).collect(),
-                body: generator.emit_instructions(body),
+                body: generator.emit_instructions(function, body),
             }));
         }
     }
@@ -1303,6 +1941,7 @@ pub fn emit(program: &ast::Program, semantics: SemanticData) -> Program {
             Attributes::Static {
                 initial_value,
                 global,
+                thread_local,
             } => {
                 let ty = symbol_data.ty.clone();
                 match initial_value {
@@ -1311,6 +1950,7 @@ pub fn emit(program: &ast::Program, semantics: SemanticData) -> Program {
                             name: name.clone(),
                             ty,
                             global,
+                            thread_local,
                             init,
                         }))
                     }
@@ -1320,6 +1960,7 @@ pub fn emit(program: &ast::Program, semantics: SemanticData) -> Program {
                             name: name.clone(),
                             ty,
                             global,
+                            thread_local,
                             init: vec![init],
                         }))
                     }
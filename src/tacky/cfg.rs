@@ -1,4 +1,5 @@
 use crate::optimization::cfg::{GenericCfg, GenericInstruction, GenericNode, InstructionKind};
+use crate::symbol::Symbol;
 use crate::tacky::Instruction;
 use crate::tacky::pretty::pp_instruction;
 use std::fmt::Formatter;
@@ -23,6 +24,19 @@ impl GenericInstruction for Instruction {
     fn pp(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         pp_instruction(f, self)
     }
+
+    fn label(label: Symbol) -> Self {
+        Instruction::Label(label)
+    }
+
+    fn retarget(&mut self, label: Symbol) {
+        match self {
+            Instruction::Jump { target }
+            | Instruction::JumpIfZero { target, .. }
+            | Instruction::JumpIfNotZero { target, .. } => *target = label,
+            _ => panic!("Not a jump"),
+        }
+    }
 }
 
 pub type Cfg = GenericCfg<Instruction>;
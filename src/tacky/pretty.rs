@@ -33,6 +33,9 @@ fn pp_static_variable(stream: &mut impl Write, variable: &tacky::StaticVariable)
     if variable.global {
         write!(stream, "global ")?;
     }
+    if variable.thread_local {
+        write!(stream, "thread_local ")?;
+    }
     write!(stream, "{}", variable.name)?;
     write!(stream, ": ")?;
     pp_type(stream, &variable.ty)?;
@@ -60,11 +63,14 @@ fn pp_initializer(out: &mut impl Write, init: &StaticInit) -> Result<()> {
     match init {
         StaticInit::Char(v) => write!(out, "{:?}", (*v as u8) as char)?,
         StaticInit::UChar(v) => write!(out, "{}UC", *v)?,
+        StaticInit::Short(v) => write!(out, "{v}S")?,
+        StaticInit::UShort(v) => write!(out, "{v}US")?,
         StaticInit::Int(v) => write!(out, "{v}")?,
         StaticInit::UInt(v) => write!(out, "{v}U")?,
         StaticInit::Long(v) => write!(out, "{v}L")?,
         StaticInit::ULong(v) => write!(out, "{v}UL")?,
         StaticInit::Double(v) => write!(out, "{v}D")?,
+        StaticInit::Float(v) => write!(out, "{v}F")?,
         StaticInit::ZeroInit(v) => write!(out, "zero[{v}]")?,
         StaticInit::String {
             symbol,
@@ -77,7 +83,12 @@ fn pp_initializer(out: &mut impl Write, init: &StaticInit) -> Result<()> {
             };
             write!(out, "{s:?}")?
         }
-        StaticInit::Pointer(name) => write!(out, "&{name}")?,
+        StaticInit::Pointer(name, offset) | StaticInit::Address(name, offset) => {
+            write!(out, "&{name}")?;
+            if *offset != 0 {
+                write!(out, "{offset:+}")?;
+            }
+        }
     }
     Ok(())
 }
@@ -206,6 +217,23 @@ pub(crate) fn pp_instruction(
             }
             write!(stream, ")")?;
         }
+        tacky::Instruction::IndirectCall { ptr, args, dst } => {
+            write!(stream, "{indent}")?;
+            if let Some(dst) = dst {
+                pp_val(stream, dst)?;
+                write!(stream, " = ")?;
+            }
+            write!(stream, "(*")?;
+            pp_val(stream, ptr)?;
+            write!(stream, ")(")?;
+            for (i, arg) in args.iter().enumerate() {
+                pp_val(stream, arg)?;
+                if i != args.len() - 1 {
+                    write!(stream, ", ")?;
+                }
+            }
+            write!(stream, ")")?;
+        }
         tacky::Instruction::SignExtend { src, dst } => {
             write!(stream, "{indent}")?;
             pp_val(stream, dst)?;
@@ -249,6 +277,18 @@ pub(crate) fn pp_instruction(
             write!(stream, " = uint_to_double ")?;
             pp_val(stream, src)?;
         }
+        tacky::Instruction::FloatToDouble { src, dst } => {
+            write!(stream, "{indent}")?;
+            pp_val(stream, dst)?;
+            write!(stream, " = float_to_double ")?;
+            pp_val(stream, src)?;
+        }
+        tacky::Instruction::DoubleToFloat { src, dst } => {
+            write!(stream, "{indent}")?;
+            pp_val(stream, dst)?;
+            write!(stream, " = double_to_float ")?;
+            pp_val(stream, src)?;
+        }
         tacky::Instruction::GetAddress { src, dst } => {
             write!(stream, "{indent}")?;
             pp_val(stream, dst)?;
@@ -293,6 +333,48 @@ pub(crate) fn pp_instruction(
             pp_val(stream, dst)?;
             write!(stream, " = {src}[{offset}]")?;
         }
+        tacky::Instruction::VaStart { ap } => {
+            write!(stream, "{indent}va_start(")?;
+            pp_val(stream, ap)?;
+            write!(stream, ")")?;
+        }
+        tacky::Instruction::VaArg { ap, dst } => {
+            write!(stream, "{indent}")?;
+            pp_val(stream, dst)?;
+            write!(stream, " = va_arg(")?;
+            pp_val(stream, ap)?;
+            write!(stream, ")")?;
+        }
+        tacky::Instruction::AllocateStack { size, dst } => {
+            write!(stream, "{indent}")?;
+            pp_val(stream, dst)?;
+            write!(stream, " = allocate_stack(")?;
+            pp_val(stream, size)?;
+            write!(stream, ")")?;
+        }
+        tacky::Instruction::SaveStack { dst } => {
+            write!(stream, "{indent}")?;
+            pp_val(stream, dst)?;
+            write!(stream, " = save_stack()")?;
+        }
+        tacky::Instruction::RestoreStack { src } => {
+            write!(stream, "{indent}restore_stack(")?;
+            pp_val(stream, src)?;
+            write!(stream, ")")?;
+        }
+        tacky::Instruction::Phi { dst, args } => {
+            write!(stream, "{indent}")?;
+            pp_val(stream, dst)?;
+            write!(stream, " = phi(")?;
+            for (i, (pred_id, val)) in args.iter().enumerate() {
+                if i > 0 {
+                    write!(stream, ", ")?;
+                }
+                write!(stream, "{pred_id}: ")?;
+                pp_val(stream, val)?;
+            }
+            write!(stream, ")")?;
+        }
     }
     writeln!(stream)?;
     Ok(())
@@ -304,11 +386,14 @@ fn pp_val(stream: &mut impl Write, val: &tacky::Val) -> Result<()> {
             write!(stream, "{:?}", (*value as u8) as char)?
         }
         tacky::Val::Constant(ast::Constant::UChar(value)) => write!(stream, "{value}UC")?,
+        tacky::Val::Constant(ast::Constant::Short(value)) => write!(stream, "{value}S")?,
+        tacky::Val::Constant(ast::Constant::UShort(value)) => write!(stream, "{value}US")?,
         tacky::Val::Constant(ast::Constant::Int(value)) => write!(stream, "{value}")?,
         tacky::Val::Constant(ast::Constant::Long(value)) => write!(stream, "{value}L")?,
         tacky::Val::Constant(ast::Constant::UInt(value)) => write!(stream, "{value}U")?,
         tacky::Val::Constant(ast::Constant::ULong(value)) => write!(stream, "{value}UL")?,
         tacky::Val::Constant(ast::Constant::Double(value)) => write!(stream, "{value}D")?,
+        tacky::Val::Constant(ast::Constant::Float(value)) => write!(stream, "{value}F")?,
         tacky::Val::Var(name) => write!(stream, "{name}")?,
     }
     Ok(())
@@ -316,15 +401,19 @@ fn pp_val(stream: &mut impl Write, val: &tacky::Val) -> Result<()> {
 
 fn pp_type(stream: &mut impl Write, ty: &Type) -> Result<()> {
     match ty {
+        Type::Bool => write!(stream, "Bool"),
         Type::Char => write!(stream, "Char"),
         Type::SChar => write!(stream, "Signed Char"),
         Type::UChar => write!(stream, "Unsigned Char"),
+        Type::Short => write!(stream, "Short"),
+        Type::UShort => write!(stream, "Unsigned Short"),
         Type::Int => write!(stream, "Int"),
         Type::Long => write!(stream, "Long"),
         Type::UInt => write!(stream, "Unsigned Int"),
         Type::ULong => write!(stream, "Unsigned Long"),
         Type::Function(_) => write!(stream, "Function(...)"),
         Type::Double => write!(stream, "Double"),
+        Type::Float => write!(stream, "Float"),
         Type::Void => write!(stream, "Void"),
         Type::Pointer(referenced) => {
             write!(stream, "Pointer(")?;
@@ -337,12 +426,22 @@ fn pp_type(stream: &mut impl Write, ty: &Type) -> Result<()> {
             pp_type(stream, inner.as_ref())?;
             write!(stream, ")")
         }
+        Type::VariableArray(inner, length) => {
+            write!(stream, "VariableArray({},", length.0)?;
+            pp_type(stream, inner.as_ref())?;
+            write!(stream, ")")
+        }
         Type::Struct(name) => {
             write!(stream, "Struct({name})")
         }
         Type::Union(name) => {
             write!(stream, "Union({name})")
         }
+        Type::Qualified(qualifiers, inner) => {
+            write!(stream, "Qualified({qualifiers},")?;
+            pp_type(stream, inner.as_ref())?;
+            write!(stream, ")")
+        }
     }?;
     Ok(())
 }
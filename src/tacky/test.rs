@@ -1,3 +1,29 @@
+use crate::error::Warning;
+use crate::parser::{DEFAULT_ERROR_LIMIT, parse};
+use crate::pretty::{dedent, diagnostics};
+use crate::semantic::validate;
+use crate::tacky::emit;
+
+fn assert_warnings(src: &str, expected: &[&str]) {
+    let src = dedent(src);
+    let ast = parse(&src, DEFAULT_ERROR_LIMIT).expect("Parse error");
+    let (ast, semantics) = validate(ast).expect("Validation error");
+    let program = emit(&ast, semantics);
+    let warnings: Vec<_> = program
+        .semantics
+        .warnings
+        .into_iter()
+        .filter(|w| {
+            matches!(
+                w.warning_kind(),
+                Some(Warning::UnreachableCode | Warning::ReturnType)
+            )
+        })
+        .collect();
+    assert_eq!(diagnostics(&src, &warnings), expected);
+}
+
+mod test_bool;
 mod test_chapter_1;
 mod test_chapter_10;
 mod test_chapter_11;
@@ -16,3 +42,6 @@ mod test_chapter_6;
 mod test_chapter_7;
 mod test_chapter_8;
 mod test_chapter_9;
+mod test_constant_expressions;
+mod test_thread_local;
+mod test_warnings;
@@ -0,0 +1,38 @@
+use crate::pretty::{dedent, dump_tacky};
+
+#[test]
+fn test_bool_conversions() {
+    let src = r#"
+        _Bool g = 256;
+        int main(void) {
+            double d = 0.5;
+            _Bool b = d;
+            _Bool c = &g;
+            c--;
+            ++b;
+            return b + c;
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            d.0 = 0.5D
+            tmp.0 = d.0 != 0D
+            b.1 = tmp.0
+            tmp.1 = &g
+            tmp.2 = tmp.1 != 0UL
+            c.2 = tmp.2
+            tmp.3 = c.2
+            tmp.4 = ! c.2
+            c.2 = tmp.4
+            tmp.5 = 1UC
+            b.1 = tmp.5
+            tmp.6 = zero_extend b.1
+            tmp.8 = zero_extend c.2
+            tmp.7 = tmp.6 + tmp.8
+            return tmp.7
+            return 0
+        }
+        static global g: Bool = 1UC
+    "#;
+    assert_eq!(dump_tacky(src), dedent(expected));
+}
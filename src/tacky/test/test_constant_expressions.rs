@@ -0,0 +1,40 @@
+use crate::pretty::{dedent, dump_tacky};
+
+#[test]
+fn test_valid_folded_static_initializers() {
+    let src = r#"
+        struct pair { int a; long b; };
+        struct pair pairs[2];
+        long *second = &pairs[1].b;
+        char *tail = "abc" + 2;
+        int neg = -(1 + 2);
+        double half = 1 / 2.0;
+        enum { SIZE = sizeof(struct pair) / 4 };
+        int arr[SIZE * 2];
+        int main(void) {
+            static int *end = arr + SIZE;
+            return end - arr + SIZE;
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            tmp.1 = &arr
+            tmp.2 = end.2 - tmp.1
+            tmp.0 = tmp.2 / 4L
+            tmp.4 = sign_extend 4
+            tmp.3 = tmp.0 + tmp.4
+            tmp.5 = truncate tmp.3
+            return tmp.5
+            return 0
+        }
+        static global arr: Array(8,Int) = zero[32]
+        static end.2: Pointer(Int) = &arr+16
+        static global half: Double = 0.5D
+        static global neg: Int = -3
+        static global pairs: Array(2,Struct(pair.0)) = zero[32]
+        static global second: Pointer(Long) = &pairs+24
+        constant string.0: Array(4,Char) = "abc\\0"
+        static global tail: Pointer(Char) = &string.0+2
+    "#;
+    assert_eq!(dump_tacky(src), dedent(expected));
+}
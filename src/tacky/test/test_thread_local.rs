@@ -0,0 +1,23 @@
+use crate::pretty::{dedent, dump_tacky};
+
+#[test]
+fn test_thread_local_access() {
+    let src = r#"
+        _Thread_local int counter = 1;
+        extern _Thread_local int shared;
+        int main(void) {
+            return counter + shared;
+        }
+    "#;
+    let expected = r#"
+        global function main() { 
+            tmp.1 = &shared
+            tmp.2 = *tmp.1
+            tmp.0 = counter + tmp.2
+            return tmp.0
+            return 0
+        }
+        static global thread_local counter: Int = 1
+    "#;
+    assert_eq!(dump_tacky(src), dedent(expected));
+}
@@ -0,0 +1,83 @@
+use super::assert_warnings;
+
+#[test]
+fn test_unreachable_code_after_return() {
+    assert_warnings(
+        r#"
+        int main(void) {
+            int x = 1;
+            if (x) {
+                return 1;
+                x = 2;
+                x = 3;
+            }
+            switch (x) {
+                case 1:
+                    return 2;
+                case 2:
+                    return 3;
+                    ;
+                default:
+                    x = 4;
+            }
+            return 0;
+        }
+    "#,
+        &["5:9: Code will never be executed"],
+    );
+}
+
+#[test]
+fn test_missing_return() {
+    assert_warnings(
+        r#"
+        int no_return(void) {
+        }
+        int partial(int x) {
+            if (x)
+                return 1;
+        }
+        int complete(int x) {
+            if (x)
+                return 1;
+            else
+                return 2;
+        }
+        int infinite(void) {
+            for (;;) {}
+        }
+        int with_goto(int x) {
+        start:
+            if (x)
+                return x;
+            x = x + 1;
+            goto start;
+        }
+        void procedure(void) {
+        }
+        int main(void) {
+        }
+    "#,
+        &[
+            "2:1: Non-void function 'no_return' does not return a value in all control paths",
+            "6:1: Non-void function 'partial' does not return a value in all control paths",
+        ],
+    );
+}
+
+#[test]
+fn test_unreachable_code_after_labeled_return() {
+    assert_warnings(
+        r#"
+        int main(void) {
+            int x = 1;
+            goto end;
+        end:
+            return x;
+            x = 3;
+            return 0;
+        }
+    "#,
+        &["6:5: Code will never be executed"],
+    );
+}